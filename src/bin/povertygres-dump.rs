@@ -0,0 +1,55 @@
+use povertygres::catalog::catalog_manager::CatalogManager;
+use povertygres::consts::catalog_consts::{CATALOG_FILE, DATA_DIR};
+use povertygres::dump::write_dump;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::exit;
+
+/// povertygres-dump [--file out.sql] [data_dir]
+///
+/// Writes a SQL script that `povertygres --restore` loads into an empty data directory.
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut output = None;
+    let mut data_dir = DATA_DIR.to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" | "-f" => output = args.next(),
+            _ => data_dir = arg,
+        }
+    }
+
+    let data_dir = Path::new(&data_dir);
+    if !data_dir.join(CATALOG_FILE).exists() {
+        eprintln!("povertygres-dump: no catalog in '{}'", data_dir.display());
+        exit(1);
+    }
+    // an older catalog is upgraded in memory; the data directory is never written
+    let cat = match CatalogManager::open_read_only(data_dir) {
+        Ok(cat) => cat,
+        Err(e) => {
+            eprintln!("povertygres-dump: {e}");
+            exit(1);
+        }
+    };
+
+    let result = match &output {
+        Some(path) => File::create(path)
+            .map_err(|e| format!("could not create '{}': {}", path, e))
+            .and_then(|f| {
+                let mut out = BufWriter::new(f);
+                write_dump(&cat, data_dir, &mut out)?;
+                out.flush().map_err(|e| e.to_string())
+            }),
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_dump(&cat, data_dir, &mut out).and_then(|_| out.flush().map_err(|e| e.to_string()))
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("povertygres-dump: {e}");
+        exit(1);
+    }
+}
@@ -1,8 +1,7 @@
 use super::io::*;
 use crate::consts::catalog_consts::DATA_DIR;
 use crate::errors::catalog_error::CatalogError;
-use crate::types::catalog_types::{Catalog, ColumnMeta, IndexMeta, TableMeta};
-use crate::types::storage_types::ForeignKeyConstraint;
+use crate::types::catalog_types::{Catalog, IndexMeta};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -26,6 +25,16 @@ impl CatalogManager {
         })
     }
 
+    /// Load an existing catalog without ever writing to `data_dir`: an older format is
+    /// upgraded in memory only. For readers such as povertygres-dump.
+    pub fn open_read_only<P: AsRef<Path>>(data_dir: P) -> Result<Self, CatalogError> {
+        let (catalog, _) = load_catalog(data_dir.as_ref())?;
+        Ok(Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+            catalog,
+        })
+    }
+
     pub fn catalog(&self) -> &Catalog {
         &self.catalog // return immutable reference to catalog
     }
@@ -38,76 +47,22 @@ impl CatalogManager {
         save_catalog_atomic(&self.data_dir, &self.catalog)
     }
 
-    pub fn create_index(
-        &mut self,
-        name: &str,
-        table: &str,
-        columns: &[String],
-    ) -> Result<&IndexMeta, CatalogError> {
-        if self.catalog.indexes.contains_key(name) {
-            return Err(CatalogError::IndexExists(name.into()));
-        }
-
-        if !self.catalog.has_table(table) {
-            return Err(CatalogError::TableNotFound(table.into()));
-        }
-
-        let im = IndexMeta {
-            name: name.into(),
-            table: table.into(),
-            columns: columns.to_vec(),
+    /// Heap file path for `name` that no table in the catalog uses yet, nor any of `taken`:
+    /// `<name>.tbl`, or `<name>_<n>.tbl` once a renamed or truncated table holds that one
+    pub fn table_file(&self, name: &str, taken: &[String]) -> String {
+        let in_use = |file: &str| {
+            self.catalog.tables.values().any(|t| t.file == file) || taken.iter().any(|t| t == file)
         };
-
-        self.catalog.indexes.insert(name.into(), im);
-
-        save_catalog_atomic(&self.data_dir, &self.catalog)?;
-
-        Ok(self.catalog.indexes.get(name).unwrap())
+        let mut file = format!("{DATA_DIR}/{name}.tbl");
+        let mut n = 1;
+        while in_use(&file) {
+            file = format!("{DATA_DIR}/{name}_{n}.tbl");
+            n += 1;
+        }
+        file
     }
 
     pub fn get_indexes(&self) -> &HashMap<String, IndexMeta> {
         &self.catalog.indexes
     }
-
-    pub fn create_table(
-        &mut self,
-        name: &str,
-        columns: Vec<ColumnMeta>, // schema definition for new table
-        primary_key: Option<String>,
-        foreign_keys: Vec<ForeignKeyConstraint>,
-    ) -> Result<&TableMeta, CatalogError> {
-        // Prevent duplicate table creation
-        if self.catalog.has_table(name) {
-            return Err(CatalogError::TableExists(name.into()));
-        }
-
-        // Run catalog integrity checks
-        super::validate::validate_catalog(&self.catalog)?;
-
-        // Assign new table OID
-        let oid = self.catalog.next_table_oid;
-
-        // Define file path for this table
-        let file = format!("{DATA_DIR}/{name}.tbl");
-
-        // Construct table metadata
-        let tm = TableMeta {
-            oid,
-            file,
-            columns,
-            next_rowid: 1, // start row id counter
-            primary_key,
-            foreign_keys,
-        };
-
-        // Update catalog state
-        self.catalog.next_table_oid += 1;
-        self.catalog.tables.insert(name.to_string(), tm);
-
-        // Persist catalog changes to disk atomically
-        save_catalog_atomic(&self.data_dir, &self.catalog)?;
-
-        // Return reference to the created table metadata
-        Ok(self.catalog.tables.get(name).unwrap())
-    }
 }
@@ -17,10 +17,24 @@ pub fn load_or_create_catalog(data_dir: &Path) -> Result<Catalog, CatalogError>
         return Ok(cat);
     }
 
-    // load catalog from file
-    let f = File::open(&path)?;
+    let (cat, migrated) = load_catalog(data_dir)?;
+
+    // write back upgraded catalog so the migration runs only once
+    if migrated {
+        save_catalog_atomic(data_dir, &cat)?;
+    }
+    Ok(cat)
+}
+
+/// Read the catalog of `data_dir`, upgrading older formats in memory only.
+/// Returns the catalog and whether it was migrated; nothing is written.
+pub fn load_catalog(data_dir: &Path) -> Result<(Catalog, bool), CatalogError> {
+    // load catalog from file, upgrading older formats first
+    let f = File::open(data_dir.join(CATALOG_FILE))?;
     let reader = BufReader::new(f);
-    let cat: Catalog = serde_json::from_reader(reader)?;
+    let mut doc: serde_json::Value = serde_json::from_reader(reader)?;
+    let migrated = super::migrate::migrate_catalog(&mut doc)?;
+    let cat: Catalog = serde_json::from_value(doc)?;
 
     // check page size consistency
     if cat.page_size != PAGE_SIZE {
@@ -32,7 +46,7 @@ pub fn load_or_create_catalog(data_dir: &Path) -> Result<Catalog, CatalogError>
 
     // validate structure
     super::validate::validate_catalog(&cat)?;
-    Ok(cat)
+    Ok((cat, migrated))
 }
 
 pub fn save_catalog_atomic(data_dir: &Path, cat: &Catalog) -> Result<(), CatalogError> {
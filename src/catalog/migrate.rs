@@ -0,0 +1,124 @@
+use crate::consts::catalog_consts::CATALOG_VERSION;
+use crate::errors::catalog_error::CatalogError;
+use serde_json::{json, Value};
+
+/// Upgrade a raw catalog document to the current format version.
+/// Returns true if anything was changed.
+pub fn migrate_catalog(doc: &mut Value) -> Result<bool, CatalogError> {
+    let version = doc
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| CatalogError::Invalid("missing catalog version".into()))? as u32;
+
+    if version > CATALOG_VERSION {
+        return Err(CatalogError::Invalid(format!(
+            "catalog version {} is newer than supported {}",
+            version, CATALOG_VERSION
+        )));
+    }
+
+    let mut current = version;
+    while current < CATALOG_VERSION {
+        match current {
+            1 => v1_to_v2(doc)?,
+            2 => v2_to_v3(doc)?,
+            3 => v3_to_v4(doc)?,
+            other => {
+                return Err(CatalogError::Invalid(format!(
+                    "no migration from catalog version {}",
+                    other
+                )))
+            }
+        }
+        current += 1;
+        doc["version"] = json!(current);
+    }
+
+    Ok(version != CATALOG_VERSION)
+}
+
+/// v2: `primary_key` becomes a column list and gets a `<table>_pkey` unique index
+fn v1_to_v2(doc: &mut Value) -> Result<(), CatalogError> {
+    let mut pkeys = Vec::new();
+
+    let tables = doc
+        .get_mut("tables")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| CatalogError::Invalid("missing tables".into()))?;
+
+    for (name, table) in tables.iter_mut() {
+        if let Some(Value::String(col)) = table.get("primary_key").cloned() {
+            table["primary_key"] = json!([col]);
+            pkeys.push((name.clone(), col));
+        }
+    }
+
+    let indexes = doc
+        .get_mut("indexes")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| CatalogError::Invalid("missing indexes".into()))?;
+
+    for (table, col) in pkeys {
+        let name = format!("{}_pkey", table);
+        indexes.entry(name.clone()).or_insert_with(|| {
+            json!({ "name": name, "table": table, "columns": [col], "unique": true })
+        });
+    }
+
+    Ok(())
+}
+
+/// v3: columns get physical attribute numbers and tables a tuple layout + schema version
+fn v2_to_v3(doc: &mut Value) -> Result<(), CatalogError> {
+    let tables = doc
+        .get_mut("tables")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| CatalogError::Invalid("missing tables".into()))?;
+
+    for table in tables.values_mut() {
+        let columns = table
+            .get_mut("columns")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| CatalogError::Invalid("missing columns".into()))?;
+
+        let mut attributes = Vec::new();
+        for (attnum, col) in columns.iter_mut().enumerate() {
+            col["attnum"] = json!(attnum);
+            attributes.push(json!({
+                "type": col["type"].clone(),
+                "added_in": 0,
+                "missing": "Null",
+                "dropped": false,
+            }));
+        }
+        table["attributes"] = json!(attributes);
+        table["schema_version"] = json!(0);
+    }
+
+    Ok(())
+}
+
+/// v4: column defaults are expressions; a stored constant becomes a literal
+fn v3_to_v4(doc: &mut Value) -> Result<(), CatalogError> {
+    let tables = doc
+        .get_mut("tables")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| CatalogError::Invalid("missing tables".into()))?;
+
+    for table in tables.values_mut() {
+        let columns = table
+            .get_mut("columns")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| CatalogError::Invalid("missing columns".into()))?;
+
+        for col in columns {
+            if let Some(default) = col.get_mut("default")
+                && !default.is_null()
+            {
+                *default = json!({ "Literal": default.take() });
+            }
+        }
+    }
+
+    Ok(())
+}
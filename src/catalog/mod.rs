@@ -1,3 +1,4 @@
 pub mod catalog_manager;
 pub mod io;
+pub mod migrate;
 pub mod validate;
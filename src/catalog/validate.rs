@@ -1,11 +1,11 @@
-use crate::consts::catalog_consts::PAGE_SIZE;
+use crate::consts::catalog_consts::{CATALOG_VERSION, PAGE_SIZE};
 use crate::errors::catalog_error::CatalogError;
-use crate::types::catalog_types::Catalog;
+use crate::types::catalog_types::{CatColumnType, Catalog};
 use std::collections::HashSet;
 
 pub fn validate_catalog(cat: &Catalog) -> Result<(), CatalogError> {
     // check catalog version
-    if cat.version != 1 {
+    if cat.version != CATALOG_VERSION {
         return Err(CatalogError::Invalid(format!(
             "unsupported version {}",
             cat.version
@@ -46,7 +46,47 @@ pub fn validate_catalog(cat: &Catalog) -> Result<(), CatalogError> {
                 )));
             }
         }
+
+        // ENUM types of columns, dropped ones included, must be defined
+        for ty in t.columns.iter().map(|c| &c.ty).chain(t.attributes.iter().map(|a| &a.ty)) {
+            if let Some(missing) = enum_name(ty).filter(|n| !cat.enums.contains_key(*n)) {
+                return Err(CatalogError::Invalid(format!(
+                    "type \"{}\" used by table {} does not exist",
+                    missing, name
+                )));
+            }
+        }
+    }
+
+    // every index must belong to a table
+    for idx in cat.indexes.values() {
+        if !cat.has_table(&idx.table) {
+            return Err(CatalogError::TableNotFound(idx.table.clone()));
+        }
+    }
+
+    // sequences share the OIDs of tables; an owning table must exist
+    for (name, s) in &cat.sequences {
+        if !oids.insert(s.oid) || cat.tables.contains_key(name) {
+            return Err(CatalogError::Invalid(format!(
+                "sequence {name} clashes with a table"
+            )));
+        }
+        if let Some((table, _)) = &s.owned_by
+            && !cat.has_table(table)
+        {
+            return Err(CatalogError::TableNotFound(table.clone()));
+        }
     }
 
     Ok(())
 }
+
+/// Name of the ENUM type a column type is or holds
+fn enum_name(ty: &CatColumnType) -> Option<&str> {
+    match ty {
+        CatColumnType::Enum(name) => Some(name),
+        CatColumnType::Array(element_type) => enum_name(element_type),
+        _ => None,
+    }
+}
@@ -1,3 +1,4 @@
 pub const PAGE_SIZE: u32 = 8192; // default page size in bytes (8 KB)
 pub const DATA_DIR: &str = "data"; // directory where all table files are stored
 pub const CATALOG_FILE: &str = "catalog.json"; // catalog metadata file name
+pub const CATALOG_VERSION: u32 = 4; // current catalog format version
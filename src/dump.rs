@@ -0,0 +1,295 @@
+use crate::catalog::catalog_manager::CatalogManager;
+use crate::engine::{sequence_from_meta, table_from_meta};
+use crate::executer::copy::{encode_record, format_field};
+use crate::storage::sequence_file;
+use crate::types::catalog_types::{Catalog, TableMeta};
+use crate::types::filter_types::ArithOp;
+use crate::types::parser_types::{Condition, CopyFormat, CopyOptions, Operand};
+use crate::types::sequence::Sequence;
+use crate::types::storage_types::{ColumnType, Deferrable, FkAction, Identity, Value};
+use crate::types::transaction_types::TransactionManager;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Write a SQL script that recreates the database: ENUM types, tables and CHECK constraints,
+/// sequences, then the rows as `COPY ... FROM STDIN` blocks and the sequence values, then
+/// keys, indexes and foreign keys, all inside one transaction. Rows are read under a
+/// single snapshot built from the catalog's transaction table, so transactions that are
+/// still running or commit while the dump is written are left out as a whole.
+pub fn write_dump(cat: &CatalogManager, data_dir: &Path, out: &mut impl Write) -> Result<(), String> {
+    let catalog = cat.catalog();
+    let tm = TransactionManager::from_map(catalog.transactions.clone(), catalog.next_xid);
+    let snapshot = tm.snapshot();
+
+    let mut sql = format!(
+        "-- povertygres dump\n-- catalog version {}, page size {}\n-- snapshot xmin {}, xmax {}, active {:?}\n\nbegin;\n",
+        catalog.version, catalog.page_size, snapshot.xmin, snapshot.xmax, snapshot.active_xids
+    );
+
+    for ty in catalog.enums.values() {
+        let labels: Vec<String> = ty.labels.iter().map(|l| format!("'{}'", l.label.replace('\'', "''"))).collect();
+        sql.push_str(&format!("\ncreate type {} as enum ({});\n", ty.name, labels.join(", ")));
+    }
+
+    // sequence files are looked up in `data_dir` too
+    let mut sequences = Vec::new();
+    for (name, meta) in &catalog.sequences {
+        let mut sequence = sequence_from_meta(name, meta);
+        if let Some(file) = Path::new(&meta.file).file_name() {
+            sequence.file = data_dir.join(file);
+        }
+        let state = sequence_file::read_state(&sequence.file)
+            .map_err(|e| format!("could not read sequence '{}': {}", name, e))?;
+        sequences.push((sequence, state));
+    }
+
+    for (name, meta) in &catalog.tables {
+        sql.push('\n');
+        sql.push_str(&create_table_sql(name, meta, &sequences)?);
+    }
+
+    // sequences of identity columns come with their tables; the others may be OWNED BY one
+    for (sequence, _) in sequences.iter().filter(|(s, _)| !is_identity_sequence(catalog, s)) {
+        sql.push_str(&format!(
+            "\ncreate sequence {} as {} {}",
+            sequence.name,
+            sequence.data_type,
+            sequence_options_sql(sequence)
+        ));
+        if let Some((table, column)) = &sequence.owned_by {
+            sql.push_str(&format!(" owned by {}.{}", table, column));
+        }
+        sql.push_str(";\n");
+    }
+
+    let options = CopyOptions {
+        format: CopyFormat::Text,
+        header: false,
+        delimiter: '\t',
+        quote: '"',
+        null: "\\N".to_string(),
+    };
+    let enums: HashMap<String, Arc<_>> =
+        catalog.enums.iter().map(|(name, ty)| (name.clone(), Arc::new(ty.clone()))).collect();
+    for (name, meta) in &catalog.tables {
+        let mut table = table_from_meta(name, meta, &enums);
+        // heap files are looked up in `data_dir`, wherever the catalog was written from
+        if let Some(file) = Path::new(&meta.file).file_name() {
+            table.heap.path = data_dir.join(file);
+        }
+        if !table.heap.path.exists() {
+            return Err(format!("heap file '{}' of table '{}' is missing", table.heap.path.display(), name));
+        }
+
+        let columns: Vec<&str> = meta.columns.iter().map(|c| c.name.as_str()).collect();
+        sql.push_str(&format!("\ncopy {} ({}) from stdin;\n", name, columns.join(", ")));
+        for (_, _, header, row) in table.heap.scan_all(&table.columns) {
+            if !header.is_visible(0, &snapshot, &tm) {
+                continue;
+            }
+            let fields: Vec<Option<String>> = row.values.iter().map(format_field).collect();
+            sql.push_str(&encode_record(&fields, &options));
+            sql.push('\n');
+        }
+        sql.push_str("\\.\n");
+
+        out.write_all(sql.as_bytes()).map_err(|e| e.to_string())?;
+        sql.clear();
+    }
+
+    if !sequences.is_empty() {
+        sql.push('\n');
+    }
+    for (sequence, (last_value, is_called)) in &sequences {
+        sql.push_str(&format!("select setval('{}', {}, {});\n", sequence.name, last_value, is_called));
+    }
+
+    sql.push('\n');
+    for (name, meta) in &catalog.tables {
+        let mut indexes: Vec<_> = catalog.indexes.values().filter(|idx| idx.table == *name).collect();
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut primary_key = meta.primary_key.as_ref();
+        for idx in indexes {
+            let columns = idx.columns.join(", ");
+            let mode = deferrable_sql(idx.deferrable);
+            if idx.unique && primary_key == Some(&idx.columns) {
+                primary_key = None;
+                sql.push_str(&format!(
+                    "alter table {} add constraint {} primary key ({}){};\n",
+                    name, idx.name, columns, mode
+                ));
+            } else if idx.unique {
+                sql.push_str(&format!(
+                    "alter table {} add constraint {} unique ({}){};\n",
+                    name, idx.name, columns, mode
+                ));
+            } else {
+                sql.push_str(&format!("create index {} on {} ({});\n", idx.name, name, columns));
+            }
+        }
+    }
+
+    // foreign keys last, once every referenced key exists
+    for (name, meta) in &catalog.tables {
+        for fk in &meta.foreign_keys {
+            let constraint = fk.name.as_ref().map(|n| format!("constraint {} ", n)).unwrap_or_default();
+            sql.push_str(&format!(
+                "alter table {} add {}foreign key ({}) references {}({})",
+                name,
+                constraint,
+                fk.local_columns.join(", "),
+                fk.referenced_table,
+                fk.referenced_columns.join(", ")
+            ));
+            if fk.on_delete != FkAction::NoAction {
+                sql.push_str(&format!(" on delete {}", fk.on_delete.to_string().to_lowercase()));
+            }
+            if fk.on_update != FkAction::NoAction {
+                sql.push_str(&format!(" on update {}", fk.on_update.to_string().to_lowercase()));
+            }
+            sql.push_str(deferrable_sql(fk.deferrable));
+            sql.push_str(";\n");
+        }
+    }
+
+    sql.push_str("\ncommit;\n");
+    out.write_all(sql.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// CREATE TABLE with columns and CHECK constraints; keys are added after the data.
+/// An identity column names its sequence and options instead of its DEFAULT.
+fn create_table_sql(name: &str, meta: &TableMeta, sequences: &[(Sequence, (i64, bool))]) -> Result<String, String> {
+    let mut defs = Vec::new();
+    for col in &meta.columns {
+        let mut def = format!("{} {}", col.name, ColumnType::from_catalog(&col.ty, &col.typmod));
+        if let Some(identity) = col.identity {
+            let sequence = sequences
+                .iter()
+                .map(|(s, _)| s)
+                .find(|s| s.owned_by.as_ref().is_some_and(|(t, c)| t == name && *c == col.name))
+                .ok_or_else(|| format!("sequence of identity column '{}' of '{}' is missing", col.name, name))?;
+            let kind = match identity {
+                Identity::Always => "always",
+                Identity::ByDefault => "by default",
+            };
+            def.push_str(&format!(
+                " generated {} as identity (sequence name {} {})",
+                kind,
+                sequence.name,
+                sequence_options_sql(sequence)
+            ));
+            defs.push(def);
+            continue;
+        }
+        if col.not_null {
+            def.push_str(" not null");
+        }
+        if let Some(default) = &col.default {
+            def.push_str(&format!(" default {}", operand_sql(default)?));
+        }
+        defs.push(def);
+    }
+    for check in &meta.checks {
+        defs.push(format!("constraint {} check ({})", check.name, condition_sql(&check.condition)?));
+    }
+    Ok(format!("create table {} (\n    {}\n);\n", name, defs.join(",\n    ")))
+}
+
+/// Whether `sequence` belongs to an identity column
+fn is_identity_sequence(catalog: &Catalog, sequence: &Sequence) -> bool {
+    sequence.owned_by.as_ref().is_some_and(|(table, column)| {
+        catalog
+            .tables
+            .get(table)
+            .is_some_and(|t| t.columns.iter().any(|c| c.name == *column && c.identity.is_some()))
+    })
+}
+
+/// Every option of a sequence, so the restored one does not depend on defaults
+fn sequence_options_sql(sequence: &Sequence) -> String {
+    let mut sql = format!(
+        "increment by {} minvalue {} maxvalue {} start with {}",
+        sequence.increment, sequence.min_value, sequence.max_value, sequence.start
+    );
+    if sequence.cycle {
+        sql.push_str(" cycle");
+    }
+    sql
+}
+
+fn deferrable_sql(mode: Deferrable) -> &'static str {
+    match mode {
+        Deferrable::NotDeferrable => "",
+        Deferrable::InitiallyImmediate => " deferrable",
+        Deferrable::InitiallyDeferred => " deferrable initially deferred",
+    }
+}
+
+/// A literal as the parser reads it back. Double-quoted text has no escapes, so text
+/// with a double quote (JSON, say) is written single-quoted with `'` doubled.
+fn literal_sql(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Text(s) if s.contains('"') => Ok(format!("'{}'", s.replace('\'', "''"))),
+        Value::Text(s) => Ok(format!("\"{}\"", s)),
+        Value::Jsonb(_) | Value::Array(_) => Ok(format!("'{}'", value.to_string().replace('\'', "''"))),
+        Value::Enum(..) => literal_sql(&Value::Text(value.to_string())),
+        Value::Null => Ok("null".to_string()),
+        Value::Bytea(_) | Value::Uuid(_) => Ok(format!("\"{}\"", value)),
+        temporal if temporal.temporal_type().is_some() => Ok(format!("\"{}\"", temporal)),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn operand_sql(operand: &Operand) -> Result<String, String> {
+    match operand {
+        Operand::Column(name) => Ok(name.clone()),
+        // date/time, bytea, jsonb and uuid literals keep their type, so comparisons and arithmetic read the same
+        Operand::Literal(value) => match (value, value.temporal_type()) {
+            (Value::Bytea(_), _) => Ok(format!("bytea {}", literal_sql(value)?)),
+            (Value::Jsonb(_), _) => Ok(format!("jsonb {}", literal_sql(value)?)),
+            (Value::Uuid(_), _) => Ok(format!("uuid {}", literal_sql(value)?)),
+            (Value::Array(items), _) => {
+                let items = items
+                    .iter()
+                    .map(|item| operand_sql(&Operand::Literal(item.clone())))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!("array[{}]", items.join(", ")))
+            }
+            (_, Some(ty)) => Ok(format!("{} {}", ty, literal_sql(value)?)),
+            _ => literal_sql(value),
+        },
+        Operand::Func(name, args) if name == "extract" && args.len() == 2 => match &args[0] {
+            Operand::Literal(Value::Text(field)) => Ok(format!("extract({} from {})", field, operand_sql(&args[1])?)),
+            _ => Err("extract() needs a field name".to_string()),
+        },
+        Operand::Func(name, args) => {
+            let args = args.iter().map(operand_sql).collect::<Result<Vec<_>, _>>()?;
+            if name == "array" {
+                return Ok(format!("array[{}]", args.join(", ")));
+            }
+            Ok(format!("{}({})", name, args.join(", ")))
+        }
+        Operand::Arith(ArithOp::Subscript, array, index) => Ok(format!("{}[{}]", operand_sql(array)?, operand_sql(index)?)),
+        Operand::Arith(op, lhs, rhs) => Ok(format!("({} {} {})", operand_sql(lhs)?, op, operand_sql(rhs)?)),
+        Operand::Cast(inner, column_type) => Ok(format!("cast({} as {})", operand_sql(inner)?, column_type)),
+    }
+}
+
+/// Condition text with every nested AND/OR/NOT parenthesized
+fn condition_sql(cond: &Condition) -> Result<String, String> {
+    let nested = |c: &Condition| -> Result<String, String> {
+        match c {
+            Condition::Cmp(..) => condition_sql(c),
+            _ => Ok(format!("({})", condition_sql(c)?)),
+        }
+    };
+    match cond {
+        Condition::Cmp(op, lhs, rhs) => Ok(format!("{} {} {}", operand_sql(lhs)?, op, operand_sql(rhs)?)),
+        Condition::And(a, b) => Ok(format!("{} and {}", nested(a)?, nested(b)?)),
+        Condition::Or(a, b) => Ok(format!("{} or {}", nested(a)?, nested(b)?)),
+        Condition::Not(x) => Ok(format!("not {}", nested(x)?)),
+    }
+}
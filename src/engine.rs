@@ -1,14 +1,24 @@
 use crate::catalog::catalog_manager::CatalogManager;
+use crate::catalog::validate::validate_catalog;
 use crate::consts::catalog_consts::DATA_DIR;
 use crate::errors::engine_error::EngineError;
+use crate::executer::help_functions::find_index_on;
 use crate::storage::heap_file::HeapFile;
 use crate::types::b_tree::BTreeIndex;
-use crate::types::catalog_types::{CatColumnType, ColumnMeta};
-use crate::types::storage_types::{Column, Database, Table};
-use crate::types::storage_types::{ColumnType, ForeignKeyConstraint};
+use crate::errors::catalog_error::CatalogError;
+use crate::types::datetime;
+use crate::types::enum_type::{EnumLabel, EnumType};
+use crate::types::catalog_types::{AttributeMeta, CatColumnType, ColumnMeta, IndexMeta, SequenceMeta, TableMeta};
+use crate::types::parser_types::AlterAction;
+use crate::types::sequence::{Sequence, SequenceOptions};
+use crate::storage::sequence_file;
+use crate::types::storage_types::{Attribute, Column, Database, Table};
+use crate::types::storage_types::{CheckConstraint, ColumnType, Deferrable, ForeignKeyConstraint};
 use crate::types::transaction_types::{IsolationLevel, Snapshot, TransactionManager, TxStatus};
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Main database engine: holds in-memory DB + catalog manager
 pub struct Engine {
@@ -18,6 +28,19 @@ pub struct Engine {
     pub session_isolation: IsolationLevel,
     pub tx_isolation: Option<IsolationLevel>,
     pub repeatable_snapshot: Option<Snapshot>,
+    pub auto_index_foreign_keys: bool, // create indexes for FK referencing columns automatically
+    tx_start: Option<i64>,             // now() of the open transaction, µs since the epoch
+    ddl_undo: Option<DdlUndo>,         // schema before the current transaction's first DDL
+}
+
+/// Schema state saved before the first DDL statement of a transaction
+struct DdlUndo {
+    tables: HashMap<String, Table>,
+    indexes: HashMap<String, BTreeIndex>,
+    enums: HashMap<String, Arc<EnumType>>,
+    sequences: HashMap<String, Sequence>,
+    created_files: Vec<HeapFile>, // heaps created since: unlinked on rollback
+    dropped_files: Vec<HeapFile>, // heaps dropped or replaced since: unlinked after commit
 }
 
 impl Engine {
@@ -27,36 +50,21 @@ impl Engine {
         let cat = CatalogManager::open(Path::new(DATA_DIR))?;
         let mut db = Database::new();
 
+        // ENUM types first: column types refer to them
+        for (name, ty) in cat.catalog().enums.iter() {
+            db.enums.insert(name.clone(), Arc::new(ty.clone()));
+        }
+
         // Rebuild in-memory tables from catalog metadata
         for (name, tm) in cat.catalog().tables.iter() {
-            let cols: Vec<Column> = tm
-                .columns
-                .iter()
-                .map(|c| Column {
-                    name: c.name.clone(),
-                    column_type: match c.ty {
-                        CatColumnType::Int32 => ColumnType::Int,
-                        CatColumnType::Text => ColumnType::Text,
-                        CatColumnType::Bool => ColumnType::Bool,
-                    },
-                    not_null: c.not_null,
-                    default: c.default.clone(),
-                })
-                .collect();
+            db.tables.insert(name.clone(), table_from_meta(name, tm, &db.enums));
+        }
 
-            // Insert table into in-memory DB
-            db.tables.insert(
-                name.clone(),
-                Table {
-                    name: name.clone(),
-                    columns: cols,
-                    heap: HeapFile {
-                        path: PathBuf::from(&tm.file), // attach heap file
-                    },
-                    primary_key: tm.primary_key.clone(),
-                    foreign_keys: tm.foreign_keys.clone(),
-                },
-            );
+        // Sequences continue from the value their files hold
+        for (name, sm) in cat.catalog().sequences.iter() {
+            let sequence = sequence_from_meta(name, sm);
+            sequence.load_state().map_err(EngineError::Storage)?;
+            db.sequences.insert(name.clone(), sequence);
         }
 
         // Restore transaction statuses from catalog into transaction manager
@@ -71,7 +79,9 @@ impl Engine {
                 imeta.name.clone(),
                 imeta.table.clone(),
                 imeta.columns.clone(),
+                imeta.unique,
             );
+            idx.deferrable = imeta.deferrable;
         
             if let Some(table) = db.tables.get(&imeta.table) {
                 for (page_no, slot_no, _hdr, row) in table.heap.scan_all(&table.columns) {
@@ -98,42 +108,91 @@ impl Engine {
             session_isolation: IsolationLevel::ReadCommitted,
             tx_isolation: None,
             repeatable_snapshot: None,
+            auto_index_foreign_keys: false,
+            tx_start: None,
+            ddl_undo: None,
         })
     }
 
     /// Create a new table both in catalog (persistent) and in DB (in-memory)
+    #[allow(clippy::too_many_arguments)]
     pub fn create_table_in_both(
         &mut self,
         name: &str,
         columns: Vec<Column>,
-        primary_key: Option<String>,
+        primary_key: Option<Vec<String>>,
         foreign_keys: Vec<ForeignKeyConstraint>,
+        unique_constraints: Vec<Vec<String>>,
+        checks: Vec<CheckConstraint>,
+        deferrable_keys: Vec<(Vec<String>, Deferrable)>,
+        sequences: Vec<(String, SequenceOptions)>,
     ) -> Result<(), EngineError> {
-        // Convert in-memory Column → catalog ColumnMeta
-        let cols_meta: Vec<ColumnMeta> = columns
-            .iter()
-            .map(|col| {
-                let ty = match col.column_type {
-                    ColumnType::Int => CatColumnType::Int32,
-                    ColumnType::Text => CatColumnType::Text,
-                    ColumnType::Bool => CatColumnType::Bool,
-                };
-                ColumnMeta {
-                    name: col.name.clone(),
-                    ty,
-                    not_null: col.not_null,
-                    default: col.default.clone(),
+        self.run_ddl(|engine| {
+            engine.create_table(
+                name,
+                columns,
+                primary_key,
+                foreign_keys,
+                unique_constraints,
+                checks,
+                deferrable_keys,
+                sequences,
+            )
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_table(
+        &mut self,
+        name: &str,
+        mut columns: Vec<Column>,
+        primary_key: Option<Vec<String>>,
+        foreign_keys: Vec<ForeignKeyConstraint>,
+        unique_constraints: Vec<Vec<String>>,
+        checks: Vec<CheckConstraint>,
+        deferrable_keys: Vec<(Vec<String>, Deferrable)>,
+        sequences: Vec<(String, SequenceOptions)>,
+    ) -> Result<(), EngineError> {
+        // Every FK must point at a primary key or unique constraint, so lookups can use its index
+        for fk in &foreign_keys {
+            let is_key = |cols: &Vec<String>| {
+                cols.len() == fk.referenced_columns.len()
+                    && fk.referenced_columns.iter().all(|c| cols.contains(c))
+            };
+            let has_key = if fk.referenced_table == name {
+                primary_key.iter().chain(unique_constraints.iter()).any(is_key)
+            } else {
+                if !self.db.tables.contains_key(&fk.referenced_table) {
+                    return Err(EngineError::Database(format!(
+                        "Referenced table '{}' does not exist",
+                        fk.referenced_table
+                    )));
                 }
-            })
-            .collect();
+                find_index_on(&self.db, &fk.referenced_table, &fk.referenced_columns)
+                    .is_some_and(|idx| idx.unique)
+            };
+            if !has_key {
+                return Err(EngineError::Database(format!(
+                    "there is no unique constraint matching given keys for referenced table '{}' {:?}",
+                    fk.referenced_table, fk.referenced_columns
+                )));
+            }
+        }
 
-        // Create table in catalog (persist to disk)
-        self.cat
-            .create_table(name, cols_meta, primary_key.clone(), foreign_keys.clone())?;
+        if self.db.tables.contains_key(name) || self.db.sequences.contains_key(name) {
+            return Err(CatalogError::TableExists(name.into()).into());
+        }
+        for column in &mut columns {
+            self.db.resolve_column(column)?;
+        }
+        let pk_columns = primary_key.clone();
+        let fk_columns: Vec<Vec<String>> =
+            foreign_keys.iter().map(|fk| fk.local_columns.clone()).collect();
 
-        // Create empty heap file for table
-        let file_path = format!("{DATA_DIR}/{name}.tbl");
-        let heap_file = HeapFile::new(file_path.as_str());
+        // Create empty heap file for table; the catalog learns about it when the DDL commits
+        let file_path = self.new_table_file(name);
+        let heap_file = HeapFile::new(file_path.as_str(), &columns);
+        self.ddl_undo.as_mut().unwrap().created_files.push(heap_file.clone());
 
         // Create table in in-memory DB
         self.db.create_table(
@@ -142,25 +201,387 @@ impl Engine {
             heap_file,
             primary_key,
             foreign_keys,
+            checks,
         )?;
 
+        // Sequences of SERIAL and identity columns, owned by their columns
+        for (sequence_name, options) in sequences {
+            self.create_sequence(&sequence_name, options)?;
+        }
+
+        let key_mode = |cols: &Vec<String>| {
+            deferrable_keys
+                .iter()
+                .find(|(k, _)| k == cols)
+                .map_or(Deferrable::NotDeferrable, |(_, mode)| *mode)
+        };
+
+        // Primary key is enforced through a unique index: <table>_pkey
+        if let Some(cols) = pk_columns {
+            let mode = key_mode(&cols);
+            self.create_index(&format!("{}_pkey", name), name, cols, true, mode)?;
+        }
+
+        // Back every UNIQUE constraint with a unique index: <table>_<cols>_key
+        for cols in unique_constraints {
+            let index_name = format!("{}_{}_key", name, cols.join("_"));
+            let mode = key_mode(&cols);
+            self.create_index(&index_name, name, cols, true, mode)?;
+        }
+
+        // Referencing columns without an index make every parent DELETE scan this table
+        for cols in fk_columns {
+            if find_index_on(&self.db, name, &cols).is_some() {
+                continue;
+            }
+            let index_name = format!("{}_{}_idx", name, cols.join("_"));
+            if self.auto_index_foreign_keys {
+                self.create_index(&index_name, name, cols, false, Deferrable::NotDeferrable)?;
+            } else {
+                println!(
+                    "WARNING: foreign key {}({}) has no index; deletes on the referenced table will scan '{}' (CREATE INDEX {} ON {}({}))",
+                    name,
+                    cols.join(", "),
+                    name,
+                    index_name,
+                    name,
+                    cols.join(", ")
+                );
+            }
+        }
+
         Ok(())
     }
 
+    /// CREATE TYPE name AS ENUM (...); the type and each label get an OID,
+    /// and tuples store the label's
+    pub fn create_enum(&mut self, name: &str, labels: Vec<String>) -> Result<(), EngineError> {
+        self.run_ddl(|engine| {
+            if engine.db.enums.contains_key(name) {
+                return Err(format!("type \"{}\" already exists", name).into());
+            }
+            if labels.is_empty() {
+                return Err(format!("enum type \"{}\" needs at least one label", name).into());
+            }
+            let catalog = engine.cat.catalog_mut();
+            let mut next_oid = || {
+                catalog.next_table_oid += 1;
+                catalog.next_table_oid - 1
+            };
+            let mut ty = EnumType {
+                name: name.to_string(),
+                oid: next_oid(),
+                labels: Vec::new(),
+            };
+            for label in labels {
+                if ty.position(&label).is_some() {
+                    return Err(format!("enum label \"{}\" used more than once", label).into());
+                }
+                ty.labels.push(EnumLabel { oid: next_oid(), label });
+            }
+            engine.db.enums.insert(name.to_string(), Arc::new(ty));
+            Ok(())
+        })
+    }
+
+    /// CREATE SEQUENCE; the sequence gets an OID and a file holding its value
+    pub fn create_sequence_in_both(&mut self, name: &str, options: SequenceOptions) -> Result<(), EngineError> {
+        self.run_ddl(|engine| engine.create_sequence(name, options))
+    }
+
+    fn create_sequence(&mut self, name: &str, options: SequenceOptions) -> Result<(), EngineError> {
+        let catalog = self.cat.catalog_mut();
+        catalog.next_table_oid += 1;
+        let oid = catalog.next_table_oid - 1;
+        let file = PathBuf::from(format!("{DATA_DIR}/{name}_{oid}.seq"));
+        Ok(self.db.create_sequence(name, oid, options, file)?)
+    }
+
+    /// DROP SEQUENCE; like heap files, sequence files are unlinked once the catalog
+    /// no longer lists them
+    pub fn drop_sequences(&mut self, names: Vec<String>, if_exists: bool, cascade: bool) -> Result<(), EngineError> {
+        let mut existing = Vec::new();
+        for name in names {
+            if self.db.sequences.contains_key(&name) {
+                if !existing.contains(&name) {
+                    existing.push(name);
+                }
+            } else if if_exists {
+                println!("NOTICE: sequence \"{}\" does not exist, skipping", name);
+            } else {
+                return Err(format!("sequence \"{}\" does not exist", name).into());
+            }
+        }
+        self.run_ddl(|engine| Ok(engine.db.drop_sequences(&existing, cascade)?))
+    }
+
     /// Create a new index both in catalog (persistent) and in DB (in-memory)
     pub fn create_index_in_both(
         &mut self,
         index_name: &str,
         table_name: &str,
         columns: Vec<String>,
+        unique: bool,
+        deferrable: Deferrable,
+    ) -> Result<(), EngineError> {
+        self.run_ddl(|engine| engine.create_index(index_name, table_name, columns, unique, deferrable))
+    }
+
+    fn create_index(
+        &mut self,
+        index_name: &str,
+        table_name: &str,
+        columns: Vec<String>,
+        unique: bool,
+        deferrable: Deferrable,
     ) -> Result<(), EngineError> {
-        // Register index in catalog
-        self.cat.create_index(index_name, table_name, &columns)?;
-        // Build index in in-memory DB
-        self.db.create_index(index_name, table_name, columns)?;
+        if self.db.indexes.contains_key(index_name) {
+            return Err(CatalogError::IndexExists(index_name.into()).into());
+        }
+        // Build index in in-memory DB: fails on duplicates for UNIQUE
+        self.db.create_index(index_name, table_name, columns, unique)?;
+        if let Some(idx) = self.db.indexes.get_mut(index_name) {
+            idx.deferrable = deferrable;
+        }
         Ok(())
     }
 
+    /// ALTER TABLE; existing tuples are not rewritten
+    pub fn alter_table(&mut self, table_name: &str, action: AlterAction) -> Result<(), EngineError> {
+        self.run_ddl(|engine| Ok(engine.db.alter_table(table_name, action)?))
+    }
+
+    /// DROP TABLE. Heap files are unlinked only after the catalog stops listing them,
+    /// so a crash in between leaves orphaned files, never a table without its file.
+    pub fn drop_tables(
+        &mut self,
+        names: Vec<String>,
+        if_exists: bool,
+        cascade: bool,
+    ) -> Result<(), EngineError> {
+        let mut existing = Vec::new();
+        for name in names {
+            if self.db.tables.contains_key(&name) {
+                if !existing.contains(&name) {
+                    existing.push(name);
+                }
+            } else if if_exists {
+                println!("NOTICE: table \"{}\" does not exist, skipping", name);
+            } else {
+                return Err(format!("Table '{}' doesn't exist", name).into());
+            }
+        }
+
+        self.run_ddl(|engine| {
+            let heaps = engine.db.drop_tables(&existing, cascade)?;
+            engine.ddl_undo.as_mut().unwrap().dropped_files.extend(heaps);
+            Ok(())
+        })
+    }
+
+    /// DROP INDEX; indexes live only in memory and in the catalog
+    pub fn drop_index(&mut self, name: &str, if_exists: bool) -> Result<(), EngineError> {
+        if !self.db.indexes.contains_key(name) && if_exists {
+            println!("NOTICE: index \"{}\" does not exist, skipping", name);
+            return Ok(());
+        }
+        self.run_ddl(|engine| Ok(engine.db.drop_index(name)?))
+    }
+
+    /// TRUNCATE. Each table gets a new empty heap file; the catalog switches to it
+    /// atomically when the DDL commits and only then are the old files unlinked.
+    pub fn truncate(&mut self, names: Vec<String>, cascade: bool) -> Result<(), EngineError> {
+        self.run_ddl(|engine| {
+            let targets = engine.db.truncate_targets(&names, cascade)?;
+            for name in &targets {
+                let path = engine.new_table_file(name);
+                let old_heap = engine.db.truncate_table(name, &path)?;
+                let new_heap = engine.db.tables[name].heap.clone();
+                let undo = engine.ddl_undo.as_mut().unwrap();
+                undo.created_files.push(new_heap);
+                undo.dropped_files.push(old_heap);
+            }
+            Ok(())
+        })
+    }
+
+    /// Run one DDL statement. Inside a transaction block the schema change stays in memory
+    /// until COMMIT writes it to the catalog, and ROLLBACK restores the schema from before
+    /// the transaction's first DDL. Outside one, the change is committed right away.
+    /// A failing statement is undone on its own either way.
+    fn run_ddl<F>(&mut self, ddl: F) -> Result<(), EngineError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), EngineError>,
+    {
+        if self.ddl_undo.is_none() {
+            self.ddl_undo = Some(DdlUndo {
+                tables: self.db.tables.clone(),
+                indexes: self.db.indexes.clone(),
+                enums: self.db.enums.clone(),
+                sequences: self.db.sequences.clone(),
+                created_files: Vec::new(),
+                dropped_files: Vec::new(),
+            });
+        }
+
+        let tables = self.db.tables.clone();
+        let indexes = self.db.indexes.clone();
+        let enums = self.db.enums.clone();
+        let sequences = self.db.sequences.clone();
+        let undo = self.ddl_undo.as_ref().unwrap();
+        let (created, dropped) = (undo.created_files.len(), undo.dropped_files.len());
+
+        if let Err(e) = ddl(self) {
+            self.db.tables = tables;
+            self.db.indexes = indexes;
+            self.db.enums = enums;
+            remove_sequence_files(&self.db.sequences, &sequences);
+            self.db.sequences = sequences;
+            let undo = self.ddl_undo.as_mut().unwrap();
+            for heap in undo.created_files.drain(created..) {
+                remove_heap_files(&heap);
+            }
+            undo.dropped_files.truncate(dropped);
+            if self.current_xid.is_none() {
+                self.ddl_undo = None;
+            }
+            return Err(e);
+        }
+
+        if self.current_xid.is_none() {
+            let undo = self.ddl_undo.take().unwrap();
+            self.write_schema();
+            let written = validate_catalog(self.cat.catalog()).and_then(|_| self.cat.persist());
+            if let Err(e) = written {
+                self.restore_schema(undo);
+                return Err(e.into());
+            }
+            for heap in &undo.dropped_files {
+                remove_heap_files(heap);
+            }
+            remove_sequence_files(&undo.sequences, &self.db.sequences);
+        }
+        Ok(())
+    }
+
+    /// Put back the schema saved before the first DDL of a transaction and
+    /// unlink heap files created since
+    fn restore_schema(&mut self, undo: DdlUndo) {
+        let mut tables = undo.tables;
+        for table in tables.values_mut() {
+            // tuples of the aborted DDL may carry newer schema versions; never reuse them
+            if let Some(current) = self.db.tables.values().find(|t| t.heap.path == table.heap.path) {
+                table.heap.schema_version = table.heap.schema_version.max(current.heap.schema_version);
+            }
+        }
+        self.db.tables = tables;
+        self.db.indexes = undo.indexes;
+        self.db.enums = undo.enums;
+        remove_sequence_files(&self.db.sequences, &undo.sequences);
+        self.db.sequences = undo.sequences;
+        for heap in &undo.created_files {
+            remove_heap_files(heap);
+        }
+    }
+
+    /// Heap file path for a new or truncated table that neither the catalog
+    /// nor any table of the pending DDL uses
+    fn new_table_file(&self, name: &str) -> String {
+        let mut taken: Vec<String> = Vec::new();
+        let mut add = |heap: &HeapFile| taken.push(heap.path.to_string_lossy().into_owned());
+        self.db.tables.values().for_each(|t| add(&t.heap));
+        if let Some(undo) = &self.ddl_undo {
+            undo.tables.values().for_each(|t| add(&t.heap));
+            undo.created_files.iter().for_each(&mut add);
+        }
+        self.cat.table_file(name, &taken)
+    }
+
+    /// Rewrite table and index metadata in the catalog from the in-memory schema.
+    /// Tables keep their catalog entry (and OID) across renames: it is found by heap file.
+    fn write_schema(&mut self) {
+        let catalog = self.cat.catalog_mut();
+        let mut old_tables = std::mem::take(&mut catalog.tables);
+
+        for (name, table) in &self.db.tables {
+            let file = table.heap.path.to_string_lossy().into_owned();
+            let key = if old_tables.contains_key(name) {
+                Some(name.clone())
+            } else {
+                old_tables
+                    .iter()
+                    .find(|(_, tm)| tm.file == file)
+                    .map(|(k, _)| k.clone())
+            };
+            let mut tm = match key.and_then(|k| old_tables.remove(&k)) {
+                Some(tm) => tm,
+                None => {
+                    catalog.next_table_oid += 1;
+                    TableMeta {
+                        oid: catalog.next_table_oid - 1,
+                        file: file.clone(),
+                        columns: Vec::new(),
+                        next_rowid: 1,
+                        primary_key: None,
+                        foreign_keys: Vec::new(),
+                        checks: Vec::new(),
+                        attributes: Vec::new(),
+                        schema_version: 0,
+                    }
+                }
+            };
+
+            tm.file = file;
+            tm.columns = table.columns.iter().map(column_meta).collect();
+            tm.attributes = table
+                .heap
+                .attributes
+                .iter()
+                .map(|a| AttributeMeta {
+                    ty: cat_column_type(&a.column_type),
+                    added_in: a.added_in,
+                    missing: a.missing.clone(),
+                    dropped: a.dropped,
+                })
+                .collect();
+            tm.schema_version = table.heap.schema_version;
+            tm.primary_key = table.primary_key.clone();
+            tm.foreign_keys = table.foreign_keys.clone();
+            tm.checks = table.checks.clone();
+            catalog.tables.insert(name.clone(), tm);
+        }
+
+        catalog.indexes = self
+            .db
+            .indexes
+            .values()
+            .map(|idx| {
+                let meta = IndexMeta {
+                    name: idx.name.clone(),
+                    table: idx.table.clone(),
+                    columns: idx.columns.clone(),
+                    unique: idx.unique,
+                    deferrable: idx.deferrable,
+                };
+                (idx.name.clone(), meta)
+            })
+            .collect();
+
+        catalog.enums = self
+            .db
+            .enums
+            .iter()
+            .map(|(name, ty)| (name.clone(), ty.as_ref().clone()))
+            .collect();
+
+        catalog.sequences = self
+            .db
+            .sequences
+            .iter()
+            .map(|(name, sequence)| (name.clone(), sequence_meta(sequence)))
+            .collect();
+    }
+
     /// Allocate next transaction ID
     pub fn next_xid(&mut self) -> u32 {
         let xid = self.db.transaction_manager.alloc_xid();
@@ -169,8 +590,14 @@ impl Engine {
     }
     
 
+    /// The time now() reports: the start of the open transaction, or the current time
+    pub fn transaction_time(&self) -> i64 {
+        self.tx_start.unwrap_or_else(datetime::clock_timestamp)
+    }
+
     /// Start a new transaction
     pub fn begin_tx(&mut self, xid: u32) {
+        self.tx_start = Some(datetime::clock_timestamp());
         self.db.transaction_manager.begin(xid);
         self.cat.catalog_mut().transactions.insert(xid, TxStatus::InProgress);
         self.cat.persist().unwrap();
@@ -178,16 +605,205 @@ impl Engine {
 
     /// Commit a transaction
     pub fn commit_tx(&mut self, xid: u32) {
+        self.tx_start = None;
         self.db.transaction_manager.commit(xid);
         self.cat.catalog_mut().transactions.insert(xid, TxStatus::Committed);
+        // DDL of the transaction reaches the catalog in the same write as its commit
+        let undo = self.ddl_undo.take();
+        if undo.is_some() {
+            self.write_schema();
+        }
         self.cat.persist().unwrap();
+        if let Some(undo) = &undo {
+            for heap in &undo.dropped_files {
+                remove_heap_files(heap);
+            }
+            remove_sequence_files(&undo.sequences, &self.db.sequences);
+        }
+    }
+
+    /// Run constraint checks deferred to COMMIT, then commit;
+    /// a failed check rolls the transaction back instead
+    pub fn commit_checked(&mut self, xid: u32) -> Result<(), EngineError> {
+        if let Err(e) = self.db.check_deferred(xid) {
+            self.rollback_tx(xid);
+            return Err(format!("{} (transaction {} rolled back)", e, xid).into());
+        }
+        self.commit_tx(xid);
+        Ok(())
     }
 
     /// Rollback a transaction
     pub fn rollback_tx(&mut self, xid: u32) {
+        self.tx_start = None;
+        if let Some(undo) = self.ddl_undo.take() {
+            self.restore_schema(undo);
+        }
         self.db.transaction_manager.rollback(xid);
         self.cat.catalog_mut().transactions.insert(xid, TxStatus::Aborted);
         self.cat.persist().unwrap();
     }
 
 }
+
+impl Drop for Engine {
+    /// Sequences hand out values ahead of their files; a clean shutdown writes the exact ones
+    fn drop(&mut self) {
+        self.db.flush_sequences();
+    }
+}
+
+/// Unlink the files of the sequences in `before` that `after` no longer has
+fn remove_sequence_files(before: &HashMap<String, Sequence>, after: &HashMap<String, Sequence>) {
+    for sequence in before.values() {
+        if after.values().any(|s| s.file == sequence.file) {
+            continue;
+        }
+        if let Err(e) = sequence_file::remove(&sequence.file) {
+            println!("WARNING: could not remove {}: {}", sequence.file.display(), e);
+        }
+    }
+}
+
+/// Unlink a heap that the catalog no longer references; a leftover file is only wasted space
+fn remove_heap_files(heap: &HeapFile) {
+    if let Err(e) = heap.remove_files() {
+        println!("WARNING: could not remove {}: {}", heap.path.display(), e);
+    }
+}
+
+/// In-memory table for a catalog entry; its heap file is attached, not read.
+/// Column types name their ENUM types, which are looked up in `enums`.
+pub fn table_from_meta(name: &str, tm: &TableMeta, enums: &HashMap<String, Arc<EnumType>>) -> Table {
+    let resolve = |ty: ColumnType| ty.resolve(enums).expect("enum type missing from catalog");
+    let columns: Vec<Column> = tm
+        .columns
+        .iter()
+        .map(|c| Column {
+            name: c.name.clone(),
+            column_type: resolve(ColumnType::from_catalog(&c.ty, &c.typmod)),
+            not_null: c.not_null,
+            default: c.default.clone(),
+            attnum: c.attnum,
+            identity: c.identity,
+        })
+        .collect();
+    let attributes: Vec<Attribute> = tm
+        .attributes
+        .iter()
+        .map(|a| Attribute {
+            column_type: resolve(column_type(&a.ty)),
+            added_in: a.added_in,
+            missing: a.missing.clone(),
+            dropped: a.dropped,
+        })
+        .collect();
+
+    Table {
+        name: name.to_string(),
+        columns,
+        heap: HeapFile {
+            path: PathBuf::from(&tm.file), // attach heap file
+            attributes,
+            schema_version: tm.schema_version,
+        },
+        primary_key: tm.primary_key.clone(),
+        foreign_keys: tm.foreign_keys.clone(),
+        checks: tm.checks.clone(),
+    }
+}
+
+/// In-memory sequence for a catalog entry; its state is read from the file separately
+pub fn sequence_from_meta(name: &str, sm: &SequenceMeta) -> Sequence {
+    let options = SequenceOptions {
+        data_type: Some(column_type(&sm.ty)),
+        increment: Some(sm.increment),
+        min_value: Some(sm.min_value),
+        max_value: Some(sm.max_value),
+        start: Some(sm.start),
+        cycle: sm.cycle,
+        owned_by: sm.owned_by.clone(),
+        sequence_name: None,
+    };
+    Sequence::new(name, sm.oid, options, PathBuf::from(&sm.file)).expect("invalid sequence in catalog")
+}
+
+/// In-memory sequence -> catalog SequenceMeta
+fn sequence_meta(sequence: &Sequence) -> SequenceMeta {
+    SequenceMeta {
+        oid: sequence.oid,
+        file: sequence.file.to_string_lossy().into_owned(),
+        ty: cat_column_type(&sequence.data_type),
+        increment: sequence.increment,
+        min_value: sequence.min_value,
+        max_value: sequence.max_value,
+        start: sequence.start,
+        cycle: sequence.cycle,
+        owned_by: sequence.owned_by.clone(),
+    }
+}
+
+/// Catalog column type -> in-memory column type
+fn column_type(ty: &CatColumnType) -> ColumnType {
+    match ty {
+        CatColumnType::Int16 => ColumnType::SmallInt,
+        CatColumnType::Int32 => ColumnType::Int,
+        CatColumnType::Int64 => ColumnType::BigInt,
+        CatColumnType::Float32 => ColumnType::Real,
+        CatColumnType::Float64 => ColumnType::Double,
+        CatColumnType::Numeric => ColumnType::Numeric(None),
+        CatColumnType::Text => ColumnType::Text,
+        CatColumnType::Varchar => ColumnType::Varchar(None),
+        CatColumnType::Char => ColumnType::Char(1),
+        CatColumnType::Bytea => ColumnType::Bytea,
+        CatColumnType::Jsonb => ColumnType::Jsonb,
+        CatColumnType::Uuid => ColumnType::Uuid,
+        CatColumnType::Array(element_type) => ColumnType::Array(Box::new(column_type(element_type))),
+        CatColumnType::Enum(name) => ColumnType::Enum(Arc::new(EnumType::named(name))),
+        CatColumnType::Bool => ColumnType::Bool,
+        CatColumnType::Date => ColumnType::Date,
+        CatColumnType::Time => ColumnType::Time,
+        CatColumnType::Timestamp => ColumnType::Timestamp,
+        CatColumnType::TimestampTz => ColumnType::TimestampTz,
+        CatColumnType::Interval => ColumnType::Interval,
+    }
+}
+
+/// In-memory column type -> catalog column type
+fn cat_column_type(ty: &ColumnType) -> CatColumnType {
+    match ty {
+        ColumnType::SmallInt => CatColumnType::Int16,
+        ColumnType::Int => CatColumnType::Int32,
+        ColumnType::BigInt => CatColumnType::Int64,
+        ColumnType::Real => CatColumnType::Float32,
+        ColumnType::Double => CatColumnType::Float64,
+        ColumnType::Numeric(_) => CatColumnType::Numeric,
+        ColumnType::Text => CatColumnType::Text,
+        ColumnType::Varchar(_) => CatColumnType::Varchar,
+        ColumnType::Char(_) => CatColumnType::Char,
+        ColumnType::Bytea => CatColumnType::Bytea,
+        ColumnType::Jsonb => CatColumnType::Jsonb,
+        ColumnType::Uuid => CatColumnType::Uuid,
+        ColumnType::Array(element_type) => CatColumnType::Array(Box::new(cat_column_type(element_type))),
+        ColumnType::Enum(ty) => CatColumnType::Enum(ty.name.clone()),
+        ColumnType::Bool => CatColumnType::Bool,
+        ColumnType::Date => CatColumnType::Date,
+        ColumnType::Time => CatColumnType::Time,
+        ColumnType::Timestamp => CatColumnType::Timestamp,
+        ColumnType::TimestampTz => CatColumnType::TimestampTz,
+        ColumnType::Interval => CatColumnType::Interval,
+    }
+}
+
+/// In-memory Column -> catalog ColumnMeta
+fn column_meta(col: &Column) -> ColumnMeta {
+    ColumnMeta {
+        name: col.name.clone(),
+        ty: cat_column_type(&col.column_type),
+        not_null: col.not_null,
+        default: col.default.clone(),
+        attnum: col.attnum,
+        typmod: col.column_type.typmod(),
+        identity: col.identity,
+    }
+}
@@ -20,6 +20,9 @@ pub enum EvalError {
     /// Example: trying to use `<` on a BOOL column.
     InvalidOpForType { ty: ValueType, op: CmpOp },
 
+    /// A function or operator failed, e.g. `date_trunc("fortnight", ts)` or a date out of range.
+    Function(String),
+
     /// Internal consistency error — indicates a bug or unexpected state.
     Internal(&'static str),
 }
@@ -36,6 +39,7 @@ impl fmt::Display for EvalError {
             EvalError::InvalidOpForType { ty, op } => {
                 write!(f, "invalid operator {} for type {}", op, ty)
             }
+            EvalError::Function(msg) => write!(f, "{}", msg),
             EvalError::Internal(msg) => write!(f, "internal error: {}", msg),
         }
     }
@@ -0,0 +1,446 @@
+use crate::executer::filter::eval_condition_3vl;
+use crate::executer::functions::is_volatile;
+use crate::executer::help_functions::{default_value, find_index_on, lookup_rows, missing_key_error};
+use crate::executer::join::JoinTableColumn;
+use crate::types::parser_types::{AlterAction, TableConstraint};
+use crate::types::storage_types::{Attribute, Database, Deferrable, ForeignKeyConstraint, Table, Value};
+
+/// Name of a foreign key of `table_name`: the one it was given, or
+/// PostgreSQL's default `<table>_<cols>_fkey`
+pub fn fk_name(table_name: &str, fk: &ForeignKeyConstraint) -> String {
+    fk.name
+        .clone()
+        .unwrap_or_else(|| format!("{}_{}_fkey", table_name, fk.local_columns.join("_")))
+}
+
+impl Database {
+    /// Apply one ALTER TABLE action to the in-memory schema.
+    /// Existing tuples are never rewritten: added columns are read through the
+    /// heap's attribute list, dropped ones keep their physical slot.
+    /// On error the table and its indexes are left exactly as they were.
+    pub fn alter_table(&mut self, table_name: &str, action: AlterAction) -> Result<(), String> {
+        if !self.tables.contains_key(table_name) {
+            return Err(format!("Table '{}' doesn't exist", table_name));
+        }
+
+        let saved_tables = self.tables.clone();
+        let saved_indexes = self.indexes.clone();
+        let saved_sequences = self.sequences.clone();
+        let result = self.apply_alter(table_name, action);
+        if result.is_err() {
+            self.tables = saved_tables;
+            self.indexes = saved_indexes;
+            self.sequences = saved_sequences;
+        }
+        result
+    }
+
+    fn apply_alter(&mut self, table_name: &str, action: AlterAction) -> Result<(), String> {
+        match action {
+            AlterAction::AddColumn { mut column, constraints } => {
+                self.resolve_column(&mut column)?;
+                // Old tuples read the default without being rewritten, so it is evaluated once;
+                // a volatile one is not evaluated at all, as only an empty table can take it
+                let volatile = column.default.as_ref().is_some_and(is_volatile);
+                let missing = if volatile { Value::Null } else { default_value(self, &column)? };
+                let table = self.tables.get_mut(table_name).unwrap();
+                if table.columns.iter().any(|c| c.name == column.name) {
+                    return Err(format!(
+                        "column '{}' of relation '{}' already exists",
+                        column.name, table_name
+                    ));
+                }
+
+                if (column.not_null && missing == Value::Null) || volatile {
+                    let has_rows = table
+                        .heap
+                        .scan_all(&table.columns)
+                        .iter()
+                        .any(|(_, _, header, _)| !header.is_dead(&self.transaction_manager));
+                    if has_rows && volatile {
+                        return Err(format!(
+                            "cannot add column '{}' to non-empty relation '{}': its DEFAULT differs per row",
+                            column.name, table_name
+                        ));
+                    }
+                    if has_rows {
+                        return Err(format!(
+                            "column '{}' of relation '{}' contains null values",
+                            column.name, table_name
+                        ));
+                    }
+                }
+
+                table.heap.schema_version += 1;
+                table.heap.attributes.push(Attribute {
+                    column_type: column.column_type.clone(),
+                    added_in: table.heap.schema_version,
+                    missing,
+                    dropped: false,
+                });
+                column.attnum = table.heap.attributes.len() - 1;
+                table.columns.push(column);
+
+                for constraint in constraints {
+                    self.add_constraint(table_name, constraint)?;
+                }
+            }
+
+            AlterAction::DropColumn(name) => {
+                let table = self.tables.get(table_name).unwrap();
+                let pos = column_position(table, &name)?;
+                if table.columns.len() == 1 {
+                    return Err(format!(
+                        "cannot drop column '{}': '{}' would have no columns",
+                        name, table_name
+                    ));
+                }
+                for other in self.tables.values() {
+                    for fk in &other.foreign_keys {
+                        if fk.referenced_table == table_name
+                            && fk.referenced_columns.contains(&name)
+                            && !(other.name == table_name && fk.local_columns.contains(&name))
+                        {
+                            return Err(format!(
+                                "cannot drop column '{}' of '{}': foreign key {} of '{}' depends on it",
+                                name,
+                                table_name,
+                                fk_name(&other.name, fk),
+                                other.name
+                            ));
+                        }
+                    }
+                }
+
+                // Constraints and indexes on the column go with it
+                self.indexes
+                    .retain(|_, idx| !(idx.table == table_name && idx.columns.contains(&name)));
+                let table = self.tables.get_mut(table_name).unwrap();
+                if table.primary_key.as_ref().is_some_and(|pk| pk.contains(&name)) {
+                    table.primary_key = None;
+                }
+                table.foreign_keys.retain(|fk| !fk.local_columns.contains(&name));
+                table.checks.retain(|check| {
+                    let mut cols = Vec::new();
+                    check.condition.column_names(&mut cols);
+                    !cols.contains(&name)
+                });
+
+                let column = table.columns.remove(pos);
+                table.heap.attributes[column.attnum].dropped = true;
+                self.drop_owned_sequences(table_name, Some(&name));
+            }
+
+            AlterAction::RenameColumn { from, to } => {
+                let table = self.tables.get_mut(table_name).unwrap();
+                let pos = column_position(table, &from)?;
+                if table.columns.iter().any(|c| c.name == to) {
+                    return Err(format!(
+                        "column '{}' of relation '{}' already exists",
+                        to, table_name
+                    ));
+                }
+                table.columns[pos].name = to.clone();
+
+                let rename = |cols: &mut Vec<String>| {
+                    for c in cols.iter_mut().filter(|c| **c == from) {
+                        *c = to.clone();
+                    }
+                };
+                if let Some(pk) = table.primary_key.as_mut() {
+                    rename(pk);
+                }
+                for fk in table.foreign_keys.iter_mut() {
+                    rename(&mut fk.local_columns);
+                }
+                for check in table.checks.iter_mut() {
+                    check.condition.rename_column(&from, &to);
+                }
+                for other in self.tables.values_mut() {
+                    for fk in other.foreign_keys.iter_mut() {
+                        if fk.referenced_table == table_name {
+                            rename(&mut fk.referenced_columns);
+                        }
+                    }
+                }
+                for idx in self.indexes.values_mut() {
+                    if idx.table == table_name {
+                        rename(&mut idx.columns);
+                    }
+                }
+                for sequence in self.sequences.values_mut() {
+                    if let Some((t, c)) = sequence.owned_by.as_mut()
+                        && t == table_name
+                        && *c == from
+                    {
+                        *c = to.clone();
+                    }
+                }
+            }
+
+            AlterAction::RenameTable(new_name) => {
+                if self.tables.contains_key(&new_name) || self.sequences.contains_key(&new_name) {
+                    return Err(format!("Table '{}' already exists", new_name));
+                }
+                // The heap file keeps its path; only the name changes
+                let mut table = self.tables.remove(table_name).unwrap();
+                table.name = new_name.clone();
+                self.tables.insert(new_name.clone(), table);
+
+                for other in self.tables.values_mut() {
+                    for fk in other.foreign_keys.iter_mut() {
+                        if fk.referenced_table == table_name {
+                            fk.referenced_table = new_name.clone();
+                        }
+                    }
+                }
+                for idx in self.indexes.values_mut() {
+                    if idx.table == table_name {
+                        idx.table = new_name.clone();
+                    }
+                }
+                for sequence in self.sequences.values_mut() {
+                    if let Some((t, _)) = sequence.owned_by.as_mut()
+                        && t == table_name
+                    {
+                        *t = new_name.clone();
+                    }
+                }
+            }
+
+            AlterAction::SetNotNull(name) => {
+                let table = self.tables.get(table_name).unwrap();
+                let pos = column_position(table, &name)?;
+                let has_null = table.heap.scan_all(&table.columns).iter().any(|(_, _, header, row)| {
+                    !header.is_dead(&self.transaction_manager) && row.values[pos] == Value::Null
+                });
+                if has_null {
+                    return Err(format!(
+                        "column '{}' of relation '{}' contains null values",
+                        name, table_name
+                    ));
+                }
+                self.tables.get_mut(table_name).unwrap().columns[pos].not_null = true;
+            }
+
+            AlterAction::DropNotNull(name) => {
+                let table = self.tables.get_mut(table_name).unwrap();
+                let pos = column_position(table, &name)?;
+                if table.primary_key.as_ref().is_some_and(|pk| pk.contains(&name)) {
+                    return Err(format!("column '{}' is in a primary key", name));
+                }
+                table.columns[pos].not_null = false;
+            }
+
+            AlterAction::SetDefault { column, default } => {
+                let table = self.tables.get_mut(table_name).unwrap();
+                let pos = column_position(table, &column)?;
+                if table.columns[pos].identity.is_some() {
+                    return Err(format!(
+                        "column '{}' of relation '{}' is an identity column",
+                        column, table_name
+                    ));
+                }
+                let default = default
+                    .map(|value| table.columns[pos].column_type.check_default(&column, value))
+                    .transpose()?;
+                table.columns[pos].default = default;
+            }
+
+            AlterAction::AddConstraint(constraint) => self.add_constraint(table_name, constraint)?,
+
+            AlterAction::DropConstraint(name) => self.drop_constraint(table_name, &name)?,
+        }
+        Ok(())
+    }
+
+    /// Add a constraint after validating it against every tuple that is not dead
+    fn add_constraint(&mut self, table_name: &str, constraint: TableConstraint) -> Result<(), String> {
+        match constraint {
+            TableConstraint::PrimaryKey { name, columns, deferrable } => {
+                let table = self.tables.get(table_name).unwrap();
+                if table.primary_key.is_some() {
+                    return Err(format!(
+                        "multiple primary keys for table '{}' are not allowed",
+                        table_name
+                    ));
+                }
+                let mut positions = Vec::new();
+                for col in &columns {
+                    positions.push(column_position(table, col)?);
+                }
+                let has_null = table.heap.scan_all(&table.columns).iter().any(|(_, _, header, row)| {
+                    !header.is_dead(&self.transaction_manager)
+                        && positions.iter().any(|&p| row.values[p] == Value::Null)
+                });
+                if has_null {
+                    return Err(format!(
+                        "primary key column of relation '{}' contains null values",
+                        table_name
+                    ));
+                }
+
+                let index_name = name.unwrap_or_else(|| format!("{}_pkey", table_name));
+                self.add_unique_index(&index_name, table_name, columns.clone(), deferrable)?;
+
+                let table = self.tables.get_mut(table_name).unwrap();
+                for &p in &positions {
+                    table.columns[p].not_null = true;
+                }
+                table.primary_key = Some(columns);
+            }
+
+            TableConstraint::Unique { name, columns, deferrable } => {
+                let index_name =
+                    name.unwrap_or_else(|| format!("{}_{}_key", table_name, columns.join("_")));
+                self.add_unique_index(&index_name, table_name, columns, deferrable)?;
+            }
+
+            TableConstraint::ForeignKey(fk) => {
+                let table = self.tables.get(table_name).unwrap();
+                let mut positions = Vec::new();
+                for col in &fk.local_columns {
+                    positions.push(column_position(table, col)?);
+                }
+                let name = fk_name(table_name, &fk);
+                if table.foreign_keys.iter().any(|other| fk_name(table_name, other) == name)
+                    || table.checks.iter().any(|c| c.name == name)
+                    || self.indexes.contains_key(&name)
+                {
+                    return Err(format!("constraint '{}' already exists", name));
+                }
+
+                let parent = self.tables.get(&fk.referenced_table).ok_or_else(|| {
+                    format!("Referenced table '{}' does not exist", fk.referenced_table)
+                })?;
+                if !find_index_on(self, &fk.referenced_table, &fk.referenced_columns)
+                    .is_some_and(|idx| idx.unique)
+                {
+                    return Err(format!(
+                        "there is no unique constraint matching given keys for referenced table '{}' {:?}",
+                        fk.referenced_table, fk.referenced_columns
+                    ));
+                }
+
+                // Every existing non-NULL key needs a parent row that is not dead
+                let tm = &self.transaction_manager;
+                for (_, _, header, row) in table.heap.scan_all(&table.columns) {
+                    if header.is_dead(tm) {
+                        continue;
+                    }
+                    let key: Vec<Value> = positions.iter().map(|&p| row.values[p].clone()).collect();
+                    if key.contains(&Value::Null) {
+                        continue;
+                    }
+                    let found = lookup_rows(self, parent, &fk.referenced_columns, &key)?
+                        .iter()
+                        .any(|(_, _, h, _)| !h.is_dead(tm));
+                    if !found {
+                        return Err(missing_key_error(table_name, &fk, &key));
+                    }
+                }
+
+                self.tables.get_mut(table_name).unwrap().foreign_keys.push(fk);
+            }
+
+            TableConstraint::Check(check) => {
+                let table = self.tables.get(table_name).unwrap();
+                if table.checks.iter().any(|c| c.name == check.name) {
+                    return Err(format!("constraint '{}' already exists", check.name));
+                }
+                let mut cols = Vec::new();
+                check.condition.column_names(&mut cols);
+                for col in &cols {
+                    column_position(table, col)?;
+                }
+
+                let metas: Vec<JoinTableColumn> = table
+                    .columns
+                    .iter()
+                    .map(|c| JoinTableColumn {
+                        table_alias: table_name.to_string(),
+                        column_name: c.name.clone(),
+                        column_type: c.column_type.clone(),
+                    })
+                    .collect();
+                for (_, _, header, row) in table.heap.scan_all(&table.columns) {
+                    if header.is_dead(&self.transaction_manager) {
+                        continue;
+                    }
+                    let passed = eval_condition_3vl(&check.condition, &row, &metas)
+                        .map_err(|e| format!("check constraint '{}': {}", check.name, e))?;
+                    if passed == Some(false) {
+                        return Err(format!(
+                            "check constraint '{}' of relation '{}' is violated by some row",
+                            check.name, table_name
+                        ));
+                    }
+                }
+
+                self.tables.get_mut(table_name).unwrap().checks.push(check);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_unique_index(
+        &mut self,
+        index_name: &str,
+        table_name: &str,
+        columns: Vec<String>,
+        deferrable: Deferrable,
+    ) -> Result<(), String> {
+        self.create_index(index_name, table_name, columns, true)
+            .map_err(|e| e.to_string())?;
+        self.indexes.get_mut(index_name).unwrap().deferrable = deferrable;
+        Ok(())
+    }
+
+    /// Drop a CHECK constraint, a UNIQUE / PRIMARY KEY index, or a foreign key by name
+    fn drop_constraint(&mut self, table_name: &str, name: &str) -> Result<(), String> {
+        let table = self.tables.get_mut(table_name).unwrap();
+
+        if let Some(pos) = table.checks.iter().position(|c| c.name == name) {
+            table.checks.remove(pos);
+            return Ok(());
+        }
+
+        if let Some(pos) = table
+            .foreign_keys
+            .iter()
+            .position(|fk| fk_name(table_name, fk) == name)
+        {
+            table.foreign_keys.remove(pos);
+            return Ok(());
+        }
+
+        let Some(idx) = self
+            .indexes
+            .get(name)
+            .filter(|idx| idx.table == table_name && idx.unique)
+        else {
+            return Err(format!(
+                "constraint '{}' of relation '{}' does not exist",
+                name, table_name
+            ));
+        };
+
+        let columns = idx.columns.clone();
+        self.check_key_dependents(name)?;
+
+        self.indexes.remove(name);
+        let table = self.tables.get_mut(table_name).unwrap();
+        if table.primary_key.as_ref() == Some(&columns) {
+            table.primary_key = None;
+        }
+        Ok(())
+    }
+}
+
+fn column_position(table: &Table, name: &str) -> Result<usize, String> {
+    table
+        .columns
+        .iter()
+        .position(|c| c.name == name)
+        .ok_or_else(|| format!("column '{}' of relation '{}' does not exist", name, table.name))
+}
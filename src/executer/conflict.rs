@@ -0,0 +1,206 @@
+use crate::executer::filter::{eval_condition, eval_operand};
+use crate::executer::help_functions::build_key;
+use crate::executer::join::JoinTableColumn;
+use crate::types::parser_types::{ConflictAction, OnConflict, Operand};
+use crate::executer::update::check_assignment;
+use crate::types::storage_types::{Database, Deferrable, Row, Table, Value};
+use crate::types::transaction_types::{Snapshot, TxStatus};
+use std::collections::BTreeSet;
+
+/// Keys of the arbiter indexes already written by the current statement
+pub type AffectedKeys = BTreeSet<(String, Vec<Value>)>;
+
+/// Position and current values of the row a proposed row conflicts with
+pub type Conflict = ((usize, usize), Row);
+
+/// Column metadata for a row under `alias`
+fn alias_meta(alias: &str, table: &Table) -> Vec<JoinTableColumn> {
+    table
+        .columns
+        .iter()
+        .map(|c| JoinTableColumn {
+            table_alias: alias.to_string(),
+            column_name: c.name.clone(),
+            column_type: c.column_type.clone(),
+        })
+        .collect()
+}
+
+impl Database {
+    /// Unique indexes that decide whether a row conflicts: those on exactly the
+    /// target columns, or every unique index of the table when no target is given
+    pub fn conflict_arbiters(&self, table: &Table, on_conflict: &OnConflict) -> Result<Vec<String>, String> {
+        let mut arbiters: Vec<&str> = Vec::new();
+        for idx in self.indexes.values() {
+            if !idx.unique || idx.table != table.name {
+                continue;
+            }
+            if let Some(target) = &on_conflict.target {
+                let same_columns = target.len() == idx.columns.len()
+                    && target.iter().all(|c| idx.columns.contains(c));
+                if !same_columns {
+                    continue;
+                }
+                if idx.deferrable != Deferrable::NotDeferrable {
+                    return Err(format!(
+                        "ON CONFLICT does not support deferrable unique constraint '{}' as arbiter",
+                        idx.name
+                    ));
+                }
+            } else if idx.deferrable != Deferrable::NotDeferrable {
+                continue;
+            }
+            arbiters.push(&idx.name);
+        }
+
+        if arbiters.is_empty() {
+            return Err(format!(
+                "there is no unique constraint on '{}' matching the ON CONFLICT specification",
+                table.name
+            ));
+        }
+        arbiters.sort();
+        Ok(arbiters.into_iter().map(String::from).collect())
+    }
+
+    /// Arbiter keys of a row; keys containing NULL never conflict and are left out
+    pub fn arbiter_keys(
+        &self,
+        table: &Table,
+        arbiters: &[String],
+        values: &[Value],
+    ) -> Result<Vec<(String, Vec<Value>)>, String> {
+        let mut keys = Vec::new();
+        for name in arbiters {
+            let idx = &self.indexes[name];
+            let key = build_key(&idx.columns, &table.columns, values, &table.name)?;
+            if !key.iter().any(|v| matches!(v, Value::Null)) {
+                keys.push((name.clone(), key));
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Find the existing row a proposed row conflicts with. Like unique checks, rows of
+    /// other in-progress transactions count; since there is no waiting, inserts or deletes
+    /// still in flight, and committed rows this snapshot cannot see, are errors.
+    pub fn find_conflict(
+        &self,
+        table: &Table,
+        arbiters: &[String],
+        values: &[Value],
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<Option<Conflict>, String> {
+        let tm = &self.transaction_manager;
+        for (name, key) in self.arbiter_keys(table, arbiters, values)? {
+            let Some(positions) = self.indexes[&name].search_eq(&key) else {
+                continue;
+            };
+            for &(page_no, slot_no) in positions {
+                let Some((header, row)) = table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
+                else {
+                    continue;
+                };
+                if header.is_dead(tm) || header.xmax == Some(xid) {
+                    continue;
+                }
+                if header.xmin != xid && tm.status(header.xmin) == TxStatus::InProgress {
+                    return Err(format!(
+                        "duplicate key value violates unique constraint '{}': key {:?} is being inserted by transaction {}",
+                        name, key, header.xmin
+                    ));
+                }
+                if let Some(xmax) = header.xmax
+                    && tm.status(xmax) == TxStatus::InProgress
+                {
+                    return Err(format!(
+                        "could not serialize access: conflicting row with key {:?} is being changed by transaction {}",
+                        key, xmax
+                    ));
+                }
+                if !header.is_visible(xid, snapshot, tm) {
+                    return Err("could not serialize access due to concurrent update".into());
+                }
+                return Ok(Some(((page_no, slot_no), row)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// ON CONFLICT DO UPDATE for one existing row. Returns the new version,
+    /// or None when the action's WHERE rejects the row.
+    #[allow(clippy::too_many_arguments)]
+    pub fn conflict_update(
+        &mut self,
+        table: &Table,
+        (page_no, slot_no): (usize, usize),
+        existing: Row,
+        proposed: Vec<Value>,
+        action: &ConflictAction,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<Option<Row>, String> {
+        let ConflictAction::Update {
+            column_names,
+            values,
+            filter,
+        } = action
+        else {
+            return Ok(None);
+        };
+        let excluded = Row { values: proposed };
+        let metas = alias_meta(&table.name, table);
+        let excluded_metas = alias_meta("excluded", table);
+
+        if let Some(cond) = filter {
+            let keep = eval_condition(cond, &existing, &metas, Some(&excluded), Some(&excluded_metas))
+                .map_err(|e| e.to_string())?;
+            if !keep {
+                return Ok(None);
+            }
+        }
+
+        // Every assignment reads the row as it was before this update
+        let column_position = |name: &str| {
+            let bare = match name.split_once('.') {
+                Some((t, c)) if t == table.name => c,
+                _ => name,
+            };
+            table.columns.iter().position(|c| c.name == bare)
+        };
+        let mut new_values = existing.values.clone();
+        for (name, operand) in column_names.iter().zip(values) {
+            let i = column_position(name)
+                .ok_or_else(|| format!("Unknown column '{}' for table '{}'", name, table.name))?;
+            let value = match operand {
+                Operand::Literal(v) => v.clone(),
+                Operand::Column(col) => match col.split_once('.') {
+                    Some(("excluded", c)) => table
+                        .columns
+                        .iter()
+                        .position(|column| column.name == c)
+                        .map(|j| excluded.values[j].clone()),
+                    _ => column_position(col).map(|j| existing.values[j].clone()),
+                }
+                .ok_or_else(|| format!("Unknown column '{}' in ON CONFLICT DO UPDATE", col))?,
+                expr => eval_operand(&*self.bind_sequences(expr)?, &existing, &metas, Some(&excluded), Some(&excluded_metas))
+                    .map_err(|e| e.to_string())?
+                    .into_owned(),
+            };
+
+            new_values[i] = check_assignment(&table.columns[i], i, value)?;
+        }
+
+        self.update_row_version(
+            table,
+            page_no as u32,
+            slot_no,
+            &existing.values,
+            new_values.clone(),
+            xid,
+            snapshot,
+        )?;
+        Ok(Some(Row { values: new_values }))
+    }
+}
@@ -0,0 +1,314 @@
+use crate::types::parser_types::{CopyFormat, CopyOptions, Overriding};
+use crate::types::numeric::{Float, Numeric};
+use crate::types::storage_types::{Column, ColumnType, Database, Row, Value};
+use crate::types::transaction_types::Snapshot;
+
+/// Split COPY data into records of fields; `None` is a NULL field
+pub fn decode_records(data: &str, options: &CopyOptions) -> Result<Vec<Vec<Option<String>>>, String> {
+    match options.format {
+        CopyFormat::Text => Ok(data
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| decode_text_line(line, options))
+            .collect()),
+        CopyFormat::Csv => decode_csv(data, options),
+    }
+}
+
+/// One line of text format: fields split on unescaped delimiters, then unescaped.
+/// A field is NULL when its raw text equals the NULL string.
+fn decode_text_line(line: &str, options: &CopyOptions) -> Vec<Option<String>> {
+    let mut raw_fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            current.push(c);
+            if let Some(next) = chars.next() {
+                current.push(next);
+            }
+        } else if c == options.delimiter {
+            raw_fields.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    raw_fields.push(current);
+
+    raw_fields
+        .into_iter()
+        .map(|raw| (raw != options.null).then(|| unescape_text(&raw)))
+        .collect()
+}
+
+fn unescape_text(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('v') => out.push('\u{b}'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// CSV records: quoted fields may hold delimiters, newlines and doubled quotes.
+/// Only an unquoted field equal to the NULL string is NULL.
+fn decode_csv(data: &str, options: &CopyOptions) -> Result<Vec<Vec<Option<String>>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false; // current field had quotes
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut chars = data.chars().peekable();
+
+    let finish_field = |field: &mut String, quoted: &mut bool, record: &mut Vec<Option<String>>| {
+        let value = std::mem::take(field);
+        record.push((*quoted || value != options.null).then_some(value));
+        *quoted = false;
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == options.quote {
+                if chars.peek() == Some(&options.quote) {
+                    chars.next();
+                    field.push(c);
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        } else if c == options.quote {
+            in_quotes = true;
+            quoted = true;
+        } else if c == options.delimiter {
+            finish_field(&mut field, &mut quoted, &mut record);
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            // a line with nothing on it is not a record
+            if !(record.is_empty() && field.is_empty() && !quoted) {
+                finish_field(&mut field, &mut quoted, &mut record);
+                records.push(std::mem::take(&mut record));
+            }
+            line += 1;
+        } else {
+            field.push(c);
+        }
+    }
+    if in_quotes {
+        return Err(format!("unterminated CSV quoted field at line {}", line));
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        finish_field(&mut field, &mut quoted, &mut record);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Render one record (without the line break)
+pub fn encode_record(fields: &[Option<String>], options: &CopyOptions) -> String {
+    let delimiter = options.delimiter.to_string();
+    fields
+        .iter()
+        .map(|field| match (field, options.format) {
+            (None, _) => options.null.clone(),
+            (Some(s), CopyFormat::Text) => escape_text(s, options.delimiter),
+            (Some(s), CopyFormat::Csv) => {
+                let q = options.quote;
+                let needs_quotes = s == &options.null
+                    || s.contains([options.delimiter, q, '\n', '\r']);
+                if needs_quotes {
+                    format!("{q}{}{q}", s.replace(q, &format!("{q}{q}")))
+                } else {
+                    s.clone()
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter)
+}
+
+fn escape_text(s: &str, delimiter: char) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == delimiter => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Text form of a value as COPY TO writes it
+pub fn format_field(value: &Value) -> Option<String> {
+    match value {
+        Value::Int(n) => Some(n.to_string()),
+        Value::Text(s) => Some(s.clone()),
+        Value::Bool(b) => Some(if *b { "t" } else { "f" }.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::Numeric(d) => Some(d.to_string()),
+        Value::Date(_)
+        | Value::Time(_)
+        | Value::Timestamp(_)
+        | Value::TimestampTz(_)
+        | Value::Interval(_)
+        | Value::Bytea(_)
+        | Value::Jsonb(_)
+        | Value::Uuid(_)
+        | Value::Array(_)
+        | Value::Enum(..) => Some(value.to_string()),
+        Value::Null => None,
+    }
+}
+
+/// Value of a COPY FROM field for `column`
+fn parse_field(field: Option<String>, column: &Column) -> Result<Value, String> {
+    let Some(text) = field else {
+        return Ok(Value::Null);
+    };
+    let invalid = || format!("invalid input for {} column '{}': \"{}\"", column.column_type, column.name, text);
+    match column.column_type {
+        ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => {
+            let value = text.trim().parse::<i64>().map(Value::Int).map_err(|_| invalid())?;
+            column.column_type.coerce(value)
+        }
+        ColumnType::Real | ColumnType::Double => {
+            let value = Float::parse(&text).map(Value::Float).ok_or_else(invalid)?;
+            column.column_type.coerce(value)
+        }
+        ColumnType::Numeric(_) => {
+            let value = Numeric::parse(&text).map(Value::Numeric).ok_or_else(invalid)?;
+            column.column_type.coerce(value)
+        }
+        ColumnType::Bool => match text.trim().to_ascii_lowercase().as_str() {
+            "t" | "true" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+            "f" | "false" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("invalid input for boolean column '{}': \"{}\"", column.name, text)),
+        },
+        ColumnType::Text => Ok(Value::Text(text)),
+        ColumnType::Varchar(_)
+        | ColumnType::Char(_)
+        | ColumnType::Bytea
+        | ColumnType::Jsonb
+        | ColumnType::Uuid
+        | ColumnType::Array(_)
+        | ColumnType::Enum(_) => {
+            column.column_type.coerce(Value::Text(text))
+        }
+        ColumnType::Date | ColumnType::Time | ColumnType::Timestamp | ColumnType::TimestampTz | ColumnType::Interval => {
+            column.column_type.parse_temporal(&text).map_err(|_| invalid())
+        }
+    }
+}
+
+/// COPY TO output: a header (when requested), then one record per row
+pub fn encode_copy(columns: &[String], rows: &[Row], options: &CopyOptions) -> String {
+    let mut out = String::new();
+    if options.header {
+        let names: Vec<Option<String>> = columns.iter().map(|c| Some(c.clone())).collect();
+        out.push_str(&encode_record(&names, options));
+        out.push('\n');
+    }
+    for row in rows {
+        let fields: Vec<Option<String>> = row.values.iter().map(format_field).collect();
+        out.push_str(&encode_record(&fields, options));
+        out.push('\n');
+    }
+    out
+}
+
+impl Database {
+    /// COPY table [(cols)] FROM ...: converts every field of `data` (the file contents or the
+    /// inline STDIN lines) to its column's type and loads the rows through the batched insert
+    /// path. Returns the row count.
+    pub fn copy_from(
+        &mut self,
+        table_name: &str,
+        column_names: Option<Vec<String>>,
+        data: &str,
+        options: &CopyOptions,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<usize, String> {
+        let table = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+        let columns: Vec<&Column> = match &column_names {
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    table.columns.iter().find(|c| c.name == *name).ok_or_else(|| {
+                        format!("There is no '{}' column in table '{}'", name, table_name)
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            None => table.columns.iter().collect(),
+        };
+
+        let mut records = decode_records(data, options)?;
+        if options.header && !records.is_empty() {
+            records.remove(0);
+        }
+
+        let mut rows = Vec::with_capacity(records.len());
+        for (n, record) in records.into_iter().enumerate() {
+            // data lines are counted from 1, after the header
+            let line = n + 1 + options.header as usize;
+            if record.len() != columns.len() {
+                return Err(format!(
+                    "COPY {}, line {}: expected {} columns, got {}",
+                    table_name,
+                    line,
+                    columns.len(),
+                    record.len()
+                ));
+            }
+            let values = record
+                .into_iter()
+                .zip(&columns)
+                .map(|(field, column)| parse_field(field, column))
+                .collect::<Result<Vec<Value>, String>>()
+                .map_err(|e| format!("COPY {}, line {}: {}", table_name, line, e))?;
+            rows.push(values);
+        }
+
+        // COPY restores identity values as given, as pg_dump output expects
+        let inserted = self.insert_into(
+            table_name,
+            column_names,
+            rows,
+            Some(Overriding::SystemValue),
+            None,
+            xid,
+            snapshot,
+        )?;
+        Ok(inserted.len())
+    }
+}
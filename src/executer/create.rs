@@ -1,6 +1,6 @@
 use crate::errors::engine_error::EngineError;
 use crate::storage::heap_file::HeapFile;
-use crate::types::storage_types::{Column, Table};
+use crate::types::storage_types::{CheckConstraint, Column, Table};
 use crate::types::storage_types::{Database, ForeignKeyConstraint};
 
 impl Database {
@@ -10,8 +10,9 @@ impl Database {
         name: &str,
         columns: Vec<Column>,
         heap_file: HeapFile,
-        primary_key: Option<String>,
+        primary_key: Option<Vec<String>>,
         foreign_keys: Vec<ForeignKeyConstraint>,
+        checks: Vec<CheckConstraint>,
     ) -> Result<(), EngineError> {
         // Check if table already exists
         if self.tables.contains_key(name) {
@@ -27,6 +28,7 @@ impl Database {
             heap: heap_file,
             primary_key,
             foreign_keys,
+            checks,
         };
 
         // Insert table into database
@@ -34,4 +36,15 @@ impl Database {
 
         Ok(())
     }
+
+    /// Look up the ENUM types a new column names and check its DEFAULT against them
+    pub fn resolve_column(&self, column: &mut Column) -> Result<(), String> {
+        column.column_type = column.column_type.resolve(&self.enums)?;
+        column.default = column
+            .default
+            .take()
+            .map(|default| column.column_type.check_default(&column.name, default))
+            .transpose()?;
+        Ok(())
+    }
 }
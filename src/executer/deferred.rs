@@ -0,0 +1,89 @@
+use crate::executer::help_functions::{
+    check_foreign_key, check_unique_index, lookup_rows, still_referenced_error,
+};
+use crate::types::storage_types::Database;
+use crate::types::transaction_types::{DeferredConstraint, PendingCheck};
+
+impl Database {
+    /// Queue deferred constraints of a freshly written row version
+    pub fn defer_row_checks(
+        &mut self,
+        xid: u32,
+        table_name: &str,
+        pos: (usize, usize),
+        constraints: Vec<DeferredConstraint>,
+    ) {
+        for constraint in constraints {
+            self.transaction_manager.defer_check(
+                xid,
+                PendingCheck::Row {
+                    table: table_name.to_string(),
+                    pos,
+                    constraint,
+                },
+            );
+        }
+    }
+
+    /// Run every check queued by transaction `xid` against the current state.
+    /// Called before COMMIT and by SET CONSTRAINTS ALL IMMEDIATE; the queue is emptied
+    /// only when every check passes, so a failed IMMEDIATE switch keeps it for COMMIT.
+    pub fn check_deferred(&mut self, xid: u32) -> Result<(), String> {
+        let Some(pending) = self.transaction_manager.pending_checks.get(&xid).cloned() else {
+            return Ok(());
+        };
+        // Latest snapshot: rows committed by others since the statement count
+        let snapshot = self.transaction_manager.snapshot();
+        let tm = &self.transaction_manager;
+
+        for check in pending {
+            match check {
+                PendingCheck::Row { table, pos, constraint } => {
+                    let Some(table) = self.tables.get(&table) else {
+                        continue;
+                    };
+                    let Some((header, row)) = table.heap.get_tuple(pos.0 as u32, pos.1, &table.columns)
+                    else {
+                        continue;
+                    };
+                    // Later deleted or replaced by this transaction: its successor was checked
+                    if header.is_dead(tm) || header.xmax == Some(xid) {
+                        continue;
+                    }
+                    match constraint {
+                        DeferredConstraint::Unique(name) => {
+                            if let Some(idx) = self.indexes.get(&name) {
+                                check_unique_index(self, idx, table, &row.values, xid, Some(pos))?;
+                            }
+                        }
+                        DeferredConstraint::ForeignKey(fk) => {
+                            check_foreign_key(self, table, &fk, &row.values, xid, &snapshot)?;
+                        }
+                    }
+                }
+                PendingCheck::Referenced { table, child, fk, key } => {
+                    let (Some(parent), Some(child_table)) = (self.tables.get(&table), self.tables.get(&child))
+                    else {
+                        continue;
+                    };
+                    // Key re-inserted before COMMIT: references are satisfied again
+                    let restored = lookup_rows(self, parent, &fk.referenced_columns, &key)?
+                        .iter()
+                        .any(|(_, _, header, _)| header.is_visible(xid, &snapshot, tm));
+                    if restored {
+                        continue;
+                    }
+                    let referenced = lookup_rows(self, child_table, &fk.local_columns, &key)?
+                        .iter()
+                        .any(|(_, _, header, _)| !header.is_dead(tm) && header.xmax != Some(xid));
+                    if referenced {
+                        return Err(still_referenced_error("update or delete", &table, &child, &fk, &key));
+                    }
+                }
+            }
+        }
+
+        self.transaction_manager.pending_checks.remove(&xid);
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use crate::executer::filter::eval_condition;
-use crate::executer::help_functions::{build_key, ensure_not_referenced};
 use crate::executer::join::JoinTableColumn;
-use crate::types::parser_types::Condition;
-use crate::types::storage_types::{Column, Database};
+use crate::types::parser_types::{Condition, FromItem};
+use crate::types::storage_types::{Column, Database, Row};
 use crate::types::transaction_types::Snapshot;
+use std::collections::HashMap;
 
 /// Build column metadata for a single table
 fn single_meta(table_name: &str, cols: &Vec<Column>) -> Vec<JoinTableColumn> {
@@ -11,63 +11,64 @@ fn single_meta(table_name: &str, cols: &Vec<Column>) -> Vec<JoinTableColumn> {
         .map(|c| JoinTableColumn {
             table_alias: table_name.to_string(),
             column_name: c.name.clone(),
+            column_type: c.column_type.clone(),
         })
         .collect()
 }
 
 impl Database {
-    /// Deletes rows from `table_name` that match `filter`.
-    /// Returns the number of deleted rows.
+    /// Deletes rows from `table_name` that match `filter` (all rows if None).
+    /// With a USING list, a row is deleted when some USING row satisfies `filter` together with it.
+    /// Returns the deleted rows.
     pub fn delete(
         &mut self,
         table_name: &str,
+        using: Option<(FromItem, HashMap<String, String>)>,
         filter: Option<Condition>,
         xid: u32,
         snapshot: &Snapshot
-    ) -> Result<usize, String> {
-        // Immutable borrow for scanning and metadata
+    ) -> Result<Vec<Row>, String> {
+        // Clone table metadata: referential actions need `self` mutably
         let table = self
             .tables
             .get(table_name)
-            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?
+            .clone();
 
-        let mut deleted_count = 0;
+        let mut deleted = Vec::new();
         let metas = single_meta(table_name, &table.columns);
+        let using = using
+            .map(|(item, aliases)| self.collect_join_table(item, &aliases, xid, snapshot))
+            .transpose()?;
 
-        if let Some(cond) = &filter {
-            let rows = table.heap.scan_all(&table.columns);
-
-            for (page_no, slot_no, header, row) in rows {
-                if !header.is_visible(xid, snapshot, &self.transaction_manager) {
+        for (page_no, slot_no, header, row) in table.heap.scan_all(&table.columns) {
+            if !header.is_visible(xid, snapshot, &self.transaction_manager) {
+                continue;
+            }
+            if let Some(join) = &using {
+                if join.first_match(&row, &metas, filter.as_ref())?.is_none() {
                     continue;
                 }
-                match eval_condition(cond, &row, &metas, None, None) {
-                    Ok(true) => {
-                        // For each other table, check if any FK references this row
-                        ensure_not_referenced(self, table_name, &row.values)?;
-
-                        for idx in self.indexes.values_mut() {
-                            if idx.table == table.name {
-                                let key = build_key(
-                                    &idx.columns,
-                                    &table.columns,
-                                    &row.values,
-                                    table_name,
-                                )?;
-                                idx.remove(&key, (page_no as usize, slot_no));
-                            }
-                        }
-
-                        // If all FK checks passed → delete row
-                        table.heap.delete_at(page_no, slot_no, xid)?;
-                        deleted_count += 1;
-                    }
-                    Ok(false) => { /* keep row */ }
-                    Err(e) => return Err(e.to_string()),
+            } else if let Some(cond) = &filter {
+                let keep =
+                    eval_condition(cond, &row, &metas, None, None).map_err(|e| e.to_string())?;
+                if !keep {
+                    continue;
                 }
             }
+
+            // Index entries stay until VACUUM: the tuple is still
+            // visible to other snapshots and comes back on rollback
+            table.heap.delete_at(page_no, slot_no, xid)?;
+            deleted.push(row);
+        }
+
+        // ON DELETE actions run once the statement deleted all of its rows,
+        // so rows it deletes itself are never set to NULL or a default
+        for row in &deleted {
+            self.apply_fk_actions(table_name, &row.values, None, xid, snapshot)?;
         }
 
-        Ok(deleted_count)
+        Ok(deleted)
     }
 }
@@ -0,0 +1,177 @@
+use crate::executer::alter::fk_name;
+use crate::storage::heap_file::HeapFile;
+use crate::types::b_tree::BTreeIndex;
+use crate::types::storage_types::Database;
+
+impl Database {
+    /// Remove tables and their indexes from memory and return their heaps, whose files the
+    /// caller unlinks once the catalog no longer lists them. Foreign keys of other tables
+    /// that reference a dropped table are an error, or are dropped too with `cascade`.
+    pub fn drop_tables(&mut self, names: &[String], cascade: bool) -> Result<Vec<HeapFile>, String> {
+        for name in names {
+            if !self.tables.contains_key(name) {
+                return Err(format!("Table '{}' doesn't exist", name));
+            }
+        }
+
+        for other in self.tables.values().filter(|t| !names.contains(&t.name)) {
+            for fk in &other.foreign_keys {
+                if !cascade && names.contains(&fk.referenced_table) {
+                    return Err(format!(
+                        "cannot drop table '{}' because constraint {} on table '{}' depends on it (use DROP ... CASCADE)",
+                        fk.referenced_table,
+                        fk_name(&other.name, fk),
+                        other.name
+                    ));
+                }
+            }
+        }
+
+        for other in self.tables.values_mut().filter(|t| !names.contains(&t.name)) {
+            other.foreign_keys.retain(|fk| {
+                let dropped = names.contains(&fk.referenced_table);
+                if dropped {
+                    println!(
+                        "NOTICE: drop cascades to constraint {} on table {}",
+                        fk_name(&other.name, fk),
+                        other.name
+                    );
+                }
+                !dropped
+            });
+        }
+
+        self.indexes.retain(|_, idx| !names.contains(&idx.table));
+        for name in names {
+            self.drop_owned_sequences(name, None);
+        }
+        Ok(names
+            .iter()
+            .map(|name| self.tables.remove(name).unwrap().heap)
+            .collect())
+    }
+
+    /// Remove a standalone index. Primary key indexes go through ALTER TABLE ... DROP CONSTRAINT.
+    pub fn drop_index(&mut self, name: &str) -> Result<(), String> {
+        let idx = self
+            .indexes
+            .get(name)
+            .ok_or_else(|| format!("Index '{}' does not exist", name))?;
+        let table = &self.tables[&idx.table];
+        if table.primary_key.as_ref() == Some(&idx.columns) && idx.unique {
+            return Err(format!(
+                "cannot drop index '{}' because it backs the primary key of '{}' (use ALTER TABLE {} DROP CONSTRAINT {})",
+                name, table.name, table.name, name
+            ));
+        }
+        self.check_key_dependents(name)?;
+        self.indexes.remove(name);
+        Ok(())
+    }
+
+    /// A unique index may not go away while a foreign key relies on it
+    /// and no other unique index covers the same columns
+    pub fn check_key_dependents(&self, index_name: &str) -> Result<(), String> {
+        let idx = &self.indexes[index_name];
+        if !idx.unique {
+            return Ok(());
+        }
+        let same_columns = |cols: &Vec<String>| {
+            cols.len() == idx.columns.len() && idx.columns.iter().all(|c| cols.contains(c))
+        };
+        let other_key = self.indexes.values().any(|other| {
+            other.name != idx.name
+                && other.unique
+                && other.table == idx.table
+                && same_columns(&other.columns)
+        });
+        if other_key {
+            return Ok(());
+        }
+        for other in self.tables.values() {
+            for fk in &other.foreign_keys {
+                if fk.referenced_table == idx.table && same_columns(&fk.referenced_columns) {
+                    return Err(format!(
+                        "cannot drop '{}' on '{}': foreign key {} of '{}' depends on it",
+                        index_name,
+                        idx.table,
+                        fk_name(&other.name, fk),
+                        other.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Tables a TRUNCATE of `names` has to empty: with `cascade`, every table that
+    /// references one of them, transitively; otherwise such a reference is an error
+    pub fn truncate_targets(&self, names: &[String], cascade: bool) -> Result<Vec<String>, String> {
+        let mut targets = Vec::new();
+        for name in names {
+            if !self.tables.contains_key(name) {
+                return Err(format!("Table '{}' doesn't exist", name));
+            }
+            if !targets.contains(name) {
+                targets.push(name.clone());
+            }
+        }
+
+        let mut i = 0;
+        while i < targets.len() {
+            let mut referencing: Vec<&String> = self
+                .tables
+                .values()
+                .filter(|t| {
+                    !targets.contains(&t.name)
+                        && t.foreign_keys.iter().any(|fk| fk.referenced_table == targets[i])
+                })
+                .map(|t| &t.name)
+                .collect();
+            referencing.sort();
+            if let Some(first) = referencing.first() {
+                if !cascade {
+                    return Err(format!(
+                        "cannot truncate '{}': table '{}' references it (use TRUNCATE ... CASCADE)",
+                        targets[i], first
+                    ));
+                }
+                for name in referencing {
+                    println!("NOTICE: truncate cascades to table {}", name);
+                    targets.push(name.clone());
+                }
+            }
+            i += 1;
+        }
+        Ok(targets)
+    }
+
+    /// Point `table_name` at a fresh, empty heap file at `path` and empty its indexes.
+    /// The old heap is returned; its files are unlinked after the catalog is written.
+    /// The new file starts a compact layout without dropped attributes.
+    pub fn truncate_table(&mut self, table_name: &str, path: &str) -> Result<HeapFile, String> {
+        let table = self
+            .tables
+            .get_mut(table_name)
+            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+
+        for (attnum, col) in table.columns.iter_mut().enumerate() {
+            col.attnum = attnum;
+        }
+        let old_heap = std::mem::replace(&mut table.heap, HeapFile::new(path, &table.columns));
+
+        for idx in self.indexes.values_mut() {
+            if idx.table == table_name {
+                let mut empty = BTreeIndex::new(
+                    idx.name.clone(),
+                    idx.table.clone(),
+                    idx.columns.clone(),
+                    idx.unique,
+                );
+                empty.deferrable = idx.deferrable;
+                *idx = empty;
+            }
+        }
+        Ok(old_heap)
+    }
+}
@@ -2,11 +2,19 @@ use super::printer::print_table;
 use super::select::TableArg;
 use crate::engine::Engine;
 use crate::errors::engine_error::EngineError;
-use crate::types::parser_types::{FromItem, Query};
-use crate::types::transaction_types::IsolationLevel;
+use super::copy::encode_copy;
+use super::filter::eval_operand;
+use super::functions::bind_now;
+use super::join::JoinTableColumn;
+use crate::types::parser_types::{Condition, CopySource, FromItem, InsertSource, Operand, Query};
+use crate::types::storage_types::{Deferrable, Row, Value};
+use crate::types::transaction_types::{IsolationLevel, Snapshot};
+use std::collections::HashMap;
 
 /// Executes a parsed query (AST) against the database
-pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
+pub fn execute(engine: &mut Engine, mut ast: Query) -> Result<(), EngineError> {
+    engine.db.transaction_time = engine.transaction_time();
+    bind_now(&mut ast, engine.db.transaction_time);
     match ast {
         Query::Begin { isolation } => {
             if engine.current_xid.is_some() {
@@ -31,9 +39,10 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
 
         Query::Commit => {
             if let Some(xid) = engine.current_xid.take() {
-                engine.commit_tx(xid);
+                let committed = engine.commit_checked(xid);
                 engine.repeatable_snapshot = None;
                 engine.tx_isolation = None;
+                committed?;
                 println!("COMMIT (xid = {})", xid);
             } else {
                 return Err("No active transaction".to_string().into());
@@ -65,20 +74,119 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
             println!("Session isolation level set to {:?}", level);
         }
 
+        Query::SetConstraints { deferred } => {
+            let Some(xid) = engine.current_xid else {
+                return Err("SET CONSTRAINTS can only be used in transaction blocks".to_string().into());
+            };
+            engine.db.transaction_manager.constraints_deferred.insert(xid, deferred);
+            // Switching to IMMEDIATE checks everything queued so far
+            if !deferred {
+                engine.db.check_deferred(xid)?;
+            }
+            println!("SET CONSTRAINTS ALL {}", if deferred { "DEFERRED" } else { "IMMEDIATE" });
+        }
+
+        Query::SetOption { name, value } => match name.as_str() {
+            "auto_index_foreign_keys" => {
+                engine.auto_index_foreign_keys = match value.to_ascii_lowercase().as_str() {
+                    "on" | "true" => true,
+                    "off" | "false" => false,
+                    other => return Err(format!("Invalid value for {}: '{}'", name, other).into()),
+                };
+                println!("SET {} = {}", name, engine.auto_index_foreign_keys);
+            }
+            other => return Err(format!("Unknown setting '{}'", other).into()),
+        },
+
+        // CREATE TABLE IF NOT EXISTS on an existing table is a no-op
+        Query::CreateTable {
+            table_name,
+            if_not_exists: true,
+            ..
+        } if engine.db.tables.contains_key(&table_name) => {
+            println!("NOTICE: relation \"{}\" already exists, skipping", table_name);
+        }
+
         // CREATE TABLE name (...)
         Query::CreateTable {
             table_name,
+            if_not_exists: _,
             columns,
             primary_key,
             foreign_keys,
-        } => engine.create_table_in_both(&table_name, columns, primary_key, foreign_keys)?,
+            unique_constraints,
+            checks,
+            deferrable_keys,
+            sequences,
+        } => engine.create_table_in_both(
+            &table_name,
+            columns,
+            primary_key,
+            foreign_keys,
+            unique_constraints,
+            checks,
+            deferrable_keys,
+            sequences,
+        )?,
+
+        // CREATE SEQUENCE IF NOT EXISTS on an existing sequence is a no-op
+        Query::CreateSequence {
+            sequence_name,
+            if_not_exists: true,
+            ..
+        } if engine.db.sequences.contains_key(&sequence_name) => {
+            println!("NOTICE: relation \"{}\" already exists, skipping", sequence_name);
+        }
+
+        // CREATE SEQUENCE name [options]
+        Query::CreateSequence {
+            sequence_name,
+            if_not_exists: _,
+            options,
+        } => engine.create_sequence_in_both(&sequence_name, options)?,
+
+        // CREATE TYPE name AS ENUM (...)
+        Query::CreateType { type_name, labels } => engine.create_enum(&type_name, labels)?,
+
+        // ALTER TABLE name <action>
+        Query::AlterTable { table_name, action } => engine.alter_table(&table_name, action)?,
+
+        // DROP TABLE [IF EXISTS] name [, ...] [CASCADE]
+        Query::DropTable {
+            table_names,
+            if_exists,
+            cascade,
+        } => engine.drop_tables(table_names, if_exists, cascade)?,
+
+        // DROP SEQUENCE [IF EXISTS] name [, ...] [CASCADE]
+        Query::DropSequence {
+            sequence_names,
+            if_exists,
+            cascade,
+        } => engine.drop_sequences(sequence_names, if_exists, cascade)?,
+
+        // DROP INDEX [IF EXISTS] name
+        Query::DropIndex {
+            index_name,
+            if_exists,
+        } => engine.drop_index(&index_name, if_exists)?,
+
+        // TRUNCATE [TABLE] name [, ...] [CASCADE]
+        Query::Truncate {
+            table_names,
+            cascade,
+        } => engine.truncate(table_names, cascade)?,
 
-        // INSERT INTO table (...) VALUES (...)
+        // INSERT INTO table [(...)] VALUES (...), ... | SELECT ... [ON CONFLICT ...] [RETURNING ...]
         Query::Insert {
             table_name,
             column_names,
-            values,
+            source,
+            overriding,
+            on_conflict,
+            returning,
         } => {
+            let returning = returning_columns(engine, &table_name, returning)?;
             let level = engine.tx_isolation.unwrap_or(engine.session_isolation);
 
             let snapshot = if engine.current_xid.is_some() {
@@ -93,22 +201,167 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
                 engine.db.transaction_manager.snapshot()
             };
 
-            if let Some(xid) = engine.current_xid {
+            let inserted = if let Some(xid) = engine.current_xid {
                 // inside active transaction
-                engine.db.insert_into(&table_name, column_names, values, xid, &snapshot)?;
+                let rows = insert_rows(engine, source, xid, &snapshot)?;
+                engine.db.insert_into(
+                    &table_name,
+                    column_names,
+                    rows,
+                    overriding,
+                    on_conflict.as_ref(),
+                    xid,
+                    &snapshot,
+                )?
             } else {
                 // autocommit mode
                 let xid = engine.next_xid();
                 engine.begin_tx(xid);
-                engine.db.insert_into(&table_name, column_names, values, xid, &snapshot)?;
-                engine.commit_tx(xid);
+                let inserted = insert_rows(engine, source, xid, &snapshot).and_then(|rows| {
+                    Ok(engine.db.insert_into(
+                    &table_name,
+                    column_names,
+                    rows,
+                    overriding,
+                    on_conflict.as_ref(),
+                    xid,
+                    &snapshot,
+                )?)
+                });
+                let inserted = match inserted {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        engine.rollback_tx(xid);
+                        return Err(e);
+                    }
+                };
+                engine.commit_checked(xid)?;
+                inserted
+            };
+            print_returning(returning, &inserted);
+        }
+        // COPY table [(cols)] FROM { 'path' | STDIN }
+        Query::Copy {
+            source:
+                CopySource::Table {
+                    table_name,
+                    column_names,
+                },
+            to_file: false,
+            path,
+            options,
+            data,
+        } => {
+            let data = match (path, data) {
+                (Some(path), _) => std::fs::read_to_string(&path)
+                    .map_err(|e| format!("could not read '{}': {}", path, e))?,
+                (None, Some(data)) => data,
+                (None, None) => return Err("COPY FROM STDIN expects data lines ending with \\.".to_string().into()),
+            };
+
+            let level = engine.tx_isolation.unwrap_or(engine.session_isolation);
+
+            let snapshot = if engine.current_xid.is_some() {
+                match level {
+                    IsolationLevel::ReadCommitted => engine.db.transaction_manager.snapshot(),
+                    IsolationLevel::RepeatableRead => engine
+                        .repeatable_snapshot
+                        .clone()
+                        .expect("snapshot should exist in RR"),
+                }
+            } else {
+                engine.db.transaction_manager.snapshot()
+            };
+
+            let copied = if let Some(xid) = engine.current_xid {
+                engine.db.copy_from(&table_name, column_names, &data, &options, xid, &snapshot)?
+            } else {
+                let xid = engine.next_xid();
+                engine.begin_tx(xid);
+                let copied = match engine.db.copy_from(&table_name, column_names, &data, &options, xid, &snapshot) {
+                    Ok(n) => n,
+                    Err(e) => {
+                        engine.rollback_tx(xid);
+                        return Err(e.into());
+                    }
+                };
+                engine.commit_checked(xid)?;
+                copied
+            };
+            println!("COPY {}", copied);
+        }
+
+        // COPY { table [(cols)] | (SELECT ...) } TO { 'path' | STDOUT }
+        Query::Copy {
+            source,
+            to_file: _,
+            path,
+            options,
+            data: _,
+        } => {
+            let xid = engine.current_xid.unwrap_or(0);
+
+            let level = engine.tx_isolation.unwrap_or(engine.session_isolation);
+
+            let snapshot = if engine.current_xid.is_some() {
+                match level {
+                    IsolationLevel::ReadCommitted => engine.db.transaction_manager.snapshot(),
+                    IsolationLevel::RepeatableRead => engine
+                        .repeatable_snapshot
+                        .clone()
+                        .expect("snapshot should exist in RR"),
+                }
+            } else {
+                engine.db.transaction_manager.snapshot()
+            };
+
+            let (from_table, aliases, column_names, targets, filter) = match source {
+                CopySource::Table {
+                    table_name,
+                    column_names,
+                } => {
+                    let column_names = column_names.unwrap_or_else(|| vec!["*".to_string()]);
+                    let targets = if column_names[0] == "*" {
+                        Vec::new()
+                    } else {
+                        column_names.iter().cloned().map(Operand::Column).collect()
+                    };
+                    (FromItem::Table(table_name), HashMap::new(), column_names, targets, None)
+                }
+                CopySource::Query(query) => match *query {
+                    Query::Select {
+                        from_table,
+                        aliases,
+                        column_names,
+                        targets,
+                        filter,
+                    } => (from_table, aliases, column_names, targets, filter),
+                    _ => return Err("COPY (...) TO only supports SELECT".to_string().into()),
+                },
+            };
+
+            let (columns, rows) = select_rows(engine, from_table, &aliases, &targets, filter, xid, &snapshot)?;
+            let names = if column_names.first().map(|s| s.as_str()) == Some("*") {
+                columns.into_iter().map(|c| c.column_name).collect()
+            } else {
+                column_names
+            };
+            let out = encode_copy(&names, &rows, &options);
+            match path {
+                Some(path) => {
+                    std::fs::write(&path, out).map_err(|e| format!("could not write '{}': {}", path, e))?
+                }
+                None => print!("{}", out),
             }
+            println!("COPY {}", rows.len());
         }
+
         // SELECT ... FROM ...
         Query::Select {
             from_table,
             aliases,
             column_names,
+            targets,
             filter,
         } => {
             // choose xid (active or autocommit)
@@ -128,19 +381,7 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
                 engine.db.transaction_manager.snapshot()
             };
 
-            let (columns, rows) = match from_table {
-                FromItem::Table(table_name) => engine.db.select(
-                    &TableArg::TableName(table_name),
-                    &column_names,
-                    filter,
-                    xid,
-                    &snapshot
-                )?,
-                _ => {
-                    let join = engine.db.collect_join_table(from_table, &aliases)?;
-                    engine.db.select(&TableArg::JoinTable(join), &column_names, filter, xid, &snapshot)?
-                }
-            };
+            let (columns, rows) = select_rows(engine, from_table, &aliases, &targets, filter, xid, &snapshot)?;
 
             if column_names.get(0).map(|s| s.as_str()) == Some("*") {
                 let names = columns
@@ -154,7 +395,15 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
         }
 
         // DELETE FROM ...
-        Query::Delete { table_name, filter } => {
+        Query::Delete {
+            table_name,
+            using,
+            aliases,
+            filter,
+            returning,
+        } => {
+            let using = using.map(|item| (item, aliases));
+            let returning = returning_columns(engine, &table_name, returning)?;
             let level = engine.tx_isolation.unwrap_or(engine.session_isolation);
 
             let snapshot = if engine.current_xid.is_some() {
@@ -170,14 +419,22 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
             };
 
             if let Some(xid) = engine.current_xid {
-                let deleted = engine.db.delete(&table_name, filter, xid, &snapshot)?;
-                println!("DELETE {}", deleted);
+                let deleted = engine.db.delete(&table_name, using, filter, xid, &snapshot)?;
+                print_returning(returning, &deleted);
+                println!("DELETE {}", deleted.len());
             } else {
                 let xid = engine.next_xid();
                 engine.begin_tx(xid);
-                let deleted = engine.db.delete(&table_name, filter, xid, &snapshot)?;
-                engine.commit_tx(xid);
-                println!("DELETE {}", deleted);
+                let deleted = match engine.db.delete(&table_name, using, filter, xid, &snapshot) {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        engine.rollback_tx(xid);
+                        return Err(e.into());
+                    }
+                };
+                engine.commit_checked(xid)?;
+                print_returning(returning, &deleted);
+                println!("DELETE {}", deleted.len());
             }
         }
 
@@ -186,8 +443,13 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
             table_name,
             column_names,
             values,
+            from,
+            aliases,
             filter,
+            returning,
         } => {
+            let from = from.map(|item| (item, aliases));
+            let returning = returning_columns(engine, &table_name, returning)?;
             let level = engine.tx_isolation.unwrap_or(engine.session_isolation);
 
             let snapshot = if engine.current_xid.is_some() {
@@ -202,22 +464,172 @@ pub fn execute(engine: &mut Engine, ast: Query) -> Result<(), EngineError> {
                 engine.db.transaction_manager.snapshot()
             };
 
-            if let Some(xid) = engine.current_xid {
-                engine.db.update(&table_name, column_names, values, filter, xid, &snapshot)?;
+            let updated = if let Some(xid) = engine.current_xid {
+                engine.db.update(&table_name, column_names, values, from, filter, xid, &snapshot)?
             } else {
                 let xid = engine.next_xid();
                 engine.begin_tx(xid);
-                engine.db.update(&table_name, column_names, values, filter, xid, &snapshot)?;
-                engine.commit_tx(xid);
-            }
+                let updated = match engine.db.update(&table_name, column_names, values, from, filter, xid, &snapshot) {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        engine.rollback_tx(xid);
+                        return Err(e.into());
+                    }
+                };
+                engine.commit_checked(xid)?;
+                updated
+            };
+            print_returning(returning, &updated);
+        }
+
+        Query::CreateIndex {
+            index_name,
+            if_not_exists: true,
+            ..
+        } if engine.db.indexes.contains_key(&index_name) => {
+            println!("NOTICE: relation \"{}\" already exists, skipping", index_name);
         }
 
         Query::CreateIndex {
             index_name,
+            if_not_exists: _,
             table_name,
             column_names,
-        } => engine.create_index_in_both(&index_name, &table_name, column_names)?,
+            unique,
+        } => engine.create_index_in_both(
+            &index_name,
+            &table_name,
+            column_names,
+            unique,
+            Deferrable::NotDeferrable,
+        )?,
     };
 
     Ok(())
 }
+
+
+/// Run a SELECT over a table or a join
+fn select_rows(
+    engine: &Engine,
+    from_table: FromItem,
+    aliases: &HashMap<String, String>,
+    targets: &[Operand],
+    filter: Option<Condition>,
+    xid: u32,
+    snapshot: &Snapshot,
+) -> Result<(Vec<JoinTableColumn>, Vec<Row>), EngineError> {
+    Ok(match from_table {
+        FromItem::Table(table_name) => engine.db.select(
+            &TableArg::TableName(table_name),
+            targets,
+            filter,
+            xid,
+            snapshot,
+        )?,
+        _ => {
+            let join = engine.db.collect_join_table(from_table, aliases, xid, snapshot)?;
+            engine.db.select(&TableArg::JoinTable(join), targets, filter, xid, snapshot)?
+        }
+    })
+}
+
+/// Rows an INSERT writes: the VALUES list, or the result of its SELECT
+/// read with the inserting transaction's own xid and snapshot
+fn insert_rows(
+    engine: &Engine,
+    source: InsertSource,
+    xid: u32,
+    snapshot: &Snapshot,
+) -> Result<Vec<Vec<Value>>, EngineError> {
+    match source {
+        // VALUES items are constants or expressions without columns
+        InsertSource::Values(rows) => {
+            let empty = Row { values: Vec::new() };
+            let values = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|op| {
+                            let op = engine.db.bind_sequences(op)?;
+                            eval_operand(&op, &empty, &[], None, None)
+                                .map(|v| v.into_owned())
+                                .map_err(|e| e.to_string())
+                        })
+                        .collect::<Result<Vec<Value>, String>>()
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(values)
+        }
+        InsertSource::Select(query) => match *query {
+            Query::Select {
+                from_table,
+                aliases,
+                targets,
+                filter,
+                ..
+            } => {
+                let (_, rows) =
+                    select_rows(engine, from_table, &aliases, &targets, filter, xid, snapshot)?;
+                Ok(rows.into_iter().map(|row| row.values).collect())
+            }
+            _ => Err("INSERT source must be a SELECT".to_string().into()),
+        },
+    }
+}
+
+/// RETURNING column names and their positions in the target table's rows
+type Returning = Option<(Vec<String>, Vec<usize>)>;
+
+/// Resolve a RETURNING list against the target table before the statement runs:
+/// column names to print and their positions in the table's rows
+fn returning_columns(
+    engine: &Engine,
+    table_name: &str,
+    returning: Option<Vec<String>>,
+) -> Result<Returning, EngineError> {
+    let Some(names) = returning else {
+        return Ok(None);
+    };
+    let table = engine
+        .db
+        .tables
+        .get(table_name)
+        .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+
+    let mut columns = Vec::new();
+    let mut positions = Vec::new();
+    for name in names {
+        if name == "*" {
+            for (i, col) in table.columns.iter().enumerate() {
+                columns.push(col.name.clone());
+                positions.push(i);
+            }
+            continue;
+        }
+        let bare = match name.split_once('.') {
+            Some((t, c)) if t == table_name => c,
+            _ => name.as_str(),
+        };
+        let Some(i) = table.columns.iter().position(|c| c.name == bare) else {
+            return Err(format!("Unknown column '{}' in RETURNING for table '{}'", name, table_name).into());
+        };
+        columns.push(bare.to_string());
+        positions.push(i);
+    }
+    Ok(Some((columns, positions)))
+}
+
+/// Print the rows affected by INSERT/UPDATE/DELETE ... RETURNING
+fn print_returning(returning: Returning, rows: &[Row]) {
+    let Some((columns, positions)) = returning else {
+        return;
+    };
+    let projected: Vec<Row> = rows
+        .iter()
+        .map(|row| Row {
+            values: positions.iter().map(|&i| row.values[i].clone()).collect(),
+        })
+        .collect();
+    print_table(&columns, &projected);
+}
@@ -1,8 +1,14 @@
+use super::functions;
 use super::join::JoinTableColumn;
 use crate::errors::eval_error::{EvalError, EvalResult};
-use crate::types::filter_types::CmpOp;
+use crate::types::array;
+use crate::types::cast::{cast_value, CastContext};
+use crate::types::filter_types::{ArithOp, CmpOp};
+use crate::types::jsonb::Jsonb;
 use crate::types::parser_types::{Condition, Operand};
-use crate::types::storage_types::{Row, Value, ValueType};
+use crate::types::storage_types::{ColumnType, Row, Value, ValueType};
+use std::borrow::Cow;
+use std::cmp::Ordering;
 
 /// Compares two values in strict mode
 pub fn cmp_values(op: CmpOp, left: &Value, right: &Value) -> EvalResult<bool> {
@@ -11,6 +17,33 @@ pub fn cmp_values(op: CmpOp, left: &Value, right: &Value) -> EvalResult<bool> {
         return Ok(false);
     }
 
+    // JSONB containment and key existence are tests, not orderings
+    if matches!(op, CmpOp::Contains | CmpOp::HasKey) {
+        return jsonb_predicate(op, left, right);
+    }
+
+    // Numbers compare by value across INT, FLOAT and NUMERIC
+    if let Some(ord) = left.cmp_number(right) {
+        return Ok(ordering_matches(op, ord));
+    }
+
+    // Dates, times, BYTEA, JSONB, UUID, arrays and ENUMs; a text literal is read as the other side's type
+    let parsed;
+    let (left, right) = match (left, right) {
+        (Value::Text(text), other) if literal_type(other).is_some() => {
+            parsed = parse_as(text, other)?;
+            (&parsed, right)
+        }
+        (other, Value::Text(text)) if literal_type(other).is_some() => {
+            parsed = parse_as(text, other)?;
+            (left, &parsed)
+        }
+        _ => (left, right),
+    };
+    if let Some(ord) = left.cmp_temporal(right) {
+        return Ok(ordering_matches(op, ord));
+    }
+
     // Ensure types match before comparing
     let lt = left.vtype();
     let rt = right.vtype();
@@ -26,23 +59,19 @@ pub fn cmp_values(op: CmpOp, left: &Value, right: &Value) -> EvalResult<bool> {
     use CmpOp::*;
     match (left, right) {
         // Integer comparison
-        (Value::Int(a), Value::Int(b)) => Ok(match op {
-            Eq => a == b,
-            Ne => a != b,
-            Lt => a < b,
-            Lte => a <= b,
-            Gt => a > b,
-            Gte => a >= b,
-        }),
+        (Value::Int(a), Value::Int(b)) => Ok(ordering_matches(op, a.cmp(b))),
+        // Byte strings compare bytewise, a shorter prefix first
+        (Value::Bytea(a), Value::Bytea(b)) => Ok(ordering_matches(op, a.cmp(b))),
+        // Documents compare like PostgreSQL's jsonb: by kind first, then by content
+        (Value::Jsonb(a), Value::Jsonb(b)) => Ok(ordering_matches(op, a.cmp(b))),
+        // UUIDs compare bytewise, as their canonical text sorts
+        (Value::Uuid(a), Value::Uuid(b)) => Ok(ordering_matches(op, a.cmp(b))),
+        // ENUM labels sort in declaration order; different ENUM types do not compare
+        (Value::Enum(ta, a), Value::Enum(tb, b)) if ta.name == tb.name => Ok(ordering_matches(op, a.cmp(b))),
+        // Arrays compare element by element, then by length
+        (Value::Array(a), Value::Array(b)) => Ok(ordering_matches(op, cmp_arrays(a, b))),
         // String comparison (lexicographical)
-        (Value::Text(a), Value::Text(b)) => Ok(match op {
-            Eq => a == b,
-            Ne => a != b,
-            Lt => a < b,
-            Lte => a <= b,
-            Gt => a > b,
-            Gte => a >= b,
-        }),
+        (Value::Text(a), Value::Text(b)) => Ok(ordering_matches(op, a.cmp(b))),
         // Boolean comparison (only Eq/Ne are valid)
         (Value::Bool(a), Value::Bool(b)) => match op {
             Eq => Ok(a == b),
@@ -61,6 +90,133 @@ pub fn cmp_values(op: CmpOp, left: &Value, right: &Value) -> EvalResult<bool> {
     }
 }
 
+fn ordering_matches(op: CmpOp, ord: Ordering) -> bool {
+    use CmpOp::*;
+    match op {
+        Eq => ord.is_eq(),
+        Ne => ord.is_ne(),
+        Lt => ord.is_lt(),
+        Lte => ord.is_le(),
+        Gt => ord.is_gt(),
+        Gte => ord.is_ge(),
+        Contains | HasKey => false, // not orderings; see jsonb_predicate
+    }
+}
+
+/// `doc @> other` and `doc ? key`; a text right-hand side is read as JSONB for `@>`
+fn jsonb_predicate(op: CmpOp, left: &Value, right: &Value) -> EvalResult<bool> {
+    let parsed;
+    let doc = match left {
+        Value::Jsonb(doc) => doc,
+        Value::Text(text) => {
+            parsed = parse_jsonb(text)?;
+            &parsed
+        }
+        other => return Err(EvalError::InvalidOpForType { ty: other.vtype(), op }),
+    };
+    match (op, right) {
+        (_, Value::Null) => Ok(false),
+        (CmpOp::Contains, Value::Jsonb(other)) => Ok(doc.contains(other)),
+        (CmpOp::Contains, Value::Text(text)) => Ok(doc.contains(&parse_jsonb(text)?)),
+        (CmpOp::HasKey, Value::Text(key)) => Ok(doc.has_key(key)),
+        (_, other) => Err(EvalError::TypeMismatch {
+            left: ValueType::Jsonb,
+            right: other.vtype(),
+            op,
+        }),
+    }
+}
+
+fn parse_jsonb(text: &str) -> EvalResult<Jsonb> {
+    Jsonb::parse(text)
+        .ok_or_else(|| EvalError::Function(format!("invalid input syntax for type jsonb: \"{}\"", text)))
+}
+
+/// Type a text literal takes when compared with `other`: a date/time type, BYTEA, JSONB, UUID, array or ENUM
+fn literal_type(other: &Value) -> Option<ColumnType> {
+    match other {
+        Value::Bytea(_) => Some(ColumnType::Bytea),
+        Value::Jsonb(_) => Some(ColumnType::Jsonb),
+        Value::Uuid(_) => Some(ColumnType::Uuid),
+        Value::Array(_) | Value::Enum(..) => other.column_type(),
+        _ => other.temporal_type(),
+    }
+}
+
+/// Read a text literal as the type of the value it is compared with
+fn parse_as(text: &str, other: &Value) -> EvalResult<Value> {
+    let column_type = literal_type(other).ok_or(EvalError::Internal("no literal type"))?;
+    column_type
+        .coerce(Value::Text(text.to_string()))
+        .map_err(EvalError::Function)
+}
+
+/// Order of two arrays: the first differing element decides (NULL after any value),
+/// otherwise the shorter array comes first
+fn cmp_arrays(a: &[Value], b: &[Value]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x.cmp_number(y).or_else(|| x.cmp_temporal(y)).unwrap_or_else(|| x.cmp(y)))
+        .find(|ord| *ord != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// The array of `ANY(array)` on the right-hand side of a comparison
+fn any_operand(op: &Operand) -> Option<&Operand> {
+    match op {
+        Operand::Func(name, args) if name == "any" && args.len() == 1 => Some(&args[0]),
+        _ => None,
+    }
+}
+
+/// `lhs op ANY(array)`: true when the comparison holds for some element, UNKNOWN (None)
+/// when it holds for none but a NULL was involved. Array input text is read with the
+/// left-hand side's kind: `id = ANY("{1,2}")`
+fn eval_any(
+    op: CmpOp,
+    lhs: &Operand,
+    array: &Operand,
+    left_row: &Row,
+    left_cols: &[JoinTableColumn],
+    right_row: Option<&Row>,
+    right_cols: Option<&[JoinTableColumn]>,
+) -> EvalResult<Option<bool>> {
+    let lv = eval_operand(lhs, left_row, left_cols, right_row, right_cols)?;
+    let av = eval_operand(array, left_row, left_cols, right_row, right_cols)?;
+    let items = match av.into_owned() {
+        Value::Null => return Ok(None),
+        Value::Array(items) => items,
+        Value::Text(text) => array::parse_array(&text)
+            .ok_or_else(|| EvalError::Function(format!("malformed array literal: \"{}\"", text)))?
+            .into_iter()
+            .map(|item| match item {
+                None => Ok(Value::Null),
+                Some(item) if matches!(*lv, Value::Int(_) | Value::Float(_) | Value::Numeric(_)) => {
+                    Value::parse_number(item.trim())
+                        .ok_or_else(|| EvalError::Function(format!("invalid number in array: \"{}\"", item)))
+                }
+                Some(item) => Ok(Value::Text(item)),
+            })
+            .collect::<EvalResult<Vec<Value>>>()?,
+        other => return Err(EvalError::InvalidOpForType { ty: other.vtype(), op }),
+    };
+    if items.is_empty() {
+        return Ok(Some(false));
+    }
+    if matches!(*lv, Value::Null) {
+        return Ok(None);
+    }
+    let mut unknown = false;
+    for item in &items {
+        if matches!(item, Value::Null) {
+            unknown = true;
+        } else if cmp_values(op, &lv, item)? {
+            return Ok(Some(true));
+        }
+    }
+    Ok(if unknown { None } else { Some(false) })
+}
+
 /// Search column index in metadata: alias.col or just col
 fn find_col_index(metas: &[JoinTableColumn], alias: Option<&str>, col: &str) -> Option<usize> {
     if let Some(a) = alias {
@@ -81,14 +237,14 @@ fn find_col_index(metas: &[JoinTableColumn], alias: Option<&str>, col: &str) ->
     idx
 }
 
-/// Evaluate one operand (column or literal)
-fn eval_operand<'a>(
+/// Evaluate one operand: a column, a literal, a function call or `+`/`-`
+pub fn eval_operand<'a>(
     op: &'a Operand,
     left_row: &'a Row,
-    left_cols: &'a Vec<JoinTableColumn>,
+    left_cols: &'a [JoinTableColumn],
     right_row: Option<&'a Row>,
-    right_cols: Option<&'a Vec<JoinTableColumn>>,
-) -> EvalResult<&'a Value> {
+    right_cols: Option<&'a [JoinTableColumn]>,
+) -> EvalResult<Cow<'a, Value>> {
     match op {
         Operand::Column(name) => {
             // Parse alias.col or just col
@@ -104,20 +260,120 @@ fn eval_operand<'a>(
                 return left_row
                     .values
                     .get(idx)
+                    .map(Cow::Borrowed)
                     .ok_or(EvalError::Internal("row.values index out of bounds (left)"));
             }
             // Try right side if exists
             if let (Some(rcols), Some(rrow)) = (right_cols, right_row) {
                 if let Some(idx) = find_col_index(rcols, alias_opt, colname) {
-                    return rrow.values.get(idx).ok_or(EvalError::Internal(
+                    return rrow.values.get(idx).map(Cow::Borrowed).ok_or(EvalError::Internal(
                         "row.values index out of bounds (right)",
                     ));
                 }
             }
             Err(EvalError::UnknownColumn(name.clone()))
         }
-        Operand::Literal(val) => Ok(val), // return literal value directly
+        Operand::Literal(val) => Ok(Cow::Borrowed(val)), // return literal value directly
+        Operand::Func(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| {
+                    eval_operand(arg, left_row, left_cols, right_row, right_cols).map(Cow::into_owned)
+                })
+                .collect::<EvalResult<Vec<Value>>>()?;
+            functions::call(name, args).map(Cow::Owned)
+        }
+        Operand::Arith(op, lhs, rhs) => {
+            let lv = eval_operand(lhs, left_row, left_cols, right_row, right_cols)?;
+            let rv = eval_operand(rhs, left_row, left_cols, right_row, right_cols)?;
+            // integers overflow at the range of the wider operand's type
+            if let (Value::Int(a), Value::Int(b)) = (&*lv, &*rv)
+                && matches!(op, ArithOp::Add | ArithOp::Sub | ArithOp::Mul | ArithOp::Div)
+            {
+                let int_type = wider_int(
+                    int_type(lhs, left_cols, right_cols),
+                    int_type(rhs, left_cols, right_cols),
+                );
+                return functions::int_arith(*op, *a, *b, &int_type).map(|n| Cow::Owned(Value::Int(n)));
+            }
+            functions::arith(*op, lv.into_owned(), rv.into_owned()).map(Cow::Owned)
+        }
+        Operand::Cast(inner, column_type) => {
+            let value = eval_operand(inner, left_row, left_cols, right_row, right_cols)?;
+            cast_value(value.into_owned(), column_type, CastContext::Explicit)
+                .map(Cow::Owned)
+                .map_err(EvalError::Function)
+        }
+    }
+}
+
+/// Type of the column `name` refers to, looked up like `eval_operand` does
+fn column_type<'a>(
+    name: &str,
+    left_cols: &'a [JoinTableColumn],
+    right_cols: Option<&'a [JoinTableColumn]>,
+) -> Option<&'a ColumnType> {
+    let (alias, col) = match name.split_once('.') {
+        Some((alias, col)) => (Some(alias), col),
+        None => (None, name),
+    };
+    find_col_index(left_cols, alias, col)
+        .map(|i| &left_cols[i].column_type)
+        .or_else(|| right_cols.and_then(|cols| find_col_index(cols, alias, col).map(|i| &cols[i].column_type)))
+}
+
+/// Whether `op` is a CHAR(n) column, whose trailing spaces are insignificant in comparisons
+fn is_bpchar(op: &Operand, left_cols: &[JoinTableColumn], right_cols: Option<&[JoinTableColumn]>) -> bool {
+    let Operand::Column(name) = op else {
+        return false;
+    };
+    column_type(name, left_cols, right_cols).is_some_and(|t| matches!(t, ColumnType::Char(_)))
+}
+
+/// Integer type of an operand that evaluates to an integer, as PostgreSQL types it: a column
+/// or cast has its own, a literal is `integer` if it fits, arithmetic takes the wider
+/// operand's, and anything else (`count()`, `nextval()`) is `bigint`
+fn int_type(op: &Operand, left_cols: &[JoinTableColumn], right_cols: Option<&[JoinTableColumn]>) -> ColumnType {
+    let declared = match op {
+        Operand::Column(name) => column_type(name, left_cols, right_cols).cloned(),
+        Operand::Literal(Value::Int(n)) if i32::try_from(*n).is_ok() => Some(ColumnType::Int),
+        Operand::Arith(_, lhs, rhs) => Some(wider_int(
+            int_type(lhs, left_cols, right_cols),
+            int_type(rhs, left_cols, right_cols),
+        )),
+        Operand::Cast(_, column_type) => Some(column_type.clone()),
+        _ => None,
+    };
+    declared
+        .filter(|t| t.int_range().is_some())
+        .unwrap_or(ColumnType::BigInt)
+}
+
+/// The integer type with the larger range
+fn wider_int(a: ColumnType, b: ColumnType) -> ColumnType {
+    let max = |t: &ColumnType| t.int_range().map_or(i64::MAX, |(_, max)| max);
+    if max(&a) >= max(&b) { a } else { b }
+}
+
+/// Evaluate both sides of a comparison; strings compared with a CHAR(n) column lose their
+/// trailing spaces, so `code = "ab"` finds the stored `"ab   "`
+fn eval_comparison<'a>(
+    lhs: &'a Operand,
+    rhs: &'a Operand,
+    left_row: &'a Row,
+    left_cols: &'a [JoinTableColumn],
+    right_row: Option<&'a Row>,
+    right_cols: Option<&'a [JoinTableColumn]>,
+) -> EvalResult<(Cow<'a, Value>, Cow<'a, Value>)> {
+    let lv = eval_operand(lhs, left_row, left_cols, right_row, right_cols)?;
+    let rv = eval_operand(rhs, left_row, left_cols, right_row, right_cols)?;
+    if let (Value::Text(a), Value::Text(b)) = (&*lv, &*rv)
+        && (is_bpchar(lhs, left_cols, right_cols) || is_bpchar(rhs, left_cols, right_cols))
+    {
+        let trim = |s: &str| Cow::Owned(Value::Text(s.trim_end_matches(' ').to_string()));
+        return Ok((trim(a), trim(b)));
     }
+    Ok((lv, rv))
 }
 
 /// Evaluate full condition tree for a row (with optional join)
@@ -130,9 +386,12 @@ pub fn eval_condition(
 ) -> EvalResult<bool> {
     match cond {
         Condition::Cmp(op, lhs, rhs) => {
-            let lv = eval_operand(lhs, left_row, left_cols, right_row, right_cols)?;
-            let rv = eval_operand(rhs, left_row, left_cols, right_row, right_cols)?;
-            cmp_values(*op, lv, rv) // do actual comparison
+            let right_cols = right_cols.map(|c| c.as_slice());
+            if let Some(array) = any_operand(rhs) {
+                return Ok(eval_any(*op, lhs, array, left_row, left_cols, right_row, right_cols)? == Some(true));
+            }
+            let (lv, rv) = eval_comparison(lhs, rhs, left_row, left_cols, right_row, right_cols)?;
+            cmp_values(*op, &lv, &rv) // do actual comparison
         }
         Condition::And(a, b) => {
             let la = eval_condition(a, left_row, left_cols, right_row, right_cols)?;
@@ -154,3 +413,49 @@ pub fn eval_condition(
         }
     }
 }
+
+/// Evaluate a condition with SQL three-valued logic: None means UNKNOWN
+/// (a comparison involving NULL). Used for CHECK constraints, which only reject FALSE
+pub fn eval_condition_3vl(
+    cond: &Condition,
+    row: &Row,
+    cols: &[JoinTableColumn],
+) -> EvalResult<Option<bool>> {
+    match cond {
+        Condition::Cmp(op, lhs, rhs) => {
+            if let Some(array) = any_operand(rhs) {
+                return eval_any(*op, lhs, array, row, cols, None, None);
+            }
+            let (lv, rv) = eval_comparison(lhs, rhs, row, cols, None, None)?;
+            if matches!(*lv, Value::Null) || matches!(*rv, Value::Null) {
+                return Ok(None);
+            }
+            cmp_values(*op, &lv, &rv).map(Some)
+        }
+        Condition::And(a, b) => {
+            let la = eval_condition_3vl(a, row, cols)?;
+            if la == Some(false) {
+                return Ok(Some(false));
+            }
+            let lb = eval_condition_3vl(b, row, cols)?;
+            Ok(match (la, lb) {
+                (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            })
+        }
+        Condition::Or(a, b) => {
+            let la = eval_condition_3vl(a, row, cols)?;
+            if la == Some(true) {
+                return Ok(Some(true));
+            }
+            let lb = eval_condition_3vl(b, row, cols)?;
+            Ok(match (la, lb) {
+                (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            })
+        }
+        Condition::Not(x) => Ok(eval_condition_3vl(x, row, cols)?.map(|v| !v)),
+    }
+}
@@ -0,0 +1,428 @@
+use crate::errors::eval_error::{EvalError, EvalResult};
+use crate::types::datetime::{self, Interval, USECS_PER_DAY};
+use crate::types::filter_types::ArithOp;
+use crate::types::jsonb::Jsonb;
+use crate::types::numeric::{Float, Numeric};
+use crate::types::parser_types::{Operand, Query};
+use crate::types::storage_types::{ColumnType, Value};
+use crate::types::uuid;
+
+/// Functions that read the transaction's start time; they are replaced by literals
+/// before a statement runs, so every row and every statement of a transaction sees one time
+const NOW_FUNCTIONS: [&str; 5] = ["now", "current_timestamp", "transaction_timestamp", "current_date", "localtimestamp"];
+
+/// Functions that fold every row of a SELECT into one value
+const AGGREGATES: [&str; 2] = ["jsonb_agg", "array_agg"];
+
+/// Functions that read or advance a sequence; they are replaced by their results right
+/// before the expression holding them is evaluated, see Database::bind_sequences
+pub const SEQUENCE_FUNCTIONS: [&str; 3] = ["nextval", "currval", "setval"];
+
+/// Functions that give a new value on every call, even with the same arguments
+const VOLATILE_FUNCTIONS: [&str; 3] = ["gen_random_uuid", "nextval", "setval"];
+
+/// Whether evaluating `op` twice may give different values
+pub fn is_volatile(op: &Operand) -> bool {
+    match op {
+        Operand::Column(_) | Operand::Literal(_) => false,
+        Operand::Func(name, args) => VOLATILE_FUNCTIONS.contains(&name.as_str()) || args.iter().any(is_volatile),
+        Operand::Arith(_, lhs, rhs) => is_volatile(lhs) || is_volatile(rhs),
+        Operand::Cast(inner, _) => is_volatile(inner),
+    }
+}
+
+/// Whether `name` is an aggregate, which only a SELECT list may call
+pub fn is_aggregate(name: &str) -> bool {
+    AGGREGATES.contains(&name)
+}
+
+/// Replace `now()` and its relatives in the statement by the time `now` (a TIMESTAMPTZ)
+pub fn bind_now(query: &mut Query, now: i64) {
+    query.visit_operands_mut(&mut |op| bind_now_operand(op, now));
+}
+
+/// Replace a `now()` call (or one of its relatives) by the time `now`; a DEFAULT is
+/// bound this way when it is evaluated for a new row
+pub fn bind_now_operand(op: &mut Operand, now: i64) {
+    if let Operand::Func(name, args) = op
+        && args.is_empty()
+        && NOW_FUNCTIONS.contains(&name.as_str())
+    {
+        *op = Operand::Literal(match name.as_str() {
+            "current_date" => Value::Date(now.div_euclid(USECS_PER_DAY) as i32),
+            "localtimestamp" => Value::Timestamp(now),
+            _ => Value::TimestampTz(now),
+        });
+    }
+}
+
+/// Whether `op` calls nextval(), currval() or setval()
+pub fn calls_sequence(op: &Operand) -> bool {
+    match op {
+        Operand::Column(_) | Operand::Literal(_) => false,
+        Operand::Func(name, args) => SEQUENCE_FUNCTIONS.contains(&name.as_str()) || args.iter().any(calls_sequence),
+        Operand::Arith(_, lhs, rhs) => calls_sequence(lhs) || calls_sequence(rhs),
+        Operand::Cast(inner, _) => calls_sequence(inner),
+    }
+}
+
+fn error(msg: String) -> EvalError {
+    EvalError::Function(msg)
+}
+
+/// Call a built-in function. Every function but jsonb_build_object and ARRAY[...] returns
+/// NULL when an argument is NULL.
+pub fn call(name: &str, args: Vec<Value>) -> EvalResult<Value> {
+    if NOW_FUNCTIONS.contains(&name) || SEQUENCE_FUNCTIONS.contains(&name) {
+        return Err(error(format!("{}() is not available here", name)));
+    }
+    if is_aggregate(name) {
+        return Err(error(format!("aggregate function {}() is not allowed here", name)));
+    }
+    if name == "jsonb_build_object" {
+        return build_object(args);
+    }
+    if name == "array" {
+        return build_array(args);
+    }
+    if name == "any" {
+        return Err(error("ANY() is only allowed on the right of a comparison".to_string()));
+    }
+    if name == "unnest" {
+        return Err(error("unnest() is only allowed in FROM".to_string()));
+    }
+    if name == "gen_random_uuid" {
+        if !args.is_empty() {
+            return Err(error("gen_random_uuid() takes no arguments".to_string()));
+        }
+        return Ok(Value::Uuid(uuid::random_uuid()));
+    }
+    if args.iter().any(|a| matches!(a, Value::Null)) {
+        return Ok(Value::Null);
+    }
+    match (name, args.as_slice()) {
+        ("date_trunc", [Value::Text(field), source]) => date_trunc(&field.to_ascii_lowercase(), source),
+        ("extract", [Value::Text(field), source]) => {
+            let (units, scale) = extract(&field.to_ascii_lowercase(), source)?;
+            Ok(Value::Numeric(decimal(units, scale)))
+        }
+        ("date_part", [Value::Text(field), source]) => {
+            let (units, scale) = extract(&field.to_ascii_lowercase(), source)?;
+            Ok(Value::Float(Float(decimal(units, scale).to_f64())))
+        }
+        ("date_trunc" | "extract" | "date_part", _) => {
+            Err(error(format!("{}() expects a field name and a date/time value", name)))
+        }
+        _ => Err(error(format!("function {}() does not exist", name))),
+    }
+}
+
+/// `ARRAY[...]`: the elements keep their values, so all of them must have one type
+/// (numbers of different types mix); NULL elements fit any type. Arrays are one-dimensional.
+fn build_array(items: Vec<Value>) -> EvalResult<Value> {
+    if items.iter().any(|v| matches!(v, Value::Array(_))) {
+        return Err(error("arrays of arrays are not supported".to_string()));
+    }
+    let mut kinds = items.iter().filter(|v| !matches!(v, Value::Null));
+    if let Some(first) = kinds.next()
+        && let Some(other) = kinds.find(|v| v.vtype() != first.vtype() && first.cmp_number(v).is_none())
+    {
+        return Err(error(format!(
+            "ARRAY elements must have one type, found {} and {}",
+            first.vtype(),
+            other.vtype()
+        )));
+    }
+    Ok(Value::Array(items))
+}
+
+/// `array[i]`, counting from 1; NULL outside the array
+fn subscript(array: Value, index: Value) -> EvalResult<Value> {
+    match (array, index) {
+        (Value::Array(items), Value::Int(i)) => Ok(usize::try_from(i - 1)
+            .ok()
+            .and_then(|i| items.into_iter().nth(i))
+            .unwrap_or(Value::Null)),
+        (Value::Array(_), other) => Err(error(format!("array subscript must be an integer, got {}", other.vtype()))),
+        (other, _) => Err(error(format!("cannot subscript type {}", other.vtype()))),
+    }
+}
+
+/// Rows of the set-returning function `name` in FROM, one value each
+pub fn set_returning(name: &str, args: Vec<Value>) -> EvalResult<Vec<Value>> {
+    if name != "unnest" {
+        return Err(error(format!("function {}() does not return a set", name)));
+    }
+    let mut args = args.into_iter();
+    match (args.next(), args.next()) {
+        // NULL elements are rows too; a NULL array has none
+        (Some(Value::Array(items)), None) => Ok(items),
+        (Some(Value::Null), None) => Ok(Vec::new()),
+        _ => Err(error("unnest() expects one array".to_string())),
+    }
+}
+
+/// Result of the aggregate `name` over the values its argument took, one per row
+pub fn aggregate(name: &str, values: Vec<Value>) -> EvalResult<Value> {
+    match name {
+        // no rows give NULL, not an empty array
+        "jsonb_agg" if values.is_empty() => Ok(Value::Null),
+        "jsonb_agg" => Ok(Value::Jsonb(Jsonb::Array(values.into_iter().map(to_jsonb).collect()))),
+        "array_agg" if values.is_empty() => Ok(Value::Null),
+        "array_agg" => build_array(values),
+        _ => Err(error(format!("function {}() is not an aggregate", name))),
+    }
+}
+
+/// A value as a JSONB document: numbers, strings, booleans and NULL map to their JSON
+/// kinds, documents stay as they are and every other value becomes its text
+pub fn to_jsonb(value: Value) -> Jsonb {
+    match value {
+        Value::Null => Jsonb::Null,
+        Value::Bool(b) => Jsonb::Bool(b),
+        Value::Int(n) => Jsonb::Number(Numeric::from_i64(n)),
+        Value::Numeric(d) => Jsonb::Number(d),
+        Value::Float(f) => match Numeric::from_f64(f.0) {
+            Some(d) => Jsonb::Number(d),
+            None => Jsonb::String(f.to_string()),
+        },
+        Value::Text(s) => Jsonb::String(s),
+        Value::Jsonb(doc) => doc,
+        Value::Array(items) => Jsonb::Array(items.into_iter().map(to_jsonb).collect()),
+        other => Jsonb::String(other.to_string()),
+    }
+}
+
+/// jsonb_build_object(key, value, ...): keys are read as text and may not be NULL
+fn build_object(args: Vec<Value>) -> EvalResult<Value> {
+    if !args.len().is_multiple_of(2) {
+        return Err(error("jsonb_build_object() expects an even number of arguments".to_string()));
+    }
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    let mut args = args.into_iter();
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        let key = match key {
+            Value::Null => return Err(error("jsonb_build_object() keys must not be null".to_string())),
+            Value::Text(s) => s,
+            other => other.to_string(),
+        };
+        pairs.push((key, to_jsonb(value)));
+    }
+    Ok(Value::Jsonb(Jsonb::object(pairs)))
+}
+
+/// `doc -> key` and `doc ->> key`: an object field for text, an array element for an integer.
+/// A missing field or element is NULL; a text document on the left is parsed first.
+fn json_get(op: ArithOp, doc: Value, key: Value) -> EvalResult<Value> {
+    let doc = match doc {
+        Value::Jsonb(doc) => doc,
+        Value::Text(text) => Jsonb::parse(&text)
+            .ok_or_else(|| error(format!("invalid input syntax for type jsonb: \"{}\"", text)))?,
+        other => return Err(error(format!("operator does not exist: {} {} {}", other.vtype(), op, key.vtype()))),
+    };
+    let found = match &key {
+        Value::Text(field) => doc.field(field),
+        Value::Int(index) => doc.element(*index),
+        other => return Err(error(format!("operator does not exist: JSONB {} {}", op, other.vtype()))),
+    };
+    Ok(match (found, op) {
+        (None, _) => Value::Null,
+        (Some(value), ArithOp::JsonGetText) => value.to_text().map_or(Value::Null, Value::Text),
+        (Some(value), _) => Value::Jsonb(value.clone()),
+    })
+}
+
+/// `units * 10^-scale` as a NUMERIC
+fn decimal(units: i64, scale: u32) -> Numeric {
+    let digits = Numeric::from_i64(units).to_string();
+    Numeric::parse(&format!("{}e-{}", digits, scale)).expect("integer digits parse")
+}
+
+/// A text argument where a timestamp is expected is read as one
+fn as_timestamp(value: &Value) -> EvalResult<Value> {
+    match value {
+        Value::Text(text) => ColumnType::Timestamp.parse_temporal(text).map_err(error),
+        other => Ok(other.clone()),
+    }
+}
+
+fn date_trunc(field: &str, source: &Value) -> EvalResult<Value> {
+    let truncate = |usecs: i64| datetime::truncate_timestamp(field, usecs).map_err(error);
+    match as_timestamp(source)? {
+        Value::Timestamp(t) => Ok(Value::Timestamp(truncate(t)?)),
+        Value::TimestampTz(t) => Ok(Value::TimestampTz(truncate(t)?)),
+        Value::Date(d) => Ok(Value::Timestamp(truncate(d as i64 * USECS_PER_DAY)?)),
+        other => Err(error(format!("date_trunc() is not defined for {}", other.vtype()))),
+    }
+}
+
+fn extract(field: &str, source: &Value) -> EvalResult<(i64, u32)> {
+    match as_timestamp(source)? {
+        Value::Timestamp(t) | Value::TimestampTz(t) => datetime::extract_timestamp(field, t),
+        Value::Date(d) => datetime::extract_timestamp(field, d as i64 * USECS_PER_DAY),
+        Value::Time(t) => datetime::extract_time(field, t),
+        Value::Interval(i) => datetime::extract_interval(field, &i),
+        other => return Err(error(format!("extract() is not defined for {}", other.vtype()))),
+    }
+    .map_err(error)
+}
+
+/// `lhs + rhs` / `lhs - rhs` / `lhs * rhs` / `lhs / rhs`, and the JSONB `->` / `->>`. NULL in, NULL out.
+/// Numbers of two types give the wider one: integer, NUMERIC, then floating point.
+/// A text operand next to a date/time value is read as an interval, or else as the other
+/// operand's type (`ts - "2024-01-01"`); date/time values only add and subtract.
+pub fn arith(op: ArithOp, lhs: Value, rhs: Value) -> EvalResult<Value> {
+    if matches!(lhs, Value::Null) || matches!(rhs, Value::Null) {
+        return Ok(Value::Null);
+    }
+    if matches!(op, ArithOp::JsonGet | ArithOp::JsonGetText) {
+        return json_get(op, lhs, rhs);
+    }
+    if op == ArithOp::Subscript {
+        return subscript(lhs, rhs);
+    }
+    let (lhs, rhs) = match (lhs, rhs) {
+        (Value::Text(text), other) if other.temporal_type().is_some() => (resolve_text(&text, &other)?, other),
+        (other, Value::Text(text)) if other.temporal_type().is_some() => {
+            let resolved = resolve_text(&text, &other)?;
+            (other, resolved)
+        }
+        pair => pair,
+    };
+
+    let overflow = |what: &str| error(format!("{} out of range", what));
+    let no_operator = || error(format!("operator does not exist: {} {} {}", lhs.vtype(), op, rhs.vtype()));
+    let sub = op == ArithOp::Sub;
+    let result = match (&lhs, &rhs) {
+        (Value::Int(a), Value::Int(b)) => Value::Int(int_arith(op, *a, *b, &ColumnType::BigInt)?),
+        (Value::Float(_), _) | (_, Value::Float(_)) if lhs.cmp_number(&rhs).is_some() => {
+            Value::Float(float_arith(op, to_f64(&lhs), to_f64(&rhs))?)
+        }
+        (Value::Int(_) | Value::Numeric(_), Value::Int(_) | Value::Numeric(_)) => {
+            let (a, b) = (to_numeric(&lhs), to_numeric(&rhs));
+            Value::Numeric(match op {
+                ArithOp::Add => a.plus(&b),
+                ArithOp::Sub => a.minus(&b),
+                ArithOp::Mul => a.times(&b),
+                _ => a.divide(&b).map_err(error)?,
+            })
+        }
+
+        // the date/time operators below add and subtract only
+        _ if !matches!(op, ArithOp::Add | ArithOp::Sub) => return Err(no_operator()),
+
+        // dates move by whole days; two dates differ by a number of days
+        (Value::Date(d), Value::Int(n)) => {
+            let days = if sub { *d as i64 - n } else { *d as i64 + n };
+            Value::Date(datetime::checked_date(days).ok_or_else(|| overflow("date"))?)
+        }
+        (Value::Int(n), Value::Date(d)) if !sub => {
+            Value::Date(datetime::checked_date(*d as i64 + n).ok_or_else(|| overflow("date"))?)
+        }
+        (Value::Date(a), Value::Date(b)) if sub => Value::Int(*a as i64 - *b as i64),
+
+        // date/timestamp ± interval
+        (Value::Date(_) | Value::Timestamp(_) | Value::TimestampTz(_), Value::Interval(i)) => {
+            let i = if sub { i.checked_neg().ok_or_else(|| overflow("interval"))? } else { *i };
+            let (usecs, tz) = match lhs {
+                Value::Date(d) => (d as i64 * USECS_PER_DAY, false),
+                Value::Timestamp(t) => (t, false),
+                Value::TimestampTz(t) => (t, true),
+                _ => unreachable!(),
+            };
+            let usecs = datetime::add_interval(usecs, &i).ok_or_else(|| overflow("timestamp"))?;
+            if tz { Value::TimestampTz(usecs) } else { Value::Timestamp(usecs) }
+        }
+        (Value::Interval(_), Value::Date(_) | Value::Timestamp(_) | Value::TimestampTz(_)) if !sub => {
+            return arith(op, rhs, lhs);
+        }
+        (Value::Date(d), Value::Time(t)) | (Value::Time(t), Value::Date(d)) if !sub => {
+            Value::Timestamp(*d as i64 * USECS_PER_DAY + t)
+        }
+
+        // timestamp - timestamp: days and the remaining time
+        (Value::Timestamp(a) | Value::TimestampTz(a), Value::Timestamp(b) | Value::TimestampTz(b))
+            if sub && lhs.vtype() == rhs.vtype() =>
+        {
+            Value::Interval(datetime::timestamp_diff(*a, *b).ok_or_else(|| overflow("interval"))?)
+        }
+
+        // time of day wraps around midnight
+        (Value::Time(t), Value::Interval(i)) => {
+            let shift = if sub { -i.usecs } else { i.usecs };
+            Value::Time((t + shift % USECS_PER_DAY).rem_euclid(USECS_PER_DAY))
+        }
+        (Value::Interval(i), Value::Time(t)) if !sub => {
+            Value::Time((t + i.usecs % USECS_PER_DAY).rem_euclid(USECS_PER_DAY))
+        }
+        (Value::Time(a), Value::Time(b)) if sub => Value::Interval(Interval {
+            usecs: a - b,
+            ..Interval::default()
+        }),
+
+        (Value::Interval(a), Value::Interval(b)) => {
+            let b = if sub { b.checked_neg() } else { Some(*b) };
+            Value::Interval(b.and_then(|b| a.checked_add(&b)).ok_or_else(|| overflow("interval"))?)
+        }
+
+        _ => return Err(no_operator()),
+    };
+    Ok(result)
+}
+
+/// `a op b` for integers of `int_type`; a result outside its range is an error, and
+/// division truncates toward zero
+pub fn int_arith(op: ArithOp, a: i64, b: i64, int_type: &ColumnType) -> EvalResult<i64> {
+    let result = match op {
+        ArithOp::Add => a.checked_add(b),
+        ArithOp::Sub => a.checked_sub(b),
+        ArithOp::Mul => a.checked_mul(b),
+        ArithOp::Div if b == 0 => return Err(error("division by zero".to_string())),
+        ArithOp::Div => a.checked_div(b),
+        _ => None,
+    };
+    let (min, max) = int_type.int_range().unwrap_or((i64::MIN, i64::MAX));
+    result
+        .filter(|n| (min..=max).contains(n))
+        .ok_or_else(|| error(format!("{} out of range", int_type)))
+}
+
+/// `a op b` in double precision; an infinite result from finite operands is an overflow
+fn float_arith(op: ArithOp, a: f64, b: f64) -> EvalResult<Float> {
+    let result = match op {
+        ArithOp::Add => a + b,
+        ArithOp::Sub => a - b,
+        ArithOp::Mul => a * b,
+        ArithOp::Div if b == 0.0 => return Err(error("division by zero".to_string())),
+        _ => a / b,
+    };
+    if result.is_infinite() && a.is_finite() && b.is_finite() {
+        return Err(error("value out of range: overflow".to_string()));
+    }
+    Ok(Float(result))
+}
+
+fn resolve_text(text: &str, other: &Value) -> EvalResult<Value> {
+    if let Some(interval) = Interval::parse(text) {
+        return Ok(Value::Interval(interval));
+    }
+    let column_type = other.temporal_type().unwrap_or(ColumnType::Timestamp);
+    column_type.parse_temporal(text).map_err(error)
+}
+
+fn to_f64(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Float(f) => f.0,
+        Value::Numeric(d) => d.to_f64(),
+        _ => f64::NAN,
+    }
+}
+
+fn to_numeric(value: &Value) -> Numeric {
+    match value {
+        Value::Int(n) => Numeric::from_i64(*n),
+        Value::Numeric(d) => d.clone(),
+        _ => Numeric::from_i64(0),
+    }
+}
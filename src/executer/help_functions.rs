@@ -1,10 +1,17 @@
-use crate::types::storage_types::{Column, Database, Table};
+use crate::executer::alter::fk_name;
+use crate::executer::filter::{eval_condition_3vl, eval_operand};
+use crate::executer::functions::bind_now_operand;
+use crate::executer::join::JoinTableColumn;
+use crate::types::storage_types::{Column, Database, ForeignKeyConstraint, Table};
+use crate::types::b_tree::BTreeIndex;
+use crate::types::page_types::TupleHeader;
 use crate::types::storage_types::{Row, Value};
+use crate::types::transaction_types::{DeferredConstraint, Snapshot, TxStatus};
 
 pub fn build_key(
     index_columns: &Vec<String>,
     table_columns: &[Column],
-    values: &Vec<Value>,
+    values: &[Value],
     table_name: &str,
 ) -> Result<Vec<Value>, String> {
     let mut key = Vec::new();
@@ -18,135 +25,281 @@ pub fn build_key(
     Ok(key)
 }
 
-pub fn validate_foreign_keys(
+/// Find an index on `table_name` whose key columns are exactly `columns` (in any order).
+/// A unique index is preferred, so a plain index on the same columns does not hide a key.
+pub fn find_index_on<'a>(
+    db: &'a Database,
+    table_name: &str,
+    columns: &[String],
+) -> Option<&'a BTreeIndex> {
+    db.indexes
+        .values()
+        .filter(|idx| {
+            idx.table == table_name
+                && idx.columns.len() == columns.len()
+                && columns.iter().all(|c| idx.columns.contains(c))
+        })
+        .max_by_key(|idx| idx.unique)
+}
+
+/// Tuples of `table` whose `columns` equal `values`, as (page_no, slot_no, header, row).
+/// Probes an index when one matches, otherwise scans the heap.
+pub fn lookup_rows(
     db: &Database,
     table: &Table,
-    row_values: &Vec<Value>,
-) -> Result<(), String> {
-    for fk in &table.foreign_keys {
-        let mut local_values = Vec::new();
-        for col_name in &fk.local_columns {
-            let Some(idx) = table.columns.iter().position(|c| c.name == *col_name) else {
-                return Err(format!(
-                    "Foreign key error: local column '{}' not found in '{}'",
-                    col_name, table.name
-                ));
-            };
-            local_values.push(row_values[idx].clone());
-        }
+    columns: &[String],
+    values: &[Value],
+) -> Result<Vec<(u32, usize, TupleHeader, Row)>, String> {
+    let mut col_indices = Vec::new();
+    for col_name in columns {
+        let Some(idx) = table.columns.iter().position(|c| c.name == *col_name) else {
+            return Err(format!(
+                "Foreign key error: column '{}' not found in '{}'",
+                col_name, table.name
+            ));
+        };
+        col_indices.push(idx);
+    }
 
-        if local_values.iter().all(|v| matches!(v, Value::Null)) {
-            continue;
-        }
+    if let Some(idx) = find_index_on(db, &table.name, columns) {
+        // reorder values to match the index key order
+        let key: Vec<Value> = idx
+            .columns
+            .iter()
+            .map(|ic| values[columns.iter().position(|c| c == ic).unwrap()].clone())
+            .collect();
 
-        let parent_table = db.tables.get(&fk.referenced_table).ok_or_else(|| {
-            format!(
-                "Foreign key error: referenced table '{}' not found",
-                fk.referenced_table
-            )
-        })?;
-
-        let mut ref_indices = Vec::new();
-        for ref_col in &fk.referenced_columns {
-            let Some(idx) = parent_table.columns.iter().position(|c| c.name == *ref_col) else {
-                return Err(format!(
-                    "Foreign key error: referenced column '{}' not found in '{}'",
-                    ref_col, fk.referenced_table
-                ));
-            };
-            ref_indices.push(idx);
+        let mut found = Vec::new();
+        for &(page_no, slot_no) in idx.search_eq(&key).into_iter().flatten() {
+            if let Some((header, row)) =
+                table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
+            {
+                found.push((page_no as u32, slot_no, header, row));
+            }
         }
+        return Ok(found);
+    }
 
-        let parent_rows: Vec<Row> = parent_table
-            .heap
-            .scan_all(&parent_table.columns)
-            .into_iter()
-            .map(|(_, _, _, row)| row)
-            .collect();
-        let mut found = false;
-        for prow in parent_rows {
-            if local_values
+    Ok(table
+        .heap
+        .scan_all(&table.columns)
+        .into_iter()
+        .filter(|(_, _, _, row)| {
+            col_indices
                 .iter()
-                .zip(ref_indices.iter())
-                .all(|(lv, ri)| &prow.values[*ri] == lv)
-            {
-                found = true;
-                break;
-            }
+                .zip(values.iter())
+                .all(|(ci, v)| &row.values[*ci] == v)
+        })
+        .collect())
+}
+
+/// Check every foreign key of `table` for a new row version.
+/// Constraints deferred in transaction `xid` are skipped and returned for queuing.
+pub fn validate_foreign_keys(
+    db: &Database,
+    table: &Table,
+    row_values: &[Value],
+    xid: u32,
+    snapshot: &Snapshot,
+) -> Result<Vec<DeferredConstraint>, String> {
+    let mut deferred = Vec::new();
+    for fk in &table.foreign_keys {
+        if db.transaction_manager.is_deferred(xid, fk.deferrable) {
+            deferred.push(DeferredConstraint::ForeignKey(fk.clone()));
+            continue;
         }
+        check_foreign_key(db, table, fk, row_values, xid, snapshot)?;
+    }
+    Ok(deferred)
+}
+
+/// The referenced parent row of `fk` must be visible to this transaction
+pub fn check_foreign_key(
+    db: &Database,
+    table: &Table,
+    fk: &ForeignKeyConstraint,
+    row_values: &[Value],
+    xid: u32,
+    snapshot: &Snapshot,
+) -> Result<(), String> {
+    let mut local_values = Vec::new();
+    for col_name in &fk.local_columns {
+        let Some(idx) = table.columns.iter().position(|c| c.name == *col_name) else {
+            return Err(format!(
+                "Foreign key error: local column '{}' not found in '{}'",
+                col_name, table.name
+            ));
+        };
+        local_values.push(row_values[idx].clone());
+    }
 
-        if !found {
+    if local_values.iter().any(|v| matches!(v, Value::Null)) {
+        return Ok(());
+    }
+
+    let parent_table = db.tables.get(&fk.referenced_table).ok_or_else(|| {
+        format!(
+            "Foreign key error: referenced table '{}' not found",
+            fk.referenced_table
+        )
+    })?;
+
+    // parent row must be visible to this transaction
+    let found = lookup_rows(db, parent_table, &fk.referenced_columns, &local_values)?
+        .iter()
+        .any(|(_, _, header, _)| header.is_visible(xid, snapshot, &db.transaction_manager));
+
+    if !found {
+        return Err(missing_key_error(&table.name, fk, &local_values));
+    }
+    Ok(())
+}
+
+/// Error for a row of `table_name` whose `key` has no row in the referenced table
+pub fn missing_key_error(table_name: &str, fk: &ForeignKeyConstraint, key: &[Value]) -> String {
+    format!(
+        "insert or update on table '{}' violates foreign key constraint '{}': key {:?} is not present in '{}'",
+        table_name,
+        fk_name(table_name, fk),
+        key,
+        fk.referenced_table
+    )
+}
+
+/// Error for a `verb` ("update", "delete") that takes away `key` of `parent_name`
+/// while rows of `child_name` still reference it
+pub fn still_referenced_error(
+    verb: &str,
+    parent_name: &str,
+    child_name: &str,
+    fk: &ForeignKeyConstraint,
+    key: &[Value],
+) -> String {
+    format!(
+        "{} on table '{}' violates foreign key constraint '{}' on table '{}': key {:?} is still referenced from '{}'",
+        verb,
+        parent_name,
+        fk_name(child_name, fk),
+        child_name,
+        key,
+        child_name
+    )
+}
+
+/// Value of `column`'s DEFAULT for a new row (NULL without one); expressions such as
+/// gen_random_uuid() are evaluated on every call
+pub fn default_value(db: &Database, column: &Column) -> Result<Value, String> {
+    let Some(default) = &column.default else {
+        return Ok(Value::Null);
+    };
+    let mut default = default.clone();
+    default.visit_mut(&mut |op| bind_now_operand(op, db.transaction_time));
+    let default = db
+        .bind_sequences(&default)
+        .map_err(|e| format!("DEFAULT for column '{}': {}", column.name, e))?;
+    let value = eval_operand(&default, &Row { values: Vec::new() }, &[], None, None)
+        .map_err(|e| format!("DEFAULT for column '{}': {}", column.name, e))?
+        .into_owned();
+    column.column_type.check_default_value(&column.name, value)
+}
+
+/// Evaluate every CHECK constraint of `table` against a new row; only FALSE is a violation
+pub fn check_constraints(table: &Table, row_values: &[Value]) -> Result<(), String> {
+    if table.checks.is_empty() {
+        return Ok(());
+    }
+    let metas: Vec<JoinTableColumn> = table
+        .columns
+        .iter()
+        .map(|c| JoinTableColumn {
+            table_alias: table.name.clone(),
+            column_name: c.name.clone(),
+            column_type: c.column_type.clone(),
+        })
+        .collect();
+    let row = Row { values: row_values.to_vec() };
+
+    for check in &table.checks {
+        let passed = eval_condition_3vl(&check.condition, &row, &metas)
+            .map_err(|e| format!("check constraint '{}': {}", check.name, e))?;
+        if passed == Some(false) {
             return Err(format!(
-                "violates foreign key constraint: {:?} -> {}({:?})",
-                fk.local_columns, fk.referenced_table, fk.referenced_columns
+                "new row for '{}' violates check constraint '{}'",
+                table.name, check.name
             ));
         }
     }
     Ok(())
 }
 
-pub fn ensure_not_referenced(
+/// Enforce all unique indexes of `table` for a new row version.
+/// `ignore` skips the old version of a row being updated.
+/// Indexes deferred in transaction `xid` are skipped and returned for queuing.
+pub fn check_unique_indexes(
     db: &Database,
-    table_name: &str,
-    row_values: &Vec<Value>,
+    table: &Table,
+    row_values: &[Value],
+    xid: u32,
+    ignore: Option<(usize, usize)>,
+) -> Result<Vec<DeferredConstraint>, String> {
+    let mut deferred = Vec::new();
+    for idx in db.indexes.values() {
+        if !idx.unique || idx.table != table.name {
+            continue;
+        }
+        if db.transaction_manager.is_deferred(xid, idx.deferrable) {
+            deferred.push(DeferredConstraint::Unique(idx.name.clone()));
+            continue;
+        }
+        check_unique_index(db, idx, table, row_values, xid, ignore)?;
+    }
+    Ok(deferred)
+}
+
+/// Enforce one unique index. Conflicts with any tuple that is not dead:
+/// visible rows, rows inserted by transactions still in progress,
+/// and rows whose deleter has not committed.
+pub fn check_unique_index(
+    db: &Database,
+    idx: &BTreeIndex,
+    table: &Table,
+    row_values: &[Value],
+    xid: u32,
+    ignore: Option<(usize, usize)>,
 ) -> Result<(), String> {
-    let table = db.tables.get(table_name).unwrap();
+    let tm = &db.transaction_manager;
 
-    for (other_name, other_table) in &db.tables {
-        if other_name == table_name {
+    let key = build_key(&idx.columns, &table.columns, row_values, &table.name)?;
+    // NULLs never collide
+    if key.iter().any(|v| matches!(v, Value::Null)) {
+        return Ok(());
+    }
+
+    let Some(positions) = idx.search_eq(&key) else {
+        return Ok(());
+    };
+
+    for &(page_no, slot_no) in positions {
+        if Some((page_no, slot_no)) == ignore {
             continue;
         }
-        for fk in &other_table.foreign_keys {
-            if fk.referenced_table == table_name {
-                // соберём значения из удаляемой строки по ref_columns
-                let mut ref_values = Vec::new();
-                for ref_col in &fk.referenced_columns {
-                    let Some(idx) = table.columns.iter().position(|c| c.name == *ref_col) else {
-                        return Err(format!(
-                            "Foreign key error: column '{}' not found in '{}'",
-                            ref_col, table_name
-                        ));
-                    };
-                    ref_values.push(row_values[idx].clone());
-                }
-
-                let child_rows: Vec<Row> = other_table
-                    .heap
-                    .scan_all(&other_table.columns)
-                    .into_iter()
-                    .map(|(_, _, _, row)| row)
-                    .collect();
-                for child in child_rows {
-                    let mut match_all = true;
-                    for (local_col, ref_val) in fk.local_columns.iter().zip(ref_values.iter()) {
-                        let Some(idx) = other_table
-                            .columns
-                            .iter()
-                            .position(|c| c.name == *local_col)
-                        else {
-                            return Err(format!(
-                                "Foreign key error: column '{}' not found in '{}'",
-                                local_col, other_name
-                            ));
-                        };
-                        if &child.values[idx] != ref_val {
-                            match_all = false;
-                            break;
-                        }
-                    }
-                    if match_all {
-                        return Err(format!(
-                            "delete from '{}' violates foreign key constraint in '{}': {:?} -> {}({:?})",
-                            table_name,
-                            other_name,
-                            fk.local_columns,
-                            fk.referenced_table,
-                            fk.referenced_columns
-                        ));
-                    }
-                }
-            }
+        let Some((header, _)) = table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
+        else {
+            continue;
+        };
+        if header.is_dead(tm) || header.xmax == Some(xid) {
+            continue;
+        }
+        if header.xmin != xid && tm.status(header.xmin) == TxStatus::InProgress {
+            return Err(format!(
+                "duplicate key value violates unique constraint '{}': key {:?} is being inserted by transaction {}",
+                idx.name, key, header.xmin
+            ));
         }
+        return Err(format!(
+            "duplicate key value violates unique constraint '{}': key {:?} already exists",
+            idx.name, key
+        ));
     }
     Ok(())
 }
@@ -1,6 +1,8 @@
 use crate::errors::engine_error::EngineError;
+use crate::executer::help_functions::build_key;
 use crate::types::b_tree::BTreeIndex;
-use crate::types::storage_types::Database;
+use crate::types::storage_types::{Database, Value};
+use std::collections::BTreeSet;
 
 impl Database {
     pub fn create_index(
@@ -8,6 +10,7 @@ impl Database {
         name: &str,
         table_name: &str,
         columns: Vec<String>,
+        unique: bool,
     ) -> Result<(), EngineError> {
         if self.indexes.contains_key(name) {
             return Err(EngineError::Database(format!(
@@ -29,7 +32,25 @@ impl Database {
             }
         }
 
-        let idx = BTreeIndex::new(name.to_string(), table_name.to_string(), columns);
+        let mut idx = BTreeIndex::new(name.to_string(), table_name.to_string(), columns, unique);
+
+        // Fill index from existing tuples, rejecting duplicates among live ones
+        let mut live_keys = BTreeSet::new();
+        for (page_no, slot_no, header, row) in table.heap.scan_all(&table.columns) {
+            let key = build_key(&idx.columns, &table.columns, &row.values, table_name)?;
+            if unique
+                && !header.is_dead(&self.transaction_manager)
+                && !key.iter().any(|v| matches!(v, Value::Null))
+                && !live_keys.insert(key.clone())
+            {
+                return Err(EngineError::Database(format!(
+                    "could not create unique index '{}': duplicate key {:?}",
+                    name, key
+                )));
+            }
+            idx.insert(key, (page_no as usize, slot_no));
+        }
+
         self.indexes.insert(name.to_string(), idx);
 
         Ok(())
@@ -1,27 +1,129 @@
-use crate::executer::help_functions::validate_foreign_keys;
-use crate::types::page_types::TupleHeader;
-use crate::types::storage_types::Database;
-use crate::types::storage_types::{ColumnType, Row, Value};
-use crate::types::transaction_types::Snapshot;
+use crate::executer::help_functions::{
+    build_key, check_constraints, check_unique_indexes, default_value, validate_foreign_keys,
+};
+use crate::executer::conflict::AffectedKeys;
+use crate::types::parser_types::{ConflictAction, OnConflict, Overriding};
+use crate::types::storage_types::{Database, Identity, Table};
+use crate::types::storage_types::{Row, Value};
+use crate::types::transaction_types::{DeferredConstraint, Snapshot};
+use std::collections::{BTreeSet, HashMap};
 
 impl Database {
-    // Inserts a new row into a table
+    /// Inserts rows into a table and returns them as stored (defaults applied).
+    /// All rows are checked before any is written, so the heap pages are written in one batch;
+    /// a self-referencing foreign key or ON CONFLICT makes each row visible before the next
+    /// one is checked. Rows resolved by ON CONFLICT DO UPDATE are returned as updated.
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_into(
         &mut self,
         table_name: &str,
         column_names: Option<Vec<String>>, // Optional: user can specify columns
-        values: Vec<Value>,                // Values to insert
+        rows: Vec<Vec<Value>>,             // Rows of values to insert
+        overriding: Option<Overriding>,    // OVERRIDING {SYSTEM | USER} VALUE for identity columns
+        on_conflict: Option<&OnConflict>,
         xid: u32,
         snapshot: &Snapshot
-    ) -> Result<(), String> {
-        // Get the target table (immutable for now)
+    ) -> Result<Vec<Row>, String> {
+        // Clone table metadata: writing the rows needs `self` mutably
         let table = self
             .tables
             .get(table_name)
-            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?
+            .clone();
+        let row_at_a_time = on_conflict.is_some()
+            || table
+                .foreign_keys
+                .iter()
+                .any(|fk| fk.referenced_table == table_name);
+        let arbiters = match on_conflict {
+            Some(on_conflict) => self.conflict_arbiters(&table, on_conflict)?,
+            None => Vec::new(),
+        };
+
+        // Keys of non-deferred unique indexes used so far by this statement
+        let mut batch_keys: HashMap<String, BTreeSet<Vec<Value>>> = HashMap::new();
+        // Arbiter keys of rows this statement inserted or updated
+        let mut affected = AffectedKeys::new();
+        let mut pending = Vec::new();
+        let mut inserted = Vec::with_capacity(rows.len());
+
+        for values in rows {
+            let final_values = self.build_row(&table, column_names.as_ref(), values, overriding)?;
+
+            if let Some(on_conflict) = on_conflict {
+                if let Some((pos, existing)) =
+                    self.find_conflict(&table, &arbiters, &final_values, xid, snapshot)?
+                {
+                    if let ConflictAction::Update { .. } = on_conflict.action {
+                        let keys = self.arbiter_keys(&table, &arbiters, &final_values)?;
+                        if keys.iter().any(|key| affected.contains(key)) {
+                            return Err(
+                                "ON CONFLICT DO UPDATE command cannot affect row a second time".into(),
+                            );
+                        }
+                        let updated = self.conflict_update(
+                            &table,
+                            pos,
+                            existing,
+                            final_values,
+                            &on_conflict.action,
+                            xid,
+                            snapshot,
+                        )?;
+                        if let Some(row) = updated {
+                            affected.extend(self.arbiter_keys(&table, &arbiters, &row.values)?);
+                            inserted.push(row);
+                        }
+                    }
+                    continue;
+                }
+                affected.extend(self.arbiter_keys(&table, &arbiters, &final_values)?);
+            }
+
+            let deferred = self.check_row(&table, &final_values, xid, snapshot)?;
+
+            for idx in self.indexes.values() {
+                if !idx.unique
+                    || idx.table != table_name
+                    || self.transaction_manager.is_deferred(xid, idx.deferrable)
+                {
+                    continue;
+                }
+                let key = build_key(&idx.columns, &table.columns, &final_values, table_name)?;
+                // NULLs never collide
+                if key.iter().any(|v| matches!(v, Value::Null)) {
+                    continue;
+                }
+                if !batch_keys.entry(idx.name.clone()).or_default().insert(key.clone()) {
+                    return Err(format!(
+                        "duplicate key value violates unique constraint '{}': key {:?} already exists",
+                        idx.name, key
+                    ));
+                }
+            }
+
+            pending.push((Row { values: final_values }, deferred));
+            if row_at_a_time {
+                inserted.extend(self.write_rows(&table, std::mem::take(&mut pending), xid)?);
+            }
+        }
+        inserted.extend(self.write_rows(&table, pending, xid)?);
+
+        Ok(inserted)
+    }
+
+    /// Build the stored form of one VALUES row: column order, types, defaults and NOT NULL
+    fn build_row(
+        &self,
+        table: &Table,
+        column_names: Option<&Vec<String>>,
+        values: Vec<Value>,
+        overriding: Option<Overriding>,
+    ) -> Result<Vec<Value>, String> {
+        let table_name = table.name.as_str();
 
         // Reorder or validate values if column names are specified
-        let mut final_values: Vec<Value> = if let Some(ref col_names) = column_names {
+        let mut final_values: Vec<Value> = if let Some(col_names) = column_names {
             // Check if column count matches
             if col_names.len() != values.len() {
                 return Err(format!(
@@ -68,95 +170,115 @@ impl Database {
             values
         };
 
-        // Type checking for each value against column type
-        for (i, (value, column)) in final_values.iter().zip(&table.columns).enumerate() {
-            let compatible = match (value, &column.column_type) {
-                (Value::Int(_), ColumnType::Int) => true,
-                (Value::Text(_), ColumnType::Text) => true,
-                (Value::Bool(_), ColumnType::Bool) => true,
-                (Value::Null, _) => true, // NULL allowed in any column
-                _ => false,
-            };
-
-            if !compatible {
-                return Err(format!("Type mismatch at column {} ('{}')", i, column.name));
+        // Identity columns take values only as OVERRIDING allows; USER VALUE discards them
+        for (value, column) in final_values.iter_mut().zip(&table.columns) {
+            let Some(identity) = column.identity else { continue };
+            if *value == Value::Null {
+                continue;
+            }
+            match overriding {
+                Some(Overriding::UserValue) => *value = Value::Null,
+                Some(Overriding::SystemValue) => {}
+                None if identity == Identity::Always => {
+                    return Err(format!(
+                        "cannot insert a non-DEFAULT value into column '{}': it is GENERATED ALWAYS (use OVERRIDING SYSTEM VALUE)",
+                        column.name
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        // Type checking for each value against column type; numbers are converted to it
+        for (i, (value, column)) in final_values.iter_mut().zip(&table.columns).enumerate() {
+            if !column.column_type.accepts(value) {
+                return Err(format!(
+                    "Type mismatch at column {} ('{}'): {} is not assignable to {}; use an explicit cast",
+                    i,
+                    column.name,
+                    value.vtype(),
+                    column.column_type
+                ));
             }
+            *value = column
+                .column_type
+                .coerce(std::mem::replace(value, Value::Null))
+                .map_err(|e| format!("{} for column '{}'", e, column.name))?;
         }
 
         // Apply defaults + check NOT NULL constraints
         for (i, column) in table.columns.iter().enumerate() {
             if let Value::Null = final_values[i] {
-                if let Some(def) = &column.default {
-                    final_values[i] = def.clone();
-                } else if column.not_null {
+                final_values[i] = default_value(self, column)?;
+                if final_values[i] == Value::Null && column.not_null {
                     return Err(format!("Column '{}' cannot be NULL", column.name));
                 }
             }
         }
 
-        // Enforce primary key uniqueness
-        if let Some(pk_name) = &table.primary_key {
-            let Some(pk_idx) = table.columns.iter().position(|c| c.name == *pk_name) else {
-                return Err(format!("Primary key column '{}' not found", pk_name));
-            };
-
-            let pk_val = &final_values[pk_idx];
-            if let Value::Null = pk_val {
-                return Err(format!("Primary key '{}' cannot be NULL", pk_name));
-            }
-
-            let existing_rows: Vec<(Row, TupleHeader)> = table
-                .heap
-                .scan_all(&table.columns)
-                .into_iter()
-                .map(|(_, _, header, row)| (row, header))
-                .collect();
-            for (row, header) in existing_rows {
-                if header.is_visible(xid, snapshot, &self.transaction_manager) {
-                    if row.values[pk_idx] == *pk_val {
-                        return Err(format!(
-                            "duplicate key value violates primary key constraint on '{}'",
-                            pk_name
-                        ));
-                    }
+        // Primary key columns are implicitly NOT NULL;
+        // uniqueness is enforced by the <table>_pkey unique index below
+        if let Some(pk_cols) = &table.primary_key {
+            for pk_name in pk_cols {
+                let Some(pk_idx) = table.columns.iter().position(|c| c.name == *pk_name) else {
+                    return Err(format!("Primary key column '{}' not found", pk_name));
+                };
+                if let Value::Null = final_values[pk_idx] {
+                    return Err(format!("Primary key '{}' cannot be NULL", pk_name));
                 }
             }
         }
 
+        Ok(final_values)
+    }
+
+    /// Run the immediate checks on a new row; returns the checks deferred to COMMIT
+    fn check_row(
+        &self,
+        table: &Table,
+        values: &[Value],
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<Vec<DeferredConstraint>, String> {
+        // CHECK constraints
+        check_constraints(table, values)?;
+
         // Foreign key validation
-        validate_foreign_keys(self, table, &final_values)?;
+        let mut deferred = validate_foreign_keys(self, table, values, xid, snapshot)?;
 
-        // Now reopen table mutably to perform the actual insert
-        let table = self
-            .tables
-            .get_mut(table_name)
-            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+        // Unique index enforcement (visible and in-progress tuples)
+        deferred.extend(check_unique_indexes(self, table, values, xid, None)?);
 
-        // Insert row into the table's heap
-        let row = Row {
-            values: final_values,
-        };
+        Ok(deferred)
+    }
 
-        let (page_no, slot_no) = table.heap.insert_row(row.clone(), xid)?;
-
-        // Update all indexes for this table
-        for idx in self.indexes.values_mut() {
-            if idx.table == table_name {
-                let mut key = Vec::new();
-                for col in &idx.columns {
-                    let col_idx = table
-                        .columns
-                        .iter()
-                        .position(|c| c.name == *col)
-                        .ok_or_else(|| {
-                            format!("Index column '{}' not found in '{}'", col, table_name)
-                        })?;
-                    key.push(row.values[col_idx].clone());
-                }
-                idx.insert(key, (page_no, slot_no));
+    /// Append checked rows to the heap in one batch, then index them
+    /// and queue their deferred checks
+    fn write_rows(
+        &mut self,
+        table: &Table,
+        rows: Vec<(Row, Vec<DeferredConstraint>)>,
+        xid: u32,
+    ) -> Result<Vec<Row>, String> {
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (rows, deferred): (Vec<Row>, Vec<Vec<DeferredConstraint>>) = rows.into_iter().unzip();
+        let positions = table.heap.insert_rows(rows.clone(), &table.columns, xid)?;
+
+        // Update all indexes for this table, one index at a time
+        for idx in self.indexes.values_mut().filter(|i| i.table == table.name) {
+            for (row, &pos) in rows.iter().zip(&positions) {
+                let key = build_key(&idx.columns, &table.columns, &row.values, &table.name)?;
+                idx.insert(key, pos);
             }
         }
 
-        Ok(())
+        // Deferred constraints are checked at COMMIT against each tuple
+        for (&pos, deferred) in positions.iter().zip(deferred) {
+            self.defer_row_checks(xid, &table.name, pos, deferred);
+        }
+
+        Ok(rows)
     }
 }
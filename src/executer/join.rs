@@ -1,6 +1,8 @@
-use crate::executer::filter::eval_condition;
-use crate::types::parser_types::{FromItem, JoinKind};
-use crate::types::storage_types::{Database, Row, Value};
+use crate::executer::filter::{eval_condition, eval_operand};
+use crate::executer::functions;
+use crate::types::parser_types::{Condition, FromItem, JoinKind};
+use crate::types::storage_types::{ColumnType, Database, Row, Value};
+use crate::types::transaction_types::Snapshot;
 use std::collections::HashMap;
 
 /// Metadata for a single column in a join result
@@ -8,6 +10,7 @@ use std::collections::HashMap;
 pub struct JoinTableColumn {
     pub table_alias: String,
     pub column_name: String,
+    pub column_type: ColumnType,
 }
 
 /// Result of a join: columns metadata + rows
@@ -17,12 +20,37 @@ pub struct JoinTable {
     pub rows: Vec<Row>,
 }
 
+impl JoinTable {
+    /// First row that satisfies `filter` together with `row` (any row when there is no filter).
+    /// `row` is the left side of the condition, described by `metas`.
+    pub fn first_match(
+        &self,
+        row: &Row,
+        metas: &Vec<JoinTableColumn>,
+        filter: Option<&Condition>,
+    ) -> Result<Option<&Row>, String> {
+        let Some(cond) = filter else {
+            return Ok(self.rows.first());
+        };
+        for other in &self.rows {
+            if eval_condition(cond, row, metas, Some(other), Some(&self.columns))
+                .map_err(|e| e.to_string())?
+            {
+                return Ok(Some(other));
+            }
+        }
+        Ok(None)
+    }
+}
+
 impl Database {
-    /// Recursively build a JoinTable from a FromItem tree
+    /// Recursively build a JoinTable from a FromItem tree, reading the rows visible to `xid`
     pub fn collect_join_table(
         &self,
         join_struct: FromItem,
         aliases: &HashMap<String, String>,
+        xid: u32,
+        snapshot: &Snapshot,
     ) -> Result<JoinTable, String> {
         match join_struct {
             FromItem::Join {
@@ -32,8 +60,8 @@ impl Database {
                 on,
             } => {
                 // Recursively collect left and right sides
-                let left_item = self.collect_join_table(*left, aliases)?;
-                let right_item = self.collect_join_table(*right, aliases)?;
+                let left_item = self.collect_join_table(*left, aliases, xid, snapshot)?;
+                let right_item = self.collect_join_table(*right, aliases, xid, snapshot)?;
 
                 // Columns = concatenation of left + right metadata
                 let mut columns =
@@ -107,6 +135,7 @@ impl Database {
                     .map(|c| JoinTableColumn {
                         table_alias: alias.clone(),
                         column_name: c.name.clone(),
+                        column_type: c.column_type.clone(),
                     })
                     .collect();
 
@@ -116,10 +145,34 @@ impl Database {
                         .heap
                         .scan_all(&table.columns)
                         .into_iter()
+                        .filter(|(_, _, header, _)| {
+                            header.is_visible(xid, snapshot, &self.transaction_manager)
+                        })
                         .map(|(_, _, _, row)| row)
                         .collect(),
                 })
             }
+
+            FromItem::Function { name, args, alias, column } => {
+                // the arguments are constants: there is no row to read columns from
+                let no_row = Row { values: Vec::new() };
+                let args = args
+                    .iter()
+                    .map(|arg| eval_operand(arg, &no_row, &[], None, None).map(|v| v.into_owned()))
+                    .collect::<Result<Vec<Value>, _>>()
+                    .map_err(|e| e.to_string())?;
+                let values = functions::set_returning(&name, args).map_err(|e| e.to_string())?;
+                let column_type = values.iter().find_map(Value::column_type).unwrap_or(ColumnType::Text);
+                Ok(JoinTable {
+                    columns: vec![JoinTableColumn { table_alias: alias, column_name: column, column_type }],
+                    rows: values.into_iter().map(|value| Row { values: vec![value] }).collect(),
+                })
+            }
+
+            FromItem::Empty => Ok(JoinTable {
+                columns: Vec::new(),
+                rows: vec![Row { values: Vec::new() }],
+            }),
         }
     }
 }
@@ -1,11 +1,19 @@
+pub mod alter;
+pub mod conflict;
+pub mod copy;
 pub mod create;
+pub mod deferred;
 pub mod delete;
+pub mod drop;
 pub mod executer;
 pub mod filter;
+pub mod functions;
 pub mod help_functions;
 pub mod index;
 pub mod insert;
 pub mod join;
 pub mod printer;
+pub mod referential;
 pub mod select;
+pub mod sequence;
 pub mod update;
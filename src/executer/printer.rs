@@ -2,7 +2,7 @@ use crate::types::storage_types::{Row, Value};
 
 pub fn print_table(columns: &Vec<String>, rows: &[Row]) {
     // Step 1: Determine column widths based on header names
-    let mut widths: Vec<usize> = columns.iter().map(|col| col.len()).collect();
+    let mut widths: Vec<usize> = columns.iter().map(|col| col.chars().count()).collect();
 
     // Step 2: Adjust widths to fit the widest value in each column
     for row in rows {
@@ -11,11 +11,22 @@ pub fn print_table(columns: &Vec<String>, rows: &[Row]) {
                 Value::Int(v) => v.to_string(),
                 Value::Text(s) => s.clone(),
                 Value::Bool(b) => b.to_string(),
+                Value::Float(f) => f.to_string(),
+                Value::Numeric(d) => d.to_string(),
+                Value::Date(_)
+                | Value::Time(_)
+                | Value::Timestamp(_)
+                | Value::TimestampTz(_)
+                | Value::Interval(_)
+                | Value::Bytea(_)
+                | Value::Jsonb(_)
+                | Value::Uuid(_)
+                | Value::Array(_)
+                | Value::Enum(..) => value.to_string(),
                 Value::Null => "NULL".to_string(),
             };
-            if s.len() > widths[i] {
-                widths[i] = s.len();
-            }
+            // widths count characters, as the padding below does
+            widths[i] = widths[i].max(s.chars().count());
         }
     }
 
@@ -50,6 +61,18 @@ pub fn print_table(columns: &Vec<String>, rows: &[Row]) {
                     Value::Int(v) => v.to_string(),
                     Value::Text(s) => s.clone(),
                     Value::Bool(b) => b.to_string(),
+                    Value::Float(f) => f.to_string(),
+                    Value::Numeric(d) => d.to_string(),
+                    Value::Date(_)
+                    | Value::Time(_)
+                    | Value::Timestamp(_)
+                    | Value::TimestampTz(_)
+                    | Value::Interval(_)
+                    | Value::Bytea(_)
+                | Value::Jsonb(_)
+                | Value::Uuid(_)
+                | Value::Array(_)
+                | Value::Enum(..) => val.to_string(),
                     Value::Null => "NULL".to_string(),
                 };
                 format!("| {:width$} ", s, width = *w)
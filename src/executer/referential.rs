@@ -0,0 +1,190 @@
+use crate::executer::help_functions::{default_value, lookup_rows, still_referenced_error};
+use crate::types::storage_types::{Database, FkAction, ForeignKeyConstraint, Value};
+use crate::types::transaction_types::{PendingCheck, Snapshot, TxStatus};
+
+/// A deleted (`new` is None) or updated row whose referencing rows may need an action
+struct RowChange {
+    table: String,
+    old: Vec<Value>,
+    new: Option<Vec<Value>>,
+}
+
+impl Database {
+    /// Run referential actions for foreign keys that point at a changed parent row.
+    /// `new_values` is None for a DELETE and the new row version for an UPDATE.
+    /// All changes are made under `xid`, so a rollback undoes them.
+    /// Rows changed by a cascade are queued instead of recursed into, so chains
+    /// of any length work; cycles end because rows this transaction already
+    /// changed are never acted on again.
+    pub fn apply_fk_actions(
+        &mut self,
+        parent_name: &str,
+        old_values: &[Value],
+        new_values: Option<&[Value]>,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<(), String> {
+        let mut pending = vec![RowChange {
+            table: parent_name.to_string(),
+            old: old_values.to_vec(),
+            new: new_values.map(<[Value]>::to_vec),
+        }];
+        while let Some(change) = pending.pop() {
+            self.fk_actions_for(&change, xid, snapshot, &mut pending)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the actions of one changed row to the rows referencing it,
+    /// pushing each row changed in turn onto `pending`
+    fn fk_actions_for(
+        &mut self,
+        change: &RowChange,
+        xid: u32,
+        snapshot: &Snapshot,
+        pending: &mut Vec<RowChange>,
+    ) -> Result<(), String> {
+        let parent_name = change.table.as_str();
+        let old_values = change.old.as_slice();
+        let new_values = change.new.as_deref();
+
+        let parent = self
+            .tables
+            .get(parent_name)
+            .ok_or_else(|| format!("Table '{}' doesn't exist", parent_name))?;
+
+        // (child table, constraint) pairs that reference this table
+        let refs: Vec<(String, ForeignKeyConstraint)> = self
+            .tables
+            .values()
+            .flat_map(|t| t.foreign_keys.iter().map(move |fk| (t.name.clone(), fk.clone())))
+            .filter(|(_, fk)| fk.referenced_table == parent_name)
+            .collect();
+
+        let key_of = |values: &[Value], fk: &ForeignKeyConstraint| -> Result<Vec<Value>, String> {
+            fk.referenced_columns
+                .iter()
+                .map(|col| {
+                    parent
+                        .columns
+                        .iter()
+                        .position(|c| c.name == *col)
+                        .map(|i| values[i].clone())
+                        .ok_or_else(|| {
+                            format!("Foreign key error: column '{}' not found in '{}'", col, parent_name)
+                        })
+                })
+                .collect()
+        };
+
+        let mut work = Vec::new();
+        for (child_name, fk) in refs {
+            let old_key = key_of(old_values, &fk)?;
+            // NULL keys are never referenced
+            if old_key.iter().any(|v| matches!(v, Value::Null)) {
+                continue;
+            }
+            let (action, new_key) = match new_values {
+                None => (fk.on_delete, None),
+                Some(new) => {
+                    let new_key = key_of(new, &fk)?;
+                    if new_key == old_key {
+                        continue;
+                    }
+                    (fk.on_update, Some(new_key))
+                }
+            };
+            work.push((child_name, fk, action, old_key, new_key));
+        }
+
+        for (child_name, fk, action, old_key, new_key) in work {
+            // Deferred NO ACTION: the key may come back before COMMIT
+            if action == FkAction::NoAction && self.transaction_manager.is_deferred(xid, fk.deferrable) {
+                self.transaction_manager.defer_check(
+                    xid,
+                    PendingCheck::Referenced {
+                        table: parent_name.to_string(),
+                        child: child_name,
+                        fk,
+                        key: old_key,
+                    },
+                );
+                continue;
+            }
+
+            let child = self.tables.get(&child_name).unwrap().clone();
+            let tm = &self.transaction_manager;
+
+            // every referencing tuple that is not dead and not already changed by us
+            let referencing: Vec<_> = lookup_rows(self, &child, &fk.local_columns, &old_key)?
+                .into_iter()
+                .filter(|(_, _, header, _)| !header.is_dead(tm) && header.xmax != Some(xid))
+                .collect();
+
+            if referencing.is_empty() {
+                continue;
+            }
+
+            let verb = if new_key.is_some() { "update" } else { "delete" };
+            if matches!(action, FkAction::NoAction | FkAction::Restrict) {
+                return Err(still_referenced_error(verb, parent_name, &child_name, &fk, &old_key));
+            }
+
+            // Actions can only touch rows this transaction may see
+            for (_, _, header, _) in &referencing {
+                if !header.is_visible(xid, snapshot, tm) {
+                    let holder = if tm.status(header.xmin) == TxStatus::InProgress {
+                        header.xmin
+                    } else {
+                        header.xmax.unwrap_or(header.xmin)
+                    };
+                    return Err(format!(
+                        "could not {} referencing row in '{}': it is being changed by transaction {}",
+                        verb, child_name, holder
+                    ));
+                }
+            }
+
+            let local_idx: Vec<usize> = fk
+                .local_columns
+                .iter()
+                .map(|col| {
+                    child.columns.iter().position(|c| c.name == *col).ok_or_else(|| {
+                        format!("Foreign key error: column '{}' not found in '{}'", col, child_name)
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+
+            for (page_no, slot_no, _, row) in referencing {
+                if action == FkAction::Cascade && new_key.is_none() {
+                    child.heap.delete_at(page_no, slot_no, xid)?;
+                    pending.push(RowChange { table: child_name.clone(), old: row.values, new: None });
+                    continue;
+                }
+
+                let mut values = row.values.clone();
+                for (k, &ci) in local_idx.iter().enumerate() {
+                    values[ci] = match (action, &new_key) {
+                        (FkAction::Cascade, Some(key)) => key[k].clone(),
+                        (FkAction::SetDefault, _) => default_value(self, &child.columns[ci])?,
+                        _ => Value::Null,
+                    };
+                    if matches!(values[ci], Value::Null) && child.columns[ci].not_null {
+                        return Err(format!(
+                            "ON {} {} on '{}' sets NOT NULL column '{}' to NULL",
+                            verb.to_ascii_uppercase(),
+                            action,
+                            child_name,
+                            child.columns[ci].name
+                        ));
+                    }
+                }
+
+                let values = self.write_row_version(&child, page_no, slot_no, values, xid, snapshot)?;
+                pending.push(RowChange { table: child_name.clone(), old: row.values, new: Some(values) });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
-use crate::executer::filter::eval_condition;
+use crate::executer::filter::{eval_condition, eval_operand};
+use crate::executer::functions;
 use crate::executer::join::{JoinTable, JoinTableColumn};
+use crate::types::b_tree::IndexKey;
 use crate::types::filter_types::CmpOp;
 use crate::types::parser_types::{Condition, Operand};
 use crate::types::storage_types::{Column, Database, Row, Table, Value};
@@ -26,6 +28,7 @@ fn phys_to_join(
         .map(|c: &Column| JoinTableColumn {
             table_alias: alias.to_string(),
             column_name: c.name.clone(),
+            column_type: c.column_type.clone(),
         })
         .collect();
 
@@ -111,34 +114,171 @@ fn extract_range_condition(
     }
 }
 
+/// A literal converted to the type `column` stores, so it can be looked up in an index.
+/// None if the conversion would change its value (`int_col = 2.5`, a timestamp with a time
+/// against a date column): the scan decides then.
+fn index_key(table: &Table, column: &str, value: &Value) -> Option<Value> {
+    let column_type = &table.columns.iter().find(|c| c.name == column)?.column_type;
+    if !column_type.accepts(value) {
+        return None;
+    }
+    let key = column_type.coerce(value.clone()).ok()?;
+    match key.cmp_number(value).or_else(|| key.cmp_temporal(value)) {
+        Some(ord) if ord.is_ne() => None,
+        _ => Some(key),
+    }
+}
+
+/// Column names of `table` that the select list needs, or None for `SELECT *`
+fn referenced_columns(table: &Table, targets: &[Operand]) -> Option<Vec<String>> {
+    if targets.is_empty() {
+        return None;
+    }
+    let mut column_names = Vec::new();
+    targets.iter().for_each(|t| t.column_names(&mut column_names));
+    let prefix = format!("{}.", table.name);
+    Some(
+        column_names
+            .iter()
+            .map(|c| c.strip_prefix(prefix.as_str()).unwrap_or(c).to_string())
+            .collect(),
+    )
+}
+
+/// Whether the target calls an aggregate such as jsonb_agg anywhere
+fn has_aggregate(target: &Operand) -> bool {
+    match target {
+        Operand::Func(name, args) => functions::is_aggregate(name) || args.iter().any(has_aggregate),
+        Operand::Arith(_, lhs, rhs) => has_aggregate(lhs) || has_aggregate(rhs),
+        Operand::Cast(inner, _) => has_aggregate(inner),
+        Operand::Column(_) | Operand::Literal(_) => false,
+    }
+}
+
+/// `target` with every aggregate call replaced by its result over `rows`. Without GROUP BY
+/// the result is a single row, so a column outside of an aggregate has no value to take.
+fn fold_aggregates(target: &Operand, rows: &[&Row], columns: &[JoinTableColumn]) -> Result<Operand, String> {
+    match target {
+        Operand::Func(name, args) if functions::is_aggregate(name) => {
+            let [arg] = args.as_slice() else {
+                return Err(format!("{}() takes exactly one argument", name));
+            };
+            let values = rows
+                .iter()
+                .map(|row| eval_operand(arg, row, columns, None, None).map(|v| v.into_owned()))
+                .collect::<Result<Vec<Value>, _>>()
+                .map_err(|e| e.to_string())?;
+            let result = functions::aggregate(name, values).map_err(|e| e.to_string())?;
+            Ok(Operand::Literal(result))
+        }
+        Operand::Func(name, args) => Ok(Operand::Func(
+            name.clone(),
+            args.iter()
+                .map(|arg| fold_aggregates(arg, rows, columns))
+                .collect::<Result<_, _>>()?,
+        )),
+        Operand::Arith(op, lhs, rhs) => Ok(Operand::Arith(
+            *op,
+            Box::new(fold_aggregates(lhs, rows, columns)?),
+            Box::new(fold_aggregates(rhs, rows, columns)?),
+        )),
+        Operand::Cast(inner, column_type) => {
+            Ok(Operand::Cast(Box::new(fold_aggregates(inner, rows, columns)?), column_type.clone()))
+        }
+        Operand::Column(name) => Err(format!(
+            "column '{}' must be used in an aggregate function when the select list has one",
+            name
+        )),
+        Operand::Literal(_) => Ok(target.clone()),
+    }
+}
+
+/// Build a full-width row from index key values, leaving uncovered columns NULL
+fn row_from_key(table: &Table, index_columns: &[String], key: &[Value]) -> Row {
+    let mut values = vec![Value::Null; table.columns.len()];
+    for (col, val) in index_columns.iter().zip(key) {
+        if let Some(i) = table.columns.iter().position(|c| c.name == *col) {
+            values[i] = val.clone();
+        }
+    }
+    Row { values }
+}
+
 impl Database {
+    /// Resolve index hits into visible rows.
+    /// When the index covers every needed column, tuples on all-visible
+    /// pages are answered from the key alone (index-only scan).
+    fn fetch_index_hits(
+        &self,
+        table: &Table,
+        index_columns: &[String],
+        hits: Vec<(IndexKey, (usize, usize))>,
+        covering: bool,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Vec<Row> {
+        let vm = if covering {
+            Some(table.heap.read_visibility_map())
+        } else {
+            None
+        };
+
+        let mut rows = Vec::new();
+        for (key, (page_no, slot_no)) in hits {
+            if vm.as_ref().is_some_and(|vm| vm.is_all_visible(page_no as u32)) {
+                rows.push(row_from_key(table, index_columns, &key));
+                continue;
+            }
+            if let Some((header, row)) = table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
+            {
+                if header.is_visible(xid, snapshot, &self.transaction_manager) {
+                    rows.push(row);
+                }
+            }
+        }
+        rows
+    }
+
     /// Attempt to fetch rows using an index if possible.
     /// Supports equality lookups and simple range scans.
     fn try_index_lookup(
         &self,
         table: &Table,
+        targets: &[Operand],
         filter: &Option<Condition>,
         xid: u32,
         snapshot: &Snapshot,
     ) -> Result<Option<Vec<Row>>, String> {
+        let needed = referenced_columns(table, targets);
+        let covers = |idx_cols: &[String]| {
+            needed
+                .as_ref()
+                .is_some_and(|cols| cols.iter().all(|c| idx_cols.contains(c)))
+        };
+
         // Case 1: equality conditions
         if let Some(cols_vals) = filter.as_ref().and_then(|c| extract_eq_conditions(c)) {
             let filter_cols: Vec<String> = cols_vals.iter().map(|(c, _)| c.clone()).collect();
     
             for idx in self.indexes.values() {
                 if idx.table == table.name && idx.columns == filter_cols {
-                    let key: Vec<Value> = cols_vals.iter().map(|(_, v)| v.clone()).collect();
+                    let Some(key) = cols_vals
+                        .iter()
+                        .map(|(c, v)| index_key(table, c, v))
+                        .collect::<Option<Vec<Value>>>()
+                    else {
+                        continue;
+                    };
                     if let Some(positions) = idx.search_eq(&key) {
-                        let mut rows = Vec::new();
-                        for (page_no, slot_no) in positions {
-                            if let Some((header, row)) =
-                                table.heap.get_tuple(*page_no as u32, *slot_no, &table.columns)
-                            {
-                                if header.is_visible(xid, snapshot, &self.transaction_manager) {
-                                    rows.push(row);
-                                }
-                            }
-                        }
+                        let hits = positions.iter().map(|p| (key.clone(), *p)).collect();
+                        let rows = self.fetch_index_hits(
+                            table,
+                            &idx.columns,
+                            hits,
+                            covers(&idx.columns),
+                            xid,
+                            snapshot,
+                        );
                         return Ok(Some(rows));
                     }
                 }
@@ -149,17 +289,25 @@ impl Database {
         if let Some((col, lower, upper)) = filter.as_ref().and_then(|c| extract_range_condition(c)) {
             for idx in self.indexes.values() {
                 if idx.table == table.name && idx.columns.len() == 1 && idx.columns[0] == col {
-                    let positions = idx.search_range(lower, upper);
-                    let mut rows = Vec::new();
-                    for (page_no, slot_no) in positions {
-                        if let Some((header, row)) =
-                            table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
-                        {
-                            if header.is_visible(xid, snapshot, &self.transaction_manager) {
-                                rows.push(row);
-                            }
-                        }
-                    }
+                    let convert = |bound: Bound<Vec<Value>>| -> Option<Bound<Vec<Value>>> {
+                        Some(match bound {
+                            Bound::Included(k) => Bound::Included(vec![index_key(table, &col, &k[0])?]),
+                            Bound::Excluded(k) => Bound::Excluded(vec![index_key(table, &col, &k[0])?]),
+                            Bound::Unbounded => Bound::Unbounded,
+                        })
+                    };
+                    let (Some(lower), Some(upper)) = (convert(lower.clone()), convert(upper.clone())) else {
+                        continue;
+                    };
+                    let hits = idx.search_range(lower, upper);
+                    let rows = self.fetch_index_hits(
+                        table,
+                        &idx.columns,
+                        hits,
+                        covers(&idx.columns),
+                        xid,
+                        snapshot,
+                    );
                     return Ok(Some(rows));
                 }
             }
@@ -168,11 +316,12 @@ impl Database {
         Ok(None)
     }    
 
-    /// Execute SELECT on a single table or join
+    /// Execute SELECT on a single table or join.
+    /// `targets` are the select list items; empty selects every column.
     pub fn select(
         &self,
         table_arg: &TableArg,
-        column_names: &Vec<String>,
+        targets: &[Operand],
         filter: Option<Condition>,
         xid: u32,
         snapshot: &Snapshot,
@@ -185,7 +334,7 @@ impl Database {
                     .tables
                     .get(name)
                     .ok_or_else(|| format!("Table '{}' doesn't exist", name))?;
-                if let Some(rows) = self.try_index_lookup(t, &filter, xid, snapshot)? {
+                if let Some(rows) = self.try_index_lookup(t, targets, &filter, xid, snapshot)? {
                     JoinTable {
                         columns: t
                             .columns
@@ -193,6 +342,7 @@ impl Database {
                             .map(|c| JoinTableColumn {
                                 table_alias: name.clone(),
                                 column_name: c.name.clone(),
+                                column_type: c.column_type.clone(),
                             })
                             .collect(),
                         rows,
@@ -205,18 +355,18 @@ impl Database {
         };
 
         let mut rows: Vec<Row> = Vec::new();
-        let is_star = matches!(column_names.get(0).map(|s| s.as_str()), Some("*"));
+        let is_star = targets.is_empty();
+        let aggregating = targets.iter().any(has_aggregate);
+        let mut matched: Vec<&Row> = Vec::new();
 
-        // Resolve selected columns to indexes
-        let idxs: Option<Vec<usize>> = if is_star {
-            None
-        } else {
-            let mut v = Vec::with_capacity(column_names.len());
-            for name in column_names {
-                v.push(find_idx(&exec.columns, name)?);
-            }
-            Some(v)
-        };
+        // Resolve selected columns to indexes; expressions are evaluated per row
+        let mut idxs: Vec<Option<usize>> = Vec::with_capacity(targets.len());
+        for target in targets {
+            idxs.push(match target {
+                Operand::Column(name) => Some(find_idx(&exec.columns, name)?),
+                _ => None,
+            });
+        }
 
         // Filter and project rows
         for r in &exec.rows {
@@ -236,19 +386,41 @@ impl Database {
             }
 
             // Project values into result set
-            if is_star {
+            if aggregating {
+                matched.push(r);
+            } else if is_star {
                 rows.push(Row {
                     values: r.values.clone(),
                 });
             } else {
-                let mut vals = Vec::with_capacity(idxs.as_ref().unwrap().len());
-                for &i in idxs.as_ref().unwrap() {
-                    vals.push(r.values[i].clone());
+                let mut vals = Vec::with_capacity(idxs.len());
+                for (target, idx) in targets.iter().zip(&idxs) {
+                    vals.push(match idx {
+                        Some(i) => r.values[*i].clone(),
+                        None => eval_operand(&*self.bind_sequences(target)?, r, &exec.columns, None, None)
+                            .map_err(|e| e.to_string())?
+                            .into_owned(),
+                    });
                 }
                 rows.push(Row { values: vals });
             }
         }
 
+        // Aggregates turn all matching rows into one
+        if aggregating {
+            let empty = Row { values: Vec::new() };
+            let mut vals = Vec::with_capacity(targets.len());
+            for target in targets {
+                let folded = fold_aggregates(target, &matched, &exec.columns)?;
+                vals.push(
+                    eval_operand(&*self.bind_sequences(&folded)?, &empty, &[], None, None)
+                        .map_err(|e| e.to_string())?
+                        .into_owned(),
+                );
+            }
+            rows.push(Row { values: vals });
+        }
+
         Ok((exec.columns, rows))
     }
 }
@@ -0,0 +1,169 @@
+use super::filter::eval_operand;
+use super::functions::{calls_sequence, SEQUENCE_FUNCTIONS};
+use crate::types::parser_types::Operand;
+use crate::types::sequence::{Sequence, SequenceOptions};
+use crate::types::storage_types::{Database, Row, Value};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+impl Database {
+    /// CREATE SEQUENCE: a sequence shares the namespace of tables, and OWNED BY must name
+    /// an existing column. The sequence file is written before the sequence is used.
+    pub fn create_sequence(
+        &mut self,
+        name: &str,
+        oid: u32,
+        options: SequenceOptions,
+        file: PathBuf,
+    ) -> Result<(), String> {
+        if self.sequences.contains_key(name) || self.tables.contains_key(name) {
+            return Err(format!("relation \"{}\" already exists", name));
+        }
+        if let Some((table, column)) = &options.owned_by {
+            let owner = self
+                .tables
+                .get(table)
+                .ok_or_else(|| format!("Table '{}' doesn't exist", table))?;
+            if !owner.columns.iter().any(|c| c.name == *column) {
+                return Err(format!("column '{}' of relation '{}' does not exist", column, table));
+            }
+        }
+        let sequence = Sequence::new(name, oid, options, file)?;
+        sequence.write_state()?;
+        self.sequences.insert(name.to_string(), sequence);
+        Ok(())
+    }
+
+    /// DROP SEQUENCE. A sequence a column DEFAULT uses is only dropped with CASCADE, which
+    /// drops the DEFAULT as well; the sequence of an identity column stays with its column.
+    pub fn drop_sequences(&mut self, names: &[String], cascade: bool) -> Result<(), String> {
+        for name in names {
+            if !self.sequences.contains_key(name) {
+                return Err(format!("sequence \"{}\" does not exist", name));
+            }
+            for table in self.tables.values_mut() {
+                for column in &mut table.columns {
+                    let mut used = Vec::new();
+                    column.default.iter().for_each(|default| sequence_names(default, &mut used));
+                    if !used.contains(name) {
+                        continue;
+                    }
+                    if column.identity.is_some() {
+                        return Err(format!(
+                            "cannot drop sequence {}: identity column '{}' of '{}' requires it",
+                            name, column.name, table.name
+                        ));
+                    }
+                    if !cascade {
+                        return Err(format!(
+                            "cannot drop sequence {}: default of column '{}' of '{}' depends on it (use CASCADE)",
+                            name, column.name, table.name
+                        ));
+                    }
+                    column.default = None;
+                }
+            }
+            self.sequences.remove(name);
+        }
+        Ok(())
+    }
+
+    /// Drop the sequences owned by `table`, or only those of its `column`
+    pub fn drop_owned_sequences(&mut self, table: &str, column: Option<&str>) {
+        self.sequences.retain(|_, sequence| {
+            !sequence
+                .owned_by
+                .as_ref()
+                .is_some_and(|(t, c)| t == table && column.is_none_or(|column| column == c))
+        });
+    }
+
+    /// Write every sequence that handed out values its file does not hold yet
+    pub fn flush_sequences(&self) {
+        for sequence in self.sequences.values() {
+            if let Err(e) = sequence.flush() {
+                println!("WARNING: {}", e);
+            }
+        }
+    }
+
+    /// `op` with its nextval(), currval() and setval() calls replaced by their results,
+    /// innermost first; borrowed when it has none. Their arguments may not refer to columns.
+    pub fn bind_sequences<'a>(&self, op: &'a Operand) -> Result<Cow<'a, Operand>, String> {
+        if !calls_sequence(op) {
+            return Ok(Cow::Borrowed(op));
+        }
+        let mut op = op.clone();
+        self.bind_sequence_calls(&mut op)?;
+        Ok(Cow::Owned(op))
+    }
+
+    fn bind_sequence_calls(&self, op: &mut Operand) -> Result<(), String> {
+        match op {
+            Operand::Column(_) | Operand::Literal(_) => {}
+            Operand::Func(name, args) => {
+                for arg in args.iter_mut() {
+                    self.bind_sequence_calls(arg)?;
+                }
+                if SEQUENCE_FUNCTIONS.contains(&name.as_str()) {
+                    let empty = Row { values: Vec::new() };
+                    let args = args
+                        .iter()
+                        .map(|arg| eval_operand(arg, &empty, &[], None, None).map(Cow::into_owned))
+                        .collect::<Result<Vec<Value>, _>>()
+                        .map_err(|e| e.to_string())?;
+                    let value = self.call_sequence_function(name, args)?;
+                    *op = Operand::Literal(value);
+                }
+            }
+            Operand::Arith(_, lhs, rhs) => {
+                self.bind_sequence_calls(lhs)?;
+                self.bind_sequence_calls(rhs)?;
+            }
+            Operand::Cast(inner, _) => self.bind_sequence_calls(inner)?,
+        }
+        Ok(())
+    }
+
+    /// nextval(name), currval(name), setval(name, value [, is_called]); NULL for a NULL argument
+    fn call_sequence_function(&self, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        if args.iter().any(|arg| matches!(arg, Value::Null)) {
+            return Ok(Value::Null);
+        }
+        let sequence = match args.first() {
+            Some(Value::Text(sequence)) => self
+                .sequences
+                .get(sequence)
+                .ok_or_else(|| format!("relation \"{}\" does not exist", sequence))?,
+            _ => return Err(format!("{}() expects a sequence name", name)),
+        };
+        let value = match (name, &args[1..]) {
+            ("nextval", []) => sequence.nextval()?,
+            ("currval", []) => sequence.currval()?,
+            ("setval", [Value::Int(value)]) => sequence.setval(*value, true)?,
+            ("setval", [Value::Int(value), Value::Bool(is_called)]) => sequence.setval(*value, *is_called)?,
+            _ => return Err(format!("invalid arguments for {}()", name)),
+        };
+        Ok(Value::Int(value))
+    }
+}
+
+/// Sequences `op` names as the first argument of nextval(), currval() or setval()
+pub fn sequence_names(op: &Operand, out: &mut Vec<String>) {
+    match op {
+        Operand::Column(_) | Operand::Literal(_) => {}
+        Operand::Func(name, args) => {
+            if SEQUENCE_FUNCTIONS.contains(&name.as_str())
+                && let Some(Operand::Literal(Value::Text(sequence))) = args.first()
+            {
+                out.push(sequence.clone());
+            }
+            args.iter().for_each(|arg| sequence_names(arg, out));
+        }
+        Operand::Arith(_, lhs, rhs) => {
+            sequence_names(lhs, out);
+            sequence_names(rhs, out);
+        }
+        Operand::Cast(inner, _) => sequence_names(inner, out),
+    }
+}
@@ -1,9 +1,11 @@
-use super::filter::eval_condition;
-use crate::executer::help_functions::{build_key, validate_foreign_keys};
+use super::filter::{eval_condition, eval_operand};
+use crate::executer::help_functions::{
+    build_key, check_constraints, check_unique_indexes, validate_foreign_keys,
+};
 use crate::executer::join::JoinTableColumn;
-use crate::types::parser_types::Condition;
-use crate::types::storage_types::{Column, Database};
-use crate::types::storage_types::{ColumnType, Value};
+use crate::types::parser_types::{Condition, FromItem, Operand};
+use crate::types::storage_types::{Column, Database, Identity, Table};
+use crate::types::storage_types::{Row, Value};
 use crate::types::transaction_types::Snapshot;
 use std::collections::HashMap;
 
@@ -13,6 +15,7 @@ fn single_meta(table_name: &str, cols: &Vec<Column>) -> Vec<JoinTableColumn> {
         .map(|c| JoinTableColumn {
             table_alias: table_name.to_string(),
             column_name: c.name.clone(),
+            column_type: c.column_type.clone(),
         })
         .collect()
 }
@@ -33,22 +36,49 @@ fn normalize_col<'a>(name: &'a str, table_name: &str) -> Result<&'a str, String>
     }
 }
 
+/// Type and NOT NULL check of a value assigned to `column`; returns the value as the column stores it
+pub fn check_assignment(column: &Column, idx: usize, val: Value) -> Result<Value, String> {
+    if !column.column_type.accepts(&val) {
+        return Err(format!(
+            "Type mismatch for column '{}' (index {}): {} is not assignable to {}; use an explicit cast",
+            column.name,
+            idx,
+            val.vtype(),
+            column.column_type
+        ));
+    }
+    let val = column
+        .column_type
+        .coerce(val)
+        .map_err(|e| format!("{} for column '{}'", e, column.name))?;
+    // NOT NULL (includes primary key columns)
+    if matches!(val, Value::Null) && column.not_null {
+        return Err(format!("Column '{}' cannot be NULL", column.name));
+    }
+    Ok(val)
+}
+
 impl Database {
-    /// Updates rows in a table. The last assignment for the same column wins.
+    /// Updates rows in a table and returns the new row versions.
+    /// The last assignment for the same column wins. With a FROM list, a row is updated
+    /// once, using the first FROM row that satisfies the WHERE clause together with it.
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         table_name: &str,
         parsed_columns: Vec<String>,
-        parsed_values: Vec<Value>,
+        parsed_values: Vec<Operand>,
+        from: Option<(FromItem, HashMap<String, String>)>,
         filter: Option<Condition>,
         xid: u32,
         snapshot: &Snapshot,
-    ) -> Result<(), String> {
-        // Find the table (immutable reference only, rows will be updated later)
+    ) -> Result<Vec<Row>, String> {
+        // Clone table metadata: referential actions need `self` mutably
         let table = self
             .tables
             .get(table_name)
-            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?;
+            .ok_or_else(|| format!("Table '{}' doesn't exist", table_name))?
+            .clone();
 
         // Column/value counts must match
         if parsed_columns.len() != parsed_values.len() {
@@ -60,13 +90,13 @@ impl Database {
         }
 
         // Keep only the last assignment per column
-        let mut last: HashMap<&str, &Value> = HashMap::new();
+        let mut last: HashMap<&str, &Operand> = HashMap::new();
         for (name, val) in parsed_columns.iter().zip(parsed_values.iter()) {
             last.insert(name.as_str(), val);
         }
 
         // Map column names to schema indexes
-        let mut targets: Vec<(usize, &Value)> = Vec::with_capacity(last.len());
+        let mut targets: Vec<(usize, &Operand)> = Vec::with_capacity(last.len());
         for (i, col) in table.columns.iter().enumerate() {
             if let Some(v) = last.get(col.name.as_str()) {
                 targets.push((i, v));
@@ -90,67 +120,182 @@ impl Database {
             }
         }
 
-        // Type-check each assignment against schema
-        for (idx, val) in &targets {
+        // A GENERATED ALWAYS identity column only takes values from its sequence
+        for (idx, _) in &targets {
             let column = &table.columns[*idx];
-            let ok = match (val, &column.column_type) {
-                (Value::Int(_), ColumnType::Int) => true,
-                (Value::Text(_), ColumnType::Text) => true,
-                (Value::Bool(_), ColumnType::Bool) => true,
-                (Value::Null, _) => true, // NULL allowed
-                _ => false,
-            };
-            if !ok {
+            if column.identity == Some(Identity::Always) {
                 return Err(format!(
-                    "Type mismatch for column '{}' (index {})",
-                    column.name, idx
+                    "column '{}' is an identity column defined as GENERATED ALWAYS and cannot be updated",
+                    column.name
                 ));
             }
         }
 
+        // Type-check literal assignments against schema; column values are checked per row
+        for (idx, val) in &targets {
+            if let Operand::Literal(v) = val {
+                check_assignment(&table.columns[*idx], *idx, v.clone())?;
+            }
+        }
+
         // Build metadata for evaluation of WHERE condition
         let metas = single_meta(table_name, &table.columns);
+        let from = from
+            .map(|(item, aliases)| self.collect_join_table(item, &aliases, xid, snapshot))
+            .transpose()?;
 
-        // Walk rows and apply updates
+        // Walk rows and apply updates; referential actions and foreign key checks
+        // wait for the end of the statement, so they see every row it changed
+        let mut updated = Vec::new();
+        let mut versions = Vec::new();
         for (page_no, slot_no, header, mut row) in table.heap.scan_all(&table.columns).into_iter() {
             if !header.is_visible(xid, snapshot, &self.transaction_manager) {
                 continue;
             }
-            if let Some(cond) = &filter {
-                // Apply WHERE condition
-                let keep =
-                    eval_condition(cond, &row, &metas, None, None).map_err(|e| e.to_string())?;
-                if !keep {
-                    continue;
+
+            // Apply WHERE condition, joined with the FROM list if there is one
+            let from_row = match &from {
+                Some(join) => match join.first_match(&row, &metas, filter.as_ref())? {
+                    Some(other) => Some(other),
+                    None => continue,
+                },
+                None => {
+                    if let Some(cond) = &filter {
+                        let keep = eval_condition(cond, &row, &metas, None, None)
+                            .map_err(|e| e.to_string())?;
+                        if !keep {
+                            continue;
+                        }
+                    }
+                    None
                 }
+            };
+
+            // Every assignment reads the row as it was before this update
+            let mut new_values = Vec::with_capacity(targets.len());
+            for (idx, val) in &targets {
+                let val = self.bind_sequences(val)?;
+                let value = eval_operand(
+                    &val,
+                    &row,
+                    &metas,
+                    from_row,
+                    from.as_ref().map(|join| join.columns.as_slice()),
+                )
+                .map_err(|e| e.to_string())?
+                .into_owned();
+                new_values.push(check_assignment(&table.columns[*idx], *idx, value)?);
             }
 
-            let old_row_vals = row.values.clone();
-            let old_pos = (page_no as usize, slot_no);
+            let old_values = row.values.clone();
 
             // Write new values into row
-            for (idx, val) in &targets {
-                row.values[*idx] = (*val).clone();
+            for ((idx, _), val) in targets.iter().zip(new_values) {
+                row.values[*idx] = val;
             }
 
-            // Foreign key validation for updated row
-            validate_foreign_keys(self, table, &row.values)?;
+            let pos = self.put_row_version(&table, page_no, slot_no, row.values.clone(), xid)?;
+            versions.push((old_values, pos));
+            updated.push(row);
+        }
+
+        for ((old_values, _), row) in versions.iter().zip(&updated) {
+            self.apply_fk_actions(table_name, old_values, Some(&row.values), xid, snapshot)?;
+        }
+        // A version that a cascade replaced again was checked when that happened
+        for (_, (page_no, slot_no)) in versions {
+            if let Some((header, row)) = table.heap.get_tuple(page_no as u32, slot_no, &table.columns)
+                && header.xmax != Some(xid)
+            {
+                self.check_row_version(&table, (page_no, slot_no), &row.values, xid, snapshot)?;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Write a new version of a single row and run ON UPDATE actions
+    /// of foreign keys referencing the old key
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_row_version(
+        &mut self,
+        table: &Table,
+        page_no: u32,
+        slot_no: usize,
+        old_values: &[Value],
+        new_values: Vec<Value>,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<(), String> {
+        let new_values = self.write_row_version(table, page_no, slot_no, new_values, xid, snapshot)?;
+        self.apply_fk_actions(&table.name, old_values, Some(&new_values), xid, snapshot)
+    }
+
+    /// Write a new version of a single row: checks foreign keys and unique indexes
+    /// and maintains indexes. Returns the values written.
+    pub fn write_row_version(
+        &mut self,
+        table: &Table,
+        page_no: u32,
+        slot_no: usize,
+        new_values: Vec<Value>,
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<Vec<Value>, String> {
+        let pos = self.put_row_version(table, page_no, slot_no, new_values.clone(), xid)?;
+        self.check_row_version(table, pos, &new_values, xid, snapshot)?;
+        Ok(new_values)
+    }
 
-            // If all checks pass, write updated row back to storage
-            let (new_page_no, new_slot_no) =
-            table.heap.update_row(page_no, slot_no, row.clone(), xid)?;
+    /// Check the foreign keys of the row version at `pos`, queuing deferred ones
+    fn check_row_version(
+        &mut self,
+        table: &Table,
+        pos: (usize, usize),
+        values: &[Value],
+        xid: u32,
+        snapshot: &Snapshot,
+    ) -> Result<(), String> {
+        let deferred = validate_foreign_keys(self, table, values, xid, snapshot)?;
+        self.defer_row_checks(xid, &table.name, pos, deferred);
+        Ok(())
+    }
 
-            for idx in self.indexes.values_mut().filter(|i| i.table == table.name) {
-                let old_key = build_key(&idx.columns, &table.columns, &old_row_vals, &table.name)?;
-                let new_key = build_key(&idx.columns, &table.columns, &row.values, &table.name)?;
+    /// Write a new version of a single row without checking its foreign keys:
+    /// checks CHECK constraints and unique indexes and maintains indexes.
+    /// Returns the position of the new version.
+    fn put_row_version(
+        &mut self,
+        table: &Table,
+        page_no: u32,
+        slot_no: usize,
+        new_values: Vec<Value>,
+        xid: u32,
+    ) -> Result<(usize, usize), String> {
+        check_constraints(table, &new_values)?;
 
-                idx.insert(new_key, (new_page_no as usize, new_slot_no));
+        // Unique indexes: the old version of this row does not count
+        let deferred = check_unique_indexes(
+            self,
+            table,
+            &new_values,
+            xid,
+            Some((page_no as usize, slot_no)),
+        )?;
 
-                idx.remove(&old_key, old_pos);
-            }
+        // If all checks pass, write updated row back to storage
+        let row = Row { values: new_values };
+        let (new_page_no, new_slot_no) =
+            table.heap.update_row(page_no, slot_no, row.clone(), &table.columns, xid)?;
 
+        // Old version keeps its entries until VACUUM (needed after rollback)
+        for idx in self.indexes.values_mut().filter(|i| i.table == table.name) {
+            let new_key = build_key(&idx.columns, &table.columns, &row.values, &table.name)?;
+            idx.insert(new_key, (new_page_no as usize, new_slot_no));
         }
+        let pos = (new_page_no as usize, new_slot_no);
+        self.defer_row_checks(xid, &table.name, pos, deferred);
 
-        Ok(())
+        Ok(pos)
     }
 }
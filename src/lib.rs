@@ -1,5 +1,6 @@
 pub mod catalog;
 pub mod consts;
+pub mod dump;
 pub mod engine;
 pub mod errors;
 pub mod executer;
@@ -11,6 +11,8 @@ mod types;
 use crate::engine::Engine;
 use crate::executer::executer::execute;
 use crate::parser::main::parse_query;
+use crate::parser::script::parse_script;
+use crate::types::parser_types::Query;
 use std::env;
 use std::fs;
 
@@ -19,16 +21,11 @@ fn main() {
 
     let args: Vec<String> = env::args().collect();
 
-    if args.len() > 1 && args[1] == "--file" {
-        let filename = &args[2];
-        let contents = fs::read_to_string(filename).expect("Failed to read file");
-        for stmt in contents.split(';') {
-            let stmt = stmt.trim();
-            if stmt.is_empty() {
-                continue;
-            }
+    if args.len() > 2 && args[1] == "--file" {
+        let contents = fs::read_to_string(&args[2]).expect("Failed to read file");
+        for (stmt, query) in parse_script(&contents) {
             println!("{}", stmt);
-            match parse_query(stmt) {
+            match query {
                 Ok(ast) => {
                     if let Err(err) = execute(&mut engine, ast) {
                         eprintln!("Execution error: {err}");
@@ -40,6 +37,28 @@ fn main() {
         return;
     }
 
+    // Load a povertygres-dump script into an empty data directory, stopping at the first error
+    if args.len() > 2 && args[1] == "--restore" {
+        if !engine.cat.catalog().tables.is_empty() {
+            eprintln!("Restore error: the data directory already has tables");
+            std::process::exit(1);
+        }
+        let contents = fs::read_to_string(&args[2]).expect("Failed to read file");
+        for (stmt, query) in parse_script(&contents) {
+            let result = query.map_err(|e| format!("Parse error: {e}")).and_then(|ast| {
+                execute(&mut engine, ast).map_err(|e| format!("Execution error: {e}"))
+            });
+            if let Err(err) = result {
+                eprintln!("{err}\nin statement: {stmt}");
+                if engine.current_xid.is_some() {
+                    let _ = execute(&mut engine, Query::Rollback);
+                }
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     loop {
         // Print prompt symbol
         print!("> ");
@@ -62,7 +81,18 @@ fn main() {
 
         // Parse query into AST
         match parse_query(input) {
-            Ok(ast) => {
+            Ok(mut ast) => {
+                // COPY ... FROM STDIN reads the lines that follow, up to "\."
+                if let Query::Copy {
+                    to_file: false,
+                    path: None,
+                    data,
+                    ..
+                } = &mut ast
+                {
+                    *data = Some(read_copy_data());
+                }
+
                 // Execute AST on the database
                 if let Err(err) = execute(&mut engine, ast) {
                     println!("Execution error: {err}");
@@ -72,3 +102,17 @@ fn main() {
         }
     }
 }
+
+/// Lines typed after COPY ... FROM STDIN, up to a line holding only "\."
+fn read_copy_data() -> String {
+    let mut data = String::new();
+    let mut line = String::new();
+    while io::stdin().read_line(&mut line).is_ok_and(|n| n > 0) {
+        if line.trim_end() == "\\." {
+            break;
+        }
+        data.push_str(&line);
+        line.clear();
+    }
+    data
+}
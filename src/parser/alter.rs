@@ -0,0 +1,199 @@
+use crate::parser::create::{
+    extract_checks, parse_column_list, parse_create_table, parse_default_value,
+    parse_table_foreign_key, strip_deferrable,
+};
+use crate::parser::r#where::parse_where;
+use crate::types::parser_types::{AlterAction, Query, TableConstraint};
+use crate::types::storage_types::CheckConstraint;
+
+/// Parses `ALTER TABLE name <action>`
+pub fn parse_alter_table(input: &str) -> Result<Query, String> {
+    let prefix = "alter table ";
+    let rest = input[prefix.len()..].trim();
+    let (table_name, action_str) = rest
+        .split_once(char::is_whitespace)
+        .ok_or("Missing ALTER TABLE action")?;
+    let action_str = action_str.trim();
+
+    let tokens: Vec<&str> = action_str.split_whitespace().collect();
+    let lower: Vec<String> = tokens.iter().map(|t| t.to_ascii_lowercase()).collect();
+    let word = |i: usize| lower.get(i).map(|s| s.as_str());
+
+    let action = match word(0) {
+        // ADD [COLUMN] <column definition> | ADD [CONSTRAINT name] <constraint>
+        Some("add") => {
+            let after = action_str["add".len()..].trim();
+            match word(1) {
+                Some("constraint" | "check" | "unique" | "primary" | "foreign") => {
+                    AlterAction::AddConstraint(parse_table_constraint(table_name, after)?)
+                }
+                Some(w) if w.starts_with("check(") || w.starts_with("unique(") => {
+                    AlterAction::AddConstraint(parse_table_constraint(table_name, after)?)
+                }
+                Some("column") => parse_add_column(table_name, after["column".len()..].trim())?,
+                Some(_) => parse_add_column(table_name, after)?,
+                None => return Err("Missing definition after ADD".into()),
+            }
+        }
+
+        // DROP CONSTRAINT name | DROP [COLUMN] name
+        Some("drop") => match (word(1), tokens.len()) {
+            (Some("constraint"), 3) => AlterAction::DropConstraint(tokens[2].to_string()),
+            (Some("column"), 3) => AlterAction::DropColumn(tokens[2].to_string()),
+            (Some(_), 2) => AlterAction::DropColumn(tokens[1].to_string()),
+            _ => return Err("Invalid ALTER TABLE ... DROP syntax".into()),
+        },
+
+        // RENAME TO new_name | RENAME [COLUMN] a TO b
+        Some("rename") => match (word(1), word(2), word(3), tokens.len()) {
+            (Some("to"), _, _, 3) => AlterAction::RenameTable(tokens[2].to_string()),
+            (Some("column"), _, Some("to"), 5) => AlterAction::RenameColumn {
+                from: tokens[2].to_string(),
+                to: tokens[4].to_string(),
+            },
+            (_, Some("to"), _, 4) => AlterAction::RenameColumn {
+                from: tokens[1].to_string(),
+                to: tokens[3].to_string(),
+            },
+            _ => return Err("Invalid ALTER TABLE ... RENAME syntax".into()),
+        },
+
+        // ALTER [COLUMN] c SET NOT NULL | DROP NOT NULL | SET DEFAULT v | DROP DEFAULT
+        Some("alter") => {
+            let start = if word(1) == Some("column") { 2 } else { 1 };
+            let column = tokens.get(start).ok_or("Missing column name")?.to_string();
+            let rest: Vec<&str> = lower[start + 1..].iter().map(|s| s.as_str()).collect();
+            match rest.as_slice() {
+                ["set", "not", "null"] => AlterAction::SetNotNull(column),
+                ["drop", "not", "null"] => AlterAction::DropNotNull(column),
+                ["drop", "default"] => AlterAction::SetDefault {
+                    column,
+                    default: None,
+                },
+                ["set", "default", ..] if rest.len() > 2 => AlterAction::SetDefault {
+                    column,
+                    default: Some(parse_default_value(&tokens[start + 3..].join(" "))?),
+                },
+                _ => return Err("Invalid ALTER COLUMN syntax".into()),
+            }
+        }
+
+        _ => return Err("Unsupported ALTER TABLE action".into()),
+    };
+
+    Ok(Query::AlterTable {
+        table_name: table_name.to_string(),
+        action,
+    })
+}
+
+/// Parse a column definition the same way CREATE TABLE does
+fn parse_add_column(table_name: &str, def: &str) -> Result<AlterAction, String> {
+    let query = parse_create_table(&format!("create table {} ({})", table_name, def))?;
+    let Query::CreateTable {
+        mut columns,
+        primary_key,
+        foreign_keys,
+        unique_constraints,
+        checks,
+        deferrable_keys,
+        sequences,
+        ..
+    } = query
+    else {
+        return Err("Invalid column definition".into());
+    };
+    if !sequences.is_empty() {
+        return Err("ADD COLUMN does not support SERIAL or identity columns".into());
+    }
+
+    if columns.len() != 1 {
+        return Err("ADD COLUMN expects exactly one column definition".into());
+    }
+    let column = columns.remove(0);
+
+    let mode = |cols: &Vec<String>| {
+        deferrable_keys
+            .iter()
+            .find(|(k, _)| k == cols)
+            .map(|(_, m)| *m)
+            .unwrap_or_default()
+    };
+
+    let mut constraints = Vec::new();
+    if let Some(cols) = primary_key {
+        constraints.push(TableConstraint::PrimaryKey {
+            name: None,
+            deferrable: mode(&cols),
+            columns: cols,
+        });
+    }
+    for cols in unique_constraints {
+        constraints.push(TableConstraint::Unique {
+            name: None,
+            deferrable: mode(&cols),
+            columns: cols,
+        });
+    }
+    constraints.extend(foreign_keys.into_iter().map(TableConstraint::ForeignKey));
+    constraints.extend(checks.into_iter().map(TableConstraint::Check));
+
+    Ok(AlterAction::AddColumn { column, constraints })
+}
+
+/// Parse "[constraint name] check (...) | unique (...) | primary key (...) | foreign key ..."
+fn parse_table_constraint(table_name: &str, def: &str) -> Result<TableConstraint, String> {
+    let mut def = def.trim();
+    let mut name = None;
+
+    if def.to_ascii_lowercase().starts_with("constraint ") {
+        let after = def["constraint".len()..].trim_start();
+        let (n, rest) = after
+            .split_once(char::is_whitespace)
+            .ok_or("Missing constraint after CONSTRAINT name")?;
+        name = Some(n.to_string());
+        def = rest.trim();
+    }
+
+    let lower = def.to_ascii_lowercase();
+
+    if lower.starts_with("check") {
+        let (rest, clauses) = extract_checks(def)?;
+        if !rest.is_empty() || clauses.len() != 1 {
+            return Err("Invalid CHECK constraint".into());
+        }
+        let (_, expr) = clauses.into_iter().next().unwrap();
+        return Ok(TableConstraint::Check(CheckConstraint {
+            name: name.unwrap_or_else(|| format!("{}_check", table_name)),
+            condition: parse_where(&expr)?,
+        }));
+    }
+
+    let (def, mode) = strip_deferrable(def)?;
+    let deferrable = mode.unwrap_or_default();
+
+    if lower.starts_with("unique") {
+        Ok(TableConstraint::Unique {
+            name,
+            columns: parse_column_list(&def["unique".len()..])?,
+            deferrable,
+        })
+    } else if lower.starts_with("primary") {
+        let spec = def["primary".len()..].trim();
+        if !spec.to_ascii_lowercase().starts_with("key") {
+            return Err("Invalid PRIMARY KEY syntax".into());
+        }
+        Ok(TableConstraint::PrimaryKey {
+            name,
+            columns: parse_column_list(&spec["key".len()..])?,
+            deferrable,
+        })
+    } else if lower.starts_with("foreign") {
+        let mut fk = parse_table_foreign_key(&def)?;
+        fk.deferrable = deferrable;
+        fk.name = name;
+        Ok(TableConstraint::ForeignKey(fk))
+    } else {
+        Err(format!("Unsupported constraint: {}", def))
+    }
+}
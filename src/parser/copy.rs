@@ -0,0 +1,212 @@
+use crate::parser::main::parse_query;
+use crate::types::parser_types::{CopyFormat, CopyOptions, CopySource, Query};
+
+/// Parses `COPY table [(cols)] FROM { 'path' | STDIN }` and
+/// `COPY { table [(cols)] | (SELECT ...) } TO { 'path' | STDOUT }`, both with optional `[WITH] (FORMAT csv|text, HEADER [bool], DELIMITER 'c', QUOTE 'c', NULL 's')`.
+pub fn parse_copy(input: &str) -> Result<Query, String> {
+    let rest = input["copy".len()..].trim_start();
+
+    let (source, rest) = if rest.starts_with('(') {
+        let close = matching_paren(rest).ok_or("Missing ')' after COPY query")?;
+        let query = parse_query(&rest[1..close])?;
+        if !matches!(query, Query::Select { .. }) {
+            return Err("COPY (...) TO only supports SELECT".into());
+        }
+        (CopySource::Query(Box::new(query)), rest[close + 1..].trim_start())
+    } else {
+        let name_end = rest
+            .find(|c: char| c == '(' || c.is_whitespace())
+            .ok_or("Expected FROM or TO after COPY table")?;
+        let table_name = rest[..name_end].to_string();
+        let mut rest = rest[name_end..].trim_start();
+        let column_names = if rest.starts_with('(') {
+            let close = rest.find(')').ok_or("Missing ')' in column list")?;
+            let cols = rest[1..close].split(',').map(|s| s.trim().to_string()).collect();
+            rest = rest[close + 1..].trim_start();
+            Some(cols)
+        } else {
+            None
+        };
+        (
+            CopySource::Table {
+                table_name,
+                column_names,
+            },
+            rest,
+        )
+    };
+
+    let lower = rest.to_ascii_lowercase();
+    let (to_file, rest) = if lower.starts_with("from ") {
+        (false, rest["from ".len()..].trim_start())
+    } else if lower.starts_with("to ") {
+        (true, rest["to ".len()..].trim_start())
+    } else {
+        return Err("Expected FROM or TO in COPY".into());
+    };
+    if !to_file && matches!(source, CopySource::Query(_)) {
+        return Err("COPY (SELECT ...) only supports TO".into());
+    }
+
+    let stdio = if to_file { "stdout" } else { "stdin" };
+    let word_end = rest.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(rest.len());
+    let (path, rest) = if rest[..word_end].eq_ignore_ascii_case(stdio) {
+        (None, &rest[word_end..])
+    } else {
+        let (path, rest) = quoted(rest)
+            .ok_or_else(|| format!("Expected a quoted file path or {} in COPY", stdio.to_uppercase()))?;
+        (Some(path), rest)
+    };
+    let options = parse_copy_options(rest)?;
+
+    Ok(Query::Copy {
+        source,
+        to_file,
+        path,
+        options,
+        data: None,
+    })
+}
+
+/// Parse the `[WITH] (option value, ...)` tail of a COPY statement
+fn parse_copy_options(input: &str) -> Result<CopyOptions, String> {
+    let mut input = input.trim();
+    if input.to_ascii_lowercase().starts_with("with") {
+        input = input["with".len()..].trim_start();
+    }
+
+    let mut format = CopyFormat::Text;
+    let mut header = false;
+    let mut delimiter = None;
+    let mut quote = '"';
+    let mut null = None;
+
+    if !input.is_empty() {
+        let inner = input
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(|| format!("Expected COPY options in parentheses, got '{}'", input))?;
+
+        for option in split_options(inner) {
+            let option = option.trim();
+            let (name, value) = match option.split_once(char::is_whitespace) {
+                Some((name, value)) => (name.to_ascii_lowercase(), Some(value.trim())),
+                None => (option.to_ascii_lowercase(), None),
+            };
+            // Option values are bare words or single-quoted strings
+            let value = value.map(|v| quoted(v).map(|(s, _)| s).unwrap_or_else(|| v.to_string()));
+
+            match (name.as_str(), value) {
+                ("format", Some(v)) => {
+                    format = match v.to_ascii_lowercase().as_str() {
+                        "csv" => CopyFormat::Csv,
+                        "text" => CopyFormat::Text,
+                        other => return Err(format!("COPY format '{}' not recognized", other)),
+                    }
+                }
+                ("header", None) => header = true,
+                ("header", Some(v)) => {
+                    header = match v.to_ascii_lowercase().as_str() {
+                        "true" | "on" => true,
+                        "false" | "off" => false,
+                        other => return Err(format!("Invalid value for HEADER: '{}'", other)),
+                    }
+                }
+                ("delimiter", Some(v)) => delimiter = Some(single_char("DELIMITER", &v)?),
+                ("quote", Some(v)) => quote = single_char("QUOTE", &v)?,
+                ("null", Some(v)) => null = Some(v),
+                (other, _) => return Err(format!("Invalid COPY option '{}'", other)),
+            }
+        }
+    }
+
+    let (default_delimiter, default_null) = match format {
+        CopyFormat::Text => ('\t', "\\N"),
+        CopyFormat::Csv => (',', ""),
+    };
+    let options = CopyOptions {
+        format,
+        header,
+        delimiter: delimiter.unwrap_or(default_delimiter),
+        quote,
+        null: null.unwrap_or_else(|| default_null.to_string()),
+    };
+    if options.delimiter == '\n'
+        || options.delimiter == '\r'
+        || (options.delimiter == '\\' && format == CopyFormat::Text)
+    {
+        return Err("COPY delimiter cannot be newline, carriage return or backslash".into());
+    }
+    if format == CopyFormat::Csv && options.delimiter == options.quote {
+        return Err("COPY delimiter and quote must be different".into());
+    }
+    Ok(options)
+}
+
+fn single_char(option: &str, value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("COPY {} must be a single character", option)),
+    }
+}
+
+/// `'text' rest` -> ("text", rest); two single quotes inside stand for one
+fn quoted(input: &str) -> Option<(String, &str)> {
+    let body = input.strip_prefix('\'')?;
+    let mut text = String::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            if let Some((_, '\'')) = chars.peek() {
+                chars.next();
+                text.push('\'');
+                continue;
+            }
+            return Some((text, &body[i + 1..]));
+        }
+        text.push(c);
+    }
+    None
+}
+
+/// Split an option list on commas outside single quotes
+fn split_options(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '\'' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                parts.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Byte offset of the ')' closing the '(' that starts `input`
+fn matching_paren(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_quotes = None;
+    for (i, c) in input.char_indices() {
+        match (c, in_quotes) {
+            ('"' | '\'', None) => in_quotes = Some(c),
+            (q, Some(open)) if q == open => in_quotes = None,
+            (_, Some(_)) => {}
+            ('(', None) => depth += 1,
+            (')', None) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -1,10 +1,29 @@
-use crate::types::parser_types::Query;
-use crate::types::storage_types::{Column, ColumnType, ForeignKeyConstraint, Value};
+use crate::parser::insert::parse_operand;
+use crate::parser::r#where::parse_where;
+use crate::parser::sequence::parse_sequence_options;
+use crate::types::enum_type::EnumType;
+use crate::types::parser_types::{Operand, Query};
+use crate::types::sequence::SequenceOptions;
+use crate::types::storage_types::{
+    CheckConstraint, Column, ColumnType, Deferrable, FkAction, ForeignKeyConstraint, Identity, Value,
+};
+use std::sync::Arc;
+
+/// Constraint a column-level DEFERRABLE clause applies to
+enum LastConstraint {
+    Key,
+    ForeignKey,
+}
 
 /// Parse a CREATE TABLE SQL statement into a Query::CreateTable AST node
 pub fn parse_create_table(input: &str) -> Result<Query, String> {
     let prefix = "create table ";
-    let after_prefix = &input[prefix.len()..];
+    let mut after_prefix = input[prefix.len()..].trim_start();
+
+    let if_not_exists = after_prefix.to_ascii_lowercase().starts_with("if not exists ");
+    if if_not_exists {
+        after_prefix = &after_prefix["if not exists ".len()..];
+    }
 
     // Find '(' to split table name and column definitions
     let paren_index = after_prefix
@@ -19,24 +38,73 @@ pub fn parse_create_table(input: &str) -> Result<Query, String> {
         .rfind(')')
         .ok_or("Missing closing ')' in create table")?;
     let inside_parens = &rest[..close_index].trim();
-    let column_defs: Vec<&str> = inside_parens.split(',').collect();
+    let column_defs: Vec<&str> = split_top_level(inside_parens);
 
     let mut columns = Vec::new();
-    let mut primary_key: Option<String> = None;
+    let mut primary_key: Option<Vec<String>> = None;
     let mut foreign_keys = Vec::new();
+    let mut unique_constraints: Vec<Vec<String>> = Vec::new();
+    let mut deferrable_keys: Vec<(Vec<String>, Deferrable)> = Vec::new();
+    let mut checks: Vec<CheckConstraint> = Vec::new();
+    let mut sequences: Vec<(String, SequenceOptions)> = Vec::new();
 
     for col_def in column_defs {
-        let tokens: Vec<&str> = col_def.trim().split_whitespace().collect();
+        // Pull out CHECK (...) clauses first: their expressions may contain spaces and keywords
+        let (col_def, clauses) = extract_checks(col_def)?;
+
+        // Table-level key constraints may end with a DEFERRABLE clause
+        let first = col_def.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+        let (col_def, key_mode) =
+            if matches!(first.as_str(), "unique" | "primary" | "foreign") || first.starts_with("unique(") {
+                strip_deferrable(&col_def)?
+            } else {
+                (col_def, None)
+            };
+        let col_def = col_def.as_str();
+        let tokens: Vec<&str> = split_words(col_def);
 
         if tokens.is_empty() {
+            // Table-level CHECK, named after the table: <table>_check
+            for (name, expr) in clauses {
+                let name = name.unwrap_or_else(|| format!("{}_check", table_name));
+                checks.push(CheckConstraint { name, condition: parse_where(&expr)? });
+            }
             continue;
         }
 
-        // Handle table-level PRIMARY KEY constraint
+        // Column-level CHECK, named after the column: <table>_<column>_check
+        for (name, expr) in clauses {
+            let name = name.unwrap_or_else(|| format!("{}_{}_check", table_name, tokens[0]));
+            checks.push(CheckConstraint { name, condition: parse_where(&expr)? });
+        }
+
+        // Handle table-level UNIQUE (a, b) constraint
+        if first == "unique" || first.starts_with("unique(") {
+            let cols = parse_column_list(&col_def.trim()["unique".len()..])?;
+            if let Some(mode) = key_mode {
+                deferrable_keys.push((cols.clone(), mode));
+            }
+            unique_constraints.push(cols);
+            continue;
+        }
+
+        // Handle table-level PRIMARY KEY constraint: "primary key id" or "primary key (a, b)"
         if tokens[0].eq_ignore_ascii_case("primary") {
             if tokens.len() >= 3 && tokens[1].eq_ignore_ascii_case("key") {
-                let pk_col = tokens[2].trim_matches(|c| c == '(' || c == ')');
-                primary_key = Some(pk_col.to_string());
+                if primary_key.is_some() {
+                    return Err("Multiple primary keys are not allowed".into());
+                }
+                let key_pos = col_def.to_ascii_lowercase().find("key").unwrap();
+                let pk_spec = col_def[key_pos + "key".len()..].trim();
+                let pk_cols = if pk_spec.starts_with('(') {
+                    parse_column_list(pk_spec)?
+                } else {
+                    vec![pk_spec.to_string()]
+                };
+                if let Some(mode) = key_mode {
+                    deferrable_keys.push((pk_cols.clone(), mode));
+                }
+                primary_key = Some(pk_cols);
                 continue;
             } else {
                 return Err("Invalid PRIMARY KEY syntax".into());
@@ -45,74 +113,52 @@ pub fn parse_create_table(input: &str) -> Result<Query, String> {
 
         // Handle table-level FOREIGN KEY constraint
         if tokens[0].eq_ignore_ascii_case("foreign") {
-            if tokens.len() >= 5 && tokens[1].eq_ignore_ascii_case("key") {
-                let local_col = tokens[2].trim_matches(|c| c == '(' || c == ')');
-
-                if !tokens[3].eq_ignore_ascii_case("references") {
-                    return Err("Expected 'references' after FOREIGN KEY".into());
-                }
-
-                let ref_table_token = tokens[4];
-
-                let (ref_table, ref_col) = if ref_table_token.contains('(') {
-                    match (ref_table_token.find('('), ref_table_token.find(')')) {
-                        (Some(open), Some(close)) if close > open => {
-                            let table = &ref_table_token[..open];
-                            let col = &ref_table_token[open + 1..close];
-                            if table.trim().is_empty() || col.trim().is_empty() {
-                                return Err("Invalid FOREIGN KEY reference format".into());
-                            }
-                            (table.trim(), col.trim())
-                        }
-                        _ => {
-                            return Err("Invalid FOREIGN KEY reference format".into());
-                        }
-                    }
-                } else {
-                    // "table column" form
-                    let ref_table = ref_table_token;
-                    let ref_col = tokens
-                        .get(5)
-                        .map(|s| s.trim_matches(|c| c == '(' || c == ')'))
-                        .ok_or("Missing referenced column")?;
-                    (ref_table, ref_col)
-                };
-
-                foreign_keys.push(ForeignKeyConstraint {
-                    local_columns: vec![local_col.to_string()],
-                    referenced_table: ref_table.to_string(),
-                    referenced_columns: vec![ref_col.to_string()],
-                });
-                continue;
-            } else {
-                return Err("Invalid FOREIGN KEY syntax".into());
-            }
+            let mut fk = parse_table_foreign_key(col_def)?;
+            fk.deferrable = key_mode.unwrap_or_default();
+            foreign_keys.push(fk);
+            continue;
         }
 
         let name = tokens[0];
-        let type_str = tokens.get(1).ok_or("Missing column type")?;
-
-        // Map string type to enum
-        let column_type = match type_str.to_ascii_lowercase().as_str() {
-            "int" => ColumnType::Int,
-            "text" => ColumnType::Text,
-            "bool" => ColumnType::Bool,
-            _ => return Err(format!("Unknown column type: {}", type_str)),
+        if tokens.len() < 2 {
+            return Err("Missing column type".into());
+        }
+        // SMALLSERIAL / SERIAL / BIGSERIAL: an integer column drawing its DEFAULT from an owned sequence
+        let serial_type = match tokens[1].to_ascii_lowercase().as_str() {
+            "smallserial" | "serial2" => Some(ColumnType::SmallInt),
+            "serial" | "serial4" => Some(ColumnType::Int),
+            "bigserial" | "serial8" => Some(ColumnType::BigInt),
+            _ => None,
+        };
+        let (column_type, type_tokens) = match &serial_type {
+            Some(column_type) => (column_type.clone(), 1),
+            None => parse_column_type(&tokens[1..])?,
         };
 
         let mut not_null = false;
-        let mut default: Option<Value> = None;
+        let mut default: Option<Operand> = None;
+        let mut identity: Option<(Identity, SequenceOptions)> = None;
+        let mut last_constraint: Option<LastConstraint> = None;
 
         // Parse column constraints
-        let mut i = 2;
+        let mut i = 1 + type_tokens;
         while i < tokens.len() {
             match tokens[i].to_ascii_lowercase().as_str() {
                 "not" if i + 1 < tokens.len() && tokens[i + 1].eq_ignore_ascii_case("null") => {
                     not_null = true;
                     i += 2;
                 }
+                "unique" => {
+                    unique_constraints.push(vec![name.to_string()]);
+                    last_constraint = Some(LastConstraint::Key);
+                    i += 1;
+                }
                 "primary" if i + 1 < tokens.len() && tokens[i + 1].eq_ignore_ascii_case("key") => {
-                    primary_key = Some(name.to_string());
+                    if primary_key.is_some() {
+                        return Err("Multiple primary keys are not allowed".into());
+                    }
+                    primary_key = Some(vec![name.to_string()]);
+                    last_constraint = Some(LastConstraint::Key);
                     not_null = true; // PK always NOT NULL
                     i += 2;
                 }
@@ -127,7 +173,12 @@ pub fn parse_create_table(input: &str) -> Result<Query, String> {
                             local_columns: vec![name.to_string()],
                             referenced_table: ref_table.trim().to_string(),
                             referenced_columns: vec![ref_col.trim().to_string()],
+                            on_delete: FkAction::NoAction,
+                            on_update: FkAction::NoAction,
+                            deferrable: Deferrable::NotDeferrable,
+                            name: None,
                         });
+                        last_constraint = Some(LastConstraint::ForeignKey);
                         i += 2;
                     } else {
                         let ref_table = tokens[i + 1];
@@ -139,26 +190,75 @@ pub fn parse_create_table(input: &str) -> Result<Query, String> {
                             local_columns: vec![name.to_string()],
                             referenced_table: ref_table.to_string(),
                             referenced_columns: vec![ref_col.to_string()],
+                            on_delete: FkAction::NoAction,
+                            on_update: FkAction::NoAction,
+                            deferrable: Deferrable::NotDeferrable,
+                            name: None,
                         });
+                        last_constraint = Some(LastConstraint::ForeignKey);
                         i += 3;
                     }
                 }
-                // Handle DEFAULT values
+                // Handle ON DELETE / ON UPDATE after a column-level REFERENCES
+                "on" if i + 2 < tokens.len() => {
+                    let fk = foreign_keys
+                        .last_mut()
+                        .filter(|fk| fk.local_columns == [name])
+                        .ok_or("ON DELETE/UPDATE without REFERENCES")?;
+                    let (action, used) = parse_fk_action(&tokens, i + 2)?;
+                    match tokens[i + 1].to_ascii_lowercase().as_str() {
+                        "delete" => fk.on_delete = action,
+                        "update" => fk.on_update = action,
+                        other => return Err(format!("Expected DELETE or UPDATE after ON, got '{}'", other)),
+                    }
+                    i += 2 + used;
+                }
+                // DEFAULT takes an expression, up to the next column constraint
                 "default" if i + 1 < tokens.len() => {
-                    let val_str = tokens[i + 1];
-                    let val = if val_str.starts_with('"') && val_str.ends_with('"') {
-                        Value::Text(val_str.trim_matches('"').to_string())
-                    } else if val_str.eq_ignore_ascii_case("true") {
-                        Value::Bool(true)
-                    } else if val_str.eq_ignore_ascii_case("false") {
-                        Value::Bool(false)
-                    } else if let Ok(num) = val_str.parse::<i64>() {
-                        Value::Int(num)
-                    } else {
-                        return Err(format!("Unsupported default value: {}", val_str));
+                    let end = (i + 2..tokens.len())
+                        .find(|&j| COLUMN_CONSTRAINT_WORDS.contains(&tokens[j].to_ascii_lowercase().as_str()))
+                        .unwrap_or(tokens.len());
+                    default = Some(parse_default_value(&tokens[i + 1..end].join(" "))?);
+                    i = end;
+                }
+                // GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY [(sequence options)]
+                "generated" => {
+                    let lower = |j: usize| tokens.get(j).map(|t| t.to_ascii_lowercase());
+                    let (kind, used) = match (lower(i + 1).as_deref(), lower(i + 2).as_deref()) {
+                        (Some("always"), _) => (Identity::Always, 2),
+                        (Some("by"), Some("default")) => (Identity::ByDefault, 3),
+                        _ => return Err("Expected ALWAYS or BY DEFAULT after GENERATED".into()),
                     };
-                    default = Some(val);
-                    i += 2;
+                    if lower(i + used).as_deref() != Some("as") || lower(i + used + 1).as_deref() != Some("identity") {
+                        return Err("Expected AS IDENTITY after GENERATED".into());
+                    }
+                    i += used + 2;
+                    let mut options = SequenceOptions::default();
+                    if tokens.get(i).is_some_and(|t| t.starts_with('(')) {
+                        let end = (i..tokens.len())
+                            .find(|&j| tokens[j].ends_with(')'))
+                            .ok_or("Missing ')' in identity options")?;
+                        let text = tokens[i..=end].join(" ");
+                        let words: Vec<&str> = text[1..text.len() - 1].split_whitespace().collect();
+                        options = parse_sequence_options(&words, true)?;
+                        i = end + 1;
+                    }
+                    identity = Some((kind, options));
+                }
+                // [NOT] DEFERRABLE [INITIALLY ...] after UNIQUE / PRIMARY KEY / REFERENCES
+                "not" | "deferrable" | "initially" => {
+                    let Some((mode, used)) = parse_deferrable(&tokens, i)? else {
+                        i += 1;
+                        continue;
+                    };
+                    match last_constraint {
+                        Some(LastConstraint::Key) => deferrable_keys.push((vec![name.to_string()], mode)),
+                        Some(LastConstraint::ForeignKey) => {
+                            foreign_keys.last_mut().unwrap().deferrable = mode;
+                        }
+                        None => return Err("DEFERRABLE needs a UNIQUE, PRIMARY KEY or REFERENCES constraint".into()),
+                    }
+                    i += used;
                 }
                 _ => {
                     i += 1;
@@ -166,18 +266,511 @@ pub fn parse_create_table(input: &str) -> Result<Query, String> {
             }
         }
 
+        // SERIAL and identity columns are NOT NULL and default to nextval('<table>_<column>_seq')
+        if serial_type.is_some() || identity.is_some() {
+            if default.is_some() || (serial_type.is_some() && identity.is_some()) {
+                return Err(format!("both default and identity specified for column '{}'", name));
+            }
+            if !matches!(column_type, ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt) {
+                return Err(format!("identity column type must be smallint, integer, or bigint, not {}", column_type));
+            }
+            let mut options = identity.as_ref().map(|(_, options)| options.clone()).unwrap_or_default();
+            let sequence_name = options
+                .sequence_name
+                .take()
+                .unwrap_or_else(|| format!("{}_{}_seq", table_name, name));
+            options.data_type = Some(column_type.clone());
+            options.owned_by = Some((table_name.to_string(), name.to_string()));
+            default = Some(Operand::Func(
+                "nextval".to_string(),
+                vec![Operand::Literal(Value::Text(sequence_name.clone()))],
+            ));
+            not_null = true;
+            sequences.push((sequence_name, options));
+        }
+
+        let default = default.map(|value| column_type.check_default(name, value)).transpose()?;
+
         columns.push(Column {
             name: name.to_string(),
             column_type,
             not_null,
             default,
+            attnum: columns.len(),
+            identity: identity.map(|(kind, _)| kind),
         });
     }
 
+    // Primary key columns must exist and are always NOT NULL
+    if let Some(pk_cols) = &primary_key {
+        for pk in pk_cols {
+            let col = columns
+                .iter_mut()
+                .find(|c: &&mut Column| c.name == *pk)
+                .ok_or_else(|| format!("Primary key column '{}' not found", pk))?;
+            col.not_null = true;
+        }
+    }
+
+    // CHECK expressions may only reference this table's columns; duplicate names get a suffix
+    for i in 0..checks.len() {
+        let mut cols = Vec::new();
+        checks[i].condition.column_names(&mut cols);
+        if let Some(missing) = cols.iter().find(|c| !columns.iter().any(|col| col.name == **c)) {
+            return Err(format!(
+                "Column '{}' in CHECK constraint '{}' not found",
+                missing, checks[i].name
+            ));
+        }
+        let base = checks[i].name.clone();
+        let mut n = 0;
+        while checks[..i].iter().any(|c| c.name == checks[i].name) {
+            n += 1;
+            checks[i].name = format!("{}{}", base, n);
+        }
+    }
+
     Ok(Query::CreateTable {
         table_name: table_name.to_string(),
+        if_not_exists,
         columns,
         primary_key,
         foreign_keys,
+        unique_constraints,
+        checks,
+        deferrable_keys,
+        sequences,
     })
 }
+
+/// CHECK clauses of a definition: (optional constraint name, expression text)
+pub type CheckClauses = Vec<(Option<String>, String)>;
+
+/// Remove "[constraint name] check (expr)" clauses from a definition;
+/// returns the remaining text and the (optional name, expression) pairs
+pub fn extract_checks(def: &str) -> Result<(String, CheckClauses), String> {
+    let mut rest = String::new();
+    let mut clauses = Vec::new();
+    let mut pending_name: Option<String> = None;
+    let mut words = def.trim();
+
+    while !words.is_empty() {
+        let end = words.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(words.len());
+        let word = &words[..end];
+
+        if word.eq_ignore_ascii_case("constraint") {
+            let after = words[end..].trim_start();
+            let name_end = after.find(char::is_whitespace).ok_or("Missing constraint name")?;
+            pending_name = Some(after[..name_end].to_string());
+            words = after[name_end..].trim_start();
+            continue;
+        }
+
+        if word.eq_ignore_ascii_case("check") {
+            let after = words[end..].trim_start();
+            if !after.starts_with('(') {
+                return Err("Expected '(' after CHECK".into());
+            }
+            // Find the matching ')' (quotes may contain parentheses)
+            let mut depth = 0;
+            let mut quote: Option<char> = None;
+            let mut close = None;
+            for (i, c) in after.char_indices() {
+                match (c, quote) {
+                    (q, Some(open)) if q == open => quote = None,
+                    (_, Some(_)) => {}
+                    ('"' | '\'', None) => quote = Some(c),
+                    ('(', None) => depth += 1,
+                    (')', None) => {
+                        depth -= 1;
+                        if depth == 0 {
+                            close = Some(i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let close = close.ok_or("Missing ')' in CHECK constraint")?;
+            clauses.push((pending_name.take(), after[1..close].trim().to_string()));
+            words = after[close + 1..].trim_start();
+            continue;
+        }
+
+        if pending_name.is_some() {
+            return Err("Only CHECK constraints can be named".into());
+        }
+
+        // Keep any other word (including a following parenthesized group) untouched
+        let next = words[end.max(1)..]
+            .find(char::is_whitespace)
+            .map(|p| p + end.max(1))
+            .unwrap_or(words.len());
+        rest.push_str(&words[..next]);
+        rest.push(' ');
+        words = words[next..].trim_start();
+    }
+
+    if pending_name.is_some() {
+        return Err("Missing CHECK after CONSTRAINT name".into());
+    }
+    Ok((rest.trim_end().to_string(), clauses))
+}
+
+/// Parse the type at the start of `tokens`, e.g. `bigint`, `double precision`,
+/// `timestamp with time zone`, `character varying(20)` or
+/// `numeric(10, 2)` (modifiers may be split over several tokens); returns the type and
+/// the number of tokens it used
+pub fn parse_column_type(tokens: &[&str]) -> Result<(ColumnType, usize), String> {
+    let first = tokens.first().ok_or("Missing column type")?;
+    let mut spec = first.to_ascii_lowercase();
+    let mut used = 1;
+    if spec == "double" && tokens.get(1).is_some_and(|t| t.trim_end_matches("[]").eq_ignore_ascii_case("precision")) {
+        spec.push_str(" precision");
+        used = 2;
+    }
+    if spec == "character" && tokens.get(1).is_some_and(|t| t.to_ascii_lowercase().starts_with("varying")) {
+        spec = format!("character {}", tokens[1].to_ascii_lowercase());
+        used = 2;
+    }
+    if matches!(spec.as_str(), "time" | "timestamp") {
+        let zone: Vec<String> =
+            tokens.iter().skip(1).take(3).map(|t| t.trim_end_matches("[]").to_ascii_lowercase()).collect();
+        if zone.len() == 3 && matches!(zone[0].as_str(), "with" | "without") && zone[1] == "time" && zone[2] == "zone" {
+            spec = format!("{} {} time zone", spec, zone[0]);
+            used = 4;
+        }
+    }
+    // gather "(...)" modifiers, also when separated from the name by whitespace
+    if spec.contains('(') || tokens.get(used).is_some_and(|t| t.starts_with('(')) {
+        while !spec.contains(')') {
+            let next = tokens.get(used).ok_or_else(|| format!("Missing ')' in type {}", spec))?;
+            spec.push_str(next);
+            used += 1;
+        }
+    }
+    // a trailing "[]", on the type or as its own word, makes it an array of the type
+    let mut array = tokens[used - 1].ends_with("[]");
+    if !array && tokens.get(used) == Some(&"[]") {
+        array = true;
+        used += 1;
+    }
+    if let Some(element) = spec.strip_suffix("[]") {
+        spec = element.to_string();
+    }
+
+    let (base, modifiers) = match spec.split_once('(') {
+        Some((base, rest)) => {
+            let args = rest.strip_suffix(')').ok_or_else(|| format!("Invalid type modifiers in {}", spec))?;
+            let args = args
+                .split(',')
+                .map(|a| a.trim().parse::<u32>().map_err(|_| format!("Invalid type modifier in {}", spec)))
+                .collect::<Result<Vec<u32>, String>>()?;
+            (base.trim().to_string(), Some(args))
+        }
+        None => (spec.clone(), None),
+    };
+
+    let column_type = match (base.as_str(), modifiers.as_deref()) {
+        ("smallint" | "int2", None) => ColumnType::SmallInt,
+        ("int" | "integer" | "int4", None) => ColumnType::Int,
+        ("bigint" | "int8", None) => ColumnType::BigInt,
+        ("real" | "float4", None) => ColumnType::Real,
+        ("double precision" | "float8" | "float", None) => ColumnType::Double,
+        ("numeric" | "decimal", None) => ColumnType::Numeric(None),
+        ("numeric" | "decimal", Some(&[precision])) => numeric_type(precision, 0)?,
+        ("numeric" | "decimal", Some(&[precision, scale])) => numeric_type(precision, scale)?,
+        ("text", None) => ColumnType::Text,
+        ("varchar" | "character varying", None) => ColumnType::Varchar(None),
+        ("varchar" | "character varying", Some(&[n])) => ColumnType::Varchar(Some(string_length(n, &spec)?)),
+        ("char" | "character", None) => ColumnType::Char(1),
+        ("char" | "character", Some(&[n])) => ColumnType::Char(string_length(n, &spec)?),
+        ("bytea", None) => ColumnType::Bytea,
+        ("jsonb", None) => ColumnType::Jsonb,
+        ("uuid", None) => ColumnType::Uuid,
+        ("bool" | "boolean", None) => ColumnType::Bool,
+        ("date", None) => ColumnType::Date,
+        ("time" | "time without time zone", None) => ColumnType::Time,
+        ("timestamp" | "timestamp without time zone", None) => ColumnType::Timestamp,
+        ("timestamptz" | "timestamp with time zone", None) => ColumnType::TimestampTz,
+        ("interval", None) => ColumnType::Interval,
+        // any other name may be a user-defined ENUM type, looked up when the statement runs
+        (name, None) if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+            ColumnType::Enum(Arc::new(EnumType::named(name)))
+        }
+        _ => return Err(format!("Unknown column type: {}", spec)),
+    };
+    if array {
+        return Ok((ColumnType::Array(Box::new(column_type)), used));
+    }
+    Ok((column_type, used))
+}
+
+fn string_length(n: u32, spec: &str) -> Result<u32, String> {
+    if n == 0 || n > 10_485_760 {
+        return Err(format!("length for type {} must be between 1 and 10485760", spec));
+    }
+    Ok(n)
+}
+
+fn numeric_type(precision: u32, scale: u32) -> Result<ColumnType, String> {
+    if precision == 0 || precision > 1000 {
+        return Err(format!("NUMERIC precision {} must be between 1 and 1000", precision));
+    }
+    if scale > precision {
+        return Err(format!("NUMERIC scale {} must be between 0 and precision {}", scale, precision));
+    }
+    Ok(ColumnType::Numeric(Some((precision, scale))))
+}
+
+/// Words that end a DEFAULT expression in a column definition
+const COLUMN_CONSTRAINT_WORDS: [&str; 11] = [
+    "not", "null", "unique", "primary", "references", "on", "deferrable", "initially", "constraint", "check",
+    "generated",
+];
+
+/// Parse a DEFAULT: a literal ("text" or 'text', true/false, a number, NULL) or an
+/// expression such as gen_random_uuid()
+pub fn parse_default_value(val_str: &str) -> Result<Operand, String> {
+    parse_operand(val_str.trim()).map_err(|e| format!("Unsupported default value: {} ({})", val_str, e))
+}
+
+/// Split on whitespace outside of quotes, so `default "a b"` keeps its literal whole
+fn split_words(input: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = None;
+    for (i, c) in input.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            None if c == '"' || c == '\'' => quote = Some(c),
+            _ => {}
+        }
+        if c.is_whitespace() && quote.is_none() {
+            if let Some(s) = start.take() {
+                words.push(&input[s..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        words.push(&input[s..]);
+    }
+    words
+}
+
+/// Split definitions on commas that are not nested inside parentheses, brackets or quotes
+pub fn split_top_level(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match (c, quote) {
+            (q, Some(open)) if q == open => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            ('(' | '[', None) => depth += 1,
+            (')' | ']', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Parse CREATE TYPE name AS ENUM ('label', ...)
+pub fn parse_create_type(input: &str) -> Result<Query, String> {
+    let rest = input["create type ".len()..].trim_start();
+    let lower = rest.to_ascii_lowercase();
+    let as_enum = lower.find(" as enum").ok_or("Only CREATE TYPE name AS ENUM (...) is supported")?;
+    let type_name = rest[..as_enum].trim();
+    if type_name.is_empty() || type_name.contains(char::is_whitespace) {
+        return Err(format!("Invalid type name '{}'", type_name));
+    }
+
+    let list = rest[as_enum + " as enum".len()..].trim();
+    let inner = list
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or("Expected a list of labels in parentheses after AS ENUM")?;
+    let mut labels = Vec::new();
+    if !inner.trim().is_empty() {
+        for item in split_top_level(inner) {
+            let item = item.trim();
+            let label = item
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .filter(|s| s.replace("''", "").find('\'').is_none())
+                .ok_or_else(|| format!("ENUM labels must be quoted strings, got '{}'", item))?;
+            labels.push(label.replace("''", "'"));
+        }
+    }
+
+    Ok(Query::CreateType {
+        type_name: type_name.to_string(),
+        labels,
+    })
+}
+
+/// Parse "(a, b, c)" into column names
+pub fn parse_column_list(input: &str) -> Result<Vec<String>, String> {
+    let input = input.trim();
+    let inner = input
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(|| format!("Expected column list in parentheses, got '{}'", input))?;
+
+    let cols: Vec<String> = inner
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if cols.is_empty() {
+        return Err("Empty column list".into());
+    }
+    Ok(cols)
+}
+
+/// Parse "foreign key (a, b) references t(x, y) [on delete ...] [on update ...]";
+/// single columns may omit the parentheses ("foreign key a references t x")
+pub fn parse_table_foreign_key(def: &str) -> Result<ForeignKeyConstraint, String> {
+    let def = def.trim();
+    let lower = def.to_ascii_lowercase();
+
+    let refs_pos = lower
+        .find("references")
+        .ok_or("Expected 'references' after FOREIGN KEY")?;
+    let local_spec = def["foreign".len()..refs_pos].trim();
+    if !local_spec.to_ascii_lowercase().starts_with("key") {
+        return Err("Invalid FOREIGN KEY syntax".into());
+    }
+    let local_spec = local_spec["key".len()..].trim();
+    if local_spec.is_empty() {
+        return Err("Invalid FOREIGN KEY syntax".into());
+    }
+    let local_columns = if local_spec.starts_with('(') {
+        parse_column_list(local_spec)?
+    } else {
+        vec![local_spec.to_string()]
+    };
+
+    let after = def[refs_pos + "references".len()..].trim();
+    let (referenced_table, referenced_columns, rest) = match after.find('(') {
+        Some(open) if !after[..open].trim().contains(char::is_whitespace) => {
+            let close = open + after[open..].find(')').ok_or("Invalid FOREIGN KEY reference format")?;
+            let table = after[..open].trim();
+            if table.is_empty() {
+                return Err("Invalid FOREIGN KEY reference format".into());
+            }
+            (table, parse_column_list(&after[open..=close])?, &after[close + 1..])
+        }
+        _ => {
+            // "table column" form
+            let mut parts = after.split_whitespace();
+            let table = parts.next().ok_or("Missing referenced table")?;
+            let col = parts.next().ok_or("Missing referenced column")?;
+            let table_end = after.find(table).unwrap() + table.len();
+            let rest_pos = table_end + after[table_end..].find(col).unwrap() + col.len();
+            let col = col.trim_matches(|c| c == '(' || c == ')');
+            (table, vec![col.to_string()], &after[rest_pos..])
+        }
+    };
+
+    if local_columns.len() != referenced_columns.len() {
+        return Err("FOREIGN KEY column count does not match referenced columns".into());
+    }
+
+    let mut fk = ForeignKeyConstraint {
+        local_columns,
+        referenced_table: referenced_table.to_string(),
+        referenced_columns,
+        on_delete: FkAction::NoAction,
+        on_update: FkAction::NoAction,
+        deferrable: Deferrable::NotDeferrable,
+        name: None,
+    };
+
+    // Optional ON DELETE / ON UPDATE clauses
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        if !tokens[i].eq_ignore_ascii_case("on") || i + 2 >= tokens.len() {
+            return Err(format!("Unexpected '{}' in FOREIGN KEY", tokens[i]));
+        }
+        let (action, used) = parse_fk_action(&tokens, i + 2)?;
+        match tokens[i + 1].to_ascii_lowercase().as_str() {
+            "delete" => fk.on_delete = action,
+            "update" => fk.on_update = action,
+            other => return Err(format!("Expected DELETE or UPDATE after ON, got '{}'", other)),
+        }
+        i += 2 + used;
+    }
+
+    Ok(fk)
+}
+
+/// Parse a referential action starting at tokens[i]; returns the action and tokens consumed
+fn parse_fk_action(tokens: &[&str], i: usize) -> Result<(FkAction, usize), String> {
+    let word = |k: usize| tokens.get(i + k).map(|t| t.to_ascii_lowercase());
+    match (word(0).as_deref(), word(1).as_deref()) {
+        (Some("cascade"), _) => Ok((FkAction::Cascade, 1)),
+        (Some("restrict"), _) => Ok((FkAction::Restrict, 1)),
+        (Some("no"), Some("action")) => Ok((FkAction::NoAction, 2)),
+        (Some("set"), Some("null")) => Ok((FkAction::SetNull, 2)),
+        (Some("set"), Some("default")) => Ok((FkAction::SetDefault, 2)),
+        _ => Err(format!("Unknown referential action at '{}'", tokens.get(i).unwrap_or(&""))),
+    }
+}
+
+/// Parse "[not] deferrable [initially deferred | initially immediate]" at tokens[i];
+/// returns the mode and tokens consumed, or None if no such clause starts there
+fn parse_deferrable(tokens: &[&str], i: usize) -> Result<Option<(Deferrable, usize)>, String> {
+    let word = |k: usize| tokens.get(i + k).map(|t| t.to_ascii_lowercase());
+    let (deferrable, mut used) = match (word(0).as_deref(), word(1).as_deref()) {
+        (Some("not"), Some("deferrable")) => (Some(false), 2),
+        (Some("deferrable"), _) => (Some(true), 1),
+        (Some("initially"), _) => (None, 0),
+        _ => return Ok(None),
+    };
+
+    let mut initially_deferred = false;
+    if word(used).as_deref() == Some("initially") {
+        initially_deferred = match word(used + 1).as_deref() {
+            Some("deferred") => true,
+            Some("immediate") => false,
+            _ => return Err("Expected DEFERRED or IMMEDIATE after INITIALLY".into()),
+        };
+        used += 2;
+    }
+
+    let mode = match (deferrable, initially_deferred) {
+        (Some(false), true) => {
+            return Err("Constraint declared INITIALLY DEFERRED must be DEFERRABLE".into());
+        }
+        (_, true) => Deferrable::InitiallyDeferred,
+        (Some(true), false) => Deferrable::InitiallyImmediate,
+        _ => Deferrable::NotDeferrable,
+    };
+    Ok(Some((mode, used)))
+}
+
+/// Split a trailing deferrability clause off a table-level constraint definition
+pub fn strip_deferrable(def: &str) -> Result<(String, Option<Deferrable>), String> {
+    let tokens: Vec<&str> = def.split_whitespace().collect();
+    for i in 0..tokens.len() {
+        if let Some((mode, used)) = parse_deferrable(&tokens, i)?
+            && i + used == tokens.len()
+        {
+            return Ok((tokens[..i].join(" "), Some(mode)));
+        }
+    }
+    Ok((def.to_string(), None))
+}
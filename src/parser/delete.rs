@@ -1,13 +1,27 @@
+use crate::parser::main::find_keyword;
+use crate::parser::select::parse_from_clause;
 use crate::types::parser_types::{Condition, Query};
+use std::collections::HashMap;
 
-/// Parses a very minimal `DELETE FROM <table>` statement.
+/// Parses `DELETE FROM <table> [USING <from_item>]`; the WHERE clause comes in as `filter`.
 pub fn parse_delete(input: &str, filter: Option<Condition>) -> Result<Query, String> {
     let from_index = input.find("from").ok_or("Missing 'from'")?;
 
-    let table_name = input[from_index + "from".len()..].trim();
+    let mut table_name = input[from_index + "from".len()..].trim();
+    let (using, aliases) = match find_keyword(table_name, "using") {
+        Some(i) => {
+            let (item, aliases) = parse_from_clause(&table_name[i..])?;
+            table_name = table_name[..i].trim();
+            (Some(item), aliases)
+        }
+        None => (None, HashMap::new()),
+    };
 
-    return Ok(Query::Delete {
+    Ok(Query::Delete {
         table_name: table_name.to_string(),
+        using,
+        aliases,
         filter,
-    });
+        returning: None,
+    })
 }
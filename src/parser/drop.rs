@@ -0,0 +1,85 @@
+use crate::types::parser_types::Query;
+
+/// Parses `DROP TABLE [IF EXISTS] a [, b ...] [CASCADE | RESTRICT]`, `DROP INDEX [IF EXISTS] name`
+/// and `DROP SEQUENCE [IF EXISTS] a [, b ...] [CASCADE | RESTRICT]`
+pub fn parse_drop(input: &str) -> Result<Query, String> {
+    let lower = input.to_ascii_lowercase();
+    if lower.starts_with("drop sequence ") {
+        let (names, if_exists, cascade) = parse_target_list(&input["drop sequence ".len()..], true)?;
+        return Ok(Query::DropSequence {
+            sequence_names: names,
+            if_exists,
+            cascade,
+        });
+    }
+    let (is_table, prefix) = if lower.starts_with("drop table ") {
+        (true, "drop table ")
+    } else if lower.starts_with("drop index ") {
+        (false, "drop index ")
+    } else {
+        return Err("Invalid DROP syntax".into());
+    };
+
+    let (names, if_exists, cascade) = parse_target_list(&input[prefix.len()..], true)?;
+
+    if is_table {
+        Ok(Query::DropTable {
+            table_names: names,
+            if_exists,
+            cascade,
+        })
+    } else {
+        if names.len() != 1 || cascade {
+            return Err("DROP INDEX expects a single index name".into());
+        }
+        Ok(Query::DropIndex {
+            index_name: names.into_iter().next().unwrap(),
+            if_exists,
+        })
+    }
+}
+
+/// Parses `TRUNCATE [TABLE] a [, b ...] [CASCADE | RESTRICT]`
+pub fn parse_truncate(input: &str) -> Result<Query, String> {
+    let rest = input["truncate".len()..].trim_start();
+    let rest = if rest.to_ascii_lowercase().starts_with("table ") {
+        &rest["table ".len()..]
+    } else {
+        rest
+    };
+
+    let (names, _, cascade) = parse_target_list(rest, false)?;
+    Ok(Query::Truncate {
+        table_names: names,
+        cascade,
+    })
+}
+
+/// `[IF EXISTS] name [, name ...] [CASCADE | RESTRICT]` -> (names, if_exists, cascade)
+fn parse_target_list(input: &str, allow_if_exists: bool) -> Result<(Vec<String>, bool, bool), String> {
+    let mut rest = input.trim();
+
+    let mut if_exists = false;
+    if allow_if_exists && rest.to_ascii_lowercase().starts_with("if exists ") {
+        if_exists = true;
+        rest = rest["if exists ".len()..].trim_start();
+    }
+
+    let mut cascade = false;
+    let lower = rest.to_ascii_lowercase();
+    if lower.ends_with(" cascade") {
+        cascade = true;
+        rest = rest[..rest.len() - " cascade".len()].trim_end();
+    } else if lower.ends_with(" restrict") {
+        rest = rest[..rest.len() - " restrict".len()].trim_end();
+    }
+
+    let names: Vec<String> = rest.split(',').map(|n| n.trim().to_string()).collect();
+    if names
+        .iter()
+        .any(|n| n.is_empty() || n.contains(char::is_whitespace))
+    {
+        return Err(format!("Invalid name list: '{}'", rest));
+    }
+    Ok((names, if_exists, cascade))
+}
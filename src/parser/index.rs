@@ -4,11 +4,20 @@ pub fn parse_create_index(input: &str) -> Result<Query, String> {
     let input = input.trim();
 
     let lower = input.to_ascii_lowercase();
-    if !lower.starts_with("create index") {
+    let (prefix, unique) = if lower.starts_with("create unique index") {
+        ("create unique index", true)
+    } else if lower.starts_with("create index") {
+        ("create index", false)
+    } else {
         return Err("Invalid CREATE INDEX syntax".into());
-    }
+    };
 
-    let after_prefix = input["create index".len()..].trim();
+    let mut after_prefix = input[prefix.len()..].trim();
+
+    let if_not_exists = after_prefix.to_ascii_lowercase().starts_with("if not exists ");
+    if if_not_exists {
+        after_prefix = after_prefix["if not exists ".len()..].trim_start();
+    }
     let lower_tail = after_prefix.to_ascii_lowercase();
 
     let (index_name_opt, after_on) = if lower_tail.starts_with("on ") {
@@ -49,7 +58,9 @@ pub fn parse_create_index(input: &str) -> Result<Query, String> {
 
     Ok(Query::CreateIndex {
         index_name,
+        if_not_exists,
         table_name,
         column_names,
+        unique,
     })
 }
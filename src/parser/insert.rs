@@ -1,67 +1,181 @@
-use crate::types::parser_types::Query;
+use crate::parser::create::split_top_level;
+use crate::parser::select::parse_select;
+use crate::parser::main::find_keyword;
+use crate::parser::r#where::{parse_expression, parse_where};
+use crate::types::parser_types::{Condition, ConflictAction, InsertSource, OnConflict, Operand, Overriding, Query};
 use crate::types::storage_types::Value;
 
-pub fn parse_insert(input: &str) -> Result<Query, String> {
+/// Parses `INSERT INTO table [(cols)] VALUES (...), (...)` or `INSERT INTO table [(cols)] SELECT ...`.
+/// `filter` is the WHERE clause split off by `parse_query`; only the SELECT form accepts one.
+pub fn parse_insert(input: &str, filter: Option<Condition>) -> Result<Query, String> {
     let prefix = "insert into ";
+    let rest = input[prefix.len()..].trim();
 
-    let values_index = input.find("values").ok_or("Missing 'values'")?;
-    let before_values = input[prefix.len()..values_index].trim();
-    let after_values = input[values_index + "values".len()..].trim();
+    // Table name ends at the column list or the first whitespace
+    let name_end = rest
+        .find(|c: char| c == '(' || c.is_whitespace())
+        .ok_or("Missing 'values'")?;
+    let table_name = rest[..name_end].trim();
+    let mut rest = rest[name_end..].trim_start();
 
     // Check for optional column list: insert into table(col1, col2) ...
-    let (table_name, column_names): (&str, Option<Vec<&str>>) = if before_values.contains('(') {
-        let open = before_values
-            .find('(')
-            .ok_or("Missing '(' in column list")?;
-        let close = before_values
-            .find(')')
-            .ok_or("Missing ')' in column list")?;
+    let column_names = if rest.starts_with('(') {
+        let close = rest.find(')').ok_or("Missing ')' in column list")?;
+        let cols: Vec<String> = rest[1..close].split(',').map(|s| s.trim().to_string()).collect();
+        rest = rest[close + 1..].trim_start();
+        Some(cols)
+    } else {
+        None
+    };
 
-        let table = before_values[..open].trim();
-        let cols_str = &before_values[open + 1..close];
-        let cols: Vec<&str> = cols_str.split(',').map(|s| s.trim()).collect();
+    // OVERRIDING { SYSTEM | USER } VALUE, for identity columns
+    let words: Vec<String> = rest.split_whitespace().take(3).map(|w| w.to_ascii_lowercase()).collect();
+    let overriding = match words.iter().map(|w| w.as_str()).collect::<Vec<_>>().as_slice() {
+        ["overriding", "system", "value"] => Some(Overriding::SystemValue),
+        ["overriding", "user", "value"] => Some(Overriding::UserValue),
+        ["overriding", ..] => return Err("Expected SYSTEM VALUE or USER VALUE after OVERRIDING".into()),
+        _ => None,
+    };
+    if overriding.is_some() {
+        for _ in 0..3 {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = rest[word_end..].trim_start();
+        }
+    }
 
-        (table, Some(cols))
+    let lower = rest.to_ascii_lowercase();
+    let source = if lower.starts_with("values") {
+        if filter.is_some() {
+            return Err("WHERE is not allowed in INSERT ... VALUES".into());
+        }
+        InsertSource::Values(parse_value_rows(&rest["values".len()..])?)
+    } else if lower.starts_with("select ") {
+        InsertSource::Select(Box::new(parse_select(rest, filter)?))
     } else {
-        (before_values, None)
+        return Err("Missing 'values'".into());
     };
 
-    // Parse values from parentheses
-    let open = after_values.find('(').ok_or("Missing '(' in values")?;
-    let close = after_values.find(')').ok_or("Missing ')' in values")?;
-    let values_str = &after_values[open + 1..close];
-    let raw_values: Vec<&str> = values_str.split(',').map(|s| s.trim()).collect();
+    Ok(Query::Insert {
+        table_name: table_name.to_string(),
+        column_names,
+        source,
+        overriding,
+        on_conflict: None,
+        returning: None,
+    })
+}
 
-    // Convert each raw string value to a Value enum
-    let parsed_values: Result<Vec<Value>, String> = raw_values
-        .into_iter()
-        .map(|raw| {
-            if raw.starts_with('"') && raw.ends_with('"') {
-                let text = &raw[1..raw.len() - 1];
-                return Ok(Value::Text(text.to_string()));
-            }
-            if raw.eq_ignore_ascii_case("true") {
-                return Ok(Value::Bool(true));
-            }
-            if raw.eq_ignore_ascii_case("false") {
-                return Ok(Value::Bool(false));
-            }
-            if let Ok(num) = raw.parse::<i64>() {
-                return Ok(Value::Int(num));
-            }
-            if raw.eq_ignore_ascii_case("null") {
-                return Ok(Value::Null);
-            }
-            Err(format!("Unrecognized value: {}", raw))
-        })
-        .collect();
+/// Parses `ON CONFLICT [(cols)] DO NOTHING` or `ON CONFLICT (cols) DO UPDATE SET col = val, ... [WHERE ...]`.
+/// Values are literals or column references such as `excluded.col`.
+pub fn parse_on_conflict(input: &str) -> Result<OnConflict, String> {
+    let rest = input["on conflict".len()..].trim_start();
 
-    let values = parsed_values?;
-    let column_names = column_names.map(|cols| cols.into_iter().map(|s| s.to_string()).collect());
+    let (target, rest) = if rest.starts_with('(') {
+        let close = rest.find(')').ok_or("Missing ')' in ON CONFLICT target")?;
+        let cols = rest[1..close].split(',').map(|s| s.trim().to_string()).collect();
+        (Some(cols), rest[close + 1..].trim_start())
+    } else {
+        (None, rest)
+    };
 
-    return Ok(Query::Insert {
-        table_name: table_name.to_string(),
-        column_names: column_names,
-        values: values,
-    });
+    let lower = rest.to_ascii_lowercase();
+    if lower == "do nothing" {
+        return Ok(OnConflict {
+            target,
+            action: ConflictAction::Nothing,
+        });
+    }
+    if !lower.starts_with("do update set ") {
+        return Err("Expected DO NOTHING or DO UPDATE SET after ON CONFLICT".into());
+    }
+    if target.is_none() {
+        return Err("ON CONFLICT DO UPDATE requires a conflict target, e.g. ON CONFLICT (id)".into());
+    }
+
+    let mut assignments = rest["do update set ".len()..].trim();
+    let mut filter = None;
+    if let Some(i) = find_keyword(assignments, "where") {
+        filter = Some(parse_where(assignments[i + "where".len()..].trim())?);
+        assignments = assignments[..i].trim();
+    }
+
+    let mut column_names = Vec::new();
+    let mut values = Vec::new();
+    for assignment in split_top_level(assignments) {
+        let (col, raw) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected 'col = value', got '{}'", assignment.trim()))?;
+        column_names.push(col.trim().to_string());
+        values.push(parse_operand(raw.trim())?);
+    }
+
+    Ok(OnConflict {
+        target,
+        action: ConflictAction::Update {
+            column_names,
+            values,
+            filter,
+        },
+    })
+}
+
+/// Parse `(v1, v2), (v3, v4), ...` into rows of values
+fn parse_value_rows(input: &str) -> Result<Vec<Vec<Operand>>, String> {
+    let mut rows = Vec::new();
+    for tuple in split_top_level(input.trim()) {
+        let tuple = tuple.trim();
+        if !tuple.starts_with('(') {
+            return Err("Missing '(' in values".into());
+        }
+        if !tuple.ends_with(')') {
+            return Err("Missing ')' in values".into());
+        }
+        let row = split_top_level(&tuple[1..tuple.len() - 1])
+            .into_iter()
+            .map(|raw| parse_operand(raw.trim()))
+            .collect::<Result<Vec<Operand>, String>>()?;
+        // VALUES has no row to read columns from
+        let mut columns = Vec::new();
+        row.iter().for_each(|op| op.column_names(&mut columns));
+        if let Some(column) = columns.first() {
+            return Err(format!("Unrecognized value '{}' in VALUES", column));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// A literal, or otherwise an expression: a column reference such as `col` or `table.col`,
+/// a function call or arithmetic
+pub fn parse_operand(raw: &str) -> Result<Operand, String> {
+    match parse_value(raw) {
+        Ok(value) => Ok(Operand::Literal(value)),
+        Err(e) if raw.is_empty() => Err(e),
+        Err(_) => parse_expression(raw),
+    }
+}
+
+/// Convert a raw literal to a Value
+fn parse_value(raw: &str) -> Result<Value, String> {
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+        let text = &raw[1..raw.len() - 1];
+        return Ok(Value::Text(text.to_string()));
+    }
+    // 'text' writes a single quote as ''
+    if raw.len() >= 2 && raw.starts_with('\'') && raw.ends_with('\'') {
+        return Ok(Value::Text(raw[1..raw.len() - 1].replace("''", "'")));
+    }
+    if raw.eq_ignore_ascii_case("true") {
+        return Ok(Value::Bool(true));
+    }
+    if raw.eq_ignore_ascii_case("false") {
+        return Ok(Value::Bool(false));
+    }
+    if let Some(num) = Value::parse_number(raw) {
+        return Ok(num);
+    }
+    if raw.eq_ignore_ascii_case("null") {
+        return Ok(Value::Null);
+    }
+    Err(format!("Unrecognized value: {}", raw))
 }
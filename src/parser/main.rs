@@ -1,9 +1,9 @@
 use super::{
-    create::parse_create_table, insert::parse_insert, select::parse_select, r#where::parse_where,
+    alter::parse_alter_table, copy::parse_copy, create::{parse_create_table, parse_create_type}, sequence::parse_create_sequence, insert::{parse_insert, parse_on_conflict}, select::parse_select, r#where::parse_where,
 };
 use crate::{
     parser::{
-        begin::parse_begin, delete::parse_delete, index::parse_create_index, set_isolation::parse_set_session, update::parse_update, vacuum::parse_vacuum
+        begin::parse_begin, delete::parse_delete, drop::{parse_drop, parse_truncate}, index::parse_create_index, set_constraints::parse_set_constraints, set_isolation::parse_set_session, set_option::parse_set_option, update::parse_update, vacuum::parse_vacuum
     },
     types::parser_types::{Condition, Query},
 };
@@ -13,6 +13,87 @@ pub fn parse_query(input: &str) -> Result<Query, String> {
     // Normalize input (trim and lowercase)
     let mut input = input.trim();
 
+    // COPY may embed a whole SELECT, so none of the clause splitting below applies
+    if input.to_ascii_lowercase().starts_with("copy ") {
+        return parse_copy(input);
+    }
+
+    let (rest, returning) = split_returning(input);
+    input = rest;
+
+    // ON CONFLICT has its own WHERE, so it comes off before the statement's WHERE is split
+    let mut on_conflict = None;
+    if input.to_ascii_lowercase().starts_with("insert into")
+        && let Some(i) = find_keyword(input, "on conflict")
+    {
+        on_conflict = Some(parse_on_conflict(&input[i..])?);
+        input = input[..i].trim();
+    }
+
+    let mut query = parse_statement(input)?;
+    match &mut query {
+        Query::Insert {
+            on_conflict: slot_on_conflict,
+            returning: slot,
+            ..
+        } => {
+            *slot_on_conflict = on_conflict;
+            *slot = returning;
+        }
+        Query::Update { returning: slot, .. } | Query::Delete { returning: slot, .. } => *slot = returning,
+        _ if returning.is_some() => {
+            return Err("RETURNING is only supported for INSERT, UPDATE and DELETE".to_string());
+        }
+        _ => {}
+    }
+    Ok(query)
+}
+
+/// Byte offset of the last `keyword` outside of quotes and parentheses that follows whitespace
+pub fn find_keyword(input: &str, keyword: &str) -> Option<usize> {
+    let lower = input.to_ascii_lowercase();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    let mut found = None;
+    for (i, c) in lower.char_indices() {
+        if let Some(open) = quote {
+            if c == open {
+                quote = None;
+            }
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if c == '(' {
+            depth += 1;
+        } else if c == ')' {
+            depth -= 1;
+        } else if depth == 0
+            && c.is_whitespace()
+            && lower[i + 1..].starts_with(keyword)
+            && lower[i + 1 + keyword.len()..].starts_with(char::is_whitespace)
+        {
+            found = Some(i + 1);
+        }
+    }
+    found
+}
+
+/// Split off a trailing `RETURNING a, b` (or `RETURNING *`) clause
+fn split_returning(input: &str) -> (&str, Option<Vec<String>>) {
+    match find_keyword(input, "returning") {
+        Some(i) => {
+            let list = input[i + "returning".len()..]
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+            (input[..i].trim(), Some(list))
+        }
+        None => (input, None),
+    }
+}
+
+fn parse_statement(input: &str) -> Result<Query, String> {
+    let mut input = input;
+
     let where_index = input.to_ascii_lowercase().find("where");
 
     let mut condition: Option<Condition> = None;
@@ -27,10 +108,20 @@ pub fn parse_query(input: &str) -> Result<Query, String> {
 
     if lower.starts_with("create table ") {
         parse_create_table(input)
-    } else if lower.starts_with("create index") {
+    } else if lower.starts_with("create type ") {
+        parse_create_type(input)
+    } else if lower.starts_with("create sequence ") {
+        parse_create_sequence(input)
+    } else if lower.starts_with("alter table ") {
+        parse_alter_table(input)
+    } else if lower.starts_with("create index") || lower.starts_with("create unique index") {
         parse_create_index(input)
+    } else if lower.starts_with("drop ") {
+        parse_drop(input)
+    } else if lower.starts_with("truncate ") {
+        parse_truncate(input)
     } else if lower.starts_with("insert into") {
-        parse_insert(input)
+        parse_insert(input, condition)
     } else if lower.starts_with("select ") {
         parse_select(input, condition)
     } else if lower.starts_with("delete ") {
@@ -47,6 +138,10 @@ pub fn parse_query(input: &str) -> Result<Query, String> {
         parse_vacuum(input)
     } else if lower.starts_with("set session characteristics") {
         parse_set_session(input)
+    } else if lower.starts_with("set constraints") {
+        parse_set_constraints(input)
+    } else if lower.starts_with("set ") {
+        parse_set_option(input)
     } else {
         Err("Unrecognized command".to_string())
     }
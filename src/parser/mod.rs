@@ -1,11 +1,18 @@
+mod alter;
+mod copy;
 mod create;
 mod delete;
+mod drop;
 mod index;
 mod insert;
 pub mod main;
 mod select;
+mod sequence;
+pub mod script;
 mod update;
 mod vacuum;
 mod begin;
+mod set_constraints;
 mod set_isolation;
+mod set_option;
 pub mod r#where;
@@ -0,0 +1,89 @@
+use crate::parser::main::parse_query;
+use crate::types::parser_types::Query;
+
+/// Source text of a script statement and its parse result
+pub type ScriptStatement = (String, Result<Query, String>);
+
+/// Split a SQL script into statements and parse each one. Statements end at `;` outside
+/// quotes and `--` comments run to the end of the line. The lines after
+/// `COPY ... FROM STDIN;`, up to a line holding only `\.`, are that statement's data.
+pub fn parse_script(contents: &str) -> Vec<ScriptStatement> {
+    let mut statements = Vec::new();
+    let mut rest = contents;
+
+    while let Some((text, after)) = next_statement(rest) {
+        rest = after;
+        let mut query = parse_query(&text);
+
+        if let Ok(Query::Copy {
+            to_file: false,
+            path: None,
+            data,
+            ..
+        }) = &mut query
+        {
+            // data starts on the line after the statement
+            let body = rest.find('\n').map_or("", |i| &rest[i + 1..]);
+            match copy_data(body) {
+                Some((lines, after)) => {
+                    *data = Some(lines);
+                    rest = after;
+                }
+                None => {
+                    query = Err("COPY FROM STDIN data is missing its terminating \\. line".into());
+                    rest = "";
+                }
+            }
+        }
+        statements.push((text, query));
+    }
+    statements
+}
+
+/// Text of the next non-empty statement (without the `;`) and the input after it
+fn next_statement(input: &str) -> Option<(String, &str)> {
+    let mut text = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (c, quote) {
+            (q, Some(open)) if q == open => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            ('-', None) if chars.peek().is_some_and(|&(_, next)| next == '-') => {
+                while chars.next_if(|&(_, next)| next != '\n').is_some() {}
+                continue;
+            }
+            (';', None) => {
+                if text.trim().is_empty() {
+                    text.clear();
+                    continue;
+                }
+                return Some((text.trim().to_string(), &input[i + 1..]));
+            }
+            _ => {}
+        }
+        text.push(c);
+    }
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| (text.to_string(), ""))
+}
+
+/// Lines up to the `\.` terminator and the input after it
+fn copy_data(body: &str) -> Option<(String, &str)> {
+    let mut data = String::new();
+    let mut pos = 0;
+    while pos < body.len() {
+        let end = body[pos..].find('\n').map_or(body.len(), |i| pos + i + 1);
+        let line = body[pos..end].trim_end_matches(['\n', '\r']);
+        if line == "\\." {
+            return Some((data, &body[end..]));
+        }
+        data.push_str(line);
+        data.push('\n');
+        pos = end;
+    }
+    None
+}
@@ -1,37 +1,60 @@
 use std::collections::HashMap;
 
-use super::r#where::parse_where;
-use crate::types::parser_types::{Condition, FromItem, JoinKind, Query};
+use super::create::split_top_level;
+use super::main::find_keyword;
+use super::r#where::{parse_expression, parse_where};
+use crate::types::parser_types::{Condition, FromItem, JoinKind, Operand, Query};
 
 /// Parse a SELECT query into a Query::Select AST node
 pub fn parse_select(input: &str, filter: Option<Condition>) -> Result<Query, String> {
     let prefix = "select ";
 
-    let mut aliases: HashMap<String, String> = HashMap::new();
-
-    let from_index = input.find("from").ok_or("Missing 'from'")?;
-
-    // Split column list before FROM
-    let column_names: Vec<&str> = input[prefix.len()..from_index].trim().split(',').collect();
+    // without FROM the items are computed once, as a single row
+    let from_index = find_keyword(input, "from").unwrap_or(input.len());
 
-    let column_names: Vec<String> = column_names
+    // Split column list before FROM; items are columns or expressions
+    let column_names: Vec<String> = split_top_level(input[prefix.len()..from_index].trim())
         .iter()
         .map(|name| name.trim().to_string())
         .collect();
+    let targets = if column_names.first().map(|s| s.as_str()) == Some("*") {
+        Vec::new()
+    } else {
+        column_names
+            .iter()
+            .map(|name| parse_expression(name))
+            .collect::<Result<_, _>>()?
+    };
+
+    let (from_table, aliases) = if from_index < input.len() {
+        parse_from_clause(input[from_index..].trim())?
+    } else if targets.is_empty() {
+        return Err("SELECT * with no tables specified is not valid".into());
+    } else {
+        (FromItem::Empty, HashMap::new())
+    };
+
+    Ok(Query::Select {
+        from_table,
+        aliases,
+        column_names,
+        targets,
+        filter,
+    })
+}
 
-    let after_columns = input[from_index..].trim();
-    let mut current_from: Option<FromItem> = None;
-    let tokens = tokenize(after_columns);
+/// Parse `<keyword> table [[AS] alias] [[LEFT | INNER] JOIN table [[AS] alias] ON cond ...]`,
+/// where the leading keyword is FROM (SELECT, UPDATE ... FROM) or USING (DELETE ... USING)
+pub fn parse_from_clause(input: &str) -> Result<(FromItem, HashMap<String, String>), String> {
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    let tokens = tokenize(input);
     let mut i = 0;
+    let mut current_from = parse_from_item(&tokens, &mut i, &mut aliases)?;
     let mut join_type: Option<JoinKind> = None;
 
-    // Parse FROM + JOIN clauses
+    // Parse JOIN clauses
     while i < tokens.len() {
         match tokens[i].to_ascii_lowercase().as_str() {
-            "from" => {
-                let table = parse_from_item(&tokens, &mut i, &mut aliases)?;
-                current_from = Some(table);
-            }
             "left" => {
                 join_type = Some(JoinKind::Left);
                 i += 1;
@@ -44,13 +67,12 @@ pub fn parse_select(input: &str, filter: Option<Condition>) -> Result<Query, Str
                 let right_table = parse_from_item(&tokens, &mut i, &mut aliases)?;
                 expect_token("on", &tokens, &mut i)?;
                 let on_condition: Condition = parse_condition(&tokens, &mut i)?;
-                let left_item = current_from.take().expect("JOIN without left side");
-                current_from = Some(FromItem::Join {
-                    left: Box::new(left_item),
+                current_from = FromItem::Join {
+                    left: Box::new(current_from),
                     right: Box::new(right_table),
                     kind: join_type.take().unwrap_or(JoinKind::Inner),
                     on: on_condition,
-                });
+                };
             }
             _ => {
                 i += 1;
@@ -58,15 +80,11 @@ pub fn parse_select(input: &str, filter: Option<Condition>) -> Result<Query, Str
         }
     }
 
-    Ok(Query::Select {
-        from_table: current_from.unwrap(),
-        aliases,
-        column_names,
-        filter,
-    })
+    Ok((current_from, aliases))
 }
 
-/// Parse a FROM item (table with optional alias)
+/// Parse a FROM item: a table or a set-returning function call such as unnest(...),
+/// with an optional alias; a function's alias may name its column: `AS u(x)`
 fn parse_from_item(
     tokens: &[String],
     i: &mut usize,
@@ -79,29 +97,68 @@ fn parse_from_item(
         .to_string();
     *i += 1;
 
-    let alias = if tokens
-        .get(*i)
-        .map(|t| t.eq_ignore_ascii_case("as"))
-        .unwrap_or(false)
-    {
-        *i += 1;
-        Some(
-            tokens
-                .get(*i)
-                .ok_or_else(|| format!("Expected alias after AS at position {}", i))?
-                .to_string(),
-        )
+    let call = if tokens.get(*i).is_some_and(|t| t == "(") {
+        let args = parenthesized(tokens, i)?;
+        match parse_expression(&format!("{}({})", name, args.join(" ")))? {
+            Operand::Func(name, args) => Some((name, args)),
+            other => return Err(format!("Expected a function call in FROM, got {:?}", other)),
+        }
     } else {
         None
     };
 
+    let alias = match tokens.get(*i).map(|t| t.to_ascii_lowercase()) {
+        Some(t) if t == "as" => {
+            *i += 1;
+            Some(
+                tokens
+                    .get(*i)
+                    .ok_or_else(|| format!("Expected alias after AS at position {}", i))?
+                    .to_string(),
+            )
+        }
+        // a bare alias: anything that does not start the next clause
+        Some(t) if !matches!(t.as_str(), "left" | "inner" | "join" | "on") => Some(tokens[*i].clone()),
+        _ => None,
+    };
     if alias.is_some() {
-        aliases.insert(alias.unwrap(), name.clone());
+        *i += 1;
     }
 
-    *i += 1;
+    let Some((name, args)) = call else {
+        if let Some(alias) = alias {
+            aliases.insert(alias, name.clone());
+        }
+        return Ok(FromItem::Table(name));
+    };
+    let alias = alias.unwrap_or_else(|| name.clone());
+    let column = if tokens.get(*i).is_some_and(|t| t == "(") {
+        match parenthesized(tokens, i)?.as_slice() {
+            [column] => column.clone(),
+            _ => return Err(format!("Expected one column name for {}()", name)),
+        }
+    } else {
+        alias.clone()
+    };
+    Ok(FromItem::Function { name, args, alias, column })
+}
 
-    Ok(FromItem::Table(name))
+/// Tokens between the '(' at `i` and its matching ')'; moves `i` past the ')'
+fn parenthesized(tokens: &[String], i: &mut usize) -> Result<Vec<String>, String> {
+    let start = *i + 1;
+    let mut depth = 0;
+    while let Some(token) = tokens.get(*i) {
+        match token.as_str() {
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            _ => {}
+        }
+        *i += 1;
+        if depth == 0 {
+            return Ok(tokens[start..*i - 1].to_vec());
+        }
+    }
+    Err("Missing ')' in FROM".into())
 }
 
 /// Ensure the next token matches the expected keyword
@@ -166,9 +223,13 @@ fn tokenize(input: &str) -> Vec<String> {
                 while let Some(&next) = chars.peek() {
                     literal.push(next);
                     chars.next();
-                    if next == quote {
+                    // '' inside a single-quoted literal is a quote, not its end
+                    if next == quote && !(quote == '\'' && chars.peek() == Some(&'\'')) {
                         break;
                     }
+                    if next == quote {
+                        literal.push(chars.next().unwrap());
+                    }
                 }
                 tokens.push(literal);
             }
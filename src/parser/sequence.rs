@@ -0,0 +1,107 @@
+use super::create::parse_column_type;
+use crate::types::parser_types::Query;
+use crate::types::sequence::SequenceOptions;
+
+/// Parses `CREATE SEQUENCE [IF NOT EXISTS] name [options]`
+pub fn parse_create_sequence(input: &str) -> Result<Query, String> {
+    let mut rest = input["create sequence ".len()..].trim_start();
+    let if_not_exists = rest.to_ascii_lowercase().starts_with("if not exists ");
+    if if_not_exists {
+        rest = rest["if not exists ".len()..].trim_start();
+    }
+
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let name = tokens.first().ok_or("Missing sequence name")?;
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid sequence name '{}'", name));
+    }
+
+    Ok(Query::CreateSequence {
+        sequence_name: name.to_string(),
+        if_not_exists,
+        options: parse_sequence_options(&tokens[1..], false)?,
+    })
+}
+
+/// Parses sequence options: `AS type`, `INCREMENT [BY] n`, `MINVALUE n | NO MINVALUE`,
+/// `MAXVALUE n | NO MAXVALUE`, `START [WITH] n`, `[NO] CYCLE` and `OWNED BY table.column | NONE`.
+/// The options of an identity column take `SEQUENCE NAME name` instead of AS and OWNED BY.
+pub fn parse_sequence_options(tokens: &[&str], identity: bool) -> Result<SequenceOptions, String> {
+    let lower: Vec<String> = tokens.iter().map(|t| t.to_ascii_lowercase()).collect();
+    let word = |i: usize| lower.get(i).map(|s| s.as_str());
+    let number = |i: usize, what: &str| -> Result<i64, String> {
+        let token = tokens.get(i).ok_or_else(|| format!("Missing value for {}", what))?;
+        token.parse::<i64>().map_err(|_| format!("Invalid {} value: '{}'", what, token))
+    };
+
+    let mut options = SequenceOptions::default();
+    let mut i = 0;
+    while i < tokens.len() {
+        match (word(i), word(i + 1)) {
+            (Some("as"), _) if !identity => {
+                let (data_type, used) = parse_column_type(tokens.get(i + 1..).unwrap_or_default())?;
+                options.data_type = Some(data_type);
+                i += 1 + used;
+            }
+            (Some("increment"), Some("by")) => {
+                options.increment = Some(number(i + 2, "INCREMENT")?);
+                i += 3;
+            }
+            (Some("increment"), _) => {
+                options.increment = Some(number(i + 1, "INCREMENT")?);
+                i += 2;
+            }
+            (Some("minvalue"), _) => {
+                options.min_value = Some(number(i + 1, "MINVALUE")?);
+                i += 2;
+            }
+            (Some("maxvalue"), _) => {
+                options.max_value = Some(number(i + 1, "MAXVALUE")?);
+                i += 2;
+            }
+            (Some("start"), Some("with")) => {
+                options.start = Some(number(i + 2, "START")?);
+                i += 3;
+            }
+            (Some("start"), _) => {
+                options.start = Some(number(i + 1, "START")?);
+                i += 2;
+            }
+            (Some("cycle"), _) => {
+                options.cycle = true;
+                i += 1;
+            }
+            (Some("no"), Some("minvalue")) => {
+                options.min_value = None;
+                i += 2;
+            }
+            (Some("no"), Some("maxvalue")) => {
+                options.max_value = None;
+                i += 2;
+            }
+            (Some("no"), Some("cycle")) => {
+                options.cycle = false;
+                i += 2;
+            }
+            (Some("owned"), Some("by")) if !identity => {
+                let target = tokens.get(i + 2).ok_or("Missing column after OWNED BY")?;
+                options.owned_by = if target.eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    let (table, column) = target
+                        .split_once('.')
+                        .ok_or_else(|| format!("OWNED BY expects table.column, got '{}'", target))?;
+                    Some((table.to_string(), column.to_string()))
+                };
+                i += 3;
+            }
+            (Some("sequence"), Some("name")) if identity => {
+                let name = tokens.get(i + 2).ok_or("Missing name after SEQUENCE NAME")?;
+                options.sequence_name = Some(name.to_string());
+                i += 3;
+            }
+            _ => return Err(format!("Unexpected '{}' in sequence options", tokens[i])),
+        }
+    }
+    Ok(options)
+}
@@ -0,0 +1,23 @@
+use crate::types::parser_types::Query;
+
+/// Parses `SET CONSTRAINTS ALL DEFERRED` / `SET CONSTRAINTS ALL IMMEDIATE`
+pub fn parse_set_constraints(input: &str) -> Result<Query, String> {
+    let tokens: Vec<String> = input
+        .split_whitespace()
+        .map(|t| t.to_ascii_lowercase())
+        .collect();
+
+    if tokens.len() != 4 || tokens[0] != "set" || tokens[1] != "constraints" {
+        return Err("Invalid SET CONSTRAINTS syntax".to_string());
+    }
+    if tokens[2] != "all" {
+        return Err("Only SET CONSTRAINTS ALL is supported".to_string());
+    }
+
+    let deferred = match tokens[3].as_str() {
+        "deferred" => true,
+        "immediate" => false,
+        other => return Err(format!("Expected DEFERRED or IMMEDIATE, got '{}'", other)),
+    };
+    Ok(Query::SetConstraints { deferred })
+}
@@ -0,0 +1,24 @@
+use crate::types::parser_types::Query;
+
+/// Parses `SET name = value` / `SET name TO value`
+pub fn parse_set_option(input: &str) -> Result<Query, String> {
+    let prefix = "set ";
+    let rest = input[prefix.len()..].trim();
+
+    let (name, value) = if let Some((n, v)) = rest.split_once('=') {
+        (n.trim(), v.trim())
+    } else {
+        let lower = rest.to_ascii_lowercase();
+        let pos = lower.find(" to ").ok_or("Expected '=' or 'TO' in SET")?;
+        (rest[..pos].trim(), rest[pos + " to ".len()..].trim())
+    };
+
+    if name.is_empty() || value.is_empty() {
+        return Err("Invalid SET syntax".to_string());
+    }
+
+    Ok(Query::SetOption {
+        name: name.to_ascii_lowercase(),
+        value: value.trim_matches(|c| c == '"' || c == '\'').to_string(),
+    })
+}
@@ -1,8 +1,13 @@
+use crate::parser::create::split_top_level;
+use crate::parser::insert::parse_operand;
+use crate::parser::main::find_keyword;
+use crate::parser::select::parse_from_clause;
 use crate::types::parser_types::Condition;
 use crate::types::parser_types::Query;
-use crate::types::storage_types::Value;
+use std::collections::HashMap;
 
-/// Parses a minimal `UPDATE table SET col=val, ...`
+/// Parses `UPDATE table SET col = val, ... [FROM from_item]`; the WHERE clause comes in as `filter`.
+/// A value is a literal or a column of the table or of the FROM list.
 pub fn parse_update(input: &str, filter: Option<Condition>) -> Result<Query, String> {
     // Normalize input (trim and drop a trailing ';' if present)
     let input = input.trim().trim_end_matches(';');
@@ -17,58 +22,38 @@ pub fn parse_update(input: &str, filter: Option<Condition>) -> Result<Query, Str
     // Find the first "set" (case-insensitive) and slice around it
     let set_pos = lower.find("set").ok_or("Missing 'set'")?;
     let table_name = input[prefix.len()..set_pos].trim();
-    let after_set = input[set_pos + "set".len()..].trim();
-
-    // Naive split of assignments by comma: "col = val"
-    let split_col_val: Vec<(String, String)> = after_set
-        .split(',')
-        .map(|col_val| {
-            // Split only at the first '=' to keep RHS intact
-            let mut parts = col_val.splitn(2, '=');
-            let col = parts.next().unwrap().trim().to_string();
-            let val = parts.next().unwrap().trim().to_string();
-            (col, val)
-        })
-        .collect();
-
-    // Separate columns and raw values
-    let (parsed_cols, values): (Vec<String>, Vec<String>) = split_col_val.into_iter().unzip();
-
-    // Convert raw strings to Value (supports "text", true/false, null, integer)
-    let parsed_values: Result<Vec<Value>, String> = values
-        .into_iter()
-        .map(|raw| {
-            // double-quoted text literal
-            if raw.starts_with('"') && raw.ends_with('"') && raw.len() >= 2 {
-                let text = &raw[1..raw.len() - 1];
-                return Ok(Value::Text(text.to_string()));
-            }
-            // booleans
-            if raw.eq_ignore_ascii_case("true") {
-                return Ok(Value::Bool(true));
-            }
-            if raw.eq_ignore_ascii_case("false") {
-                return Ok(Value::Bool(false));
-            }
-            // NULL literal
-            if raw.eq_ignore_ascii_case("null") {
-                return Ok(Value::Null);
-            }
-            // integer
-            if let Ok(num) = raw.parse::<i64>() {
-                return Ok(Value::Int(num));
-            }
-            Err(format!("Unrecognized value: {}", raw))
-        })
-        .collect();
-
-    let parsed_values = parsed_values?;
+    let mut after_set = input[set_pos + "set".len()..].trim();
+
+    // Optional FROM list after the assignments
+    let (from, aliases) = match find_keyword(after_set, "from") {
+        Some(i) => {
+            let (item, aliases) = parse_from_clause(&after_set[i..])?;
+            after_set = after_set[..i].trim();
+            (Some(item), aliases)
+        }
+        None => (None, HashMap::new()),
+    };
+
+    // Split assignments by top-level comma: "col = val"
+    let mut parsed_cols = Vec::new();
+    let mut parsed_values = Vec::new();
+    for col_val in split_top_level(after_set) {
+        // Split only at the first '=' to keep RHS intact
+        let (col, val) = col_val
+            .split_once('=')
+            .ok_or_else(|| format!("Expected 'col = value', got '{}'", col_val.trim()))?;
+        parsed_cols.push(col.trim().to_string());
+        parsed_values.push(parse_operand(val.trim())?);
+    }
 
     // Build the parsed query
     Ok(Query::Update {
         table_name: table_name.to_string(),
         column_names: parsed_cols,
         values: parsed_values,
+        from,
+        aliases,
         filter,
+        returning: None,
     })
 }
@@ -1,12 +1,98 @@
-use crate::types::filter_types::CmpOp;
+use super::create::parse_column_type;
+use crate::types::filter_types::{ArithOp, CmpOp};
 use crate::types::parser_types::{Condition, Node, Operand, Token};
-use crate::types::storage_types::Value;
+use crate::types::storage_types::{ColumnType, Value};
 
 /// Main entry point for parsing a WHERE clause string into a Condition AST
 pub fn parse_where(where_input: &str) -> Result<Condition, String> {
     let tokens = tokenize(where_input)?; // Step 1: tokenize the input string
     let rpn = shunting_yard(tokens)?; // Step 2: convert tokens to RPN using Shunting-Yard
-    rpn_to_condition(rpn) // Step 3: build Condition AST from RPN
+    match rpn_to_node(rpn)? {
+        // Step 3: build Condition AST from RPN
+        Node::Cond(root) => Ok(root),
+        other => Err(format!("Expected a condition, got {:?}", other)),
+    }
+}
+
+/// Parse a value expression: a column, a literal, a function call or arithmetic on them
+pub fn parse_expression(input: &str) -> Result<Operand, String> {
+    let tokens = tokenize(input)?;
+    let rpn = shunting_yard(tokens)?;
+    to_operand(rpn_to_node(rpn)?)
+}
+
+/// Literal type names: `date "2024-01-31"` is a DATE literal
+fn typed_literal_type(word: &str) -> Option<ColumnType> {
+    match word {
+        "date" => Some(ColumnType::Date),
+        "time" => Some(ColumnType::Time),
+        "timestamp" => Some(ColumnType::Timestamp),
+        "timestamptz" => Some(ColumnType::TimestampTz),
+        "interval" => Some(ColumnType::Interval),
+        "bytea" => Some(ColumnType::Bytea),
+        "jsonb" => Some(ColumnType::Jsonb),
+        "uuid" => Some(ColumnType::Uuid),
+        _ => None,
+    }
+}
+
+/// The string literal whose opening quote is at `start` and the position after it.
+/// `"..."` has no escapes; `'...'` writes a single quote as `''`, so JSON text fits in it.
+fn read_quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut text = String::new();
+    let mut i = start + 1;
+    loop {
+        match chars.get(i) {
+            None => return Err("Unterminated string literal".into()),
+            Some(&c) if c == quote && quote == '\'' && chars.get(i + 1) == Some(&'\'') => {
+                text.push(c);
+                i += 2;
+            }
+            Some(&c) if c == quote => return Ok((text, i + 1)),
+            Some(&c) => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+}
+
+/// The type name starting at `start`, as written after `::` or `AS` in a cast, and the
+/// position after it: `int`, `double precision`, `varchar(10)`, `numeric(10, 2)`, `text[]`
+fn read_type(chars: &[char], start: usize) -> Result<(ColumnType, usize), String> {
+    // the next few words, each with the position after it; parse_column_type takes what it needs
+    let mut words: Vec<(String, usize)> = Vec::new();
+    let mut i = start;
+    while words.len() < 4 {
+        while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+            i += 1;
+        }
+        let word_start = i;
+        while chars.get(i).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+            i += 1;
+        }
+        if i == word_start {
+            break;
+        }
+        if chars.get(i) == Some(&'(') {
+            while chars.get(i).is_some_and(|c| *c != ')') {
+                i += 1;
+            }
+            i += 1;
+        }
+        if chars.get(i) == Some(&'[') && chars.get(i + 1) == Some(&']') {
+            i += 2;
+        }
+        let end = i.min(chars.len());
+        words.push((chars[word_start..end].iter().collect(), end));
+    }
+    let names: Vec<&str> = words.iter().map(|(word, _)| word.as_str()).collect();
+    if names.is_empty() {
+        return Err("Expected a type name in cast".into());
+    }
+    let (column_type, used) = parse_column_type(&names)?;
+    Ok((column_type, words[used - 1].1))
 }
 
 /// Splits a WHERE clause string into a vector of tokens
@@ -14,6 +100,8 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
     let chars: Vec<char> = input.chars().collect();
     let mut tokens: Vec<Token> = Vec::new();
     let mut i = 0;
+    // open parentheses and brackets; true for the one opened by CAST(
+    let mut casts: Vec<bool> = Vec::new();
 
     while i < chars.len() {
         let char = chars[i];
@@ -24,10 +112,48 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
             }
             '(' => {
                 tokens.push(Token::LParen);
+                casts.push(false);
                 i += 1;
             }
             ')' => {
                 tokens.push(Token::RParen);
+                casts.pop();
+                i += 1;
+            }
+            ':' if chars.get(i + 1) == Some(&':') => {
+                let (column_type, end) = read_type(&chars, i + 2)?;
+                tokens.push(Token::Cast(column_type));
+                i = end;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            // ARRAY[...] lists its elements like call arguments; any other '[' subscripts
+            // the operand before it
+            '[' => {
+                if !matches!(tokens.last(), Some(Token::Func(name, _)) if name == "array") {
+                    tokens.push(Token::Subscript);
+                }
+                tokens.push(Token::LParen);
+                casts.push(false);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RParen);
+                casts.pop();
+                i += 1;
+            }
+            '+' => {
+                tokens.push(Token::Plus);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            '/' => {
+                tokens.push(Token::Slash);
                 i += 1;
             }
             '=' => {
@@ -54,20 +180,30 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
                 tokens.push(Token::Lt);
                 i += 1;
             }
-            '"' => {
-                // Parse string literal
-                i += 1;
-                let start = i;
-                while i < chars.len() && chars[i] != '"' {
-                    i += 1;
-                }
-                if i >= chars.len() {
-                    return Err("Unterminated string literal".into());
+            // JSONB operators
+            '-' if chars.get(i + 1) == Some(&'>') => {
+                if chars.get(i + 2) == Some(&'>') {
+                    tokens.push(Token::JsonGetText);
+                    i += 3;
+                } else {
+                    tokens.push(Token::JsonGet);
+                    i += 2;
                 }
-                let text: String = chars[start..i].iter().collect();
-                tokens.push(Token::Str(text));
+            }
+            '@' if chars.get(i + 1) == Some(&'>') => {
+                tokens.push(Token::Contains);
+                i += 2;
+            }
+            '?' => {
+                tokens.push(Token::HasKey);
                 i += 1;
             }
+            '"' | '\'' => {
+                // Parse string literal
+                let (text, end) = read_quoted(&chars, i)?;
+                tokens.push(Token::Str(text));
+                i = end;
+            }
             // ↓↓↓ Разрешаем идентификатор начинаться с буквы ИЛИ '_'
             _ if char.is_alphabetic() || char == '_' => {
                 // Parse identifier or keyword
@@ -79,26 +215,94 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
                     i += 1;
                 }
                 let word: String = chars[start..i].iter().collect();
-                match word.to_ascii_lowercase().as_str() {
+                let lower = word.to_ascii_lowercase();
+                let mut next = i;
+                while next < chars.len() && chars[next].is_whitespace() {
+                    next += 1;
+                }
+                match lower.as_str() {
                     "and" => tokens.push(Token::And),
                     "or" => tokens.push(Token::Or),
                     "not" => tokens.push(Token::Not),
                     "true" => tokens.push(Token::Bool(true)),
                     "false" => tokens.push(Token::Bool(false)),
+                    "null" => tokens.push(Token::Null),
+                    // typed literal: the type name, then a string
+                    _ if matches!(chars.get(next), Some('"' | '\'')) && typed_literal_type(&lower).is_some() => {
+                        let (text, end) = read_quoted(&chars, next)?;
+                        let value = typed_literal_type(&lower).unwrap().coerce(Value::Text(text))?;
+                        tokens.push(Token::Typed(value));
+                        i = end;
+                    }
+                    // extract(field FROM source) separates its arguments with FROM
+                    "from"
+                        if matches!(
+                            tokens.as_slice(),
+                            [.., Token::Func(name, _), Token::LParen, Token::Ident(_) | Token::Str(_)] if name == "extract"
+                        ) =>
+                    {
+                        tokens.push(Token::Comma)
+                    }
+                    // CAST(x AS type) is read as (x)::type
+                    "cast" if chars.get(next) == Some(&'(') => {
+                        tokens.push(Token::LParen);
+                        casts.push(true);
+                        i = next + 1;
+                    }
+                    "as" if casts.last() == Some(&true) => {
+                        let (column_type, end) = read_type(&chars, i)?;
+                        let mut close = end;
+                        while chars.get(close).is_some_and(|c| c.is_whitespace()) {
+                            close += 1;
+                        }
+                        if chars.get(close) != Some(&')') {
+                            return Err("Expected ')' after the type in CAST".into());
+                        }
+                        tokens.extend([Token::RParen, Token::Cast(column_type)]);
+                        casts.pop();
+                        i = close + 1;
+                    }
+                    // SQL's parenthesis-free functions
+                    "current_timestamp" | "current_date" | "localtimestamp" if chars.get(next) != Some(&'(') => {
+                        tokens.extend([Token::Func(lower.clone(), 0), Token::LParen, Token::RParen]);
+                    }
+                    _ if chars.get(next) == Some(&'(') => tokens.push(Token::Func(lower.clone(), 0)),
+                    "array" if chars.get(next) == Some(&'[') => tokens.push(Token::Func(lower.clone(), 0)),
                     _ => tokens.push(Token::Ident(word)), // может быть 'u.id' и т.п.
                 }
             }
-            _ if char.is_ascii_digit() => {
-                // Parse integer literal
+            // A '-' right before digits, where no operand precedes it, is the sign of a literal
+            _ if char.is_ascii_digit()
+                || (char == '-'
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+                    && tokens
+                        .last()
+                        .is_none_or(|t| {
+                            (is_operator(t) && !matches!(t, Token::Cast(_))) || matches!(t, Token::LParen | Token::Comma)
+                        })) =>
+            {
+                // Parse number literal: integer, or NUMERIC with a fraction or beyond i64
                 let start = i;
+                i += 1;
                 while i < chars.len() && chars[i].is_ascii_digit() {
                     i += 1;
                 }
+                if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+                    i += 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
                 let number_str: String = chars[start..i].iter().collect();
-                let number = number_str
-                    .parse::<i64>()
-                    .map_err(|_| format!("Invalid number: {}", number_str))?;
-                tokens.push(Token::Int(number));
+                match Value::parse_number(&number_str) {
+                    Some(Value::Int(n)) => tokens.push(Token::Int(n)),
+                    Some(Value::Numeric(d)) => tokens.push(Token::Numeric(d)),
+                    _ => return Err(format!("Invalid number: {}", number_str)),
+                }
+            }
+            '-' => {
+                tokens.push(Token::Minus);
+                i += 1;
             }
             _ => {
                 return Err(format!("Unexpected character: '{}'", char));
@@ -122,6 +326,16 @@ fn is_operator(t: &Token) -> bool {
             | Token::Lt
             | Token::Gte
             | Token::Lte
+            | Token::Plus
+            | Token::Minus
+            | Token::Star
+            | Token::Slash
+            | Token::JsonGet
+            | Token::JsonGetText
+            | Token::Contains
+            | Token::HasKey
+            | Token::Subscript
+            | Token::Cast(_)
     )
 }
 
@@ -137,6 +351,12 @@ fn precedence(token: &Token) -> u8 {
         Token::And => 2,
         Token::Not => 3,
         Token::Eq | Token::Neq | Token::Gt | Token::Lt | Token::Gte | Token::Lte => 4,
+        Token::Contains | Token::HasKey => 4,
+        Token::JsonGet | Token::JsonGetText => 5,
+        Token::Plus | Token::Minus => 6,
+        Token::Star | Token::Slash => 7,
+        Token::Cast(_) => 8,
+        Token::Subscript => 9,
         _ => 0,
     }
 }
@@ -145,12 +365,32 @@ fn precedence(token: &Token) -> u8 {
 fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
     let mut out: Vec<Token> = Vec::new(); // Output queue (RPN result)
     let mut stack: Vec<Token> = Vec::new(); // Operator stack
+    let mut parens: Vec<(bool, usize)> = Vec::new(); // Open '(': function call?, commas seen
+    let mut prev: Option<Token> = None;
 
     for token in tokens.into_iter() {
+        let current = token.clone();
         match token {
+            Token::Func(name, _) => {
+                stack.push(Token::Func(name, 0));
+            }
             Token::LParen => {
+                parens.push((matches!(stack.last(), Some(Token::Func(..))), 0));
                 stack.push(Token::LParen);
             }
+            Token::Comma => {
+                // An argument ends: pop operators down to the call's '('
+                while let Some(top) = stack.last() {
+                    if matches!(top, Token::LParen) {
+                        break;
+                    }
+                    out.push(stack.pop().unwrap());
+                }
+                match parens.last_mut() {
+                    Some((true, commas)) if !matches!(prev, Some(Token::LParen | Token::Comma)) => *commas += 1,
+                    _ => return Err("Unexpected ','".into()),
+                }
+            }
             Token::RParen => {
                 // Pop operators until matching '('
                 let mut found = false;
@@ -165,6 +405,14 @@ fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
                 if !found {
                     return Err("Mismatched parentheses: unexpected ')'".into());
                 }
+                // Closing a call emits the function with its argument count
+                if let Some((true, commas)) = parens.pop() {
+                    let argc = if matches!(prev, Some(Token::LParen)) { 0 } else { commas + 1 };
+                    match stack.pop() {
+                        Some(Token::Func(name, _)) => out.push(Token::Func(name, argc)),
+                        _ => return Err("Function call without a name".into()),
+                    }
+                }
             }
             op if is_operator(&op) => {
                 // Pop higher-precedence operators from stack
@@ -185,11 +433,12 @@ fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
                 out.push(other);
             }
         }
+        prev = Some(current);
     }
 
     // Pop remaining operators
     while let Some(op) = stack.pop() {
-        if matches!(op, Token::LParen | Token::RParen) {
+        if matches!(op, Token::LParen | Token::RParen | Token::Func(..)) {
             return Err("Mismatched parentheses".into());
         }
         out.push(op);
@@ -198,8 +447,18 @@ fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
     Ok(out)
 }
 
-/// Convert an RPN token list into a Condition AST
-fn rpn_to_condition(rpn: Vec<Token>) -> Result<Condition, String> {
+/// Operand of a comparison, function call or arithmetic
+fn to_operand(n: Node) -> Result<Operand, String> {
+    match n {
+        Node::Col(c) => Ok(Operand::Column(c)),
+        Node::Val(v) => Ok(Operand::Literal(v)),
+        Node::Expr(op) => Ok(op),
+        Node::Cond(_) => Err("Comparison expects operands, not conditions".into()),
+    }
+}
+
+/// Convert an RPN token list into a Condition AST, or an operand for a value expression
+fn rpn_to_node(rpn: Vec<Token>) -> Result<Node, String> {
     let mut stack: Vec<Node> = Vec::new(); // Stack for intermediate nodes
 
     for token in rpn {
@@ -208,21 +467,69 @@ fn rpn_to_condition(rpn: Vec<Token>) -> Result<Condition, String> {
             Token::Ident(name) => stack.push(Node::Col(name)),
             Token::Str(value) => stack.push(Node::Val(Value::Text(value))),
             Token::Int(value) => stack.push(Node::Val(Value::Int(value))),
+            Token::Numeric(value) => stack.push(Node::Val(Value::Numeric(value))),
             Token::Bool(value) => stack.push(Node::Val(Value::Bool(value))),
+            Token::Null => stack.push(Node::Val(Value::Null)),
+            Token::Typed(value) => stack.push(Node::Val(value)),
+
+            // Function calls take their arguments off the stack
+            Token::Func(name, argc) => {
+                if stack.len() < argc {
+                    return Err(format!("RPN underflow ({} arguments)", name));
+                }
+                let mut args = stack
+                    .split_off(stack.len() - argc)
+                    .into_iter()
+                    .map(to_operand)
+                    .collect::<Result<Vec<Operand>, String>>()?;
+                // extract's field is a bare word: extract(year from ts)
+                if name == "extract"
+                    && let Some(Operand::Column(field)) = args.first()
+                {
+                    args[0] = Operand::Literal(Value::Text(field.to_ascii_lowercase()));
+                }
+                stack.push(Node::Expr(Operand::Func(name, args)));
+            }
+
+            Token::Plus
+            | Token::Minus
+            | Token::Star
+            | Token::Slash
+            | Token::JsonGet
+            | Token::JsonGetText
+            | Token::Subscript => {
+                let rhs = to_operand(stack.pop().ok_or("RPN underflow (rhs arith)")?)?;
+                let lhs = to_operand(stack.pop().ok_or("RPN underflow (lhs arith)")?)?;
+                let op = match token {
+                    Token::Plus => ArithOp::Add,
+                    Token::Minus => ArithOp::Sub,
+                    Token::Star => ArithOp::Mul,
+                    Token::Slash => ArithOp::Div,
+                    Token::JsonGet => ArithOp::JsonGet,
+                    Token::JsonGetText => ArithOp::JsonGetText,
+                    _ => ArithOp::Subscript,
+                };
+                stack.push(Node::Expr(Operand::Arith(op, Box::new(lhs), Box::new(rhs))));
+            }
+
+            // Casts apply to the operand before them
+            Token::Cast(column_type) => {
+                let operand = to_operand(stack.pop().ok_or("RPN underflow (cast)")?)?;
+                stack.push(Node::Expr(Operand::Cast(Box::new(operand), column_type)));
+            }
 
             // Handle binary comparison operators
-            Token::Eq | Token::Neq | Token::Gt | Token::Lt | Token::Gte | Token::Lte => {
+            Token::Eq
+            | Token::Neq
+            | Token::Gt
+            | Token::Lt
+            | Token::Gte
+            | Token::Lte
+            | Token::Contains
+            | Token::HasKey => {
                 let rhs = stack.pop().ok_or("RPN underflow (rhs cond)")?;
                 let lhs = stack.pop().ok_or("RPN underflow (lhs cond)")?;
 
-                // ← Раньше требовал (Col, Val). Теперь преобразуем в Operand с обеих сторон.
-                let to_operand = |n: Node| -> Result<Operand, String> {
-                    match n {
-                        Node::Col(c) => Ok(Operand::Column(c)),
-                        Node::Val(v) => Ok(Operand::Literal(v)),
-                        Node::Cond(_) => Err("Comparison expects operands, not conditions".into()),
-                    }
-                };
                 let lhs_op = to_operand(lhs)?;
                 let rhs_op = to_operand(rhs)?;
 
@@ -233,6 +540,8 @@ fn rpn_to_condition(rpn: Vec<Token>) -> Result<Condition, String> {
                     Token::Lt => CmpOp::Lt,
                     Token::Gte => CmpOp::Gte,
                     Token::Lte => CmpOp::Lte,
+                    Token::Contains => CmpOp::Contains,
+                    Token::HasKey => CmpOp::HasKey,
                     _ => unreachable!(),
                 };
 
@@ -272,15 +581,15 @@ fn rpn_to_condition(rpn: Vec<Token>) -> Result<Condition, String> {
             }
 
             // Parentheses should not appear in RPN
-            Token::LParen | Token::RParen => {
+            Token::LParen | Token::RParen | Token::Comma => {
                 return Err("Parenthesis leaked into RPN".into());
             }
         }
     }
 
-    // At the end, stack should contain exactly one condition
+    // At the end, stack should contain exactly one node
     match stack.pop() {
-        Some(Node::Cond(root)) if stack.is_empty() => Ok(root),
+        Some(root) if stack.is_empty() => Ok(root),
         Some(other) => Err(format!("Leftover on stack: {:?}", other)),
         None => Err("Empty RPN".into()),
     }
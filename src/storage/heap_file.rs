@@ -2,8 +2,8 @@ use crate::consts::catalog_consts::PAGE_SIZE;
 use crate::consts::page_consts::{ITEM_ID_SIZE, PAGE_HEADER_SIZE};
 use crate::executer::help_functions::build_key;
 use crate::types::b_tree::BTreeIndex;
-use crate::types::page_types::{ItemId, Page, TupleHeader};
-use crate::types::storage_types::{Column, Row};
+use crate::types::page_types::{ItemId, Page, TupleHeader, VisibilityMap};
+use crate::types::storage_types::{Attribute, Column, Row, Value};
 use crate::types::transaction_types::TransactionManager;
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
@@ -13,10 +13,12 @@ use std::path::PathBuf;
 #[derive(Debug, Clone)]
 pub struct HeapFile {
     pub path: PathBuf, // path to the physical heap file
+    pub attributes: Vec<Attribute>, // physical tuple layout
+    pub schema_version: u16, // written into every new tuple's flags
 }
 
 impl HeapFile {
-    pub fn new(path: &str) -> Self {
+    pub fn new(path: &str, columns: &[Column]) -> Self {
         // create file and initialize with first empty page
         let page = Page::new(0);
         let bytes = page.to_bytes();
@@ -24,10 +26,41 @@ impl HeapFile {
         let mut file = File::create(path).expect("create file failed");
         file.write_all(&bytes).expect("write failed");
         file.sync_all().unwrap();
+        // a visibility map left behind by an earlier file at this path is stale
+        let _ = std::fs::remove_file(PathBuf::from(path).with_extension("vm"));
+
+        // initial layout: one attribute per column, all from schema version 0
+        let attributes = columns
+            .iter()
+            .map(|c| Attribute {
+                column_type: c.column_type.clone(),
+                added_in: 0,
+                missing: Value::Null,
+                dropped: false,
+            })
+            .collect();
 
         Self {
             path: PathBuf::from(path),
+            attributes,
+            schema_version: 0,
+        }
+    }
+
+    /// Physical row (one value per attribute) -> row in `schema` column order
+    fn project(&self, row: Row, schema: &[Column]) -> Row {
+        Row {
+            values: schema.iter().map(|c| row.values[c.attnum].clone()).collect(),
+        }
+    }
+
+    /// Row in `schema` column order -> physical row; dropped attributes stay NULL
+    fn to_physical(&self, row: &Row, schema: &[Column]) -> Row {
+        let mut values = vec![Value::Null; self.attributes.len()];
+        for (col, val) in schema.iter().zip(row.values.iter()) {
+            values[col.attnum] = val.clone();
         }
+        Row { values }
     }
 
     pub fn read_page(&self, page_no: u32) -> Page {
@@ -61,6 +94,70 @@ impl HeapFile {
         file.sync_all().unwrap();
     }
 
+    pub fn vm_path(&self) -> PathBuf {
+        // visibility map lives next to the heap: users.tbl -> users.vm
+        self.path.with_extension("vm")
+    }
+
+    /// Unlink the heap and its visibility map. A file that is already gone is not an error,
+    /// so a drop interrupted after the catalog was written can simply be repeated.
+    pub fn remove_files(&self) -> std::io::Result<()> {
+        for path in [self.path.clone(), self.vm_path()] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_visibility_map(&self) -> VisibilityMap {
+        // missing file means no page is known to be all-visible
+        match std::fs::read(self.vm_path()) {
+            Ok(bytes) => VisibilityMap::from_bytes(bytes),
+            Err(_) => VisibilityMap::default(),
+        }
+    }
+
+    pub fn write_visibility_map(&self, vm: &VisibilityMap) -> std::io::Result<()> {
+        let mut file = File::create(self.vm_path())?;
+        file.write_all(&vm.bytes)?;
+        file.sync_all()
+    }
+
+    /// Drop the all-visible bits of pages that are about to change.
+    /// Only the byte holding each bit is read, and written back only if the bit was set.
+    fn clear_visible(&self, pages: &[u32]) -> std::io::Result<()> {
+        let mut file = match OpenOptions::new().read(true).write(true).open(self.vm_path()) {
+            Ok(file) => file,
+            // no map: no page is marked
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        let mut changed = false;
+        for &page_no in pages {
+            let (byte, _) = VisibilityMap::bit(page_no);
+            if byte as u64 >= len {
+                continue;
+            }
+            let mut buf = [0u8];
+            file.seek(SeekFrom::Start(byte as u64))?;
+            file.read_exact(&mut buf)?;
+            // the byte alone is the map of its 8 pages
+            let mut vm = VisibilityMap::from_bytes(buf.to_vec());
+            if vm.clear(page_no % 8) {
+                file.seek(SeekFrom::Start(byte as u64))?;
+                file.write_all(&vm.bytes)?;
+                changed = true;
+            }
+        }
+        if changed {
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
     pub fn append_page(&self) -> Page {
         // open file in append mode
         let mut file = OpenOptions::new()
@@ -88,31 +185,67 @@ impl HeapFile {
         schema: &[Column],
     ) -> Option<(TupleHeader, Row)> {
         let page = self.read_page(page_no);
-        page.get_tuple(slot_no, schema)
+        page.get_tuple(slot_no, &self.attributes)
+            .map(|(header, row)| (header, self.project(row, schema)))
     }
 
-    pub fn insert_row(&self, row: Row, xid: u32) -> Result<(usize, usize), String> {
+    /// Append rows in one pass: the file is opened once, each page is written
+    /// when it fills up or at the end, and the file is synced once
+    pub fn insert_rows(
+        &self,
+        rows: Vec<Row>,
+        schema: &[Column],
+        xid: u32,
+    ) -> Result<Vec<(usize, usize)>, String> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+
         // find last page number
-        let metadata = std::fs::metadata(&self.path).map_err(|e| e.to_string())?;
-        let page_count = (metadata.len() / PAGE_SIZE as u64) as u32;
-        let last_page_no = if page_count == 0 { 0 } else { page_count - 1 };
+        let metadata = file.metadata().map_err(|e| e.to_string())?;
+        let mut page_count = (metadata.len() / PAGE_SIZE as u64) as u32;
+        let last_page_no = page_count.saturating_sub(1);
 
         // load last page
-        let mut page = self.read_page(last_page_no);
+        let mut buf = [0u8; PAGE_SIZE as usize];
+        file.seek(SeekFrom::Start(last_page_no as u64 * PAGE_SIZE as u64))
+            .map_err(|e| e.to_string())?;
+        file.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        let mut page = Page::from_bytes(buf);
+        page_count = page_count.max(1);
 
-        // try insert row
-        if let Ok(slot_no) = page.insert_tuple(row.clone(), xid) {
-            // row fits into existing page
-            self.write_page(&page);
-            Ok((last_page_no as usize, slot_no))
-        } else {
-            // not enough space → create new page
-            let mut page = self.append_page();
-            let slot_no = page.insert_tuple(row, xid).map_err(|e| e.to_string())?;
-            let new_page_no = page_count;
-            self.write_page(&page);
-            Ok((new_page_no as usize, slot_no))
+        let write = |file: &mut File, page: &Page| -> Result<(), String> {
+            file.seek(SeekFrom::Start(page.header.page_no as u64 * PAGE_SIZE as u64))
+                .map_err(|e| e.to_string())?;
+            file.write_all(&page.to_bytes()).map_err(|e| e.to_string())
+        };
+
+        let mut positions = Vec::with_capacity(rows.len());
+        let mut touched = vec![page.header.page_no];
+        for row in rows {
+            let row = self.to_physical(&row, schema);
+            let slot_no = match page.insert_tuple(row.clone(), &self.attributes, xid, self.schema_version) {
+                Ok(slot_no) => slot_no,
+                Err(_) => {
+                    // not enough space → flush this page and start a new one
+                    write(&mut file, &page)?;
+                    page = Page::new(page_count);
+                    page_count += 1;
+                    touched.push(page.header.page_no);
+                    page.insert_tuple(row, &self.attributes, xid, self.schema_version)?
+                }
+            };
+            positions.push((page.header.page_no as usize, slot_no));
         }
+        write(&mut file, &page)?;
+        file.sync_all().map_err(|e| e.to_string())?;
+
+        // pages that got new tuples are no longer all-visible
+        self.clear_visible(&touched).map_err(|e| e.to_string())?;
+
+        Ok(positions)
     }
 
     pub fn scan_all(&self, schema: &[Column]) -> Vec<(u32, usize, TupleHeader, Row)> {
@@ -123,8 +256,8 @@ impl HeapFile {
         for page_no in 0..page_count {
             let page = self.read_page(page_no);
             for slot_no in 0..page.header.slot_count {
-                if let Some((header, row)) = page.get_tuple(slot_no as usize, schema) {
-                    rows.push((page_no, slot_no as usize, header, row));
+                if let Some((header, row)) = page.get_tuple(slot_no as usize, &self.attributes) {
+                    rows.push((page_no, slot_no as usize, header, self.project(row, schema)));
                 }
             }
         }
@@ -161,6 +294,7 @@ impl HeapFile {
     
         tuple_bytes[XMAX_OFFSET..XMAX_OFFSET + 4].copy_from_slice(&xid.to_le_bytes());
     
+        self.clear_visible(&[page_no]).map_err(|e| e.to_string())?;
         self.write_page(&page);
         Ok(())
     }
@@ -172,19 +306,24 @@ impl HeapFile {
         page_no: u32,
         slot_no: usize,
         new_row: Row,
+        schema: &[Column],
         xid: u32,
     ) -> Result<(u32, usize), String> {
+        let new_row = self.to_physical(&new_row, schema);
+
         // mark old tuple as deleted for this xid
         self.delete_at(page_no, slot_no, xid)?;
     
         // insert new tuple with xmin = xid
         let mut page: Page = self.read_page(page_no);
-        if let Ok(new_slot) = page.insert_tuple(new_row.clone(), xid) {
+        if let Ok(new_slot) = page.insert_tuple(new_row.clone(), &self.attributes, xid, self.schema_version) {
             self.write_page(&page);
             return Ok((page_no, new_slot));
         } else {
             let mut new_page = self.append_page();
-            let new_slot = new_page.insert_tuple(new_row, xid).map_err(|e| e.to_string())?;
+            let new_slot = new_page
+                .insert_tuple(new_row, &self.attributes, xid, self.schema_version)
+                .map_err(|e| e.to_string())?;
             let new_page_no = new_page.header.page_no;
             self.write_page(&new_page);
             return Ok((new_page_no, new_slot));
@@ -202,11 +341,14 @@ impl HeapFile {
         let mut removed = 0;
         let metadata = std::fs::metadata(&self.path).expect("metadata failed");
         let page_count = (metadata.len() / PAGE_SIZE as u64) as u32;
+        let horizon = tm.global_xmin();
+        let mut vm = VisibilityMap::default();
     
         for page_no in 0..page_count {
             let mut page = self.read_page(page_no);
     
             let mut write_ptr: usize = PAGE_HEADER_SIZE as usize;
+            let mut all_visible = true;
     
             for slot_no in 0..page.header.slot_count as usize {
                 let slot_off = PAGE_SIZE as usize - (slot_no + 1) * ITEM_ID_SIZE;
@@ -214,7 +356,8 @@ impl HeapFile {
     
                 if !item.is_used() { continue; }
     
-                if let Some((header, row)) = page.get_tuple(slot_no, columns) {
+                if let Some((header, row)) = page.get_tuple(slot_no, &self.attributes) {
+                    let row = self.project(row, columns);
                     if header.is_dead(tm) {
                         for idx in indexes.values_mut().filter(|i| i.table == table_name) {
                             let key = build_key(&idx.columns, columns, &row.values, table_name)
@@ -228,6 +371,10 @@ impl HeapFile {
                         continue;
                     }
     
+                    if !header.is_visible_to_all(horizon, tm) {
+                        all_visible = false;
+                    }
+
                     let src_lo = item.offset as usize;
                     let src_hi = src_lo + item.len as usize;
                     let len = item.len as usize;
@@ -247,6 +394,16 @@ impl HeapFile {
             }
             page.header.free_start = write_ptr as u16;
             self.write_page(&page);
+
+            if all_visible {
+                vm.set_all_visible(page_no);
+            }
+        }
+
+        // a map that could not be written marks fewer pages, never too many:
+        // File::create truncated it first
+        if let Err(e) = self.write_visibility_map(&vm) {
+            println!("WARNING: could not write {}: {}", self.vm_path().display(), e);
         }
     
         removed
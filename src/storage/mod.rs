@@ -2,4 +2,6 @@ pub mod heap_file;
 pub mod item_id;
 pub mod page;
 pub mod page_header;
+pub mod sequence_file;
 pub mod tuple_header;
+pub mod visibility_map;
@@ -1,6 +1,9 @@
 use crate::consts::page_consts::{ITEM_ID_SIZE, PAGE_HEADER_SIZE, PAGE_SIZE};
 use crate::types::page_types::{ItemId, NullBitmap, Page, PageHeader, TupleHeader};
-use crate::types::storage_types::{Column, ColumnType, Row, Value};
+use crate::types::datetime::Interval;
+use crate::types::jsonb::Jsonb;
+use crate::types::numeric::{real_to_f64, Float, Numeric};
+use crate::types::storage_types::{Attribute, ColumnType, Row, Value};
 
 impl Page {
     pub fn new(page_no: u32) -> Self {
@@ -27,7 +30,15 @@ impl Page {
         Self { header, data: buf }
     }
 
-    pub fn insert_tuple(&mut self, row: Row, xid: u32) -> Result<usize, String> {
+    /// Append a tuple holding one value per physical attribute;
+    /// `schema_version` is stored in the header flags
+    pub fn insert_tuple(
+        &mut self,
+        row: Row,
+        attributes: &[Attribute],
+        xid: u32,
+        schema_version: u16,
+    ) -> Result<usize, String> {
         // build null bitmap
         let mut nullmap_bytes = NullBitmap::new(row.values.len());
         for (i, val) in row.values.iter().enumerate() {
@@ -40,34 +51,23 @@ impl Page {
         let header = TupleHeader {
             xmin: xid,
             xmax: None,
-            flags: 0,
+            flags: schema_version,
             nullmap_bytes,
         };
 
         // serialize tuple (header + values)
         let mut tuple_bytes = header.to_bytes();
-        for val in row.values.iter() {
-            match val {
-                Value::Int(i) => tuple_bytes.extend_from_slice(&(*i as i32).to_le_bytes()),
-                Value::Text(s) => {
-                    let bytes = s.as_bytes();
-                    let len = bytes.len() as u16;
-                    tuple_bytes.extend_from_slice(&len.to_le_bytes());
-                    tuple_bytes.extend_from_slice(bytes);
-                }
-                Value::Bool(b) => tuple_bytes.push(if *b { 1 } else { 0 }),
-                _ => {}
-            }
+        for (val, attr) in row.values.iter().zip(attributes) {
+            encode_value(val, &attr.column_type, &mut tuple_bytes)?;
         }
 
-        let tuple_len = tuple_bytes.len() as u16;
-
-        // check available free space
-        let needed_space = tuple_len as usize + ITEM_ID_SIZE;
+        // check available free space; a tuple that fits has a 2-byte length
+        let needed_space = tuple_bytes.len() + ITEM_ID_SIZE;
         let available_space = (self.header.free_end - self.header.free_start) as usize;
         if needed_space > available_space {
             return Err("Not enough space on page".into());
         }
+        let tuple_len = tuple_bytes.len() as u16;
 
         // write tuple bytes into free space
         let offset = self.header.free_start;
@@ -92,15 +92,15 @@ impl Page {
         Ok((self.header.slot_count - 1) as usize)
     }
 
-    pub fn get_tuple(&self, slot_no: usize, columns: &[Column]) -> Option<(TupleHeader, Row)> {
+    /// Decode a tuple into one value per physical attribute (dropped ones included)
+    pub fn get_tuple(&self, slot_no: usize, attributes: &[Attribute]) -> Option<(TupleHeader, Row)> {
         // out of bounds
         if slot_no as u16 >= self.header.slot_count {
             return None;
         }
 
         // find item id in slot array
-        let slot_offset = self.header.free_end as usize + slot_no * ITEM_ID_SIZE;
-        if slot_offset + ITEM_ID_SIZE > PAGE_SIZE { return None; }
+        let slot_offset = PAGE_SIZE - (slot_no + 1) * ITEM_ID_SIZE;
         let item_bytes = &self.data[slot_offset..slot_offset + ITEM_ID_SIZE];
         let item = ItemId::from_bytes(item_bytes);
 
@@ -130,35 +130,19 @@ impl Page {
             bytes: nullmap_bytes,
         };
 
-        // parse values
+        // parse values; attributes added after this tuple was written are not stored
         let mut values = Vec::new();
         let mut cursor = flags_offset + 2;
-        for (i, col) in columns.iter().enumerate() {
+        for (i, attr) in attributes.iter().enumerate() {
+            if attr.added_in > flags {
+                values.push(attr.missing.clone());
+                continue;
+            }
             if nullmap.is_null(i) {
                 values.push(Value::Null);
                 continue;
             }
-            match col.column_type {
-                ColumnType::Int => {
-                    let v = i32::from_le_bytes(tuple_bytes[cursor..cursor + 4].try_into().unwrap());
-                    values.push(Value::Int(v as i64));
-                    cursor += 4;
-                }
-                ColumnType::Text => {
-                    let len =
-                        u16::from_le_bytes(tuple_bytes[cursor..cursor + 2].try_into().unwrap())
-                            as usize;
-                    cursor += 2;
-                    let s = String::from_utf8(tuple_bytes[cursor..cursor + len].to_vec()).unwrap();
-                    values.push(Value::Text(s));
-                    cursor += len;
-                }
-                ColumnType::Bool => {
-                    let v = tuple_bytes[cursor] != 0;
-                    values.push(Value::Bool(v));
-                    cursor += 1;
-                }
-            }
+            values.push(decode_value(tuple_bytes, &mut cursor, &attr.column_type));
         }
 
         let header = TupleHeader {
@@ -171,3 +155,146 @@ impl Page {
         Some((header, Row { values }))
     }
 }
+
+/// Append the stored form of a non-NULL value of an attribute of `column_type`
+fn encode_value(val: &Value, column_type: &ColumnType, out: &mut Vec<u8>) -> Result<(), String> {
+    match val {
+        // integers take the width of their attribute: 2, 4 or 8 bytes
+        Value::Int(i) => {
+            let out_of_range = || format!("{} out of range: {}", column_type, i);
+            match column_type {
+                ColumnType::SmallInt => {
+                    out.extend_from_slice(&i16::try_from(*i).map_err(|_| out_of_range())?.to_le_bytes())
+                }
+                ColumnType::Int => out.extend_from_slice(&i32::try_from(*i).map_err(|_| out_of_range())?.to_le_bytes()),
+                ColumnType::BigInt => out.extend_from_slice(&i.to_le_bytes()),
+                _ => return Err(format!("integer value for a {} attribute", column_type)),
+            }
+        }
+        Value::Text(s) => encode_with_len(s.as_bytes(), column_type, out)?,
+        Value::Bytea(bytes) => encode_with_len(bytes, column_type, out)?,
+        // JSONB keeps its parsed binary form, so reads need no JSON parsing
+        Value::Jsonb(doc) => {
+            let mut bytes = Vec::new();
+            doc.encode(&mut bytes);
+            encode_with_len(&bytes, column_type, out)?;
+        }
+        Value::Uuid(bytes) => out.extend_from_slice(bytes),
+        // arrays nest the tuple layout: element count, a null bitmap, then the non-NULL elements
+        Value::Array(items) => {
+            let ColumnType::Array(element_type) = column_type else {
+                return Err(format!("array value for a {} attribute", column_type));
+            };
+            let mut nulls = NullBitmap::new(items.len());
+            for (i, item) in items.iter().enumerate() {
+                if let Value::Null = item {
+                    nulls.set_null(i);
+                }
+            }
+            out.extend_from_slice(&(items.len() as u16).to_le_bytes());
+            out.extend_from_slice(&nulls.bytes);
+            for item in items.iter().filter(|item| !matches!(item, Value::Null)) {
+                encode_value(item, element_type, out)?;
+            }
+        }
+        // ENUM values store the OID of their label
+        Value::Enum(ty, position) => out.extend_from_slice(&ty.labels[*position as usize].oid.to_le_bytes()),
+        Value::Bool(b) => out.push(if *b { 1 } else { 0 }),
+        Value::Float(f) => match column_type {
+            ColumnType::Real => out.extend_from_slice(&(f.0 as f32).to_le_bytes()),
+            ColumnType::Double => out.extend_from_slice(&f.0.to_le_bytes()),
+            _ => return Err(format!("float value for a {} attribute", column_type)),
+        },
+        // NUMERIC is kept exact as its decimal text
+        Value::Numeric(d) => encode_with_len(d.to_string().as_bytes(), column_type, out)?,
+        Value::Date(d) => out.extend_from_slice(&d.to_le_bytes()),
+        Value::Time(t) | Value::Timestamp(t) | Value::TimestampTz(t) => out.extend_from_slice(&t.to_le_bytes()),
+        Value::Interval(i) => {
+            out.extend_from_slice(&i.months.to_le_bytes());
+            out.extend_from_slice(&i.days.to_le_bytes());
+            out.extend_from_slice(&i.usecs.to_le_bytes());
+        }
+        Value::Null => {}
+    }
+    Ok(())
+}
+
+/// Append `bytes` after their 2-byte length; longer values cannot be stored
+fn encode_with_len(bytes: &[u8], column_type: &ColumnType, out: &mut Vec<u8>) -> Result<(), String> {
+    let len = u16::try_from(bytes.len()).map_err(|_| {
+        format!(
+            "value too long for type {}: {} bytes, at most {}",
+            column_type,
+            bytes.len(),
+            u16::MAX
+        )
+    })?;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Read the value of an attribute of `column_type` stored at `cursor` and move past it
+fn decode_value(tuple_bytes: &[u8], cursor: &mut usize, column_type: &ColumnType) -> Value {
+    let mut take = |n: usize| {
+        let bytes = &tuple_bytes[*cursor..*cursor + n];
+        *cursor += n;
+        bytes
+    };
+    match column_type {
+        ColumnType::SmallInt => Value::Int(i16::from_le_bytes(take(2).try_into().unwrap()) as i64),
+        ColumnType::Int => Value::Int(i32::from_le_bytes(take(4).try_into().unwrap()) as i64),
+        ColumnType::BigInt => Value::Int(i64::from_le_bytes(take(8).try_into().unwrap())),
+        ColumnType::Real => Value::Float(Float(real_to_f64(f32::from_le_bytes(take(4).try_into().unwrap())))),
+        ColumnType::Double => Value::Float(Float(f64::from_le_bytes(take(8).try_into().unwrap()))),
+        ColumnType::Numeric(_) => {
+            let len = u16::from_le_bytes(take(2).try_into().unwrap()) as usize;
+            let text = std::str::from_utf8(take(len)).unwrap();
+            Value::Numeric(Numeric::parse(text).expect("corrupt numeric"))
+        }
+        ColumnType::Text | ColumnType::Varchar(_) | ColumnType::Char(_) => {
+            let len = u16::from_le_bytes(take(2).try_into().unwrap()) as usize;
+            Value::Text(String::from_utf8(take(len).to_vec()).unwrap())
+        }
+        ColumnType::Bytea => {
+            let len = u16::from_le_bytes(take(2).try_into().unwrap()) as usize;
+            Value::Bytea(take(len).to_vec())
+        }
+        ColumnType::Jsonb => {
+            let len = u16::from_le_bytes(take(2).try_into().unwrap()) as usize;
+            Value::Jsonb(Jsonb::decode(take(len)).expect("corrupt jsonb"))
+        }
+        ColumnType::Uuid => Value::Uuid(take(16).try_into().unwrap()),
+        ColumnType::Array(element_type) => {
+            let count = u16::from_le_bytes(take(2).try_into().unwrap()) as usize;
+            let nulls = NullBitmap { bytes: take(count.div_ceil(8)).to_vec() };
+            let items = (0..count)
+                .map(|i| {
+                    if nulls.is_null(i) {
+                        Value::Null
+                    } else {
+                        decode_value(tuple_bytes, cursor, element_type)
+                    }
+                })
+                .collect();
+            Value::Array(items)
+        }
+        ColumnType::Enum(ty) => {
+            let oid = u32::from_le_bytes(take(4).try_into().unwrap());
+            Value::Enum(ty.clone(), ty.position_of_oid(oid).expect("unknown enum label"))
+        }
+        ColumnType::Bool => Value::Bool(take(1)[0] != 0),
+        ColumnType::Date => Value::Date(i32::from_le_bytes(take(4).try_into().unwrap())),
+        ColumnType::Time => Value::Time(i64::from_le_bytes(take(8).try_into().unwrap())),
+        ColumnType::Timestamp => Value::Timestamp(i64::from_le_bytes(take(8).try_into().unwrap())),
+        ColumnType::TimestampTz => Value::TimestampTz(i64::from_le_bytes(take(8).try_into().unwrap())),
+        ColumnType::Interval => {
+            let bytes = take(16);
+            Value::Interval(Interval {
+                months: i32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+                days: i32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+                usecs: i64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            })
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Size of a sequence file: last_value (i64, little endian) and is_called (one byte)
+const SEQUENCE_FILE_SIZE: usize = 9;
+
+/// Read a sequence's (last_value, is_called) from its file
+pub fn read_state(path: &Path) -> io::Result<(i64, bool)> {
+    let bytes = fs::read(path)?;
+    if bytes.len() != SEQUENCE_FILE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("sequence file {} has {} bytes", path.display(), bytes.len()),
+        ));
+    }
+    let last_value = i64::from_le_bytes(bytes[..8].try_into().unwrap());
+    Ok((last_value, bytes[8] != 0))
+}
+
+/// Replace a sequence's file atomically: a crash leaves either the old or the new state
+pub fn write_state(path: &Path, last_value: i64, is_called: bool) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let tmp = NamedTempFile::new_in(dir)?;
+    {
+        let mut f = tmp.as_file();
+        f.write_all(&last_value.to_le_bytes())?;
+        f.write_all(&[is_called as u8])?;
+        f.sync_all()?;
+    }
+    tmp.persist(path).map_err(|e| e.error)?;
+
+    // on unix: also sync directory metadata
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Unlink a sequence's file; one that is already gone is fine
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -45,7 +45,23 @@ impl TupleHeader {
 
     }
 
+    /// True if every current and future snapshot sees this tuple:
+    /// inserter committed before `horizon` and no live deleter.
+    pub fn is_visible_to_all(&self, horizon: u32, tm: &TransactionManager) -> bool {
+        if self.xmin >= horizon || tm.status(self.xmin) != TxStatus::Committed {
+            return false;
+        }
+        match self.xmax {
+            None => true,
+            Some(x) => tm.status(x) == TxStatus::Aborted,
+        }
+    }
+
     pub fn is_dead(&self, tm: &TransactionManager) -> bool {
+        // inserted by an aborted transaction: never visible to anyone
+        if tm.status(self.xmin) == TxStatus::Aborted {
+            return true;
+        }
         match self.xmax {
             None => false,
             Some(x) => match tm.status(x) {
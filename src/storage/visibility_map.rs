@@ -0,0 +1,41 @@
+use crate::types::page_types::VisibilityMap;
+
+impl VisibilityMap {
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    /// Byte offset and bit mask of a page's bit
+    pub fn bit(page_no: u32) -> (usize, u8) {
+        (page_no as usize / 8, 1 << (page_no % 8))
+    }
+
+    pub fn set_all_visible(&mut self, page_no: u32) {
+        // grow bitmap on demand, new pages start as not all-visible
+        let (byte, mask) = Self::bit(page_no);
+        if byte >= self.bytes.len() {
+            self.bytes.resize(byte + 1, 0);
+        }
+        self.bytes[byte] |= mask;
+    }
+
+    pub fn clear(&mut self, page_no: u32) -> bool {
+        // returns true if the bit was set before
+        let (byte, mask) = Self::bit(page_no);
+        match self.bytes.get_mut(byte) {
+            Some(b) if *b & mask != 0 => {
+                *b &= !mask;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_all_visible(&self, page_no: u32) -> bool {
+        let (byte, mask) = Self::bit(page_no);
+        self.bytes
+            .get(byte)
+            .map(|b| b & mask != 0)
+            .unwrap_or(false)
+    }
+}
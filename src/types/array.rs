@@ -0,0 +1,83 @@
+use super::storage_types::Value;
+
+/// Elements of array input text such as `{1,2,NULL}` or `{"a b",c}`; None is a NULL
+/// element. Quoted elements keep their spaces and read `\x` as `x`; unquoted ones are
+/// trimmed, and an unquoted NULL (in any case) is the NULL element.
+pub fn parse_array(text: &str) -> Option<Vec<Option<String>>> {
+    let inner = text.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let chars: Vec<char> = inner.chars().collect();
+    let mut items = Vec::new();
+    if inner.trim().is_empty() {
+        return Some(items);
+    }
+    let mut i = 0;
+    loop {
+        while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+            i += 1;
+        }
+        let item = if chars.get(i) == Some(&'"') {
+            let mut item = String::new();
+            i += 1;
+            loop {
+                match *chars.get(i)? {
+                    '"' => break,
+                    '\\' => {
+                        item.push(*chars.get(i + 1)?);
+                        i += 2;
+                    }
+                    c => {
+                        item.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+                i += 1;
+            }
+            Some(item)
+        } else {
+            let start = i;
+            while chars.get(i).is_some_and(|&c| c != ',') {
+                if matches!(chars[i], '{' | '}' | '"' | '\\') {
+                    return None; // only one dimension, special characters need quotes
+                }
+                i += 1;
+            }
+            let item: String = chars[start..i].iter().collect::<String>().trim().to_string();
+            if item.is_empty() {
+                return None;
+            }
+            (!item.eq_ignore_ascii_case("null")).then_some(item)
+        };
+        items.push(item);
+        match chars.get(i) {
+            None => return Some(items),
+            Some(',') => i += 1,
+            Some(_) => return None,
+        }
+    }
+}
+
+/// Array output text: `{1,2,NULL}`, with elements quoted when they are empty, contain
+/// spaces, braces, commas, quotes or backslashes, or read as NULL
+pub fn format_array(items: &[Value]) -> String {
+    let elements: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            Value::Null => "NULL".to_string(),
+            other => {
+                let text = other.to_string();
+                let plain = !text.is_empty()
+                    && !text.eq_ignore_ascii_case("null")
+                    && !text.chars().any(|c| c.is_whitespace() || matches!(c, '{' | '}' | ',' | '"' | '\\'));
+                if plain {
+                    text
+                } else {
+                    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+                }
+            }
+        })
+        .collect();
+    format!("{{{}}}", elements.join(","))
+}
@@ -1,4 +1,4 @@
-use crate::types::storage_types::Value;
+use crate::types::storage_types::{Deferrable, Value};
 use std::{collections::BTreeMap, ops::Bound};
 
 pub type IndexKey = Vec<Value>;
@@ -8,16 +8,20 @@ pub struct BTreeIndex {
     pub name: String,
     pub table: String,
     pub columns: Vec<String>,
+    pub unique: bool, // reject duplicate non-NULL keys among live tuples
+    pub deferrable: Deferrable, // when a UNIQUE / PRIMARY KEY constraint is checked
     pub map: BTreeMap<IndexKey, Vec<(usize, usize)>>, // key -> list of (page_no, slot_no)
 }
 
 impl BTreeIndex {
     /// Create new empty index
-    pub fn new(name: String, table: String, columns: Vec<String>) -> Self {
+    pub fn new(name: String, table: String, columns: Vec<String>, unique: bool) -> Self {
         Self {
             name,
             table,
             columns,
+            unique,
+            deferrable: Deferrable::NotDeferrable,
             map: BTreeMap::new(),
         }
     }
@@ -52,15 +56,15 @@ impl BTreeIndex {
         }
     }
 
-    /// Range scan: return positions for keys in [lower, upper)
+    /// Range scan: return (key, position) pairs for keys in [lower, upper)
     pub fn search_range(
         &self,
         lower: Bound<Vec<Value>>,
         upper: Bound<Vec<Value>>,
-    ) -> Vec<(usize, usize)> {
+    ) -> Vec<(IndexKey, (usize, usize))> {
         let mut res = Vec::new();
-        for (_k, v) in self.map.range((lower, upper)) {
-            res.extend(v.clone());
+        for (k, v) in self.map.range((lower, upper)) {
+            res.extend(v.iter().map(|pos| (k.clone(), *pos)));
         }
         res
     }
@@ -0,0 +1,46 @@
+/// BYTEA input: hex format `\x0a1b...` (whitespace allowed between byte pairs) or the
+/// escape format, where `\\` is a backslash, `\ooo` an octal byte and every other
+/// character stands for its own UTF-8 bytes
+pub fn parse_bytea(text: &str) -> Option<Vec<u8>> {
+    if let Some(hex) = text.strip_prefix("\\x").or_else(|| text.strip_prefix("\\X")) {
+        let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return None;
+        }
+        return digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .collect();
+    }
+
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            out.push(bytes[i]);
+            i += 1;
+        } else if bytes.get(i + 1) == Some(&b'\\') {
+            out.push(b'\\');
+            i += 2;
+        } else {
+            let octal = bytes.get(i + 1..i + 4)?;
+            if octal[0] > b'3' || !octal.iter().all(|b| (b'0'..=b'7').contains(b)) {
+                return None;
+            }
+            out.push(octal.iter().fold(0, |acc, b| acc * 8 + (b - b'0')));
+            i += 4;
+        }
+    }
+    Some(out)
+}
+
+/// BYTEA output in hex format: `\x` followed by two lowercase digits per byte
+pub fn format_bytea(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("\\x");
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
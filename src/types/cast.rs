@@ -0,0 +1,107 @@
+use super::jsonb::Jsonb;
+use super::storage_types::{ColumnType, Value};
+
+/// Where a cast may be applied, as in PostgreSQL's pg_cast: anywhere in an expression,
+/// when a value is stored in a column (INSERT, UPDATE, COPY, DEFAULT), or only when
+/// written out with CAST(x AS type) / x::type. Each context allows the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CastContext {
+    Implicit,
+    Assignment,
+    Explicit,
+}
+
+/// Rank of a number type; casting to a higher rank widens and is implicit,
+/// casting to a lower one may lose range or precision and needs an assignment
+fn number_rank(ty: &ColumnType) -> u8 {
+    match ty {
+        ColumnType::SmallInt => 0,
+        ColumnType::Int => 1,
+        ColumnType::BigInt => 2,
+        ColumnType::Numeric(_) => 3,
+        ColumnType::Real => 4,
+        _ => 5,
+    }
+}
+
+/// The cast table: the context in which a value of type `from` may become a `to`,
+/// or None when there is no such cast. Type modifiers do not matter here; whether
+/// the value fits them is checked when it is converted.
+pub fn cast_context(from: &ColumnType, to: &ColumnType) -> Option<CastContext> {
+    use CastContext::*;
+    use ColumnType as T;
+    let context = match (from, to) {
+        (from, to) if from.is_number() && to.is_number() => {
+            if number_rank(to) >= number_rank(from) {
+                Implicit
+            } else {
+                Assignment
+            }
+        }
+        (from, to) if from.is_string() && to.is_string() => Implicit,
+        // every type has a text form
+        (_, to) if to.is_string() => Assignment,
+        // text reads as a number or boolean only when asked to
+        (from, to) if from.is_string() && (to.is_number() || *to == T::Bool) => Explicit,
+        // string literals are input for the types without literals of their own
+        (from, _) if from.is_string() => Assignment,
+        (T::Bool, T::Bool) => Implicit,
+        (T::SmallInt | T::Int | T::BigInt, T::Bool) | (T::Bool, T::SmallInt | T::Int | T::BigInt) => Explicit,
+        (T::Jsonb, to) if to.is_number() || *to == T::Bool => Explicit,
+        (T::Date, T::Timestamp | T::TimestampTz) | (T::Timestamp, T::TimestampTz) => Implicit,
+        (T::Timestamp | T::TimestampTz, T::Date | T::Time) | (T::TimestampTz, T::Timestamp) => Assignment,
+        // arrays cast element by element
+        (T::Array(from), T::Array(to)) => return cast_context(from, to),
+        (T::Enum(from), T::Enum(to)) if from.name == to.name => Implicit,
+        (T::Date, T::Date)
+        | (T::Time, T::Time)
+        | (T::Timestamp, T::Timestamp)
+        | (T::TimestampTz, T::TimestampTz)
+        | (T::Interval, T::Interval)
+        | (T::Bytea, T::Bytea)
+        | (T::Jsonb, T::Jsonb)
+        | (T::Uuid, T::Uuid) => Implicit,
+        _ => return None,
+    };
+    Some(context)
+}
+
+/// `value` converted to `to` by a cast allowed in `context`. An explicit cast to
+/// VARCHAR(n) / CHAR(n) cuts the text to n characters instead of failing.
+pub fn cast_value(value: Value, to: &ColumnType, context: CastContext) -> Result<Value, String> {
+    let Some(from) = value.column_type() else {
+        return Ok(Value::Null);
+    };
+    if cast_context(&from, to).is_none_or(|allowed| allowed > context) {
+        return Err(format!("cannot cast type {} to {}", from, to));
+    }
+
+    let value = match (value, to) {
+        (Value::Text(text), to) if to.is_number() => Value::parse_number(text.trim())
+            .ok_or_else(|| format!("invalid input syntax for type {}: \"{}\"", to, text))?,
+        (Value::Text(text), ColumnType::Bool) => match text.trim().to_ascii_lowercase().as_str() {
+            "t" | "true" | "yes" | "on" | "1" => Value::Bool(true),
+            "f" | "false" | "no" | "off" | "0" => Value::Bool(false),
+            _ => return Err(format!("invalid input syntax for type boolean: \"{}\"", text)),
+        },
+        (Value::Array(items), ColumnType::Array(element_type)) => {
+            let items = items
+                .into_iter()
+                .map(|item| cast_value(item, element_type, context))
+                .collect::<Result<_, _>>()?;
+            return Ok(Value::Array(items));
+        }
+        (Value::Int(n), ColumnType::Bool) => Value::Bool(n != 0),
+        (Value::Bool(b), _) if to.is_number() => Value::Int(b as i64),
+        (Value::Jsonb(Jsonb::Number(d)), _) if to.is_number() => Value::Numeric(d),
+        (Value::Jsonb(Jsonb::Bool(b)), ColumnType::Bool) => Value::Bool(b),
+        (Value::Jsonb(doc), _) if !to.is_string() && *to != ColumnType::Jsonb => {
+            return Err(format!("cannot cast jsonb value {} to type {}", doc, to));
+        }
+        (value, ColumnType::Varchar(Some(n)) | ColumnType::Char(n)) if context == CastContext::Explicit => {
+            Value::Text(value.to_string().chars().take(*n as usize).collect())
+        }
+        (value, _) => value,
+    };
+    to.coerce(value)
+}
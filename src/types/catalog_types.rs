@@ -1,15 +1,36 @@
-use crate::types::{storage_types::ForeignKeyConstraint, transaction_types::TxStatus};
+use crate::consts::catalog_consts::CATALOG_VERSION;
+use crate::types::parser_types::Operand;
+use crate::types::storage_types::{CheckConstraint, Deferrable, ForeignKeyConstraint};
+use crate::types::transaction_types::TxStatus;
 use serde::{Deserialize, Serialize};
 
-use super::storage_types::Value;
+use super::enum_type::EnumType;
+use super::storage_types::{Identity, Value};
 use std::collections::{BTreeMap, HashMap};
 
 /// Supported column types in catalog metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CatColumnType {
+    Int16, // smallint column
     Int32, // integer column
+    Int64, // bigint column
+    Float32, // real column
+    Float64, // double precision column
+    Numeric, // numeric column; precision and scale are in ColumnMeta::typmod
     Text,  // string column
+    Varchar, // string column of at most ColumnMeta::typmod[0] characters (no typmod: unbounded)
+    Char,    // string column of exactly ColumnMeta::typmod[0] characters
+    Bytea,   // binary string column
+    Jsonb,   // JSON document column, stored in binary form
+    Uuid,    // 16-byte UUID column
+    Array(Box<CatColumnType>), // one-dimensional array; the element's typmod is the column's
+    Enum(String),              // user-defined ENUM type, by name; see Catalog::enums
     Bool,  // boolean column
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Interval,
 }
 
 /// Metadata describing a single column
@@ -19,7 +40,22 @@ pub struct ColumnMeta {
     #[serde(rename = "type")]
     pub ty: CatColumnType, // column type
     pub not_null: bool, // whether column is NOT NULL
-    pub default: Option<Value>, // optional default value
+    pub default: Option<Operand>, // optional default expression
+    pub attnum: usize,          // physical attribute number in heap tuples
+    #[serde(default)]
+    pub typmod: Vec<u32>,       // type modifiers: NUMERIC(precision, scale), VARCHAR(n), CHAR(n)
+    #[serde(default)]
+    pub identity: Option<Identity>, // GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY
+}
+
+/// Metadata describing one physical attribute of a table's tuples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeMeta {
+    #[serde(rename = "type")]
+    pub ty: CatColumnType, // storage type
+    pub added_in: u16,     // schema version that added the attribute
+    pub missing: Value,    // value for tuples written before `added_in`
+    pub dropped: bool,     // dropped column, kept so old tuples stay readable
 }
 
 /// Metadata describing a table definition
@@ -29,8 +65,12 @@ pub struct TableMeta {
     pub file: String,                            // file path for table storage
     pub columns: Vec<ColumnMeta>,                // schema definition
     pub next_rowid: u64,                         // auto-increment row ID counter
-    pub primary_key: Option<String>,             // optional primary key
+    pub primary_key: Option<Vec<String>>,        // optional (composite) primary key
     pub foreign_keys: Vec<ForeignKeyConstraint>, // list of foreign keys
+    #[serde(default)]
+    pub checks: Vec<CheckConstraint>,            // CHECK constraints (serialized conditions)
+    pub attributes: Vec<AttributeMeta>,          // physical tuple layout
+    pub schema_version: u16,                     // version stamped on newly written tuples
 }
 
 /// Global catalog structure, persisted on disk
@@ -38,11 +78,30 @@ pub struct TableMeta {
 pub struct Catalog {
     pub version: u32,                        // catalog format version
     pub page_size: u32,                      // page size used by DB
-    pub next_table_oid: u32,                 // counter for new table IDs
+    pub next_table_oid: u32,                 // counter for new table, type, enum label and sequence IDs
     pub next_xid: u32,                       // counter for new transaction IDs
     pub transactions: HashMap<u32, TxStatus>,// transaction status map (xid -> status)
     pub indexes: HashMap<String, IndexMeta>, // defined indexes
     pub tables: BTreeMap<String, TableMeta>, // map table name → metadata
+    #[serde(default)]
+    pub enums: BTreeMap<String, EnumType>,   // user-defined ENUM types by name
+    #[serde(default)]
+    pub sequences: BTreeMap<String, SequenceMeta>, // sequences by name; values live in their files
+}
+
+/// Metadata describing a sequence; its current value is kept in `file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceMeta {
+    pub oid: u32,                           // unique sequence ID
+    pub file: String,                       // file holding last_value and is_called
+    #[serde(rename = "type")]
+    pub ty: CatColumnType,                  // Int16, Int32 or Int64
+    pub increment: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    pub start: i64,
+    pub cycle: bool,
+    pub owned_by: Option<(String, String)>, // (table, column) the sequence is dropped with
 }
 
 /// Metadata describing an index
@@ -51,17 +110,23 @@ pub struct IndexMeta {
     pub name: String,           // index name
     pub table: String,          // table name this index belongs to
     pub columns: Vec<String>,   // indexed columns
+    #[serde(default)]
+    pub unique: bool,           // UNIQUE index / constraint
+    #[serde(default)]
+    pub deferrable: Deferrable, // DEFERRABLE UNIQUE / PRIMARY KEY
 }
 
 impl Catalog {
     /// Create a new empty catalog with no tables or indexes
     pub fn empty(page_size: u32) -> Self {
         Self {
-            version: 1,
+            version: CATALOG_VERSION,
             page_size,
             next_table_oid: 1,
             next_xid: 1,
             tables: BTreeMap::new(),
+            enums: BTreeMap::new(),
+            sequences: BTreeMap::new(),
             indexes: HashMap::new(),
             transactions: HashMap::new(),
         }
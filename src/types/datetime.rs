@@ -0,0 +1,528 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+// DATE is stored as days and TIME / TIMESTAMP / TIMESTAMPTZ as microseconds, all counted
+// from 1970-01-01 00:00:00. TIMESTAMPTZ values are UTC, and UTC is also the session
+// time zone, so they print with a `+00` offset.
+
+pub const USECS_PER_SEC: i64 = 1_000_000;
+pub const USECS_PER_MINUTE: i64 = 60 * USECS_PER_SEC;
+pub const USECS_PER_HOUR: i64 = 60 * USECS_PER_MINUTE;
+pub const USECS_PER_DAY: i64 = 24 * USECS_PER_HOUR;
+
+/// Supported years; keeps every date and timestamp printable as `YYYY-MM-DD`
+const MIN_YEAR: i64 = 1;
+const MAX_YEAR: i64 = 9999;
+
+/// Days from 1970-01-01 to a date of the proleptic Gregorian calendar
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Year, month and day of a day number
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn year_in_range(days: i64) -> bool {
+    (MIN_YEAR..=MAX_YEAR).contains(&civil_from_days(days).0)
+}
+
+/// A day number, if its year is supported
+pub fn checked_date(days: i64) -> Option<i32> {
+    year_in_range(days).then(|| i32::try_from(days).ok()).flatten()
+}
+
+/// A timestamp, if its year is supported
+pub fn checked_timestamp(usecs: i64) -> Option<i64> {
+    year_in_range(usecs.div_euclid(USECS_PER_DAY)).then_some(usecs)
+}
+
+/// Current time as a TIMESTAMPTZ
+pub fn clock_timestamp() -> i64 {
+    let since_epoch = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.as_micros() as i64
+}
+
+fn parse_digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Parse an ISO date `YYYY-MM-DD`
+pub fn parse_date(s: &str) -> Option<i32> {
+    let mut parts = s.trim().split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || year.len() < 4 {
+        return None;
+    }
+    let (year, month, day) = (parse_digits(year)?, parse_digits(month)?, parse_digits(day)?);
+    if !(1..=12).contains(&month) || !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return None;
+    }
+    let month = month as u32;
+    if day < 1 || day > days_in_month(year, month) as i64 {
+        return None;
+    }
+    checked_date(days_from_civil(year, month, day as u32))
+}
+
+/// Parse `HH:MM[:SS[.ffffff]]`; `24:00:00` is allowed
+pub fn parse_time(s: &str) -> Option<i64> {
+    let mut parts = s.trim().split(':');
+    let hours = parse_digits(parts.next()?)?;
+    let minutes = parse_digits(parts.next()?)?;
+    let seconds = parts.next().map(parse_seconds).unwrap_or(Some(0))?;
+    if parts.next().is_some() || minutes > 59 || seconds >= 60 * USECS_PER_SEC {
+        return None;
+    }
+    let time = hours * USECS_PER_HOUR + minutes * USECS_PER_MINUTE + seconds;
+    (time <= USECS_PER_DAY).then_some(time)
+}
+
+/// `SS[.ffffff]` as microseconds; digits past the sixth are rounded
+fn parse_seconds(s: &str) -> Option<i64> {
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    let mut usecs = parse_digits(whole)?.checked_mul(USECS_PER_SEC)?;
+    if !frac.is_empty() {
+        parse_digits(frac)?;
+        let padded = format!("{:0<7}", &frac[..frac.len().min(7)]);
+        let sub: i64 = padded.parse().ok()?;
+        usecs += (sub + 5) / 10;
+    }
+    Some(usecs)
+}
+
+/// Parse a zone suffix: `Z`, `UTC`, `+HH`, `+HH:MM` or `+HHMM`; returns its offset in microseconds
+fn parse_zone(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") || s.eq_ignore_ascii_case("gmt") {
+        return Some(0);
+    }
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let body = &s[1..];
+    let (hours, minutes) = match body.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if body.len() == 4 => body.split_at(2),
+        None => (body, "0"),
+    };
+    let (hours, minutes) = (parse_digits(hours)?, parse_digits(minutes)?);
+    if hours > 15 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * USECS_PER_HOUR + minutes * USECS_PER_MINUTE))
+}
+
+/// Parse `YYYY-MM-DD[( |T)HH:MM[:SS[.ffffff]]][zone]`. A zone is applied for TIMESTAMPTZ
+/// (input without one is in the session zone, UTC) and ignored for TIMESTAMP.
+pub fn parse_timestamp(s: &str, with_zone: bool) -> Option<i64> {
+    let s = s.trim();
+    let (date, rest) = match s.find([' ', 'T', 't']) {
+        Some(i) => (&s[..i], s[i + 1..].trim_start()),
+        None => (s, ""),
+    };
+    let days = parse_date(date)? as i64;
+
+    // the time ends where a zone starts; a '-' inside the time part cannot occur
+    let zone_start = rest.find(['+', '-', 'Z', 'z', ' ']).unwrap_or(rest.len());
+    let (time, zone) = rest.split_at(zone_start);
+    let time = if time.is_empty() { 0 } else { parse_time(time)? };
+    let offset = if zone.trim().is_empty() { 0 } else { parse_zone(zone)? };
+
+    let local = days * USECS_PER_DAY + time;
+    checked_timestamp(if with_zone { local - offset } else { local })
+}
+
+pub fn format_date(days: i32) -> String {
+    let (year, month, day) = civil_from_days(days as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// `HH:MM:SS`, with as many fraction digits as needed
+pub fn format_time(usecs: i64) -> String {
+    let (hours, rest) = (usecs / USECS_PER_HOUR, usecs % USECS_PER_HOUR);
+    let (minutes, rest) = (rest / USECS_PER_MINUTE, rest % USECS_PER_MINUTE);
+    let (seconds, fraction) = (rest / USECS_PER_SEC, rest % USECS_PER_SEC);
+    let mut out = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+    if fraction != 0 {
+        out.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+    }
+    out
+}
+
+pub fn format_timestamp(usecs: i64) -> String {
+    let days = usecs.div_euclid(USECS_PER_DAY);
+    format!("{} {}", format_date(days as i32), format_time(usecs.rem_euclid(USECS_PER_DAY)))
+}
+
+pub fn format_timestamptz(usecs: i64) -> String {
+    format!("{}+00", format_timestamp(usecs))
+}
+
+/// INTERVAL: months, days and microseconds are kept apart because their lengths vary
+/// (a month is 28 to 31 days, a day 23 to 25 hours across DST changes). Intervals are
+/// compared like PostgreSQL does, counting a month as 30 days: `1 mon = 30 days`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub usecs: i64,
+}
+
+impl Interval {
+    fn span(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * USECS_PER_DAY as i128 + self.usecs as i128
+    }
+
+    pub fn checked_add(&self, other: &Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            usecs: self.usecs.checked_add(other.usecs)?,
+        })
+    }
+
+    pub fn checked_neg(&self) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+            usecs: self.usecs.checked_neg()?,
+        })
+    }
+
+    /// Parse `[-]N unit ...` (with an optional `HH:MM:SS` part and `ago`), e.g.
+    /// `1 year 2 mons 3 days 04:05:06`, or ISO 8601 `P1Y2M3DT4H5M6S`
+    pub fn parse(s: &str) -> Option<Interval> {
+        let s = s.trim();
+        if let Some(iso) = s.strip_prefix(['P', 'p']) {
+            return Interval::parse_iso(iso);
+        }
+
+        let mut interval = Interval::default();
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (words, ago) = match words.split_last() {
+            Some((last, rest)) if last.eq_ignore_ascii_case("ago") => (rest, true),
+            _ => (&words[..], false),
+        };
+        if words.is_empty() {
+            return None;
+        }
+
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            if word.contains(':') {
+                let (negative, time) = match word.strip_prefix('-') {
+                    Some(t) => (true, t),
+                    None => (false, word.strip_prefix('+').unwrap_or(word)),
+                };
+                let mut parts = time.split(':');
+                let hours = parse_digits(parts.next()?)?;
+                let minutes = parse_digits(parts.next()?)?;
+                let seconds = parts.next().map(parse_seconds).unwrap_or(Some(0))?;
+                if parts.next().is_some() || minutes > 59 {
+                    return None;
+                }
+                let usecs = hours.checked_mul(USECS_PER_HOUR)? + minutes * USECS_PER_MINUTE + seconds;
+                interval.usecs = interval.usecs.checked_add(if negative { -usecs } else { usecs })?;
+                i += 1;
+                continue;
+            }
+
+            // the unit may be glued to the number (`3days`) or follow it; a bare number is seconds
+            let split = word
+                .find(|c: char| c.is_ascii_alphabetic())
+                .unwrap_or(word.len());
+            let (number, unit) = if split < word.len() {
+                word.split_at(split)
+            } else if let Some(next) = words.get(i + 1).filter(|w| w.chars().all(|c| c.is_ascii_alphabetic())) {
+                i += 1;
+                (word, *next)
+            } else {
+                (word, "seconds")
+            };
+            interval.add_unit(number, unit)?;
+            i += 1;
+        }
+
+        if ago { interval.checked_neg() } else { Some(interval) }
+    }
+
+    fn parse_iso(s: &str) -> Option<Interval> {
+        let mut interval = Interval::default();
+        let (date, time) = match s.split_once(['T', 't']) {
+            Some((d, t)) => (d, Some(t)),
+            None => (s, None),
+        };
+        let mut parse_part = |part: &str, in_time: bool| -> Option<()> {
+            let mut number = String::new();
+            for c in part.chars() {
+                if c.is_ascii_digit() || c == '.' || c == '-' {
+                    number.push(c);
+                    continue;
+                }
+                let unit = match (c.to_ascii_uppercase(), in_time) {
+                    ('Y', false) => "years",
+                    ('M', false) => "months",
+                    ('W', false) => "weeks",
+                    ('D', false) => "days",
+                    ('H', true) => "hours",
+                    ('M', true) => "minutes",
+                    ('S', true) => "seconds",
+                    _ => return None,
+                };
+                interval.add_unit(&number, unit)?;
+                number.clear();
+            }
+            number.is_empty().then_some(())
+        };
+        parse_part(date, false)?;
+        if let Some(time) = time {
+            if time.is_empty() {
+                return None;
+            }
+            parse_part(time, true)?;
+        }
+        Some(interval)
+    }
+
+    /// Add `number` (possibly fractional) of `unit`; fractions of months and days
+    /// spill into days and microseconds
+    fn add_unit(&mut self, number: &str, unit: &str) -> Option<()> {
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(d) => (true, d),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let whole = if whole.is_empty() && !frac.is_empty() { 0 } else { parse_digits(whole)? };
+        let frac: f64 = if frac.is_empty() {
+            0.0
+        } else {
+            parse_digits(frac)?;
+            format!("0.{}", frac).parse().ok()?
+        };
+        let sign = if negative { -1 } else { 1 };
+
+        let unit = unit.to_ascii_lowercase();
+        let (months, days): (i64, f64) = match unit.as_str() {
+            "microsecond" | "microseconds" | "us" | "usec" | "usecs" => return self.add_usecs(sign, whole, frac, 1),
+            "millisecond" | "milliseconds" | "ms" | "msec" | "msecs" => return self.add_usecs(sign, whole, frac, 1000),
+            "second" | "seconds" | "s" | "sec" | "secs" => return self.add_usecs(sign, whole, frac, USECS_PER_SEC),
+            "minute" | "minutes" | "m" | "min" | "mins" => return self.add_usecs(sign, whole, frac, USECS_PER_MINUTE),
+            "hour" | "hours" | "h" | "hr" | "hrs" => return self.add_usecs(sign, whole, frac, USECS_PER_HOUR),
+            "day" | "days" | "d" => (0, whole as f64 + frac),
+            "week" | "weeks" | "w" => (0, (whole as f64 + frac) * 7.0),
+            "month" | "months" | "mon" | "mons" => (whole, frac * 30.0),
+            "year" | "years" | "y" | "yr" | "yrs" => (whole * 12, frac * 12.0 * 30.0),
+            "decade" | "decades" => (whole * 120, frac * 120.0 * 30.0),
+            "century" | "centuries" => (whole * 1200, frac * 1200.0 * 30.0),
+            _ => return None,
+        };
+        let whole_days = days.trunc();
+        let usecs = (days - whole_days) * USECS_PER_DAY as f64;
+        self.months = self.months.checked_add(i32::try_from(sign * months).ok()?)?;
+        self.days = self.days.checked_add(i32::try_from(sign * whole_days as i64).ok()?)?;
+        self.usecs = self.usecs.checked_add(sign * usecs.round() as i64)?;
+        Some(())
+    }
+
+    fn add_usecs(&mut self, sign: i64, whole: i64, frac: f64, unit: i64) -> Option<()> {
+        let usecs = whole.checked_mul(unit)?.checked_add((frac * unit as f64).round() as i64)?;
+        self.usecs = self.usecs.checked_add(sign * usecs)?;
+        Some(())
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.span().cmp(&other.span())
+    }
+}
+
+/// PostgreSQL's output style: `1 year 2 mons -3 days 04:05:06`
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (n, unit) in [(years, "year"), (months, "mon"), (self.days, "day")] {
+            if n != 0 {
+                parts.push(format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" }));
+            }
+        }
+        if self.usecs != 0 || parts.is_empty() {
+            let sign = if self.usecs < 0 { "-" } else { "" };
+            parts.push(format!("{}{}", sign, format_time(self.usecs.unsigned_abs() as i64)));
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// Timestamp plus an interval: months first (clamping the day to the month's length,
+/// so Jan 31 + 1 mon is Feb 28/29), then days, then the time part
+pub fn add_interval(usecs: i64, interval: &Interval) -> Option<i64> {
+    let mut usecs = usecs;
+    if interval.months != 0 {
+        let (days, time) = (usecs.div_euclid(USECS_PER_DAY), usecs.rem_euclid(USECS_PER_DAY));
+        let (year, month, day) = civil_from_days(days);
+        let total = year * 12 + (month as i64 - 1) + interval.months as i64;
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+        let day = day.min(days_in_month(year, month));
+        usecs = days_from_civil(year, month, day) * USECS_PER_DAY + time;
+    }
+    let usecs = usecs
+        .checked_add((interval.days as i64).checked_mul(USECS_PER_DAY)?)?
+        .checked_add(interval.usecs)?;
+    checked_timestamp(usecs)
+}
+
+/// Difference of two timestamps as whole days plus the rest (`1 day 02:00:00`)
+pub fn timestamp_diff(a: i64, b: i64) -> Option<Interval> {
+    let diff = a.checked_sub(b)?;
+    Some(Interval {
+        months: 0,
+        days: i32::try_from(diff / USECS_PER_DAY).ok()?,
+        usecs: diff % USECS_PER_DAY,
+    })
+}
+
+/// Monday = 1 ... Sunday = 7
+fn iso_day_of_week(days: i64) -> i64 {
+    (days + 3).rem_euclid(7) + 1
+}
+
+/// ISO 8601 year and week number: week 1 holds the year's first Thursday
+fn iso_week(days: i64) -> (i64, i64) {
+    let thursday = days - iso_day_of_week(days) + 4;
+    let (year, _, _) = civil_from_days(thursday);
+    (year, (thursday - days_from_civil(year, 1, 1)) / 7 + 1)
+}
+
+/// `date_trunc(field, timestamp)`: the timestamp rounded down to the start of `field`
+pub fn truncate_timestamp(field: &str, usecs: i64) -> Result<i64, String> {
+    let days = usecs.div_euclid(USECS_PER_DAY);
+    let (year, month, _) = civil_from_days(days);
+    let start_of = |unit: i64| usecs - usecs.rem_euclid(unit);
+    let start_of_year = |year: i64| days_from_civil(year, 1, 1) * USECS_PER_DAY;
+    Ok(match field {
+        "microseconds" | "microsecond" => usecs,
+        "milliseconds" | "millisecond" => start_of(1000),
+        "second" => start_of(USECS_PER_SEC),
+        "minute" => start_of(USECS_PER_MINUTE),
+        "hour" => start_of(USECS_PER_HOUR),
+        "day" => start_of(USECS_PER_DAY),
+        "week" => (days - iso_day_of_week(days) + 1) * USECS_PER_DAY,
+        "month" => days_from_civil(year, month, 1) * USECS_PER_DAY,
+        "quarter" => days_from_civil(year, (month - 1) / 3 * 3 + 1, 1) * USECS_PER_DAY,
+        "year" => start_of_year(year),
+        "decade" => start_of_year(year.div_euclid(10) * 10),
+        "century" => start_of_year((year - 1).div_euclid(100) * 100 + 1),
+        "millennium" => start_of_year((year - 1).div_euclid(1000) * 1000 + 1),
+        other => return Err(format!("unit \"{}\" not recognized for date_trunc", other)),
+    })
+}
+
+/// `extract(field from timestamp)` as a decimal: `(units, scale)` stands for
+/// `units * 10^-scale`, so seconds keep their microseconds (`6.500000`)
+pub fn extract_timestamp(field: &str, usecs: i64) -> Result<(i64, u32), String> {
+    let days = usecs.div_euclid(USECS_PER_DAY);
+    let time = usecs.rem_euclid(USECS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    Ok(match field {
+        "year" => (year, 0),
+        "month" => (month as i64, 0),
+        "day" => (day as i64, 0),
+        "dow" => (iso_day_of_week(days) % 7, 0),
+        "isodow" => (iso_day_of_week(days), 0),
+        "doy" => (days - days_from_civil(year, 1, 1) + 1, 0),
+        "week" => (iso_week(days).1, 0),
+        "isoyear" => (iso_week(days).0, 0),
+        "quarter" => ((month as i64 - 1) / 3 + 1, 0),
+        "decade" => (year.div_euclid(10), 0),
+        "century" => ((year + 99).div_euclid(100), 0),
+        "millennium" => ((year + 999).div_euclid(1000), 0),
+        "epoch" => (usecs, 6),
+        _ => return extract_time(field, time),
+    })
+}
+
+/// `extract(field from time)`; also the time-of-day fields of timestamps
+pub fn extract_time(field: &str, usecs: i64) -> Result<(i64, u32), String> {
+    Ok(match field {
+        "hour" => (usecs / USECS_PER_HOUR, 0),
+        "minute" => (usecs % USECS_PER_HOUR / USECS_PER_MINUTE, 0),
+        "second" => (usecs % USECS_PER_MINUTE, 6),
+        "milliseconds" | "millisecond" => (usecs % USECS_PER_MINUTE, 3),
+        "microseconds" | "microsecond" => (usecs % USECS_PER_MINUTE, 0),
+        "epoch" => (usecs, 6),
+        other => return Err(format!("unit \"{}\" not supported for this type", other)),
+    })
+}
+
+/// `extract(field from interval)`
+pub fn extract_interval(field: &str, interval: &Interval) -> Result<(i64, u32), String> {
+    let months = interval.months as i64;
+    Ok(match field {
+        "year" => (months / 12, 0),
+        "month" => (months % 12, 0),
+        "day" => (interval.days as i64, 0),
+        "quarter" => (months % 12 / 3 + 1, 0),
+        "decade" => (months / 120, 0),
+        "century" => (months / 1200, 0),
+        "millennium" => (months / 12000, 0),
+        "epoch" => {
+            // a year counts 365.25 days and a month 30 days
+            let span = (months / 12 * 1461 + months % 12 * 120) * USECS_PER_DAY / 4
+                + interval.days as i64 * USECS_PER_DAY
+                + interval.usecs;
+            (span, 6)
+        }
+        "hour" => (interval.usecs / USECS_PER_HOUR, 0),
+        _ => extract_time(field, interval.usecs % USECS_PER_HOUR)?,
+    })
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// One label of an ENUM type; heap tuples store its OID
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EnumLabel {
+    pub oid: u32,
+    pub label: String,
+}
+
+/// A user-defined ENUM type (CREATE TYPE name AS ENUM (...)). Labels are kept in
+/// declaration order, which is also the order values sort in.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: String,
+    pub oid: u32,
+    pub labels: Vec<EnumLabel>,
+}
+
+impl EnumType {
+    /// The type as the parser sees it: a name only, without labels. The executor
+    /// replaces it with the definition from the catalog before it is used.
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            oid: 0,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Whether this is the catalog's definition rather than a name from the parser
+    pub fn is_resolved(&self) -> bool {
+        !self.labels.is_empty()
+    }
+
+    /// Position of `label` in declaration order
+    pub fn position(&self, label: &str) -> Option<u32> {
+        self.labels.iter().position(|l| l.label == label).map(|i| i as u32)
+    }
+
+    /// Position of the label a tuple stored as `oid`
+    pub fn position_of_oid(&self, oid: u32) -> Option<u32> {
+        self.labels.iter().position(|l| l.oid == oid).map(|i| i as u32)
+    }
+
+    pub fn label(&self, position: u32) -> &str {
+        &self.labels[position as usize].label
+    }
+}
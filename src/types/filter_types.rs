@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Enumerates supported comparison operators for conditions.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum CmpOp {
     Eq,
     Ne,
@@ -9,6 +10,8 @@ pub enum CmpOp {
     Lte,
     Gt,
     Gte,
+    Contains, // JSONB @>
+    HasKey,   // JSONB ?
 }
 
 impl fmt::Display for CmpOp {
@@ -20,7 +23,35 @@ impl fmt::Display for CmpOp {
             CmpOp::Lte => "<=",
             CmpOp::Gt => ">",
             CmpOp::Gte => ">=",
+            CmpOp::Contains => "@>",
+            CmpOp::HasKey => "?",
         };
         f.write_str(s)
     }
 }
+
+/// Binary operators of value expressions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    JsonGet,     // JSONB field or array element
+    JsonGetText, // same, as text
+    Subscript,   // array element, counted from 1
+}
+
+impl fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+            ArithOp::JsonGet => "->",
+            ArithOp::JsonGetText => "->>",
+            ArithOp::Subscript => "[]",
+        })
+    }
+}
@@ -0,0 +1,428 @@
+use super::numeric::Numeric;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A JSONB document, parsed once on input. Objects are normalized like PostgreSQL's:
+/// keys are unique (the last duplicate wins) and sorted shorter first, then bytewise.
+/// Variants are declared in PostgreSQL's sort order across kinds.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Jsonb {
+    Null,
+    String(String),
+    Number(Numeric),
+    Bool(bool),
+    Array(Vec<Jsonb>),
+    Object(Vec<(String, Jsonb)>),
+}
+
+// Tags of the binary encoding kept in heap tuples
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_ARRAY: u8 = 5;
+const TAG_OBJECT: u8 = 6;
+
+impl Jsonb {
+    /// Object from key/value pairs in any order, normalized
+    pub fn object(mut pairs: Vec<(String, Jsonb)>) -> Jsonb {
+        // a stable sort keeps duplicates in input order, so the last one is kept
+        pairs.sort_by(|(a, _), (b, _)| key_order(a, b));
+        let mut out: Vec<(String, Jsonb)> = Vec::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            match out.last_mut() {
+                Some(last) if last.0 == key => last.1 = value,
+                _ => out.push((key, value)),
+            }
+        }
+        Jsonb::Object(out)
+    }
+
+    /// Parse JSON text; None if it is not a single valid JSON value
+    pub fn parse(text: &str) -> Option<Jsonb> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        (parser.pos == parser.chars.len()).then_some(value)
+    }
+
+    /// Value under `key` of an object (`->` with text)
+    pub fn field(&self, key: &str) -> Option<&Jsonb> {
+        match self {
+            Jsonb::Object(pairs) => pairs
+                .binary_search_by(|(k, _)| key_order(k, key))
+                .ok()
+                .map(|i| &pairs[i].1),
+            _ => None,
+        }
+    }
+
+    /// Element `index` of an array, counting from the end when negative (`->` with an integer)
+    pub fn element(&self, index: i64) -> Option<&Jsonb> {
+        match self {
+            Jsonb::Array(items) => {
+                let i = if index < 0 { items.len() as i64 + index } else { index };
+                usize::try_from(i).ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        }
+    }
+
+    /// Text of the value as `->>` returns it: strings without quotes, JSON null as SQL NULL
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Jsonb::Null => None,
+            Jsonb::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// `@>`: every key/value of an object and every element of an array on the right is
+    /// found on the left; scalars must be equal. A top-level array also contains a scalar.
+    pub fn contains(&self, other: &Jsonb) -> bool {
+        match (self, other) {
+            (Jsonb::Object(_), Jsonb::Object(right)) => right
+                .iter()
+                .all(|(key, wanted)| self.field(key).is_some_and(|v| v.contains_nested(wanted))),
+            (Jsonb::Array(left), Jsonb::Array(right)) => {
+                right.iter().all(|wanted| left.iter().any(|item| item.contains_nested(wanted)))
+            }
+            (Jsonb::Array(left), scalar) if !matches!(scalar, Jsonb::Object(_)) => left.contains(scalar),
+            (left, right) => left == right,
+        }
+    }
+
+    /// Containment below the top level, where an array no longer contains a bare scalar
+    fn contains_nested(&self, other: &Jsonb) -> bool {
+        match (self, other) {
+            (Jsonb::Array(_), Jsonb::Array(_)) | (Jsonb::Object(_), Jsonb::Object(_)) => self.contains(other),
+            _ => self == other,
+        }
+    }
+
+    /// `?`: `key` is a key of an object, a string element of an array or the string itself
+    pub fn has_key(&self, key: &str) -> bool {
+        match self {
+            Jsonb::Object(_) => self.field(key).is_some(),
+            Jsonb::Array(items) => items.iter().any(|item| matches!(item, Jsonb::String(s) if s == key)),
+            Jsonb::String(s) => s == key,
+            _ => false,
+        }
+    }
+
+    /// Binary form stored in heap tuples: a tag byte, then u32 lengths or counts and the payload
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let put_text = |out: &mut Vec<u8>, text: &str| {
+            out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            out.extend_from_slice(text.as_bytes());
+        };
+        match self {
+            Jsonb::Null => out.push(TAG_NULL),
+            Jsonb::Bool(b) => out.push(if *b { TAG_TRUE } else { TAG_FALSE }),
+            Jsonb::Number(n) => {
+                out.push(TAG_NUMBER);
+                put_text(out, &n.to_string());
+            }
+            Jsonb::String(s) => {
+                out.push(TAG_STRING);
+                put_text(out, s);
+            }
+            Jsonb::Array(items) => {
+                out.push(TAG_ARRAY);
+                out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                items.iter().for_each(|item| item.encode(out));
+            }
+            Jsonb::Object(pairs) => {
+                out.push(TAG_OBJECT);
+                out.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+                for (key, value) in pairs {
+                    put_text(out, key);
+                    value.encode(out);
+                }
+            }
+        }
+    }
+
+    /// Read a document written by `encode`; None if the bytes are not one
+    pub fn decode(bytes: &[u8]) -> Option<Jsonb> {
+        let mut cursor = 0;
+        let value = decode_at(bytes, &mut cursor)?;
+        (cursor == bytes.len()).then_some(value)
+    }
+}
+
+/// JSONB key order: shorter keys first, then bytewise
+fn key_order(a: &str, b: &str) -> std::cmp::Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn decode_at(bytes: &[u8], cursor: &mut usize) -> Option<Jsonb> {
+    let tag = *bytes.get(*cursor)?;
+    *cursor += 1;
+    let length = |cursor: &mut usize| -> Option<usize> {
+        let slice = bytes.get(*cursor..*cursor + 4)?;
+        *cursor += 4;
+        Some(u32::from_le_bytes(slice.try_into().ok()?) as usize)
+    };
+    let text = |cursor: &mut usize, len: usize| -> Option<String> {
+        let slice = bytes.get(*cursor..*cursor + len)?;
+        *cursor += len;
+        String::from_utf8(slice.to_vec()).ok()
+    };
+    Some(match tag {
+        TAG_NULL => Jsonb::Null,
+        TAG_FALSE => Jsonb::Bool(false),
+        TAG_TRUE => Jsonb::Bool(true),
+        TAG_NUMBER => {
+            let len = length(cursor)?;
+            Jsonb::Number(Numeric::parse(&text(cursor, len)?)?)
+        }
+        TAG_STRING => {
+            let len = length(cursor)?;
+            Jsonb::String(text(cursor, len)?)
+        }
+        TAG_ARRAY => {
+            let count = length(cursor)?;
+            let items = (0..count).map(|_| decode_at(bytes, cursor)).collect::<Option<Vec<_>>>()?;
+            Jsonb::Array(items)
+        }
+        TAG_OBJECT => {
+            let count = length(cursor)?;
+            let mut pairs = Vec::with_capacity(count.min(bytes.len()));
+            for _ in 0..count {
+                let len = length(cursor)?;
+                let key = text(cursor, len)?;
+                pairs.push((key, decode_at(bytes, cursor)?));
+            }
+            Jsonb::Object(pairs)
+        }
+        _ => return None,
+    })
+}
+
+/// Recursive descent parser over RFC 8259 JSON
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        let end = self.pos + word.len();
+        if self.chars.get(self.pos..end).is_some_and(|s| s.iter().copied().eq(word.chars())) {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Option<Jsonb> {
+        self.skip_whitespace();
+        let first = *self.chars.get(self.pos)?;
+        match first {
+            '{' => {
+                self.pos += 1;
+                let mut pairs = Vec::new();
+                if !self.eat('}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        if !self.eat(':') {
+                            return None;
+                        }
+                        pairs.push((key, self.value()?));
+                        if self.eat('}') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Jsonb::object(pairs))
+            }
+            '[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        items.push(self.value()?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Jsonb::Array(items))
+            }
+            '"' => self.string().map(Jsonb::String),
+            't' if self.keyword("true") => Some(Jsonb::Bool(true)),
+            'f' if self.keyword("false") => Some(Jsonb::Bool(false)),
+            'n' if self.keyword("null") => Some(Jsonb::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.chars.get(self.pos) != Some(&'"') {
+            return None;
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = *self.chars.get(self.pos)?;
+            self.pos += 1;
+            match c {
+                '"' => return Some(out),
+                '\\' => {
+                    let escaped = *self.chars.get(self.pos)?;
+                    self.pos += 1;
+                    out.push(match escaped {
+                        '"' | '\\' | '/' => escaped,
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.unicode_escape()?,
+                        _ => return None,
+                    });
+                }
+                c if (c as u32) < 0x20 => return None,
+                c => out.push(c),
+            }
+        }
+    }
+
+    /// `\uXXXX`, combining a surrogate pair
+    fn unicode_escape(&mut self) -> Option<char> {
+        let hex4 = |p: &mut Parser| -> Option<u32> {
+            let digits: String = p.chars.get(p.pos..p.pos + 4)?.iter().collect();
+            p.pos += 4;
+            u32::from_str_radix(&digits, 16).ok()
+        };
+        let high = hex4(self)?;
+        if (0xD800..0xDC00).contains(&high) {
+            if !self.keyword("\\u") {
+                return None;
+            }
+            let low = hex4(self)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return None;
+            }
+            return char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00));
+        }
+        char::from_u32(high)
+    }
+
+    /// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+    fn number(&mut self) -> Option<Jsonb> {
+        let start = self.pos;
+        let digits = |p: &mut Parser| {
+            let from = p.pos;
+            while p.chars.get(p.pos).is_some_and(|c| c.is_ascii_digit()) {
+                p.pos += 1;
+            }
+            p.pos - from
+        };
+        if self.chars.get(self.pos) == Some(&'-') {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        let int_digits = digits(self);
+        if int_digits == 0 || (int_digits > 1 && self.chars[int_start] == '0') {
+            return None;
+        }
+        if self.chars.get(self.pos) == Some(&'.') {
+            self.pos += 1;
+            if digits(self) == 0 {
+                return None;
+            }
+        }
+        if matches!(self.chars.get(self.pos), Some('e' | 'E')) {
+            self.pos += 1;
+            if matches!(self.chars.get(self.pos), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return None;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        Numeric::parse(&text).map(Jsonb::Number)
+    }
+}
+
+/// JSON text as PostgreSQL prints jsonb: `{"a": 1, "b": [true, null]}`
+impl fmt::Display for Jsonb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Jsonb::Null => f.write_str("null"),
+            Jsonb::Bool(b) => write!(f, "{}", b),
+            Jsonb::Number(n) => write!(f, "{}", n),
+            Jsonb::String(s) => write_string(f, s),
+            Jsonb::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Jsonb::Object(pairs) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
@@ -1,7 +1,16 @@
+pub mod array;
 pub mod b_tree;
+pub mod bytea;
+pub mod cast;
 pub mod catalog_types;
+pub mod datetime;
+pub mod enum_type;
+pub mod jsonb;
 pub mod filter_types;
+pub mod numeric;
 pub mod page_types;
 pub mod parser_types;
+pub mod sequence;
 pub mod storage_types;
 pub mod transaction_types;
+pub mod uuid;
@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// REAL / DOUBLE PRECISION value. Ordered like PostgreSQL: -0 equals 0,
+/// NaN equals NaN and sorts above every other value, so it can be an index key.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Float {}
+
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Float {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .partial_cmp(&other.0)
+            .unwrap_or_else(|| self.0.is_nan().cmp(&other.0.is_nan()))
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            v if v.is_nan() => f.write_str("NaN"),
+            v if v == f64::INFINITY => f.write_str("Infinity"),
+            v if v == f64::NEG_INFINITY => f.write_str("-Infinity"),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
+impl Float {
+    /// Parse float input: decimal or exponent notation, `NaN`, `[-]Infinity`
+    pub fn parse(s: &str) -> Option<Float> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "nan" => Some(Float(f64::NAN)),
+            "infinity" | "+infinity" | "inf" => Some(Float(f64::INFINITY)),
+            "-infinity" | "-inf" => Some(Float(f64::NEG_INFINITY)),
+            lower if lower.contains("inf") || lower.contains("nan") => None,
+            _ => s.parse().ok().map(Float),
+        }
+    }
+
+    /// The value as REAL stores it: rounded to `f32`, then widened back through its
+    /// shortest decimal form so that `0.1` stays `0.1`
+    pub fn to_real(self) -> Result<Float, String> {
+        if self.0.is_finite() && self.0.abs() > f32::MAX as f64 {
+            return Err(format!("real out of range: {}", self));
+        }
+        Ok(Float(real_to_f64(self.0 as f32)))
+    }
+}
+
+/// Widen a stored REAL without picking up binary noise digits
+pub fn real_to_f64(v: f32) -> f64 {
+    if v.is_finite() {
+        v.to_string().parse().unwrap_or(v as f64)
+    } else {
+        v as f64
+    }
+}
+
+/// Exact decimal of any size: `(-1)^negative * digits * 10^-scale`.
+/// `digits` are the coefficient's decimal digits, most significant first, without leading
+/// zeros (zero is empty). The scale is kept, so `1.50` displays as written, but it does
+/// not take part in comparisons: `1.5 = 1.50`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Numeric {
+    negative: bool,
+    digits: Vec<u8>,
+    scale: u32,
+}
+
+impl Numeric {
+    fn new(negative: bool, mut digits: Vec<u8>, scale: u32) -> Self {
+        let leading = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading);
+        Numeric {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale,
+        }
+    }
+
+    /// Parse `[+-]digits[.digits][e[+-]exp]`
+    pub fn parse(s: &str) -> Option<Numeric> {
+        let s = s.trim();
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let all_digits = |p: &str| p.bytes().all(|b| b.is_ascii_digit());
+        if int_part.len() + frac_part.len() == 0 || !all_digits(int_part) || !all_digits(frac_part) {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = int_part.bytes().chain(frac_part.bytes()).map(|b| b - b'0').collect();
+        let mut scale = frac_part.len() as i64 - exponent;
+        if scale < 0 {
+            digits.extend(std::iter::repeat_n(0, (-scale) as usize));
+            scale = 0;
+        }
+        Some(Numeric::new(negative, digits, u32::try_from(scale).ok()?))
+    }
+
+    pub fn from_i64(n: i64) -> Numeric {
+        let digits = n.unsigned_abs().to_string().bytes().map(|b| b - b'0').collect();
+        Numeric::new(n < 0, digits, 0)
+    }
+
+    /// Exact decimal form of a finite float (its shortest round-trip representation)
+    pub fn from_f64(v: f64) -> Option<Numeric> {
+        if !v.is_finite() {
+            return None;
+        }
+        Numeric::parse(&v.to_string())
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Rounded to a whole number (half away from zero), if it fits an i64
+    pub fn to_i64(&self) -> Option<i64> {
+        let rounded = self.round(0);
+        let magnitude = rounded.digits.iter().try_fold(0i128, |acc, &d| {
+            let next = acc * 10 + d as i128;
+            (next <= i64::MAX as i128 + 1).then_some(next)
+        })?;
+        i64::try_from(if rounded.negative { -magnitude } else { magnitude }).ok()
+    }
+
+    /// Number of digits before the decimal point
+    pub fn integer_digits(&self) -> usize {
+        self.digits.len().saturating_sub(self.scale as usize)
+    }
+
+    /// Round (half away from zero) or pad with zeros to exactly `scale` fraction digits
+    pub fn round(&self, scale: u32) -> Numeric {
+        let mut digits = self.digits.clone();
+        if scale >= self.scale {
+            digits.extend(std::iter::repeat_n(0, (scale - self.scale) as usize));
+            return Numeric::new(self.negative, digits, scale);
+        }
+
+        let dropped = (self.scale - scale) as usize;
+        if digits.len() < dropped {
+            return Numeric::new(false, Vec::new(), scale);
+        }
+        let keep = digits.len() - dropped;
+        let round_up = digits[keep] >= 5;
+        digits.truncate(keep);
+        if round_up {
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    digits.insert(0, 1);
+                    break;
+                }
+                i -= 1;
+                if digits[i] == 9 {
+                    digits[i] = 0;
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+        Numeric::new(self.negative, digits, scale)
+    }
+
+    /// Exact sum; the result keeps the larger scale of the two
+    pub fn plus(&self, other: &Numeric) -> Numeric {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (self.round(scale), other.round(scale));
+        if a.negative == b.negative {
+            return Numeric::new(a.negative, add_digits(&a.digits, &b.digits), scale);
+        }
+        // opposite signs: subtract the smaller magnitude from the larger
+        match a.cmp_magnitude(&b) {
+            Ordering::Less => Numeric::new(b.negative, sub_digits(&b.digits, &a.digits), scale),
+            _ => Numeric::new(a.negative, sub_digits(&a.digits, &b.digits), scale),
+        }
+    }
+
+    pub fn minus(&self, other: &Numeric) -> Numeric {
+        let negated = Numeric {
+            negative: !other.negative && !other.digits.is_empty(),
+            ..other.clone()
+        };
+        self.plus(&negated)
+    }
+
+    /// Exact product; the result's scale is the sum of both scales
+    pub fn times(&self, other: &Numeric) -> Numeric {
+        let mut product = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            for (j, &b) in other.digits.iter().enumerate() {
+                product[i + j + 1] += a as u32 * b as u32;
+            }
+        }
+        for k in (1..product.len()).rev() {
+            product[k - 1] += product[k] / 10;
+            product[k] %= 10;
+        }
+        let digits = product.into_iter().map(|d| d as u8).collect();
+        Numeric::new(self.negative != other.negative, digits, self.scale + other.scale)
+    }
+
+    /// Quotient rounded (half away from zero) to PostgreSQL's division scale
+    pub fn divide(&self, other: &Numeric) -> Result<Numeric, String> {
+        if other.digits.is_empty() {
+            return Err("division by zero".to_string());
+        }
+        let scale = self.division_scale(other);
+
+        // one digit more than `scale`, truncated; rounding that digit away is exact
+        let shift = scale as i64 + 1 - self.scale as i64 + other.scale as i64;
+        let (mut dividend, mut divisor) = (self.digits.clone(), other.digits.clone());
+        if shift >= 0 {
+            dividend.extend(std::iter::repeat_n(0, shift as usize));
+        } else {
+            divisor.extend(std::iter::repeat_n(0, (-shift) as usize));
+        }
+        let quotient = div_digits(&dividend, &divisor);
+        Ok(Numeric::new(self.negative != other.negative, quotient, scale + 1).round(scale))
+    }
+
+    /// PostgreSQL's select_div_scale: at least 16 significant digits, counted in its
+    /// base-10000 digit groups, and no fewer fraction digits than either operand has
+    fn division_scale(&self, other: &Numeric) -> u32 {
+        let (weight1, first1) = self.leading_group();
+        let (weight2, first2) = other.leading_group();
+        let mut qweight = weight1 - weight2;
+        if first1 <= first2 {
+            qweight -= 1;
+        }
+        let scale = (16 - qweight * 4).max(self.scale as i64).max(other.scale as i64);
+        scale.clamp(0, 1000) as u32
+    }
+
+    /// Weight of the most significant base-10000 group, 10000^weight, and that group's value
+    fn leading_group(&self) -> (i64, u32) {
+        if self.digits.is_empty() {
+            return (0, 0);
+        }
+        let exponent = self.digits.len() as i64 - 1 - self.scale as i64;
+        let weight = exponent.div_euclid(4);
+        let width = (exponent - weight * 4 + 1) as usize;
+        let value = (0..width).fold(0, |acc, i| acc * 10 + *self.digits.get(i).unwrap_or(&0) as u32);
+        (weight, value)
+    }
+
+    /// Compare magnitudes, aligning both to the larger scale
+    fn cmp_magnitude(&self, other: &Numeric) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        let len = |n: &Numeric| {
+            if n.digits.is_empty() {
+                0
+            } else {
+                n.digits.len() + (scale - n.scale) as usize
+            }
+        };
+        len(self).cmp(&len(other)).then_with(|| {
+            let padded = |n: &Numeric| {
+                n.digits
+                    .clone()
+                    .into_iter()
+                    .chain(std::iter::repeat_n(0, (scale - n.scale) as usize))
+            };
+            padded(self).cmp(padded(other))
+        })
+    }
+}
+
+impl PartialEq for Numeric {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Numeric {}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Numeric {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self),
+        }
+    }
+}
+
+impl fmt::Display for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = self.scale as usize;
+        let mut coefficient: String = self.digits.iter().map(|d| (b'0' + d) as char).collect();
+        if coefficient.len() <= scale {
+            coefficient = format!("{}{}", "0".repeat(scale + 1 - coefficient.len()), coefficient);
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        if scale == 0 {
+            f.write_str(&coefficient)
+        } else {
+            let (int_part, frac_part) = coefficient.split_at(coefficient.len() - scale);
+            write!(f, "{}.{}", int_part, frac_part)
+        }
+    }
+}
+
+/// Sum of two digit strings (most significant first)
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let (mut i, mut j, mut carry) = (a.len(), b.len(), 0);
+    while i > 0 || j > 0 || carry > 0 {
+        let mut d = carry;
+        if i > 0 {
+            i -= 1;
+            d += a[i];
+        }
+        if j > 0 {
+            j -= 1;
+            d += b[j];
+        }
+        out.push(d % 10);
+        carry = d / 10;
+    }
+    out.reverse();
+    out
+}
+
+/// Integer quotient of two digit strings by long division; `b` has no leading zeros
+fn div_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut quotient = Vec::with_capacity(a.len());
+    let mut rest: Vec<u8> = Vec::with_capacity(b.len() + 1);
+    for &digit in a {
+        if !(rest.is_empty() && digit == 0) {
+            rest.push(digit);
+        }
+        let mut q = 0;
+        while rest.len() > b.len() || (rest.len() == b.len() && rest.as_slice() >= b) {
+            rest = sub_digits(&rest, b);
+            let leading = rest.iter().take_while(|&&d| d == 0).count();
+            rest.drain(..leading);
+            q += 1;
+        }
+        quotient.push(q);
+    }
+    quotient
+}
+
+/// Difference of two digit strings, where `a` is at least `b`
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(a.len());
+    let (mut j, mut borrow) = (b.len(), 0i8);
+    for &digit in a.iter().rev() {
+        let mut d = digit as i8 - borrow;
+        if j > 0 {
+            j -= 1;
+            d -= b[j] as i8;
+        }
+        borrow = (d < 0) as i8;
+        out.push((d + 10 * borrow) as u8);
+    }
+    out.reverse();
+    out
+}
@@ -34,3 +34,9 @@ pub struct TupleHeader {
 pub struct NullBitmap {
     pub bytes: Vec<u8>,      // raw bitmap bytes
 }
+
+// per-table map of pages whose tuples are visible to every transaction
+#[derive(Debug, Clone, Default)]
+pub struct VisibilityMap {
+    pub bytes: Vec<u8>, // one bit per page, set = all-visible
+}
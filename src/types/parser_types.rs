@@ -1,8 +1,15 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::types::{filter_types::CmpOp, storage_types::ForeignKeyConstraint, transaction_types::IsolationLevel};
+use crate::types::{
+    filter_types::{ArithOp, CmpOp},
+    storage_types::{CheckConstraint, Deferrable, ForeignKeyConstraint},
+    transaction_types::IsolationLevel,
+};
 
-use super::storage_types::{Column, Value};
+use super::numeric::Numeric;
+use super::sequence::SequenceOptions;
+use super::storage_types::{Column, ColumnType, Value};
 
 /// Abstract Syntax Tree (AST) for parsed SQL-like queries
 #[derive(Debug)]
@@ -11,55 +18,309 @@ pub enum Query {
     Commit,
     Rollback,
     SetSessionIsolationLevel(IsolationLevel),
+    /// SET CONSTRAINTS ALL DEFERRED | IMMEDIATE
+    SetConstraints {
+        deferred: bool,
+    },
+    /// SET name = value (engine settings)
+    SetOption {
+        name: String,
+        value: String,
+    },
     Vacuum {
         table_name: String,
     },
-    /// CREATE TABLE table_name (col1 type1, col2 type2, ...)
+    /// COPY table [(cols)] FROM { 'path' | STDIN } | COPY { table [(cols)] | (SELECT ...) } TO { 'path' | STDOUT } [WITH (...)]
+    Copy {
+        source: CopySource,
+        to_file: bool,        // true for COPY ... TO, false for COPY ... FROM
+        path: Option<String>, // None for STDIN / STDOUT
+        options: CopyOptions,
+        data: Option<String>, // lines of COPY ... FROM STDIN, attached by whoever reads the input
+    },
+    /// CREATE TABLE [IF NOT EXISTS] table_name (col1 type1, col2 type2, ...)
     CreateTable {
         table_name: String,
+        if_not_exists: bool,
         columns: Vec<Column>,
-        primary_key: Option<String>,
+        primary_key: Option<Vec<String>>,
         foreign_keys: Vec<ForeignKeyConstraint>,
+        unique_constraints: Vec<Vec<String>>, // UNIQUE column sets, each backed by a unique index
+        checks: Vec<CheckConstraint>,         // named CHECK (expr) constraints
+        deferrable_keys: Vec<(Vec<String>, Deferrable)>, // PRIMARY KEY / UNIQUE column sets declared DEFERRABLE
+        sequences: Vec<(String, SequenceOptions)>, // sequences of SERIAL and identity columns, owned by them
     },
-    /// INSERT INTO table (col1, col2) VALUES (...)
+    /// INSERT INTO table (col1, col2) VALUES (...), (...) | SELECT ... [ON CONFLICT ...]
     Insert {
         table_name: String,
         column_names: Option<Vec<String>>, // Optional list of target columns; None means "all columns"
-        values: Vec<Value>,                // Values to insert (order matches columns)
+        source: InsertSource,              // Rows to insert
+        overriding: Option<Overriding>,    // OVERRIDING { SYSTEM | USER } VALUE
+        on_conflict: Option<OnConflict>,   // ON CONFLICT clause
+        returning: Option<Vec<String>>,    // RETURNING columns; "*" is represented as ["*"]
     },
     /// SELECT col1, col2 FROM table
     Select {
         from_table: FromItem,
         aliases: HashMap<String, String>, // Table aliases mapping: alias -> table name
-        column_names: Vec<String>,        // "*" is represented as ["*"]
+        column_names: Vec<String>,        // "*" is represented as ["*"]; otherwise the text of each item
+        targets: Vec<Operand>,            // Parsed select items; empty for "*"
         filter: Option<Condition>,        // Optional WHERE clause condition
     },
-    /// DELETE FROM table
+    /// DELETE FROM table [USING from_item] [WHERE ...]
     Delete {
         table_name: String,
-        filter: Option<Condition>, // Optional WHERE clause condition
+        using: Option<FromItem>,          // Other tables the WHERE clause may refer to
+        aliases: HashMap<String, String>, // Aliases of the USING tables: alias -> table name
+        filter: Option<Condition>,        // Optional WHERE clause condition
+        returning: Option<Vec<String>>, // RETURNING columns of the deleted rows
     },
-    /// UPDATE table SET col = val ...
+    /// UPDATE table SET col = val, ... [FROM from_item] [WHERE ...]
     Update {
         table_name: String,
-        column_names: Vec<String>, // Target columns to update
-        values: Vec<Value>,        // New values for those columns
-        filter: Option<Condition>, // Optional WHERE clause condition
+        column_names: Vec<String>,        // Target columns to update
+        values: Vec<Operand>,             // New values: literals or columns of the table / FROM list
+        from: Option<FromItem>,           // Other tables the values and WHERE clause may refer to
+        aliases: HashMap<String, String>, // Aliases of the FROM tables: alias -> table name
+        filter: Option<Condition>,        // Optional WHERE clause condition
+        returning: Option<Vec<String>>, // RETURNING columns of the new row versions
     },
+    /// ALTER TABLE table <action>
+    AlterTable {
+        table_name: String,
+        action: AlterAction,
+    },
+    /// DROP TABLE [IF EXISTS] name [, ...] [CASCADE | RESTRICT]
+    DropTable {
+        table_names: Vec<String>,
+        if_exists: bool,
+        cascade: bool, // also drop foreign keys of other tables that reference these
+    },
+    /// DROP INDEX [IF EXISTS] name
+    DropIndex {
+        index_name: String,
+        if_exists: bool,
+    },
+    /// TRUNCATE [TABLE] name [, ...] [CASCADE | RESTRICT]
+    Truncate {
+        table_names: Vec<String>,
+        cascade: bool, // also truncate tables that reference these through foreign keys
+    },
+    /// CREATE TYPE name AS ENUM ('label', ...)
+    CreateType {
+        type_name: String,
+        labels: Vec<String>, // in declaration order, which is their sort order
+    },
+    /// CREATE SEQUENCE [IF NOT EXISTS] name [AS type] [INCREMENT BY n] [MINVALUE n] ...
+    CreateSequence {
+        sequence_name: String,
+        if_not_exists: bool,
+        options: SequenceOptions,
+    },
+    /// DROP SEQUENCE [IF EXISTS] name [, ...] [CASCADE | RESTRICT]
+    DropSequence {
+        sequence_names: Vec<String>,
+        if_exists: bool,
+        cascade: bool, // also drop column DEFAULTs that use these
+    },
+    /// CREATE [UNIQUE] INDEX [IF NOT EXISTS] [name] ON table (col1, col2, ...)
     CreateIndex {
         index_name: String,
+        if_not_exists: bool,
         table_name: String,
         column_names: Vec<String>,
+        unique: bool,
     },
 }
 
+impl Query {
+    /// Call `f` on every operand of the statement's expressions and conditions
+    pub fn visit_operands_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        match self {
+            Query::Select {
+                from_table,
+                targets,
+                filter,
+                ..
+            } => {
+                from_table.visit_operands_mut(f);
+                targets.iter_mut().for_each(|op| op.visit_mut(f));
+                filter.iter_mut().for_each(|c| c.visit_operands_mut(f));
+            }
+            Query::Insert {
+                source, on_conflict, ..
+            } => {
+                match source {
+                    InsertSource::Values(rows) => rows.iter_mut().flatten().for_each(|op| op.visit_mut(f)),
+                    InsertSource::Select(query) => query.visit_operands_mut(f),
+                }
+                if let Some(OnConflict {
+                    action: ConflictAction::Update { values, filter, .. },
+                    ..
+                }) = on_conflict
+                {
+                    values.iter_mut().for_each(|op| op.visit_mut(f));
+                    filter.iter_mut().for_each(|c| c.visit_operands_mut(f));
+                }
+            }
+            Query::Update {
+                values, from, filter, ..
+            } => {
+                values.iter_mut().for_each(|op| op.visit_mut(f));
+                from.iter_mut().for_each(|item| item.visit_operands_mut(f));
+                filter.iter_mut().for_each(|c| c.visit_operands_mut(f));
+            }
+            Query::Delete { using, filter, .. } => {
+                using.iter_mut().for_each(|item| item.visit_operands_mut(f));
+                filter.iter_mut().for_each(|c| c.visit_operands_mut(f));
+            }
+            Query::Copy {
+                source: CopySource::Query(query),
+                ..
+            } => query.visit_operands_mut(f),
+            _ => {}
+        }
+    }
+}
+
+/// What a COPY reads from or writes to besides the file
+#[derive(Debug)]
+pub enum CopySource {
+    Table {
+        table_name: String,
+        column_names: Option<Vec<String>>, // None means all columns in table order
+    },
+    Query(Box<Query>), // COPY (SELECT ...) TO
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyFormat {
+    Text, // tab separated, backslash escapes, NULL as \N
+    Csv,
+}
+
+/// Options of COPY ... WITH (...); defaults depend on the format
 #[derive(Debug, Clone)]
+pub struct CopyOptions {
+    pub format: CopyFormat,
+    pub header: bool,   // first line holds column names
+    pub delimiter: char,
+    pub quote: char,    // CSV only
+    pub null: String,   // how NULL is spelled in the file
+}
+
+/// Where the rows of an INSERT come from
+#[derive(Debug)]
+pub enum InsertSource {
+    Values(Vec<Vec<Operand>>), // VALUES (...), (...): one Vec per row, order matches columns
+    Select(Box<Query>),      // INSERT INTO ... SELECT
+}
+
+/// OVERRIDING clause of an INSERT into identity columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overriding {
+    SystemValue, // given values are stored, also in GENERATED ALWAYS columns
+    UserValue,   // given values are replaced by the sequence's
+}
+
+/// ON CONFLICT [(cols)] DO NOTHING | DO UPDATE SET ... [WHERE ...]
+#[derive(Debug)]
+pub struct OnConflict {
+    pub target: Option<Vec<String>>, // Columns of the arbiter unique index; None means any unique index
+    pub action: ConflictAction,
+}
+
+#[derive(Debug)]
+pub enum ConflictAction {
+    Nothing,
+    /// Assignments may read the existing row (`col`, `table.col`) and the proposed row (`excluded.col`)
+    Update {
+        column_names: Vec<String>,
+        values: Vec<Operand>,
+        filter: Option<Condition>,
+    },
+}
+
+/// A single ALTER TABLE action
+#[derive(Debug)]
+pub enum AlterAction {
+    AddColumn {
+        column: Column,
+        constraints: Vec<TableConstraint>, // column-level UNIQUE / PRIMARY KEY / REFERENCES / CHECK
+    },
+    DropColumn(String),
+    RenameColumn {
+        from: String,
+        to: String,
+    },
+    RenameTable(String),
+    SetNotNull(String),
+    DropNotNull(String),
+    SetDefault {
+        column: String,
+        default: Option<Operand>, // None for DROP DEFAULT
+    },
+    AddConstraint(TableConstraint),
+    DropConstraint(String),
+}
+
+/// Constraint added to an existing table
+#[derive(Debug)]
+pub enum TableConstraint {
+    PrimaryKey {
+        name: Option<String>,
+        columns: Vec<String>,
+        deferrable: Deferrable,
+    },
+    Unique {
+        name: Option<String>,
+        columns: Vec<String>,
+        deferrable: Deferrable,
+    },
+    ForeignKey(ForeignKeyConstraint),
+    Check(CheckConstraint),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Operand {
-    Column(String), // A column reference
-    Literal(Value), // A literal constant
+    Column(String),                               // A column reference
+    Literal(Value),                               // A literal constant
+    Func(String, Vec<Operand>),                   // A function call: now(), date_trunc("day", ts)
+    Arith(ArithOp, Box<Operand>, Box<Operand>),   // a + b, a * b, doc -> "key"
+    Cast(Box<Operand>, ColumnType),               // CAST(x AS type), x::type
 }
 
-#[derive(Debug, Clone)]
+impl Operand {
+    /// Collect column names referenced by this operand
+    pub fn column_names(&self, out: &mut Vec<String>) {
+        match self {
+            Operand::Column(name) => out.push(name.clone()),
+            Operand::Literal(_) => {}
+            Operand::Func(_, args) => args.iter().for_each(|arg| arg.column_names(out)),
+            Operand::Cast(inner, _) => inner.column_names(out),
+            Operand::Arith(_, lhs, rhs) => {
+                lhs.column_names(out);
+                rhs.column_names(out);
+            }
+        }
+    }
+
+    /// Call `f` on this operand and every operand nested in it, outermost first
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        f(self);
+        match self {
+            Operand::Column(_) | Operand::Literal(_) => {}
+            Operand::Func(_, args) => args.iter_mut().for_each(|arg| arg.visit_mut(f)),
+            Operand::Cast(inner, _) => inner.visit_mut(f),
+            Operand::Arith(_, lhs, rhs) => {
+                lhs.visit_mut(f);
+                rhs.visit_mut(f);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Condition {
     Cmp(CmpOp, Operand, Operand),        // Comparison operation
     And(Box<Condition>, Box<Condition>), // Logical AND
@@ -67,13 +328,62 @@ pub enum Condition {
     Not(Box<Condition>),                 // Logical NOT
 }
 
+impl Condition {
+    /// Collect column names referenced by this condition
+    pub fn column_names(&self, out: &mut Vec<String>) {
+        match self {
+            Condition::Cmp(_, lhs, rhs) => {
+                lhs.column_names(out);
+                rhs.column_names(out);
+            }
+            Condition::And(a, b) | Condition::Or(a, b) => {
+                a.column_names(out);
+                b.column_names(out);
+            }
+            Condition::Not(x) => x.column_names(out),
+        }
+    }
+
+    /// Rename column references `from` (bare or qualified) to `to`
+    pub fn rename_column(&mut self, from: &str, to: &str) {
+        self.visit_operands_mut(&mut |op| {
+            if let Operand::Column(name) = op {
+                match name.split_once('.') {
+                    Some((qual, col)) if col == from => *name = format!("{}.{}", qual, to),
+                    None if name == from => *name = to.to_string(),
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Call `f` on every operand of the condition, nested ones included
+    pub fn visit_operands_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        match self {
+            Condition::Cmp(_, lhs, rhs) => {
+                lhs.visit_mut(f);
+                rhs.visit_mut(f);
+            }
+            Condition::And(a, b) | Condition::Or(a, b) => {
+                a.visit_operands_mut(f);
+                b.visit_operands_mut(f);
+            }
+            Condition::Not(x) => x.visit_operands_mut(f),
+        }
+    }
+}
+
 /// Token types produced by the WHERE clause tokenizer
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Ident(String), // column name or identifier
     Int(i64),      // integer literal
+    Numeric(Numeric), // decimal literal, or an integer too large for i64
     Str(String),   // string literal
     Bool(bool),    // boolean literal
+    Null,          // NULL literal
+    Typed(Value),  // typed literal such as date "2024-01-31" or interval "1 day"
+    Func(String, usize), // function name; the argument count is filled in by the RPN conversion
 
     // Comparison operators
     Eq,  // =
@@ -83,14 +393,33 @@ pub enum Token {
     Gte, // >=
     Lte, // <=
 
+    // Arithmetic operators
+    Plus,  // +
+    Minus, // -
+    Star,  // *
+    Slash, // /
+
+    // JSONB operators
+    JsonGet,     // ->
+    JsonGetText, // ->>
+    Contains,    // @>
+    HasKey,      // ?
+
+    // Array element access: arr[i], read as arr Subscript ( i )
+    Subscript,
+
+    // Type cast to the type: x::type, and CAST(x AS type) read as ( x ) ::type
+    Cast(ColumnType),
+
     // Logical operators
     And,
     Or,
     Not,
 
-    // Parentheses for grouping
+    // Parentheses for grouping and function arguments
     LParen, // (
     RParen, // )
+    Comma,  // ,
 }
 
 /// Node type used as an intermediate representation when
@@ -99,6 +428,7 @@ pub enum Token {
 pub enum Node {
     Col(String),     // column reference
     Val(Value),      // literal value
+    Expr(Operand),   // function call or arithmetic
     Cond(Condition), // fully built condition subtree
 }
 
@@ -117,4 +447,28 @@ pub enum FromItem {
         kind: JoinKind,       // Type of join
         on: Condition,        // Join condition
     },
+    /// A set-returning function such as unnest(array), one row per value
+    Function {
+        name: String,
+        args: Vec<Operand>, // constant arguments
+        alias: String,      // [AS] alias; defaults to the function name
+        column: String,     // alias(column); defaults to the alias
+    },
+    /// No FROM clause: a single row without columns
+    Empty,
+}
+
+impl FromItem {
+    /// Call `f` on every operand of the join conditions
+    pub fn visit_operands_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        match self {
+            FromItem::Join { left, right, on, .. } => {
+                left.visit_operands_mut(f);
+                right.visit_operands_mut(f);
+                on.visit_operands_mut(f);
+            }
+            FromItem::Function { args, .. } => args.iter_mut().for_each(|arg| arg.visit_mut(f)),
+            FromItem::Table(_) | FromItem::Empty => {}
+        }
+    }
 }
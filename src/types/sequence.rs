@@ -0,0 +1,208 @@
+use super::storage_types::ColumnType;
+use crate::storage::sequence_file;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Values nextval() hands out per write of the sequence file, as PostgreSQL's SEQ_LOG_VALS:
+/// the file holds the value this many calls ahead, so a crash may skip up to this many
+/// values but never hands one out twice
+pub const SEQ_LOG_VALS: i64 = 32;
+
+/// CREATE SEQUENCE options as written; unset ones take the defaults of the data type
+/// and the direction of INCREMENT
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SequenceOptions {
+    pub data_type: Option<ColumnType>, // AS smallint | integer | bigint
+    pub increment: Option<i64>,
+    pub min_value: Option<i64>,
+    pub max_value: Option<i64>,
+    pub start: Option<i64>,
+    pub cycle: bool,
+    pub owned_by: Option<(String, String)>, // (table, column) whose drop also drops the sequence
+    pub sequence_name: Option<String>,      // SEQUENCE NAME of an identity column's sequence
+}
+
+/// A sequence: its definition and its current value. Copies of a sequence share the value,
+/// so undoing a schema change never turns the sequence back.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    pub name: String,
+    pub oid: u32,
+    pub data_type: ColumnType,
+    pub increment: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    pub start: i64,
+    pub cycle: bool,
+    pub owned_by: Option<(String, String)>,
+    pub file: PathBuf,
+    state: Arc<Mutex<SequenceState>>,
+}
+
+#[derive(Debug)]
+struct SequenceState {
+    last_value: i64,
+    is_called: bool,              // whether last_value was handed out; false until the first nextval()
+    log_cnt: i64,                 // nextval() calls covered by the value in the file
+    session_value: Option<i64>,   // what currval() returns
+}
+
+impl Sequence {
+    /// Resolve `options` into a definition starting at START; the state is not written yet
+    pub fn new(name: &str, oid: u32, options: SequenceOptions, file: PathBuf) -> Result<Self, String> {
+        let data_type = options.data_type.unwrap_or(ColumnType::BigInt);
+        let (type_min, type_max) = match data_type {
+            ColumnType::SmallInt => (i16::MIN as i64, i16::MAX as i64),
+            ColumnType::Int => (i32::MIN as i64, i32::MAX as i64),
+            ColumnType::BigInt => (i64::MIN, i64::MAX),
+            _ => return Err("sequence type must be smallint, integer, or bigint".to_string()),
+        };
+        let increment = options.increment.unwrap_or(1);
+        if increment == 0 {
+            return Err("INCREMENT must not be zero".to_string());
+        }
+        let min_value = options.min_value.unwrap_or(if increment > 0 { 1 } else { type_min });
+        let max_value = options.max_value.unwrap_or(if increment > 0 { type_max } else { -1 });
+        for (what, value) in [("MINVALUE", min_value), ("MAXVALUE", max_value)] {
+            if value < type_min || value > type_max {
+                return Err(format!("{} ({}) is out of range for sequence data type {}", what, value, data_type));
+            }
+        }
+        if min_value >= max_value {
+            return Err(format!("MINVALUE ({}) must be less than MAXVALUE ({})", min_value, max_value));
+        }
+        let start = options.start.unwrap_or(if increment > 0 { min_value } else { max_value });
+        if start < min_value {
+            return Err(format!("START value ({}) cannot be less than MINVALUE ({})", start, min_value));
+        }
+        if start > max_value {
+            return Err(format!("START value ({}) cannot be greater than MAXVALUE ({})", start, max_value));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            oid,
+            data_type,
+            increment,
+            min_value,
+            max_value,
+            start,
+            cycle: options.cycle,
+            owned_by: options.owned_by,
+            file,
+            state: Arc::new(Mutex::new(SequenceState {
+                last_value: start,
+                is_called: false,
+                log_cnt: 0,
+                session_value: None,
+            })),
+        })
+    }
+
+    /// Take the state from the sequence file, as written by the last `nextval()` that
+    /// reached it; values handed out from memory after that are skipped
+    pub fn load_state(&self) -> Result<(), String> {
+        let (last_value, is_called) = sequence_file::read_state(&self.file)
+            .map_err(|e| format!("could not read sequence \"{}\": {}", self.name, e))?;
+        let mut state = self.state.lock().unwrap();
+        state.last_value = last_value;
+        state.is_called = is_called;
+        state.log_cnt = 0;
+        Ok(())
+    }
+
+    /// Write the current state to the sequence file
+    pub fn write_state(&self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        self.write(state.last_value, state.is_called)?;
+        state.log_cnt = 0;
+        Ok(())
+    }
+
+    /// Write the exact state of a sequence that handed out values the file does not
+    /// hold yet, so a clean shutdown skips none
+    pub fn flush(&self) -> Result<(), String> {
+        let has_unwritten = self.state.lock().unwrap().log_cnt > 0;
+        if has_unwritten {
+            self.write_state()?;
+        }
+        Ok(())
+    }
+
+    /// Advance the sequence and return its new value
+    pub fn nextval(&self) -> Result<i64, String> {
+        let mut state = self.state.lock().unwrap();
+        let value = if state.is_called { self.step(state.last_value)? } else { state.last_value };
+
+        if state.log_cnt > 0 {
+            state.log_cnt -= 1;
+        } else {
+            // write the value SEQ_LOG_VALS - 1 calls ahead first; stop early at a bound
+            let mut logged = value;
+            let mut covered = 0;
+            while covered < SEQ_LOG_VALS - 1 {
+                match self.step(logged) {
+                    Ok(next) => logged = next,
+                    Err(_) => break,
+                }
+                covered += 1;
+            }
+            self.write(logged, true)?;
+            state.log_cnt = covered;
+        }
+
+        state.last_value = value;
+        state.is_called = true;
+        state.session_value = Some(value);
+        Ok(value)
+    }
+
+    /// The value nextval() last returned for this sequence in this session
+    pub fn currval(&self) -> Result<i64, String> {
+        self.state
+            .lock()
+            .unwrap()
+            .session_value
+            .ok_or_else(|| format!("currval of sequence \"{}\" is not yet defined in this session", self.name))
+    }
+
+    /// Set the value; with `is_called` the next nextval() returns the one after it
+    pub fn setval(&self, value: i64, is_called: bool) -> Result<i64, String> {
+        if value < self.min_value || value > self.max_value {
+            return Err(format!(
+                "setval: value {} is out of bounds for sequence \"{}\" ({}..{})",
+                value, self.name, self.min_value, self.max_value
+            ));
+        }
+        let mut state = self.state.lock().unwrap();
+        self.write(value, is_called)?;
+        state.last_value = value;
+        state.is_called = is_called;
+        state.log_cnt = 0;
+        if is_called {
+            state.session_value = Some(value);
+        }
+        Ok(value)
+    }
+
+    /// The value after `value`, wrapping around with CYCLE
+    fn step(&self, value: i64) -> Result<i64, String> {
+        match value.checked_add(self.increment) {
+            Some(next) if next >= self.min_value && next <= self.max_value => Ok(next),
+            _ if self.cycle => Ok(if self.increment > 0 { self.min_value } else { self.max_value }),
+            _ if self.increment > 0 => Err(format!(
+                "nextval: reached maximum value of sequence \"{}\" ({})",
+                self.name, self.max_value
+            )),
+            _ => Err(format!(
+                "nextval: reached minimum value of sequence \"{}\" ({})",
+                self.name, self.min_value
+            )),
+        }
+    }
+
+    fn write(&self, last_value: i64, is_called: bool) -> Result<(), String> {
+        sequence_file::write_state(&self.file, last_value, is_called)
+            .map_err(|e| format!("could not write sequence \"{}\": {}", self.name, e))
+    }
+}
@@ -1,16 +1,31 @@
+use super::array;
+use super::bytea;
+use super::cast::{cast_context, CastContext};
 use super::catalog_types::CatColumnType;
+use super::datetime::{self, Interval, USECS_PER_DAY};
+use super::enum_type::EnumType;
+use super::jsonb::Jsonb;
+use super::numeric::{Float, Numeric};
+use super::sequence::Sequence;
+use super::uuid;
 use crate::storage::heap_file::HeapFile;
 use crate::types::b_tree::BTreeIndex;
+use crate::types::parser_types::{Condition, Operand};
 use crate::types::transaction_types::TransactionManager;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 pub struct Database {
     // Stores tables by their name
     pub tables: HashMap<String, Table>,
     pub indexes: HashMap<String, BTreeIndex>,
+    pub enums: HashMap<String, Arc<EnumType>>, // user-defined ENUM types by name
+    pub sequences: HashMap<String, Sequence>,  // sequences by name
     pub transaction_manager: TransactionManager,
+    pub transaction_time: i64, // what now() returns in the running statement, for DEFAULTs
 }
 
 impl Database {
@@ -19,7 +34,10 @@ impl Database {
         Self {
             tables: HashMap::new(),
             indexes: HashMap::new(),
+            enums: HashMap::new(),
+            sequences: HashMap::new(),
             transaction_manager: TransactionManager::new(),
+            transaction_time: 0,
         }
     }
 }
@@ -33,10 +51,12 @@ pub struct Table {
     // Stored rows in the table
     // Low-level heap file storage for rows
     pub heap: HeapFile,
-    // Optional primary key column name
-    pub primary_key: Option<String>,
+    // Optional primary key column names (composite keys keep declaration order)
+    pub primary_key: Option<Vec<String>>,
     // List of foreign key constraints defined on this table
     pub foreign_keys: Vec<ForeignKeyConstraint>,
+    // CHECK constraints evaluated on every inserted or updated row
+    pub checks: Vec<CheckConstraint>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,24 +67,387 @@ pub struct Column {
     pub column_type: ColumnType,
     // Whether the column is NOT NULL
     pub not_null: bool,
-    // Optional default value for this column
-    pub default: Option<Value>,
+    // Optional default expression, evaluated for each inserted row
+    pub default: Option<Operand>,
+    // Physical attribute number in heap tuples (stable across ALTER TABLE)
+    pub attnum: usize,
+    // GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY; the default draws from the owned sequence
+    pub identity: Option<Identity>,
+}
+
+/// How an identity column treats values given by INSERT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Identity {
+    Always,    // rejected unless OVERRIDING SYSTEM VALUE
+    ByDefault, // used instead of the sequence's next value
+}
+
+/// Physical attribute of heap tuples. Dropped columns keep their slot so older
+/// tuples stay readable; tuples written before `added_in` read `missing` instead.
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub column_type: ColumnType,
+    pub added_in: u16, // schema version that added this attribute
+    pub missing: Value, // value seen by tuples older than `added_in` (ADD COLUMN ... DEFAULT)
+    pub dropped: bool,
 }
 
 // Supported data types for columns
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ColumnType {
-    Int,
+    SmallInt, // 2-byte integer
+    Int,      // 4-byte integer
+    BigInt,   // 8-byte integer
+    Real,     // 4-byte float
+    Double,   // 8-byte float
+    Numeric(Option<(u32, u32)>), // exact decimal, optionally NUMERIC(precision, scale)
     Text,
+    Varchar(Option<u32>), // text of at most n characters
+    Char(u32),            // text of exactly n characters, padded with spaces
+    Bytea,                // binary string
+    Jsonb,                // JSON document, stored parsed
+    Uuid,                 // 16-byte universally unique identifier
+    Array(Box<ColumnType>), // one-dimensional array of the element type: INT[], TEXT[]
+    Enum(Arc<EnumType>),    // user-defined ENUM type
     Bool,
+    Date,        // days since 1970-01-01
+    Time,        // time of day in microseconds
+    Timestamp,   // microseconds since 1970-01-01 00:00:00
+    TimestampTz, // like TIMESTAMP, in UTC
+    Interval,
+}
+
+impl ColumnType {
+    /// Inclusive range of an integer type; all integer values are `Value::Int(i64)` in memory
+    pub fn int_range(&self) -> Option<(i64, i64)> {
+        match self {
+            ColumnType::SmallInt => Some((i16::MIN as i64, i16::MAX as i64)),
+            ColumnType::Int => Some((i32::MIN as i64, i32::MAX as i64)),
+            ColumnType::BigInt => Some((i64::MIN, i64::MAX)),
+            _ => None,
+        }
+    }
+
+    /// Integer, floating point or NUMERIC
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::SmallInt
+                | ColumnType::Int
+                | ColumnType::BigInt
+                | ColumnType::Real
+                | ColumnType::Double
+                | ColumnType::Numeric(_)
+        )
+    }
+
+    /// TEXT, VARCHAR or CHAR
+    pub fn is_string(&self) -> bool {
+        matches!(self, ColumnType::Text | ColumnType::Varchar(_) | ColumnType::Char(_))
+    }
+
+    /// DATE, TIME, TIMESTAMP, TIMESTAMPTZ or INTERVAL
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Date | ColumnType::Time | ColumnType::Timestamp | ColumnType::TimestampTz | ColumnType::Interval
+        )
+    }
+
+    /// Whether a value of this kind may be stored in the column: NULL fits every column,
+    /// otherwise the cast table needs an implicit or assignment cast (see `cast_context`).
+    /// Array elements are checked one by one, so an empty array fits every array column.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Null) => true,
+            (ColumnType::Array(element_type), Value::Array(items)) => {
+                items.iter().all(|item| element_type.accepts(item))
+            }
+            _ => value
+                .column_type()
+                .and_then(|from| cast_context(&from, self))
+                .is_some_and(|context| context <= CastContext::Assignment),
+        }
+    }
+
+    /// Read date/time input text as this type
+    pub fn parse_temporal(&self, text: &str) -> Result<Value, String> {
+        let value = match self {
+            ColumnType::Date => datetime::parse_date(text).map(Value::Date),
+            ColumnType::Time => datetime::parse_time(text).map(Value::Time),
+            ColumnType::Timestamp => datetime::parse_timestamp(text, false).map(Value::Timestamp),
+            ColumnType::TimestampTz => datetime::parse_timestamp(text, true).map(Value::TimestampTz),
+            ColumnType::Interval => Interval::parse(text).map(Value::Interval),
+            _ => None,
+        };
+        value.ok_or_else(|| format!("invalid input syntax for type {}: \"{}\"", self, text))
+    }
+
+    /// The value as a column of this type stores it (for a value it `accepts`): numbers
+    /// convert like an assignment cast, rounding to whole numbers (integers), to `f32`
+    /// (REAL) or to the declared scale (NUMERIC), and must fit the type's range.
+    /// Strings must fit VARCHAR(n) / CHAR(n), where only spaces may be cut off, and CHAR(n)
+    /// pads them to n characters. Array elements are coerced to the element type, and
+    /// ENUM input text must be one of the type's labels.
+    pub fn coerce(&self, value: Value) -> Result<Value, String> {
+        if let Some((min, max)) = self.int_range() {
+            let n = match value {
+                Value::Int(n) => n,
+                Value::Numeric(d) => d.to_i64().ok_or_else(|| format!("{} out of range: {}", self, d))?,
+                Value::Float(f) if f.0.is_finite() && f.0.abs() < 9.3e18 => f.0.round_ties_even() as i64,
+                Value::Float(f) => return Err(format!("{} out of range: {}", self, f)),
+                other => return Ok(other),
+            };
+            if n < min || n > max {
+                return Err(format!("{} out of range: {}", self, n));
+            }
+            return Ok(Value::Int(n));
+        }
+
+        match (self, value) {
+            (ColumnType::Real | ColumnType::Double, value) => {
+                let f = match value {
+                    Value::Int(n) => Float(n as f64),
+                    Value::Numeric(d) => Float(d.to_f64()),
+                    Value::Float(f) => f,
+                    other => return Ok(other),
+                };
+                if *self == ColumnType::Real {
+                    f.to_real().map(Value::Float)
+                } else {
+                    Ok(Value::Float(f))
+                }
+            }
+            (ColumnType::Numeric(typmod), value) => {
+                let mut d = match value {
+                    Value::Int(n) => Numeric::from_i64(n),
+                    Value::Float(f) => {
+                        Numeric::from_f64(f.0).ok_or_else(|| format!("cannot convert {} to numeric", f))?
+                    }
+                    Value::Numeric(d) => d,
+                    other => return Ok(other),
+                };
+                if let Some((precision, scale)) = *typmod {
+                    d = d.round(scale);
+                    if d.integer_digits() > (precision - scale) as usize {
+                        return Err(format!(
+                            "numeric field overflow: {} does not fit {} (at most {} digits before the decimal point)",
+                            d,
+                            self,
+                            precision - scale
+                        ));
+                    }
+                }
+                Ok(Value::Numeric(d))
+            }
+            // every value has a text form
+            (column_type, value) if column_type.is_string() && !matches!(value, Value::Text(_) | Value::Null) => {
+                self.coerce(Value::Text(value.to_string()))
+            }
+            (ColumnType::Varchar(Some(n)) | ColumnType::Char(n), Value::Text(text)) => {
+                let mut text = fit_length(text, *n, self)?;
+                if let ColumnType::Char(n) = self {
+                    let len = text.chars().count();
+                    text.extend(std::iter::repeat_n(' ', *n as usize - len));
+                }
+                Ok(Value::Text(text))
+            }
+            (ColumnType::Bytea, Value::Text(text)) => bytea::parse_bytea(&text)
+                .map(Value::Bytea)
+                .ok_or_else(|| format!("invalid input syntax for type bytea: \"{}\"", text)),
+            (ColumnType::Jsonb, Value::Text(text)) => Jsonb::parse(&text)
+                .map(Value::Jsonb)
+                .ok_or_else(|| format!("invalid input syntax for type jsonb: \"{}\"", text)),
+            (ColumnType::Uuid, Value::Text(text)) => uuid::parse_uuid(&text)
+                .map(Value::Uuid)
+                .ok_or_else(|| format!("invalid input syntax for type uuid: \"{}\"", text)),
+            (ColumnType::Array(element_type), Value::Array(items)) => items
+                .into_iter()
+                .map(|item| {
+                    if !element_type.accepts(&item) {
+                        return Err(format!("array element {} does not match type {}", item, element_type));
+                    }
+                    element_type.coerce(item)
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            (ColumnType::Array(element_type), Value::Text(text)) => {
+                let invalid = || format!("malformed array literal: \"{}\"", text);
+                let items = array::parse_array(&text).ok_or_else(invalid)?;
+                items
+                    .into_iter()
+                    .map(|item| match item {
+                        None => Ok(Value::Null),
+                        Some(item) => element_type.parse_element(item),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Value::Array)
+            }
+            // a type only named by the parser is checked once the executor has looked it up
+            (ColumnType::Enum(ty), Value::Text(text)) if !ty.is_resolved() => Ok(Value::Text(text)),
+            (ColumnType::Enum(ty), Value::Text(text)) => ty
+                .position(&text)
+                .map(|position| Value::Enum(ty.clone(), position))
+                .ok_or_else(|| format!("invalid input value for enum {}: \"{}\"", ty.name, text)),
+            (column_type, Value::Text(text)) if column_type.is_temporal() => column_type.parse_temporal(&text),
+            // dates are midnight; timestamps are read as UTC and lose their time as dates
+            (ColumnType::Date, Value::Timestamp(t) | Value::TimestampTz(t)) => {
+                Ok(Value::Date(t.div_euclid(USECS_PER_DAY) as i32))
+            }
+            (ColumnType::Timestamp, Value::Date(d)) => Ok(Value::Timestamp(d as i64 * USECS_PER_DAY)),
+            (ColumnType::Timestamp, Value::TimestampTz(t)) => Ok(Value::Timestamp(t)),
+            (ColumnType::TimestampTz, Value::Date(d)) => Ok(Value::TimestampTz(d as i64 * USECS_PER_DAY)),
+            (ColumnType::TimestampTz, Value::Timestamp(t)) => Ok(Value::TimestampTz(t)),
+            (ColumnType::Time, Value::Timestamp(t) | Value::TimestampTz(t)) => Ok(Value::Time(t.rem_euclid(USECS_PER_DAY))),
+            (_, value) => Ok(value),
+        }
+    }
+
+    /// Array element input text as a value of this type
+    fn parse_element(&self, text: String) -> Result<Value, String> {
+        let value = if self.is_number() {
+            Value::parse_number(text.trim())
+                .ok_or_else(|| format!("invalid input syntax for type {}: \"{}\"", self, text))?
+        } else if *self == ColumnType::Bool {
+            match text.trim().to_ascii_lowercase().as_str() {
+                "t" | "true" => Value::Bool(true),
+                "f" | "false" => Value::Bool(false),
+                _ => return Err(format!("invalid input syntax for type boolean: \"{}\"", text)),
+            }
+        } else {
+            Value::Text(text)
+        };
+        self.coerce(value)
+    }
+
+    /// A DEFAULT must have the column's type and fit it; returns the value as stored
+    pub fn check_default_value(&self, column: &str, value: Value) -> Result<Value, String> {
+        if !self.accepts(&value) {
+            return Err(format!("DEFAULT {} does not match type {} of column '{}'", value, self, column));
+        }
+        self.coerce(value)
+            .map_err(|e| format!("DEFAULT for column '{}': {}", column, e))
+    }
+
+    /// A constant DEFAULT is checked and stored coerced; an expression is evaluated per row
+    /// and may not refer to columns
+    pub fn check_default(&self, column: &str, default: Operand) -> Result<Operand, String> {
+        match default {
+            Operand::Literal(value) => self.check_default_value(column, value).map(Operand::Literal),
+            expr => {
+                let mut refs = Vec::new();
+                expr.column_names(&mut refs);
+                if let Some(name) = refs.first() {
+                    return Err(format!("cannot use column reference '{}' in DEFAULT of column '{}'", name, column));
+                }
+                Ok(expr)
+            }
+        }
+    }
+}
+
+/// `text` cut to `n` characters, which may only drop trailing spaces
+fn fit_length(text: String, n: u32, column_type: &ColumnType) -> Result<String, String> {
+    match text.char_indices().nth(n as usize) {
+        None => Ok(text),
+        Some((end, _)) if text[end..].trim_start_matches(' ').is_empty() => Ok(text[..end].to_string()),
+        Some(_) => Err(format!("value too long for type {}", column_type)),
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColumnType::SmallInt => "smallint",
+            ColumnType::Int => "integer",
+            ColumnType::BigInt => "bigint",
+            ColumnType::Real => "real",
+            ColumnType::Double => "double precision",
+            ColumnType::Numeric(None) => "numeric",
+            ColumnType::Numeric(Some((p, s))) => return write!(f, "numeric({}, {})", p, s),
+            ColumnType::Text => "text",
+            ColumnType::Varchar(None) => "character varying",
+            ColumnType::Varchar(Some(n)) => return write!(f, "character varying({})", n),
+            ColumnType::Char(n) => return write!(f, "character({})", n),
+            ColumnType::Bytea => "bytea",
+            ColumnType::Jsonb => "jsonb",
+            ColumnType::Uuid => "uuid",
+            ColumnType::Array(element_type) => return write!(f, "{}[]", element_type),
+            ColumnType::Enum(ty) => &ty.name,
+            ColumnType::Bool => "bool",
+            ColumnType::Date => "date",
+            ColumnType::Time => "time",
+            ColumnType::Timestamp => "timestamp",
+            ColumnType::TimestampTz => "timestamptz",
+            ColumnType::Interval => "interval",
+        })
+    }
+}
+
+impl ColumnType {
+    /// Column type from its catalog entry and type modifiers
+    pub fn from_catalog(ty: &CatColumnType, typmod: &[u32]) -> ColumnType {
+        ColumnType::from(ty.clone()).with_typmod(typmod)
+    }
+
+    /// This type with its type modifiers applied (to the elements of an array type)
+    fn with_typmod(self, typmod: &[u32]) -> ColumnType {
+        match (self, typmod) {
+            (ColumnType::Numeric(_), &[precision, scale]) => ColumnType::Numeric(Some((precision, scale))),
+            (ColumnType::Varchar(_), &[n]) => ColumnType::Varchar(Some(n)),
+            (ColumnType::Char(_), &[n]) => ColumnType::Char(n),
+            (ColumnType::Array(element_type), typmod) => ColumnType::Array(Box::new(element_type.with_typmod(typmod))),
+            (column_type, _) => column_type,
+        }
+    }
+
+    /// This type with ENUM types named by the parser or the catalog replaced by their
+    /// definitions (also as array elements)
+    pub fn resolve(&self, enums: &HashMap<String, Arc<EnumType>>) -> Result<ColumnType, String> {
+        match self {
+            ColumnType::Enum(ty) => enums
+                .get(&ty.name)
+                .map(|ty| ColumnType::Enum(ty.clone()))
+                .ok_or_else(|| format!("type \"{}\" does not exist", ty.name)),
+            ColumnType::Array(element_type) => Ok(ColumnType::Array(Box::new(element_type.resolve(enums)?))),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Type modifiers kept in the catalog next to the type
+    pub fn typmod(&self) -> Vec<u32> {
+        match self {
+            ColumnType::Numeric(Some((precision, scale))) => vec![*precision, *scale],
+            ColumnType::Varchar(Some(n)) | ColumnType::Char(n) => vec![*n],
+            ColumnType::Array(element_type) => element_type.typmod(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl From<CatColumnType> for ColumnType {
     fn from(c: CatColumnType) -> Self {
         match c {
+            CatColumnType::Int16 => ColumnType::SmallInt,
             CatColumnType::Int32 => ColumnType::Int,
+            CatColumnType::Int64 => ColumnType::BigInt,
+            CatColumnType::Float32 => ColumnType::Real,
+            CatColumnType::Float64 => ColumnType::Double,
+            CatColumnType::Numeric => ColumnType::Numeric(None),
             CatColumnType::Text => ColumnType::Text,
+            CatColumnType::Varchar => ColumnType::Varchar(None),
+            CatColumnType::Char => ColumnType::Char(1),
+            CatColumnType::Bytea => ColumnType::Bytea,
+            CatColumnType::Jsonb => ColumnType::Jsonb,
+            CatColumnType::Uuid => ColumnType::Uuid,
+            CatColumnType::Array(element_type) => ColumnType::Array(Box::new(ColumnType::from(*element_type))),
+            CatColumnType::Enum(name) => ColumnType::Enum(Arc::new(EnumType::named(&name))),
             CatColumnType::Bool => ColumnType::Bool,
+            CatColumnType::Date => ColumnType::Date,
+            CatColumnType::Time => ColumnType::Time,
+            CatColumnType::Timestamp => ColumnType::Timestamp,
+            CatColumnType::TimestampTz => ColumnType::TimestampTz,
+            CatColumnType::Interval => ColumnType::Interval,
         }
     }
 }
@@ -81,6 +464,18 @@ pub enum Value {
     Int(i64),
     Text(String),
     Bool(bool),
+    Float(Float),     // REAL and DOUBLE PRECISION
+    Numeric(Numeric), // exact decimal
+    Date(i32),          // days since 1970-01-01
+    Time(i64),          // microseconds since midnight
+    Timestamp(i64),     // microseconds since 1970-01-01 00:00:00
+    TimestampTz(i64),   // same, in UTC
+    Interval(Interval),
+    Bytea(Vec<u8>),
+    Jsonb(Jsonb),
+    Uuid([u8; 16]),
+    Array(Vec<Value>), // one-dimensional; elements may be NULL
+    Enum(Arc<EnumType>, u32), // label position in its type's declaration order
     Null, // Equivalent to SQL NULL
 }
 
@@ -91,6 +486,18 @@ impl fmt::Display for Value {
             Value::Int(i) => write!(f, "{}", i),
             Value::Text(s) => write!(f, "{}", s),
             Value::Bool(b) => write!(f, "{}", b),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Numeric(d) => write!(f, "{}", d),
+            Value::Date(d) => f.write_str(&datetime::format_date(*d)),
+            Value::Time(t) => f.write_str(&datetime::format_time(*t)),
+            Value::Timestamp(t) => f.write_str(&datetime::format_timestamp(*t)),
+            Value::TimestampTz(t) => f.write_str(&datetime::format_timestamptz(*t)),
+            Value::Interval(i) => write!(f, "{}", i),
+            Value::Bytea(b) => f.write_str(&bytea::format_bytea(b)),
+            Value::Jsonb(j) => write!(f, "{}", j),
+            Value::Uuid(u) => f.write_str(&uuid::format_uuid(u)),
+            Value::Array(items) => f.write_str(&array::format_array(items)),
+            Value::Enum(ty, position) => f.write_str(ty.label(*position)),
             Value::Null => write!(f, "NULL"),
         }
     }
@@ -102,6 +509,18 @@ pub enum ValueType {
     Int,
     Text,
     Bool,
+    Float,
+    Numeric,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Interval,
+    Bytea,
+    Jsonb,
+    Uuid,
+    Array,
+    Enum,
     Null,
 }
 
@@ -111,18 +530,118 @@ impl fmt::Display for ValueType {
             ValueType::Int => "INT",
             ValueType::Text => "TEXT",
             ValueType::Bool => "BOOL",
+            ValueType::Float => "FLOAT",
+            ValueType::Numeric => "NUMERIC",
+            ValueType::Date => "DATE",
+            ValueType::Time => "TIME",
+            ValueType::Timestamp => "TIMESTAMP",
+            ValueType::TimestampTz => "TIMESTAMPTZ",
+            ValueType::Interval => "INTERVAL",
+            ValueType::Bytea => "BYTEA",
+            ValueType::Jsonb => "JSONB",
+            ValueType::Uuid => "UUID",
+            ValueType::Array => "ARRAY",
+            ValueType::Enum => "ENUM",
             ValueType::Null => "NULL",
         })
     }
 }
 
 impl Value {
+    /// Number literal: an integer when it has no fraction and fits i64, otherwise an exact NUMERIC
+    pub fn parse_number(raw: &str) -> Option<Value> {
+        if let Ok(n) = raw.parse::<i64>() {
+            return Some(Value::Int(n));
+        }
+        Numeric::parse(raw).map(Value::Numeric)
+    }
+
+    /// Order of two numbers of any number type: as floats when either is one,
+    /// otherwise exactly. None unless both are numbers.
+    pub fn cmp_number(&self, other: &Value) -> Option<Ordering> {
+        let float = |v: &Value| match v {
+            Value::Int(n) => Some(*n as f64),
+            Value::Float(f) => Some(f.0),
+            Value::Numeric(d) => Some(d.to_f64()),
+            _ => None,
+        };
+        let exact = |v: &Value| match v {
+            Value::Int(n) => Some(Numeric::from_i64(*n)),
+            Value::Numeric(d) => Some(d.clone()),
+            _ => None,
+        };
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(_), _) | (_, Value::Float(_)) => Some(Float(float(self)?).cmp(&Float(float(other)?))),
+            _ => Some(exact(self)?.cmp(&exact(other)?)),
+        }
+    }
+
+    /// Order of two date/time values of the same kind; dates, timestamps and
+    /// timestamptz values also compare with each other (dates as midnight, UTC)
+    pub fn cmp_temporal(&self, other: &Value) -> Option<Ordering> {
+        let instant = |v: &Value| match v {
+            Value::Date(d) => Some(*d as i64 * USECS_PER_DAY),
+            Value::Timestamp(t) | Value::TimestampTz(t) => Some(*t),
+            _ => None,
+        };
+        match (self, other) {
+            (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+            (Value::Interval(a), Value::Interval(b)) => Some(a.cmp(b)),
+            _ => Some(instant(self)?.cmp(&instant(other)?)),
+        }
+    }
+
+    /// Column type of a date/time value; None for every other value
+    pub fn temporal_type(&self) -> Option<ColumnType> {
+        match self {
+            Value::Date(_) => Some(ColumnType::Date),
+            Value::Time(_) => Some(ColumnType::Time),
+            Value::Timestamp(_) => Some(ColumnType::Timestamp),
+            Value::TimestampTz(_) => Some(ColumnType::TimestampTz),
+            Value::Interval(_) => Some(ColumnType::Interval),
+            _ => None,
+        }
+    }
+
+    /// Column type that holds this value unchanged; None for NULL
+    pub fn column_type(&self) -> Option<ColumnType> {
+        Some(match self {
+            Value::Int(_) => ColumnType::BigInt,
+            Value::Text(_) => ColumnType::Text,
+            Value::Bool(_) => ColumnType::Bool,
+            Value::Float(_) => ColumnType::Double,
+            Value::Numeric(_) => ColumnType::Numeric(None),
+            Value::Bytea(_) => ColumnType::Bytea,
+            Value::Jsonb(_) => ColumnType::Jsonb,
+            Value::Uuid(_) => ColumnType::Uuid,
+            Value::Array(items) => {
+                ColumnType::Array(Box::new(items.iter().find_map(Value::column_type).unwrap_or(ColumnType::Text)))
+            }
+            Value::Enum(ty, _) => ColumnType::Enum(ty.clone()),
+            Value::Null => return None,
+            temporal => return temporal.temporal_type(),
+        })
+    }
+
     /// Returns the `ValueType` corresponding to this `Value` variant.
     pub fn vtype(&self) -> ValueType {
         match self {
             Value::Int(_) => ValueType::Int,
             Value::Text(_) => ValueType::Text,
             Value::Bool(_) => ValueType::Bool,
+            Value::Float(_) => ValueType::Float,
+            Value::Numeric(_) => ValueType::Numeric,
+            Value::Date(_) => ValueType::Date,
+            Value::Time(_) => ValueType::Time,
+            Value::Timestamp(_) => ValueType::Timestamp,
+            Value::TimestampTz(_) => ValueType::TimestampTz,
+            Value::Interval(_) => ValueType::Interval,
+            Value::Bytea(_) => ValueType::Bytea,
+            Value::Jsonb(_) => ValueType::Jsonb,
+            Value::Uuid(_) => ValueType::Uuid,
+            Value::Array(_) => ValueType::Array,
+            Value::Enum(..) => ValueType::Enum,
             Value::Null => ValueType::Null,
         }
     }
@@ -136,4 +655,55 @@ pub struct ForeignKeyConstraint {
     pub referenced_table: String,
     // Columns in the referenced table that are targeted
     pub referenced_columns: Vec<String>,
+    // Action taken on referencing rows when the parent row is deleted
+    #[serde(default)]
+    pub on_delete: FkAction,
+    // Action taken on referencing rows when the parent key is updated
+    #[serde(default)]
+    pub on_update: FkAction,
+    // Whether the check may be postponed until COMMIT
+    #[serde(default)]
+    pub deferrable: Deferrable,
+    // Name given with CONSTRAINT name; None uses the default `<table>_<cols>_fkey`
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// When a FOREIGN KEY, UNIQUE or PRIMARY KEY constraint is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Deferrable {
+    #[default]
+    NotDeferrable,      // checked immediately, always
+    InitiallyImmediate, // DEFERRABLE: immediate until SET CONSTRAINTS ALL DEFERRED
+    InitiallyDeferred,  // DEFERRABLE INITIALLY DEFERRED: checked at COMMIT
+}
+
+/// Named CHECK (expr) constraint; the row passes unless the expression is false
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub condition: Condition,
+}
+
+/// Referential action of a foreign key (ON DELETE / ON UPDATE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FkAction {
+    #[default]
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl fmt::Display for FkAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FkAction::NoAction => "NO ACTION",
+            FkAction::Restrict => "RESTRICT",
+            FkAction::Cascade => "CASCADE",
+            FkAction::SetNull => "SET NULL",
+            FkAction::SetDefault => "SET DEFAULT",
+        })
+    }
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
+use super::storage_types::{Deferrable, ForeignKeyConstraint, Value};
+
 /// Transaction status: in-progress, committed, or aborted
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TxStatus {
@@ -22,11 +24,39 @@ pub struct Snapshot {
     pub active_xids: Vec<u32>
 }
 
+/// Constraint whose check on a new row version was postponed
+#[derive(Debug, Clone)]
+pub enum DeferredConstraint {
+    Unique(String), // unique index name
+    ForeignKey(ForeignKeyConstraint),
+}
+
+/// A constraint check queued until COMMIT (or SET CONSTRAINTS ALL IMMEDIATE)
+#[derive(Debug, Clone)]
+pub enum PendingCheck {
+    /// Row version at `pos` in `table` must satisfy `constraint`
+    Row {
+        table: String,
+        pos: (usize, usize),
+        constraint: DeferredConstraint,
+    },
+    /// `key` of `table` was removed: no live row of `child` may still reference it via `fk`
+    Referenced {
+        table: String,
+        child: String,
+        fk: ForeignKeyConstraint,
+        key: Vec<Value>,
+    },
+}
+
 /// In-memory transaction manager (maps xid -> status)
 pub struct TransactionManager {
     pub transactions: HashMap<u32, TxStatus>,
     pub next_xid: u32,
     pub active_xids: Vec<u32>, 
+    pub snapshot_xmins: HashMap<u32, u32>, // xid -> oldest xid it may still consider running
+    pub pending_checks: HashMap<u32, Vec<PendingCheck>>, // xid -> checks postponed to COMMIT
+    pub constraints_deferred: HashMap<u32, bool>, // xid -> SET CONSTRAINTS ALL DEFERRED / IMMEDIATE
 }
 
 impl TransactionManager {
@@ -36,6 +66,9 @@ impl TransactionManager {
             transactions: HashMap::new(),
             next_xid: 1,
             active_xids: Vec::new(),
+            snapshot_xmins: HashMap::new(),
+            pending_checks: HashMap::new(),
+            constraints_deferred: HashMap::new(),
         }
     }
 
@@ -51,6 +84,9 @@ impl TransactionManager {
             transactions: m,
             next_xid,
             active_xids: active,
+            snapshot_xmins: HashMap::new(),
+            pending_checks: HashMap::new(),
+            constraints_deferred: HashMap::new(),
         }
     }
 
@@ -62,6 +98,8 @@ impl TransactionManager {
 
     /// Mark transaction as started
     pub fn begin(&mut self, xid: u32) {
+        let xmin = self.snapshot().xmin;
+        self.snapshot_xmins.insert(xid, xmin);
         self.transactions.insert(xid, TxStatus::InProgress);
         self.active_xids.push(xid);
     }
@@ -70,12 +108,32 @@ impl TransactionManager {
     pub fn commit(&mut self, xid: u32) {
         self.transactions.insert(xid, TxStatus::Committed);
         self.active_xids.retain(|&x| x != xid);
+        self.snapshot_xmins.remove(&xid);
+        self.pending_checks.remove(&xid);
+        self.constraints_deferred.remove(&xid);
     }
 
     /// Mark transaction as aborted
     pub fn rollback(&mut self, xid: u32) {
         self.transactions.insert(xid, TxStatus::Aborted);
         self.active_xids.retain(|&x| x != xid);
+        self.snapshot_xmins.remove(&xid);
+        self.pending_checks.remove(&xid);
+        self.constraints_deferred.remove(&xid);
+    }
+
+    /// Whether a constraint declared with `mode` is checked at COMMIT in transaction `xid`
+    pub fn is_deferred(&self, xid: u32, mode: Deferrable) -> bool {
+        match mode {
+            Deferrable::NotDeferrable => false,
+            Deferrable::InitiallyImmediate => self.constraints_deferred.get(&xid) == Some(&true),
+            Deferrable::InitiallyDeferred => self.constraints_deferred.get(&xid) != Some(&false),
+        }
+    }
+
+    /// Queue a constraint check until the end of transaction `xid`
+    pub fn defer_check(&mut self, xid: u32, check: PendingCheck) {
+        self.pending_checks.entry(xid).or_default().push(check);
     }
 
     /// Get current status of a transaction
@@ -92,4 +150,15 @@ impl TransactionManager {
 
         Snapshot { xmin, xmax, active_xids }
     }
-}
\ No newline at end of file
+
+    /// Oldest xid any running transaction may still treat as in progress.
+    /// Tuples committed below this horizon are visible to every snapshot.
+    pub fn global_xmin(&self) -> u32 {
+        let current = self.snapshot().xmin;
+        self.snapshot_xmins
+            .values()
+            .copied()
+            .min()
+            .map_or(current, |x| x.min(current))
+    }
+}
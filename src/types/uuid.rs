@@ -0,0 +1,64 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// UUID input: 32 hex digits in either case, optionally in braces, with a hyphen allowed
+/// after any group of four digits (`a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11`, `{A0EEBC99...}`)
+pub fn parse_uuid(text: &str) -> Option<[u8; 16]> {
+    let text = text.trim();
+    let text = match text.strip_prefix('{') {
+        Some(inner) => inner.strip_suffix('}')?,
+        None => text,
+    };
+    let mut digits = Vec::with_capacity(32);
+    let chars: Vec<char> = text.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '-' {
+            // only between groups of four digits
+            if digits.is_empty() || digits.len() % 4 != 0 || i + 1 == chars.len() || chars[i + 1] == '-' {
+                return None;
+            }
+            continue;
+        }
+        digits.push(c.to_digit(16)? as u8);
+    }
+    if digits.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    for (i, pair) in digits.chunks(2).enumerate() {
+        bytes[i] = pair[0] << 4 | pair[1];
+    }
+    Some(bytes)
+}
+
+/// Canonical UUID text: lowercase, hyphenated 8-4-4-4-12
+pub fn format_uuid(bytes: &[u8; 16]) -> String {
+    let mut out = String::with_capacity(36);
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+/// A version 4 (random) UUID, as gen_random_uuid() returns
+pub fn random_uuid() -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    let from_os = std::fs::File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut bytes));
+    if from_os.is_err() {
+        // no OS source: randomly keyed hashes of the clock
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        for half in bytes.chunks_mut(8) {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            half.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+    bytes
+}
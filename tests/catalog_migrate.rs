@@ -0,0 +1,167 @@
+mod common;
+
+use common::{Session, ints};
+use povertygres::catalog::catalog_manager::CatalogManager;
+use povertygres::consts::catalog_consts::{CATALOG_FILE, CATALOG_VERSION, DATA_DIR};
+use povertygres::dump::write_dump;
+use povertygres::types::storage_types::Value::{Int, Text};
+use serde_json::{Value, json};
+
+fn catalog_path(s: &Session) -> std::path::PathBuf {
+    s.dir.path().join(DATA_DIR).join(CATALOG_FILE)
+}
+
+fn read_catalog(s: &Session) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(catalog_path(s)).unwrap()).unwrap()
+}
+
+/// Rewrite the current catalog.json the way catalog `version` stored it,
+/// leaving out the fields added with serde defaults since
+fn downgrade(doc: &mut Value, version: u32) {
+    doc["version"] = json!(version);
+    let doc = doc.as_object_mut().unwrap();
+    doc.remove("enums");
+    doc.remove("sequences");
+
+    for index in doc["indexes"].as_object_mut().unwrap().values_mut() {
+        index.as_object_mut().unwrap().remove("deferrable");
+    }
+    if version < 2 {
+        // v1 had no index behind the primary key
+        doc["indexes"].as_object_mut().unwrap().retain(|name, _| !name.ends_with("_pkey"));
+    }
+
+    for table in doc["tables"].as_object_mut().unwrap().values_mut() {
+        let table = table.as_object_mut().unwrap();
+        table.remove("checks");
+        for fk in table["foreign_keys"].as_array_mut().unwrap() {
+            let fk = fk.as_object_mut().unwrap();
+            for key in ["on_delete", "on_update", "deferrable", "name"] {
+                fk.remove(key);
+            }
+        }
+        for col in table["columns"].as_array_mut().unwrap() {
+            let col = col.as_object_mut().unwrap();
+            col.remove("typmod");
+            col.remove("identity");
+            if version < 4 && !col["default"].is_null() {
+                // v3 stored the constant itself
+                col["default"] = col["default"]["Literal"].take();
+            }
+            if version < 3 {
+                col.remove("attnum");
+            }
+        }
+        if version < 3 {
+            table.remove("attributes");
+            table.remove("schema_version");
+        }
+        if version < 2
+            && let Some(Value::Array(pk)) = table.get("primary_key")
+        {
+            // v1 keys were a single column name
+            table["primary_key"] = pk[0].clone();
+        }
+    }
+}
+
+/// Tables written by the current engine, with catalog.json rewritten as `version`
+fn written_as(version: u32) -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table p (id int primary key, name text)",
+        "create table c (id int, p_id int default 1 references p(id))",
+        "insert into p values (1, 'one'), (2, 'two')",
+        "insert into c values (10, 1), (20, 2)",
+    ]);
+    let mut doc = read_catalog(&s);
+    downgrade(&mut doc, version);
+    std::fs::write(catalog_path(&s), serde_json::to_string_pretty(&doc).unwrap()).unwrap();
+    s
+}
+
+/// The tables of `written_as`, reopened from a catalog of `version`
+fn open_from(version: u32) -> Session {
+    let mut s = written_as(version);
+    s.reopen();
+    s
+}
+
+fn check_migrated(mut s: Session) {
+    // upgraded once and written back
+    let doc = read_catalog(&s);
+    assert_eq!(doc["version"], json!(CATALOG_VERSION));
+    assert_eq!(doc["tables"]["p"]["primary_key"], json!(["id"]));
+    assert_eq!(doc["tables"]["c"]["columns"][1]["attnum"], json!(1));
+    assert_eq!(doc["tables"]["c"]["columns"][1]["default"], json!({ "Literal": { "Int": 1 } }));
+
+    let db = &s.engine.db;
+    assert_eq!(db.tables["p"].primary_key, Some(vec!["id".to_string()]));
+    assert!(db.indexes["p_pkey"].unique);
+    assert_eq!(db.tables["c"].foreign_keys.len(), 1);
+
+    assert_eq!(s.rows("p"), vec![vec![Int(1), Text("one".into())], vec![Int(2), Text("two".into())]]);
+    assert_eq!(s.rows("c"), ints(&[&[10, 1], &[20, 2]]));
+
+    // constraints and defaults work on the migrated schema
+    assert!(s.run("insert into p values (1, 'again')").is_err());
+    assert!(s.run("insert into c values (30, 3)").is_err());
+    s.run_all(&["insert into c (id) values (30)", "alter table p add column n int default 5"]);
+    assert_eq!(s.rows("c"), ints(&[&[10, 1], &[20, 2], &[30, 1]]));
+
+    s.reopen();
+    assert_eq!(s.rows("p")[0], vec![Int(1), Text("one".into()), Int(5)]);
+}
+
+#[test]
+fn test_open_v1_catalog() {
+    check_migrated(open_from(1));
+}
+
+#[test]
+fn test_open_v2_catalog() {
+    check_migrated(open_from(2));
+}
+
+#[test]
+fn test_open_v3_catalog() {
+    check_migrated(open_from(3));
+}
+
+#[test]
+fn test_newer_catalog_is_refused() {
+    let s = Session::new();
+    let mut doc = read_catalog(&s);
+    doc["version"] = json!(CATALOG_VERSION + 1);
+    std::fs::write(catalog_path(&s), doc.to_string()).unwrap();
+    let err = povertygres::engine::Engine::open().err().unwrap().to_string();
+    assert!(err.contains("newer than supported"), "{}", err);
+}
+
+#[test]
+fn test_read_only_open_migrates_in_memory() {
+    let s = written_as(1);
+    let data_dir = s.dir.path().join(DATA_DIR);
+    let files = |dir: &std::path::Path| {
+        let mut names: Vec<_> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        names.sort();
+        names
+    };
+    let (before, names) = (std::fs::read(catalog_path(&s)).unwrap(), files(&data_dir));
+
+    let cat = CatalogManager::open_read_only(&data_dir).unwrap();
+    assert_eq!(cat.catalog().version, CATALOG_VERSION);
+    assert_eq!(cat.catalog().tables["p"].primary_key, Some(vec!["id".to_string()]));
+    let mut out = Vec::new();
+    write_dump(&cat, &data_dir, &mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("create table p"));
+
+    // the v1 catalog is left as it was
+    assert_eq!(std::fs::read(catalog_path(&s)).unwrap(), before);
+    assert_eq!(files(&data_dir), names);
+
+    // and a missing catalog is an error instead of a new one
+    let empty = tempfile::tempdir().unwrap();
+    assert!(CatalogManager::open_read_only(empty.path()).is_err());
+    assert!(files(empty.path()).is_empty());
+}
@@ -0,0 +1,121 @@
+#![allow(dead_code)]
+
+use povertygres::engine::Engine;
+use povertygres::errors::engine_error::EngineError;
+use povertygres::executer::executer::execute;
+use povertygres::executer::select::TableArg;
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::{FromItem, Query};
+use povertygres::types::storage_types::Value;
+use std::sync::{Mutex, MutexGuard};
+use tempfile::TempDir;
+
+/// The engine keeps its files under the relative data directory,
+/// so tests sharing a process take turns changing into their own tempdir
+static CWD: Mutex<()> = Mutex::new(());
+
+/// Engine opened on an empty data directory; fields drop in order, engine first
+pub struct Session {
+    pub engine: Engine,
+    pub dir: TempDir,
+    _cwd: MutexGuard<'static, ()>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let cwd = CWD.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let engine = Engine::open().unwrap();
+        Self { engine, dir, _cwd: cwd }
+    }
+
+    /// Restart the engine on the same data directory
+    pub fn reopen(&mut self) {
+        self.engine = Engine::open().unwrap();
+    }
+
+    /// Make `xid` the open transaction and return the one it replaces. The engine
+    /// serves a single client, so concurrent transactions take turns on it.
+    pub fn switch_to(&mut self, xid: Option<u32>) -> Option<u32> {
+        std::mem::replace(&mut self.engine.current_xid, xid)
+    }
+
+    pub fn run(&mut self, sql: &str) -> Result<(), EngineError> {
+        execute(&mut self.engine, parse_query(sql).map_err(EngineError::Other)?)
+    }
+
+    /// Run statements that must all succeed
+    pub fn run_all(&mut self, script: &[&str]) {
+        for sql in script {
+            self.run(sql).unwrap_or_else(|e| panic!("{sql}: {e}"));
+        }
+    }
+
+    /// Rows of `table` visible to the session, ordered by their values
+    pub fn rows(&self, table: &str) -> Vec<Vec<Value>> {
+        let db = &self.engine.db;
+        let xid = self.engine.current_xid.unwrap_or(0);
+        let snapshot = db.transaction_manager.snapshot();
+        let (_, rows) = db
+            .select(&TableArg::TableName(table.to_string()), &[], None, xid, &snapshot)
+            .unwrap();
+        let mut values: Vec<Vec<Value>> = rows.into_iter().map(|r| r.values).collect();
+        values.sort();
+        values
+    }
+
+    /// Result of a SELECT on a single table, ordered by its values
+    pub fn select(&self, sql: &str) -> Vec<Vec<Value>> {
+        let Query::Select { from_table: FromItem::Table(table), targets, filter, .. } =
+            parse_query(sql).unwrap()
+        else {
+            panic!("Expected a SELECT from one table: {sql}");
+        };
+        let db = &self.engine.db;
+        let xid = self.engine.current_xid.unwrap_or(0);
+        let snapshot = db.transaction_manager.snapshot();
+        let (_, rows) = db.select(&TableArg::TableName(table), &targets, filter, xid, &snapshot).unwrap();
+        let mut values: Vec<Vec<Value>> = rows.into_iter().map(|r| r.values).collect();
+        values.sort();
+        values
+    }
+
+    /// Rows an UPDATE or DELETE hands to its RETURNING clause, ordered by their values;
+    /// runs in the open transaction
+    pub fn returned(&mut self, sql: &str) -> Result<Vec<Vec<Value>>, String> {
+        let xid = self.engine.current_xid.expect("open transaction");
+        let db = &mut self.engine.db;
+        let snapshot = db.transaction_manager.snapshot();
+        let rows = match parse_query(sql)? {
+            Query::Update { table_name, column_names, values, from, aliases, filter, .. } => {
+                let from = from.map(|item| (item, aliases));
+                db.update(&table_name, column_names, values, from, filter, xid, &snapshot)?
+            }
+            Query::Delete { table_name, using, aliases, filter, .. } => {
+                let using = using.map(|item| (item, aliases));
+                db.delete(&table_name, using, filter, xid, &snapshot)?
+            }
+            other => panic!("Expected UPDATE or DELETE, got {:?}", other),
+        };
+        let mut values: Vec<Vec<Value>> = rows.into_iter().map(|r| r.values).collect();
+        values.sort();
+        Ok(values)
+    }
+}
+
+pub fn ints(rows: &[&[i64]]) -> Vec<Vec<Value>> {
+    rows.iter().map(|r| r.iter().map(|&v| Value::Int(v)).collect()).collect()
+}
+
+/// Number of tuples in the heap of `table`, dead or alive
+pub fn tuples(s: &Session, table: &str) -> usize {
+    let t = &s.engine.db.tables[table];
+    t.heap.scan_all(&t.columns).len()
+}
+
+/// Heap positions the index `name` holds for the single-column key `id`
+pub fn entries(s: &Session, name: &str, id: i64) -> usize {
+    let idx = &s.engine.db.indexes[name];
+    idx.search_eq(&vec![Value::Int(id)]).map_or(0, |positions| positions.len())
+}
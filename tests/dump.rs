@@ -0,0 +1,75 @@
+use povertygres::catalog::catalog_manager::CatalogManager;
+use povertygres::dump::write_dump;
+use povertygres::parser::script::parse_script;
+use povertygres::storage::heap_file::HeapFile;
+use povertygres::types::catalog_types::{AttributeMeta, CatColumnType, ColumnMeta, TableMeta};
+use povertygres::types::parser_types::Query;
+use povertygres::types::storage_types::{Column, ColumnType, Row, Value};
+use povertygres::types::transaction_types::TxStatus;
+
+#[test]
+fn test_dump_uses_one_snapshot_and_restores() {
+    let dir = tempfile::tempdir().unwrap();
+    let schema = vec![Column {
+        name: "id".to_string(),
+        column_type: ColumnType::Int,
+        not_null: true,
+        default: None,
+        attnum: 0,
+        identity: None,
+    }];
+    let heap = HeapFile::new(dir.path().join("t.tbl").to_str().unwrap(), &schema);
+    heap.insert_rows(vec![Row { values: vec![Value::Int(1)] }], &schema, 1).unwrap();
+    heap.insert_rows(vec![Row { values: vec![Value::Int(2)] }], &schema, 2).unwrap();
+
+    let mut cat = CatalogManager::open(dir.path()).unwrap();
+    let catalog = cat.catalog_mut();
+    catalog.next_xid = 3;
+    catalog.transactions.insert(1, TxStatus::Committed);
+    catalog.transactions.insert(2, TxStatus::InProgress);
+    catalog.tables.insert(
+        "t".to_string(),
+        TableMeta {
+            oid: 1,
+            file: "data/t.tbl".to_string(),
+            columns: vec![ColumnMeta {
+                name: "id".to_string(),
+                ty: CatColumnType::Int32,
+                not_null: true,
+                default: None,
+                attnum: 0,
+                identity: None,
+                typmod: Vec::new(),
+            }],
+            next_rowid: 0,
+            primary_key: None,
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            attributes: vec![AttributeMeta {
+                ty: CatColumnType::Int32,
+                added_in: 0,
+                missing: Value::Null,
+                dropped: false,
+            }],
+            schema_version: 0,
+        },
+    );
+
+    let mut out = Vec::new();
+    write_dump(&cat, dir.path(), &mut out).unwrap();
+    let sql = String::from_utf8(out).unwrap();
+
+    // the row of in-progress transaction 2 is not part of the snapshot
+    let statements = parse_script(&sql);
+    let copy = statements
+        .iter()
+        .find_map(|(_, q)| match q {
+            Ok(Query::Copy { data, .. }) => data.clone(),
+            _ => None,
+        })
+        .expect("dump should hold a COPY block");
+    assert_eq!(copy, "1\n");
+    assert!(statements.iter().all(|(_, q)| q.is_ok()), "{}", sql);
+    assert!(matches!(statements.first(), Some((_, Ok(Query::Begin { .. })))));
+    assert!(matches!(statements.last(), Some((_, Ok(Query::Commit)))));
+}
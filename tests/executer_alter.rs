@@ -0,0 +1,86 @@
+mod common;
+
+use common::{Session, ints};
+use povertygres::consts::catalog_consts::DATA_DIR;
+use povertygres::dump::write_dump;
+use povertygres::types::storage_types::Value::{Int, Null, Text};
+
+#[test]
+fn test_named_foreign_key() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table p (id int primary key)",
+        "create table t (id int, pid int)",
+        "insert into p values (1)",
+        "insert into t values (1, 1)",
+        "alter table t add constraint t_parent foreign key (pid) references p(id) on delete cascade",
+    ]);
+    assert!(s.run("insert into t values (2, 2)").is_err());
+
+    // names are unique within the table, given or derived
+    let err = s.run("alter table t add constraint t_parent foreign key (id) references p(id)").unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    assert!(s.run("alter table t add constraint p_pkey foreign key (id) references p(id)").is_err());
+    s.run_all(&["alter table t add foreign key (id) references p(id)"]);
+    assert!(s.run("alter table t add constraint t_id_fkey foreign key (pid) references p(id)").is_err());
+
+    // the name survives a restart and a dump
+    s.reopen();
+    let fks = &s.engine.db.tables["t"].foreign_keys;
+    assert_eq!(fks[0].name.as_deref(), Some("t_parent"));
+    assert_eq!(fks[1].name, None);
+    let mut out = Vec::new();
+    write_dump(&s.engine.cat, &s.dir.path().join(DATA_DIR), &mut out).unwrap();
+    let dump = String::from_utf8(out).unwrap();
+    assert!(
+        dump.contains("alter table t add constraint t_parent foreign key (pid) references p(id) on delete cascade;"),
+        "{}",
+        dump
+    );
+    assert!(dump.contains("alter table t add foreign key (id) references p(id);"), "{}", dump);
+
+    // a given name stays when the table is renamed; a derived one follows it
+    s.run_all(&["alter table t rename to u"]);
+    assert!(s.run("alter table u drop constraint t_id_fkey").is_err());
+    s.run_all(&["alter table u drop constraint u_id_fkey", "alter table u drop constraint t_parent"]);
+    assert!(s.engine.db.tables["u"].foreign_keys.is_empty());
+    s.run_all(&["delete from p", "insert into u values (3, 3)"]);
+}
+
+#[test]
+fn test_tuples_older_than_add_and_drop_column() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int, a int, b text)",
+        "insert into t values (1, 10, 'x')",
+        "alter table t add column c int default 7",
+        "insert into t values (2, 20, 'y', 8)",
+        "alter table t drop column a",
+        "insert into t values (3, 'z', 9)",
+        "alter table t add column d int",
+        "alter table t add column a int default 5",
+    ]);
+    let expected = vec![
+        vec![Int(1), Text("x".into()), Int(7), Null, Int(5)],
+        vec![Int(2), Text("y".into()), Int(8), Null, Int(5)],
+        vec![Int(3), Text("z".into()), Int(9), Null, Int(5)],
+    ];
+    assert_eq!(s.rows("t"), expected);
+
+    // each tuple keeps the schema version it was written with, also after a restart
+    s.reopen();
+    assert_eq!(s.rows("t"), expected);
+
+    // rewriting old tuples stores them in the current layout
+    s.run_all(&["update t set d = id where id < 3", "delete from t where id = 3"]);
+    assert_eq!(
+        s.rows("t"),
+        vec![
+            vec![Int(1), Text("x".into()), Int(7), Int(1), Int(5)],
+            vec![Int(2), Text("y".into()), Int(8), Int(2), Int(5)],
+        ]
+    );
+    s.run_all(&["alter table t drop column c", "alter table t drop column b"]);
+    s.reopen();
+    assert_eq!(s.rows("t"), ints(&[&[1, 1, 5], &[2, 2, 5]]));
+}
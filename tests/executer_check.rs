@@ -0,0 +1,64 @@
+mod common;
+
+use common::{Session, ints};
+use povertygres::types::storage_types::Value::{Int, Null};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table acc (id int primary key, balance int check (balance >= 0), lim int, constraint within_limit check (balance <= lim))",
+        "insert into acc values (1, 10, 100), (2, 0, 50)",
+    ]);
+    s
+}
+
+fn violation(s: &mut Session, sql: &str) -> String {
+    s.run(sql).expect_err(sql).to_string()
+}
+
+#[test]
+fn test_insert_violation_names_the_constraint() {
+    let mut s = setup();
+    let err = violation(&mut s, "insert into acc values (3, -1, 100)");
+    assert!(err.contains("violates check constraint 'acc_balance_check'"), "{}", err);
+    let err = violation(&mut s, "insert into acc values (3, 200, 100)");
+    assert!(err.contains("violates check constraint 'within_limit'"), "{}", err);
+    // the failing row takes the rows of its statement with it
+    assert!(s.run("insert into acc values (3, 1, 10), (4, -1, 10)").is_err());
+    assert_eq!(s.rows("acc"), ints(&[&[1, 10, 100], &[2, 0, 50]]));
+}
+
+#[test]
+fn test_update_violation_names_the_constraint() {
+    let mut s = setup();
+    let err = violation(&mut s, "update acc set balance = balance - 5");
+    assert!(err.contains("violates check constraint 'acc_balance_check'"), "{}", err);
+    let err = violation(&mut s, "update acc set lim = 5 where id = 1");
+    assert!(err.contains("violates check constraint 'within_limit'"), "{}", err);
+    assert_eq!(s.rows("acc"), ints(&[&[1, 10, 100], &[2, 0, 50]]));
+
+    s.run_all(&["update acc set balance = balance - 5 where id = 1"]);
+    assert_eq!(s.rows("acc"), ints(&[&[1, 5, 100], &[2, 0, 50]]));
+}
+
+#[test]
+fn test_null_result_passes() {
+    let mut s = setup();
+    // balance >= 0 and balance <= lim are unknown for a NULL balance or limit
+    s.run_all(&[
+        "insert into acc values (3, null, 10), (4, 500, null)",
+        "update acc set lim = null where id = 1",
+        "update acc set balance = 1000 where id = 1",
+    ]);
+    assert_eq!(
+        s.rows("acc"),
+        vec![
+            vec![Int(1), Int(1000), Null],
+            vec![Int(2), Int(0), Int(50)],
+            vec![Int(3), Null, Int(10)],
+            vec![Int(4), Int(500), Null],
+        ]
+    );
+    let err = violation(&mut s, "update acc set balance = -1 where id = 4");
+    assert!(err.contains("violates check constraint 'acc_balance_check'"), "{}", err);
+}
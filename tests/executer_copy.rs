@@ -0,0 +1,35 @@
+mod common;
+
+use common::{Session, entries, ints, tuples};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int primary key, n int check (n > 0))",
+        "create index t_n_idx on t (n)",
+        "insert into t values (1, 1)",
+    ]);
+    s
+}
+
+#[test]
+fn test_failing_line_aborts_the_whole_copy() {
+    let mut s = setup();
+    let path = s.dir.path().join("t.txt");
+    std::fs::write(&path, "2\t1\n3\tabc\n4\t1\n").unwrap();
+
+    let err = s.run(&format!("copy t from '{}'", path.display())).unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{}", err);
+    assert_eq!(s.rows("t"), ints(&[&[1, 1]]));
+    assert_eq!(tuples(&s, "t"), 1);
+    assert_eq!(entries(&s, "t_pkey", 2), 0);
+
+    std::fs::write(&path, "2\t1\n3\t1\n1\t1\n").unwrap();
+    assert!(s.run(&format!("copy t from '{}'", path.display())).is_err());
+    assert_eq!(tuples(&s, "t"), 1);
+    assert_eq!(entries(&s, "t_pkey", 2), 0);
+
+    std::fs::write(&path, "2\t1\n3\t1\n").unwrap();
+    s.run_all(&[&format!("copy t from '{}'", path.display())]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 1], &[2, 1], &[3, 1]]));
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{Session, ints};
+
+#[test]
+fn test_deferred_foreign_key_fails_at_commit() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table parents (id int primary key)",
+        "create table children (id int, p int references parents(id) deferrable initially deferred)",
+        "insert into parents values (1)",
+        "begin",
+        "insert into parents values (2)",
+        // accepted at statement time although parent 3 never exists
+        "insert into children values (1, 3)",
+        "insert into children values (2, 1)",
+    ]);
+    assert_eq!(s.rows("children"), ints(&[&[1, 3], &[2, 1]]));
+
+    // reported like the check at statement time would have
+    let err = s.run("commit").unwrap_err().to_string();
+    assert!(err.contains("rolled back"), "{}", err);
+    assert!(
+        err.contains("insert or update on table 'children' violates foreign key constraint 'children_p_fkey': key [Int(3)] is not present in 'parents'"),
+        "{}",
+        err
+    );
+    let immediate = s.run("insert into children values (1, 3)").unwrap_err().to_string();
+    assert!(immediate.contains("on table 'children' violates foreign key constraint 'children_p_fkey'"), "{}", immediate);
+    assert_eq!(s.engine.current_xid, None);
+    assert!(s.rows("children").is_empty());
+    assert_eq!(s.rows("parents"), ints(&[&[1]]));
+
+    // a parent inserted before COMMIT satisfies the check
+    s.run_all(&[
+        "begin",
+        "insert into children values (1, 3)",
+        "insert into parents values (3)",
+        "commit",
+    ]);
+    assert_eq!(s.rows("children"), ints(&[&[1, 3]]));
+}
+
+#[test]
+fn test_deferred_unique_fails_at_commit() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int unique deferrable initially deferred, n int)",
+        "insert into t values (1, 0)",
+        "begin",
+        "insert into t values (2, 0)",
+        "insert into t values (1, 1)",
+    ]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 0], &[1, 1], &[2, 0]]));
+
+    assert!(s.run("commit").is_err());
+    assert_eq!(s.rows("t"), ints(&[&[1, 0]]));
+
+    // swapping keys passes once both rows are updated
+    s.run_all(&[
+        "insert into t values (2, 1)",
+        "begin",
+        "update t set id = 2 where n = 0",
+        "update t set id = 1 where n = 1",
+        "commit",
+    ]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 1], &[2, 0]]));
+}
+
+#[test]
+fn test_set_constraints_immediate() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table parents (id int primary key)",
+        "create table children (p int references parents(id) deferrable)",
+        "begin",
+        "set constraints all deferred",
+        "insert into children values (1)",
+    ]);
+
+    // switching to IMMEDIATE checks what was queued so far
+    assert!(s.run("set constraints all immediate").is_err());
+    s.run_all(&["insert into parents values (1)", "set constraints all immediate"]);
+
+    // and later statements are checked as they run
+    assert!(s.run("insert into children values (2)").is_err());
+    s.run_all(&["commit"]);
+    assert_eq!(s.rows("children"), ints(&[&[1]]));
+
+    // NOT DEFERRABLE constraints ignore SET CONSTRAINTS
+    s.run_all(&[
+        "create table strict (p int references parents(id))",
+        "begin",
+        "set constraints all deferred",
+    ]);
+    assert!(s.run("insert into strict values (5)").is_err());
+}
+
+#[test]
+fn test_deferred_no_action_names_the_constraint() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table parents (id int primary key)",
+        "create table children (id int, p int)",
+        "alter table children add constraint children_parent foreign key (p) references parents(id) deferrable initially deferred",
+        "insert into parents values (1), (2)",
+        "insert into children values (1, 1)",
+        "begin",
+        "delete from parents where id = 1",
+    ]);
+    let err = s.run("commit").unwrap_err().to_string();
+    assert!(
+        err.contains("on table 'parents' violates foreign key constraint 'children_parent' on table 'children': key [Int(1)] is still referenced from 'children'"),
+        "{}",
+        err
+    );
+    assert_eq!(s.rows("parents"), ints(&[&[1], &[2]]));
+
+    // the same constraint checked at once
+    s.run_all(&["begin", "set constraints all immediate"]);
+    let err = s.run("delete from parents where id = 1").unwrap_err().to_string();
+    assert!(err.contains("delete on table 'parents' violates foreign key constraint 'children_parent' on table 'children'"), "{}", err);
+}
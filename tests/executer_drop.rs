@@ -0,0 +1,100 @@
+mod common;
+
+use common::{Session, ints};
+use std::path::PathBuf;
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table p (id int primary key)",
+        "create table c (id int, p_id int references p(id))",
+        "create table other (id int)",
+        "insert into p values (1), (2)",
+        "insert into c values (10, 1)",
+        "insert into other values (7)",
+    ]);
+    s
+}
+
+fn heap_path(s: &Session, table: &str) -> PathBuf {
+    s.dir.path().join(&s.engine.db.tables[table].heap.path)
+}
+
+#[test]
+fn test_drop_referenced_table() {
+    let mut s = setup();
+    let err = s.run("drop table p").unwrap_err().to_string();
+    assert!(err.contains("constraint c_p_id_fkey on table 'c' depends on it"), "{}", err);
+    assert!(s.engine.db.tables.contains_key("p"));
+
+    // dropping the referencing table along with it needs no CASCADE
+    s.run_all(&["begin", "drop table c, p", "rollback"]);
+    assert_eq!(s.rows("c"), ints(&[&[10, 1]]));
+
+    s.run_all(&["drop table p cascade"]);
+    assert!(!s.engine.db.tables.contains_key("p"));
+    assert!(!s.engine.db.indexes.contains_key("p_pkey"));
+    // the referencing table stays, without its foreign key
+    assert!(s.engine.db.tables["c"].foreign_keys.is_empty());
+    s.run_all(&["insert into c values (20, 99)"]);
+    assert_eq!(s.rows("c"), ints(&[&[10, 1], &[20, 99]]));
+
+    s.reopen();
+    assert!(!s.engine.db.tables.contains_key("p"));
+    assert!(s.engine.db.tables["c"].foreign_keys.is_empty());
+}
+
+#[test]
+fn test_files_are_unlinked_at_commit() {
+    let mut s = setup();
+    s.run_all(&["vacuum other"]);
+    let path = heap_path(&s, "other");
+    let vm_path = path.with_extension("vm");
+    assert!(path.exists() && vm_path.exists());
+
+    s.run_all(&["begin", "drop table other"]);
+    // other transactions may still read the file until COMMIT
+    assert!(path.exists());
+    s.run_all(&["rollback"]);
+    assert!(path.exists() && vm_path.exists());
+    assert_eq!(s.rows("other"), ints(&[&[7]]));
+
+    s.run_all(&["begin", "drop table other"]);
+    assert!(path.exists());
+    s.run_all(&["commit"]);
+    assert!(!path.exists() && !vm_path.exists());
+
+    // outside a transaction block the statement commits at once
+    let path = heap_path(&s, "c");
+    s.run_all(&["drop table c"]);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_truncate_referenced_table() {
+    let mut s = setup();
+    let err = s.run("truncate p").unwrap_err().to_string();
+    assert!(err.contains("table 'c' references it"), "{}", err);
+    assert_eq!(s.rows("p"), ints(&[&[1], &[2]]));
+
+    // truncating both together is allowed
+    let (p_path, c_path) = (heap_path(&s, "p"), heap_path(&s, "c"));
+    s.run_all(&["begin", "truncate p, c"]);
+    assert!(s.rows("p").is_empty() && s.rows("c").is_empty());
+    assert!(p_path.exists() && c_path.exists());
+    s.run_all(&["rollback"]);
+    assert_eq!(s.rows("p"), ints(&[&[1], &[2]]));
+    assert_eq!(s.rows("c"), ints(&[&[10, 1]]));
+    assert_eq!(heap_path(&s, "p"), p_path);
+
+    s.run_all(&["truncate p cascade"]);
+    assert!(s.rows("p").is_empty() && s.rows("c").is_empty());
+    assert!(!p_path.exists() && !c_path.exists());
+    // the foreign key is kept and the emptied parent has no keys left
+    assert!(s.run("insert into c values (10, 1)").is_err());
+    assert_eq!(s.rows("other"), ints(&[&[7]]));
+
+    s.reopen();
+    assert!(s.rows("p").is_empty());
+    s.run_all(&["insert into p values (1)", "insert into c values (10, 1)"]);
+}
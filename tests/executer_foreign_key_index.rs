@@ -0,0 +1,76 @@
+mod common;
+
+use common::{Session, entries, ints};
+use povertygres::types::storage_types::Value;
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&["create table p (id int primary key, n int)", "insert into p values (1, 10), (2, 20), (3, 30)"]);
+    s
+}
+
+#[test]
+fn test_check_probes_the_parent_key_index() {
+    let mut s = setup();
+    s.run_all(&["create table c (id int, p_id int references p(id))"]);
+
+    // drop the index entry of key 1: a probe through the index no longer
+    // finds the parent row, which a heap scan would
+    let key = vec![Value::Int(1)];
+    let idx = s.engine.db.indexes.get_mut("p_pkey").unwrap();
+    for pos in idx.search_eq(&key).unwrap().clone() {
+        assert!(idx.delete(&key, pos));
+    }
+    assert_eq!(entries(&s, "p_pkey", 1), 0);
+
+    let err = s.run("insert into c values (1, 1)").unwrap_err().to_string();
+    assert!(err.contains("violates foreign key constraint"), "{}", err);
+    s.run_all(&["insert into c values (2, 2)"]);
+    assert_eq!(s.rows("c"), ints(&[&[2, 2]]));
+}
+
+#[test]
+fn test_referenced_columns_need_a_unique_constraint() {
+    let mut s = setup();
+    s.run_all(&["create index p_n_idx on p (n)"]);
+    for sql in [
+        "create table c (id int, p_n int references p(n))",
+        "create table c (id int, p_id int, p_n int, foreign key (p_id, p_n) references p(id, n))",
+    ] {
+        let err = s.run(sql).unwrap_err().to_string();
+        assert!(err.contains("there is no unique constraint matching given keys"), "{}: {}", sql, err);
+    }
+    s.run_all(&["create table c (id int, p_n int)"]);
+    let err = s.run("alter table c add foreign key (p_n) references p(n)").unwrap_err().to_string();
+    assert!(err.contains("there is no unique constraint matching given keys"), "{}", err);
+
+    s.run_all(&["alter table p add constraint p_n_key unique (n)", "alter table c add foreign key (p_n) references p(n)"]);
+}
+
+#[test]
+fn test_auto_index_foreign_keys() {
+    let mut s = setup();
+    s.run_all(&["create table plain (id int, p_id int references p(id))"]);
+    assert!(!s.engine.db.indexes.contains_key("plain_p_id_idx"));
+
+    s.run_all(&[
+        "set auto_index_foreign_keys = on",
+        "create table c (id int, p_id int references p(id))",
+        "insert into c values (1, 1), (2, 1), (3, 2)",
+    ]);
+    let idx = &s.engine.db.indexes["c_p_id_idx"];
+    assert_eq!((idx.table.as_str(), idx.columns.clone(), idx.unique), ("c", vec!["p_id".to_string()], false));
+    assert_eq!(entries(&s, "c_p_id_idx", 1), 2);
+
+    // deleting a parent looks up its referencing rows through the index
+    assert!(s.run("delete from p where id = 1").is_err());
+    s.run_all(&["delete from p where id = 3"]);
+
+    // and falls back to scanning once it is gone
+    s.run_all(&["drop index c_p_id_idx"]);
+    assert!(!s.engine.db.indexes.contains_key("c_p_id_idx"));
+    assert!(s.run("delete from p where id = 1").is_err());
+    assert!(s.run("update p set id = 5 where id = 2").is_err());
+    s.run_all(&["delete from c where p_id = 1", "delete from p where id = 1"]);
+    assert_eq!(s.rows("p"), ints(&[&[2, 20]]));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{Session, ints};
+
+/// Whether page 0 of `table` is marked all-visible
+fn all_visible(s: &Session, table: &str) -> bool {
+    s.engine.db.tables[table].heap.read_visibility_map().is_all_visible(0)
+}
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int primary key, n int)",
+        "insert into t values (1, 10), (2, 20), (3, 30)",
+        "vacuum t",
+    ]);
+    assert!(all_visible(&s, "t"));
+    s
+}
+
+#[test]
+fn test_index_only_answers_after_vacuum() {
+    let s = setup();
+    // id is covered by t_pkey: answered from the index
+    assert_eq!(s.select("select id from t where id = 2"), ints(&[&[2]]));
+    assert_eq!(s.select("select id from t where id > 1"), ints(&[&[2], &[3]]));
+    // n is not: the heap is read
+    assert_eq!(s.select("select id, n from t where id = 3"), ints(&[&[3, 30]]));
+}
+
+#[test]
+fn test_delete_clears_the_bit() {
+    let mut s = setup();
+    s.run_all(&["delete from t where id = 2"]);
+    assert!(!all_visible(&s, "t"));
+    // the deleted tuple keeps its index entry until the next VACUUM
+    assert_eq!(s.select("select id from t where id = 2"), Vec::<Vec<_>>::new());
+    assert_eq!(s.select("select id from t where id > 1"), ints(&[&[3]]));
+
+    s.run_all(&["vacuum t"]);
+    assert!(all_visible(&s, "t"));
+    assert_eq!(s.select("select id from t where id > 0"), ints(&[&[1], &[3]]));
+}
+
+#[test]
+fn test_insert_and_update_clear_the_bit() {
+    let mut s = setup();
+    s.run_all(&["begin", "insert into t values (4, 40)"]);
+    assert!(!all_visible(&s, "t"));
+    s.run_all(&["rollback"]);
+    assert_eq!(s.select("select id from t where id = 4"), Vec::<Vec<_>>::new());
+
+    s.run_all(&["vacuum t", "update t set id = 5 where id = 1"]);
+    assert!(!all_visible(&s, "t"));
+    assert_eq!(s.select("select id from t where id < 10"), ints(&[&[2], &[3], &[5]]));
+    assert_eq!(s.select("select id from t where id = 1"), Vec::<Vec<_>>::new());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{Session, entries, ints, tuples};
+use povertygres::types::storage_types::Value;
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int primary key, n int check (n > 0))",
+        "create index t_n_idx on t (n)",
+        "insert into t values (1, 1)",
+    ]);
+    s
+}
+
+#[test]
+fn test_failing_row_aborts_the_whole_insert() {
+    let mut s = setup();
+    let failing = [
+        "insert into t values (2, 1), (3, 0), (4, 1)",       // CHECK
+        "insert into t values (2, 1), (3, 1), (1, 1)",       // duplicate of a committed key
+        "insert into t values (2, 1), (3, 1), (3, 1)",       // duplicate within the batch
+        "insert into t values (2, 1), (3, 'x'), (4, 1)",     // type
+    ];
+    for sql in failing {
+        assert!(s.run(sql).is_err(), "{}", sql);
+        assert_eq!(s.rows("t"), ints(&[&[1, 1]]), "{}", sql);
+        // rows are validated before any is written
+        assert_eq!(tuples(&s, "t"), 1, "{}", sql);
+        assert_eq!(entries(&s, "t_pkey", 2), 0, "{}", sql);
+        assert_eq!(entries(&s, "t_n_idx", 1), 1, "{}", sql);
+    }
+
+    // NULL passes the CHECK
+    s.run_all(&["insert into t values (2, 1), (3, null), (4, null)"]);
+    assert_eq!(entries(&s, "t_pkey", 2), 1);
+    assert_eq!(s.rows("t").len(), 4);
+}
+
+#[test]
+fn test_row_at_a_time_insert_rolls_back() {
+    let mut s = Session::new();
+    s.run_all(&["create table tree (id int primary key, parent int references tree(id))"]);
+
+    // a self-referencing key writes each row before checking the next
+    assert!(s.run("insert into tree values (1, null), (2, 1), (3, 9)").is_err());
+    assert!(s.rows("tree").is_empty());
+
+    // the aborted tuples keep their index entries until VACUUM, but block nothing
+    assert_eq!(entries(&s, "tree_pkey", 1), 1);
+    s.run_all(&["insert into tree values (1, null), (2, 1)"]);
+    assert_eq!(entries(&s, "tree_pkey", 1), 2);
+
+    s.run_all(&["vacuum tree"]);
+    assert_eq!(entries(&s, "tree_pkey", 1), 1);
+    assert_eq!(entries(&s, "tree_pkey", 2), 1);
+    assert_eq!(s.rows("tree"), vec![vec![Value::Int(1), Value::Null], vec![Value::Int(2), Value::Int(1)]]);
+}
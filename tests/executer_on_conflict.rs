@@ -0,0 +1,90 @@
+mod common;
+
+use common::{Session, ints};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table t (id int primary key, n int, m int)",
+        "insert into t values (1, 10, 0), (2, 20, 0)",
+        "begin",
+    ]);
+    s
+}
+
+fn error(s: &mut Session, sql: &str) -> String {
+    s.run(sql).expect_err(sql).to_string()
+}
+
+#[test]
+fn test_conflict_with_committed_row() {
+    let mut s = setup();
+    s.run_all(&[
+        "insert into t values (1, 99, 99), (3, 30, 0) on conflict do nothing returning *",
+        "insert into t values (2, 99, 99) on conflict (id) do update set m = t.n + 1 returning id, m",
+    ]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 20, 21], &[3, 30, 0]]));
+
+    s.run_all(&["commit"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 20, 21], &[3, 30, 0]]));
+}
+
+#[test]
+fn test_excluded_and_update_filter() {
+    let mut s = setup();
+
+    // EXCLUDED is the row proposed for insertion; the WHERE filter skips row 1
+    s.run_all(&[
+        "insert into t values (1, 5, 1), (2, 25, 1), (4, 40, 1) on conflict (id) do update set n = excluded.n, m = t.m + excluded.m where t.n < excluded.n",
+    ]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 25, 1], &[4, 40, 1]]));
+
+    // the arbiter must match a unique index exactly
+    let err = error(&mut s, "insert into t values (1, 0, 0) on conflict (n) do nothing");
+    assert!(err.contains("no unique"), "{}", err);
+}
+
+#[test]
+fn test_same_statement_conflict() {
+    let mut s = setup();
+
+    // DO NOTHING skips the second proposal
+    s.run_all(&["insert into t values (6, 1, 0), (6, 2, 0) on conflict do nothing"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 20, 0], &[6, 1, 0]]));
+
+    // DO UPDATE may not change the row it inserted itself
+    let err = error(&mut s, "insert into t values (5, 1, 0), (5, 2, 0) on conflict (id) do update set n = excluded.n");
+    assert!(err.contains("cannot affect row a second time"), "{}", err);
+
+    // rolling back discards the rows the transaction inserted
+    s.run_all(&["rollback"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 20, 0]]));
+
+    // in autocommit, the failed statement leaves nothing behind
+    assert!(s.run("insert into t values (7, 1, 0), (7, 2, 0) on conflict (id) do update set n = excluded.n returning *").is_err());
+    s.run_all(&["insert into t values (1, 0, 0) on conflict (id) do update set m = 9 returning id, m"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 9], &[2, 20, 0]]));
+}
+
+#[test]
+fn test_conflict_with_concurrent_transaction() {
+    let mut s = setup();
+    s.run_all(&["insert into t values (3, 30, 0)", "delete from t where id = 2"]);
+    let first = s.switch_to(None);
+    s.run_all(&["begin"]);
+
+    // neither an in-progress insert nor an in-progress delete can be decided yet
+    let err = error(&mut s, "insert into t values (3, 0, 0) on conflict do nothing");
+    assert!(err.contains("is being inserted by transaction"), "{}", err);
+    let err = error(&mut s, "insert into t values (2, 0, 0) on conflict (id) do update set m = 1");
+    assert!(err.contains("is being changed by transaction"), "{}", err);
+    s.run_all(&["rollback"]);
+
+    // once the first transaction commits, its rows are the conflicting ones
+    s.switch_to(first);
+    s.run_all(&[
+        "commit",
+        "insert into t values (3, 0, 0), (2, 0, 0) on conflict (id) do update set m = excluded.n + 1",
+    ]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 10, 0], &[2, 0, 0], &[3, 30, 1]]));
+}
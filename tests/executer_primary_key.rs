@@ -0,0 +1,43 @@
+mod common;
+
+use common::{Session, ints};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table pairs (a int, b int, n int, primary key (a, b))",
+        "insert into pairs values (1, 1, 10), (1, 2, 20), (2, 1, 30)",
+    ]);
+    s
+}
+
+#[test]
+fn test_composite_key_is_unique_as_a_whole() {
+    let mut s = setup();
+    let err = s.run("insert into pairs values (1, 2, 99)").unwrap_err().to_string();
+    assert!(err.contains("pairs_pkey"), "{}", err);
+    // only the combination must be unique
+    s.run_all(&["insert into pairs values (1, 3, 40), (3, 2, 50)"]);
+    assert!(s.run("update pairs set b = 1 where a = 1 and b = 3").is_err());
+    // every key column is NOT NULL
+    assert!(s.run("insert into pairs values (4, null, 0)").is_err());
+    assert_eq!(s.rows("pairs"), ints(&[&[1, 1, 10], &[1, 2, 20], &[1, 3, 40], &[2, 1, 30], &[3, 2, 50]]));
+}
+
+#[test]
+fn test_foreign_key_to_composite_key() {
+    let mut s = setup();
+    s.run_all(&[
+        "create table refs (id int, a int, b int, foreign key (a, b) references pairs (a, b))",
+        "insert into refs values (1, 1, 2), (2, 2, 1)",
+    ]);
+    // (2, 2) is no key although both values occur in it
+    let err = s.run("insert into refs values (3, 2, 2)").unwrap_err().to_string();
+    assert!(err.contains("violates foreign key constraint"), "{}", err);
+    // a NULL column does not take part in the check
+    s.run_all(&["insert into refs values (3, 2, null)"]);
+
+    assert!(s.run("delete from pairs where a = 1 and b = 2").is_err());
+    s.run_all(&["delete from pairs where a = 1 and b = 1"]);
+    assert_eq!(s.rows("pairs"), ints(&[&[1, 2, 20], &[2, 1, 30]]));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{Session, ints};
+use povertygres::types::storage_types::Value::{Int, Null};
+
+fn parents_and(child: &str) -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table parents (id int primary key)",
+        child,
+        "insert into parents values (1), (2)",
+        "insert into children values (10, 1), (11, 1), (20, 2)",
+    ]);
+    s
+}
+
+#[test]
+fn test_on_delete_and_update_cascade() {
+    let mut s = parents_and(
+        "create table children (id int, p int references parents(id) on delete cascade on update cascade)",
+    );
+    s.run_all(&["delete from parents where id = 1"]);
+    assert_eq!(s.rows("children"), ints(&[&[20, 2]]));
+
+    s.run_all(&["update parents set id = 3 where id = 2"]);
+    assert_eq!(s.rows("children"), ints(&[&[20, 3]]));
+}
+
+#[test]
+fn test_on_delete_set_null_and_set_default() {
+    let mut s = parents_and(
+        "create table children (id int, p int default 2 references parents(id) on delete set default on update set null)",
+    );
+    s.run_all(&["delete from parents where id = 1"]);
+    assert_eq!(s.rows("children"), ints(&[&[10, 2], &[11, 2], &[20, 2]]));
+
+    s.run_all(&["update parents set id = 5 where id = 2"]);
+    assert_eq!(s.rows("children"), vec![vec![Int(10), Null], vec![Int(11), Null], vec![Int(20), Null]]);
+
+    // the default must itself be a valid key
+    s.run_all(&["insert into parents values (2)", "update children set p = 5 where id = 10"]);
+    assert!(s.run("delete from parents where id = 5").is_ok());
+    assert!(s.run("delete from parents where id = 2").is_err());
+}
+
+#[test]
+fn test_restrict_and_no_action() {
+    let mut s = parents_and(
+        "create table children (id int, p int references parents(id) on delete restrict)",
+    );
+    let err = s.run("delete from parents where id = 1").unwrap_err().to_string();
+    assert!(err.contains("violates foreign key constraint"), "{}", err);
+    // NO ACTION is the default for ON UPDATE
+    assert!(s.run("update parents set id = 3 where id = 2").is_err());
+    assert_eq!(s.rows("parents"), ints(&[&[1], &[2]]));
+
+    s.run_all(&["delete from children where p = 1", "delete from parents where id = 1"]);
+    assert_eq!(s.rows("parents"), ints(&[&[2]]));
+}
+
+#[test]
+fn test_cascaded_delete_rolls_back() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table a (id int primary key)",
+        "create table b (id int primary key, a_id int references a(id) on delete cascade)",
+        "create table c (id int, b_id int references b(id) on delete cascade)",
+        "insert into a values (1)",
+        "insert into b values (1, 1), (2, 1)",
+        "insert into c values (1, 1), (2, 2)",
+        "begin",
+        "delete from a",
+    ]);
+    assert!(s.rows("b").is_empty());
+    assert!(s.rows("c").is_empty());
+    s.run_all(&["rollback"]);
+    assert_eq!(s.rows("a"), ints(&[&[1]]));
+    assert_eq!(s.rows("b"), ints(&[&[1, 1], &[2, 1]]));
+    assert_eq!(s.rows("c"), ints(&[&[1, 1], &[2, 2]]));
+
+    // a RESTRICT further down fails the whole cascade
+    s.run_all(&[
+        "create table d (c_id int, b_id int references b(id) on delete restrict)",
+        "insert into d values (1, 2)",
+    ]);
+    assert!(s.run("delete from a").is_err());
+    assert_eq!(s.rows("b").len(), 2);
+    assert_eq!(s.rows("c").len(), 2);
+}
+
+#[test]
+fn test_long_and_cyclic_cascades() {
+    let mut s = Session::new();
+    s.run_all(&["create table chain (id int primary key, next int references chain(id) on delete cascade)"]);
+    // each row references the one before: deleting the first removes them all
+    let values: Vec<String> = (1..=200).map(|i| format!("({}, {})", i, i - 1)).collect();
+    s.run_all(&["insert into chain values (0, null)", &format!("insert into chain values {}", values.join(", "))]);
+    s.run_all(&["delete from chain where id = 0"]);
+    assert!(s.rows("chain").is_empty());
+
+    // a cycle ends at the rows already deleted
+    s.run_all(&[
+        "insert into chain values (1, null), (2, 1), (3, 2)",
+        "update chain set next = 3 where id = 1",
+        "delete from chain where id = 2",
+    ]);
+    assert!(s.rows("chain").is_empty());
+}
+
+#[test]
+fn test_self_referencing_actions_see_the_whole_statement() {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table n (id int primary key, p int references n(id) on delete set null)",
+        "create table d (id int primary key, p int default 0)",
+        "insert into n values (1, null), (2, 1), (3, 2)",
+        "insert into d values (0, 0), (1, 0), (2, 1), (3, 1)",
+        "alter table d add foreign key (p) references d(id) on delete set default",
+    ]);
+    // rows the statement deletes itself are not set to NULL and kept
+    s.run_all(&["delete from n"]);
+    assert!(s.rows("n").is_empty());
+    s.run_all(&["delete from d where id > 0 and id < 3"]);
+    assert_eq!(s.rows("d"), ints(&[&[0, 0], &[3, 0]]));
+
+    // every row is updated once, and the cascade follows its new version
+    s.run_all(&[
+        "create table u (id int primary key, p int references u(id) on update cascade)",
+        "insert into u values (1, null), (2, 1), (3, 2)",
+        "update u set id = id + 10",
+    ]);
+    assert_eq!(s.rows("u"), vec![vec![Int(11), Null], vec![Int(12), Int(11)], vec![Int(13), Int(12)]]);
+
+    // NO ACTION sees the referencing row's new version, which still holds the old key
+    s.run_all(&[
+        "create table r (id int primary key, p int references r(id))",
+        "insert into r values (1, null), (2, 1)",
+    ]);
+    assert!(s.run("update r set id = id + 10").is_err());
+    s.run_all(&["update r set id = id + 10, p = p + 10", "delete from r"]);
+    assert!(s.rows("r").is_empty());
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{Session, ints};
+use povertygres::consts::catalog_consts::{CATALOG_FILE, DATA_DIR};
+use std::collections::BTreeMap;
+
+/// Everything DDL may change: the in-memory schema, the schema part of
+/// catalog.json and the bytes of every data file
+#[derive(Debug, PartialEq)]
+struct State {
+    tables: Vec<String>,
+    indexes: Vec<String>,
+    catalog: serde_json::Value,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+fn state(s: &Session) -> State {
+    let db = &s.engine.db;
+    let mut tables: Vec<String> = db
+        .tables
+        .values()
+        .map(|t| {
+            // schema_version only ever grows, even across a rollback
+            format!(
+                "{} {:?} {:?} {:?} {:?} {:?} {:?}",
+                t.name, t.columns, t.primary_key, t.foreign_keys, t.checks, t.heap.path, t.heap.attributes
+            )
+        })
+        .collect();
+    tables.sort();
+    let mut indexes: Vec<String> = db
+        .indexes
+        .values()
+        .map(|i| format!("{} {} {:?} {}", i.name, i.table, i.columns, i.unique))
+        .collect();
+    indexes.sort();
+
+    let dir = s.dir.path().join(DATA_DIR);
+    let text = std::fs::read_to_string(dir.join(CATALOG_FILE)).unwrap();
+    let mut catalog: serde_json::Value = serde_json::from_str(&text).unwrap();
+    for key in ["transactions", "next_xid", "next_table_oid"] {
+        catalog.as_object_mut().unwrap().remove(key);
+    }
+
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        if name != CATALOG_FILE && !name.ends_with(".tmp") {
+            files.insert(name, std::fs::read(&path).unwrap());
+        }
+    }
+    State { tables, indexes, catalog, files }
+}
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table a (id int primary key, n int)",
+        "create table b (id int, a_id int references a(id))",
+        "create index b_a_idx on b (a_id)",
+        "insert into a values (1, 10), (2, 20)",
+        "insert into b values (1, 1)",
+    ]);
+    s
+}
+
+#[test]
+fn test_rolled_back_ddl_leaves_no_trace() {
+    let statements: &[&[&str]] = &[
+        &["create table c (id int, a_id int references a(id))", "insert into c values (1, 2)"],
+        &["drop table b", "drop table a"],
+        &["alter table a add column m int default 5", "alter table a drop column n"],
+        &["alter table a rename to z", "alter table b rename column a_id to z_id"],
+        &["alter table b add constraint b_id_key unique (id)", "create index a_n_idx on a (n)"],
+        &["truncate a cascade", "insert into a values (3, 30)"],
+        &["drop index b_a_idx", "truncate b", "create table a2 (id int)", "drop table a2"],
+    ];
+    for ddl in statements {
+        let mut s = setup();
+        let before = state(&s);
+        assert!(before.files.contains_key("a.tbl") && before.files.contains_key("b.tbl"));
+        s.run_all(&["begin"]);
+        s.run_all(ddl);
+        s.run_all(&["rollback"]);
+        assert_eq!(state(&s), before, "{:?}", ddl);
+        assert_eq!(s.rows("a"), ints(&[&[1, 10], &[2, 20]]));
+        assert_eq!(s.rows("b"), ints(&[&[1, 1]]));
+
+        // the schema survives a restart unchanged as well
+        s.reopen();
+        assert_eq!(state(&s), before, "{:?} after reopening", ddl);
+    }
+}
+
+#[test]
+fn test_committed_ddl_reaches_the_catalog() {
+    let mut s = setup();
+    let before = state(&s);
+    s.run_all(&["begin", "alter table a add column m int default 5"]);
+    // DDL is written to catalog.json at COMMIT only
+    assert_eq!(state(&s).catalog, before.catalog);
+    s.run_all(&["commit"]);
+    assert_ne!(state(&s).catalog, before.catalog);
+    assert_eq!(s.rows("a"), ints(&[&[1, 10, 5], &[2, 20, 5]]));
+}
+
+#[test]
+fn test_failed_ddl_outside_transaction_leaves_no_file() {
+    let mut s = setup();
+    let before = state(&s);
+    let failing = [
+        "create table c (id int references missing(id))",
+        "create table c (id int, check (missing > 0))",
+        "create table c (id int default 'x')",
+        "create table c (id int, id int)",
+        "create table a (id int)",
+    ];
+    for sql in failing {
+        assert!(s.run(sql).is_err(), "{}", sql);
+        assert_eq!(state(&s), before, "{}", sql);
+        assert!(!s.dir.path().join(DATA_DIR).join("c.tbl").exists(), "{}", sql);
+    }
+}
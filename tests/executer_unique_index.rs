@@ -0,0 +1,90 @@
+mod common;
+
+use common::{Session, ints};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&["create table t (id int, n int)", "create unique index t_id_key on t (id)"]);
+    s
+}
+
+/// Open a transaction next to the current one and return it; the previous one stays open
+fn begin_other(s: &mut Session) -> Option<u32> {
+    let previous = s.switch_to(None);
+    s.run_all(&["begin"]);
+    s.switch_to(previous)
+}
+
+#[test]
+fn test_concurrent_inserters_cannot_both_succeed() {
+    let mut s = setup();
+    s.run_all(&["begin", "insert into t values (1, 0)"]);
+    let second = begin_other(&mut s);
+
+    let first = s.switch_to(second);
+    let err = s.run("insert into t values (1, 0)").unwrap_err().to_string();
+    assert!(err.contains("t_id_key"), "{}", err);
+
+    // still a duplicate once the first inserter commits
+    let second = s.switch_to(first);
+    s.run_all(&["commit"]);
+    s.switch_to(second);
+    assert!(s.run("insert into t values (1, 0)").is_err());
+
+    // the same transaction cannot insert the key twice either
+    s.run_all(&["insert into t values (2, 0)"]);
+    assert!(s.run("insert into t values (2, 0)").is_err());
+    assert!(s.run("insert into t values (3, 0), (3, 1)").is_err());
+    s.run_all(&["commit"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 0], &[2, 0]]));
+}
+
+#[test]
+fn test_reinsert_after_committed_delete() {
+    let mut s = setup();
+    s.run_all(&["insert into t values (1, 0)", "delete from t where id = 1", "begin", "insert into t values (1, 1)"]);
+
+    // a rolled-back inserter frees the key as well
+    let aborted = begin_other(&mut s);
+    let xid = s.switch_to(aborted);
+    s.run_all(&["insert into t values (2, 0)", "rollback"]);
+    s.switch_to(xid);
+    s.run_all(&["insert into t values (2, 1)", "commit"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 1], &[2, 1]]));
+}
+
+#[test]
+fn test_reinsert_after_in_progress_delete() {
+    let mut s = setup();
+    s.run_all(&["insert into t values (1, 0)", "begin", "delete from t where id = 1"]);
+
+    // the deleter may still roll back, which would make the row live again
+    let other = begin_other(&mut s);
+    let deleter = s.switch_to(other);
+    assert!(s.run("insert into t values (1, 1)").is_err());
+    s.run_all(&["rollback"]);
+
+    // the deleting transaction itself may reuse the key
+    s.switch_to(deleter);
+    s.run_all(&["insert into t values (1, 2)", "commit"]);
+    assert_eq!(s.rows("t"), ints(&[&[1, 2]]));
+}
+
+#[test]
+fn test_create_unique_index_rejects_duplicates() {
+    let mut s = Session::new();
+    s.run_all(&["create table t (id int, n int)", "insert into t values (1, 0), (1, 1)"]);
+
+    let err = s.run("create unique index t_id_key on t (id)").unwrap_err().to_string();
+    assert!(err.contains("duplicate key"), "{}", err);
+    assert!(s.engine.db.indexes.is_empty());
+
+    // a non-unique index is fine, and so is a unique one once a duplicate is deleted
+    s.run_all(&[
+        "create index t_id_idx on t (id)",
+        "delete from t where n = 1",
+        "create unique index t_id_key on t (id)",
+    ]);
+    assert!(s.run("insert into t values (1, 2)").is_err());
+    assert_eq!(s.rows("t"), ints(&[&[1, 0]]));
+}
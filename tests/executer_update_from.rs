@@ -0,0 +1,57 @@
+mod common;
+
+use common::{Session, ints};
+
+fn setup() -> Session {
+    let mut s = Session::new();
+    s.run_all(&[
+        "create table items (id int primary key, price int, stock int)",
+        "create table changes (item int, delta int)",
+        "insert into items values (1, 100, 5), (2, 200, 0), (3, 300, 7)",
+        "insert into changes values (1, 10), (1, 20), (3, -5), (9, 1)",
+    ]);
+    s
+}
+
+#[test]
+fn test_update_from() {
+    let mut s = setup();
+    s.run_all(&["begin"]);
+    // item 1 matches two rows of changes: it is updated once, with the first match
+    let returned = s
+        .returned("update items set price = items.price + c.delta from changes c where items.id = c.item")
+        .unwrap();
+    assert_eq!(returned, ints(&[&[1, 110, 5], &[3, 295, 7]]));
+    assert_eq!(s.rows("items"), ints(&[&[1, 110, 5], &[2, 200, 0], &[3, 295, 7]]));
+
+    // no joined row: nothing changes
+    let returned = s.returned("update items set stock = 0 from changes where changes.item = items.id + 100").unwrap();
+    assert!(returned.is_empty());
+    s.run_all(&["commit"]);
+    assert_eq!(s.rows("items"), ints(&[&[1, 110, 5], &[2, 200, 0], &[3, 295, 7]]));
+
+    // RETURNING through the executor names columns of the target table
+    s.run_all(&["update items set stock = stock + 1 from changes where changes.item = items.id returning id, stock"]);
+    assert_eq!(s.rows("items"), ints(&[&[1, 110, 6], &[2, 200, 0], &[3, 295, 8]]));
+    assert!(s.run("update items set stock = 0 from changes where changes.item = items.id returning delta").is_err());
+    assert_eq!(s.rows("items"), ints(&[&[1, 110, 6], &[2, 200, 0], &[3, 295, 8]]));
+}
+
+#[test]
+fn test_delete_using() {
+    let mut s = setup();
+    s.run_all(&["begin"]);
+    // each row is deleted once, however many rows it joins
+    let returned = s.returned("delete from items using changes where changes.item = items.id").unwrap();
+    assert_eq!(returned, ints(&[&[1, 100, 5], &[3, 300, 7]]));
+    assert_eq!(s.rows("items"), ints(&[&[2, 200, 0]]));
+
+    let returned = s.returned("delete from items using changes c where c.item = items.id and c.delta > 100").unwrap();
+    assert!(returned.is_empty());
+    s.run_all(&["rollback"]);
+
+    s.run_all(&["delete from items using changes c where c.item = items.id and c.delta < 0 returning *"]);
+    assert_eq!(s.rows("items"), ints(&[&[1, 100, 5], &[2, 200, 0]]));
+    assert!(s.run("delete from items using changes where changes.item = items.id returning nope").is_err());
+    assert_eq!(s.rows("items"), ints(&[&[1, 100, 5], &[2, 200, 0]]));
+}
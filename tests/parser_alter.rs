@@ -0,0 +1,109 @@
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::{AlterAction, Operand, Query, TableConstraint};
+use povertygres::types::storage_types::{Deferrable, Value};
+
+fn action(sql: &str) -> AlterAction {
+    match parse_query(sql).unwrap() {
+        Query::AlterTable { action, .. } => action,
+        other => panic!("Expected AlterTable, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_add_column_with_default() {
+    let q = parse_query("ALTER TABLE users ADD COLUMN age INT NOT NULL DEFAULT 18").unwrap();
+    let Query::AlterTable { table_name, action } = q else {
+        panic!("Expected AlterTable");
+    };
+    assert_eq!(table_name, "users");
+    let AlterAction::AddColumn { column, constraints } = action else {
+        panic!("Expected AddColumn");
+    };
+    assert_eq!(column.name, "age");
+    assert!(column.not_null);
+    assert_eq!(column.default, Some(Operand::Literal(Value::Int(18))));
+    assert!(constraints.is_empty());
+}
+
+#[test]
+fn test_add_column_with_constraints() {
+    let AlterAction::AddColumn { column, constraints } =
+        action("alter table t add email text unique check (email != \"\")")
+    else {
+        panic!("Expected AddColumn");
+    };
+    assert_eq!(column.name, "email");
+    assert_eq!(constraints.len(), 2);
+    assert!(matches!(&constraints[0], TableConstraint::Unique { columns, .. } if columns == &vec!["email".to_string()]));
+    assert!(matches!(&constraints[1], TableConstraint::Check(c) if c.name == "t_email_check"));
+}
+
+#[test]
+fn test_drop_and_rename() {
+    assert!(matches!(action("alter table t drop column a"), AlterAction::DropColumn(c) if c == "a"));
+    assert!(matches!(action("alter table t drop a"), AlterAction::DropColumn(c) if c == "a"));
+    assert!(matches!(action("alter table t drop constraint t_pkey"), AlterAction::DropConstraint(c) if c == "t_pkey"));
+    assert!(matches!(action("alter table t rename to u"), AlterAction::RenameTable(n) if n == "u"));
+    assert!(matches!(
+        action("alter table t rename column a to b"),
+        AlterAction::RenameColumn { from, to } if from == "a" && to == "b"
+    ));
+    assert!(matches!(
+        action("alter table t rename a to b"),
+        AlterAction::RenameColumn { from, to } if from == "a" && to == "b"
+    ));
+}
+
+#[test]
+fn test_alter_column() {
+    assert!(matches!(action("alter table t alter column a set not null"), AlterAction::SetNotNull(c) if c == "a"));
+    assert!(matches!(action("alter table t alter a drop not null"), AlterAction::DropNotNull(c) if c == "a"));
+    assert!(matches!(
+        action("alter table t alter column a set default \"x y\""),
+        AlterAction::SetDefault { column, default: Some(Operand::Literal(Value::Text(v))) } if column == "a" && v == "x y"
+    ));
+    assert!(matches!(
+        action("alter table t alter column a drop default"),
+        AlterAction::SetDefault { default: None, .. }
+    ));
+}
+
+#[test]
+fn test_add_constraint() {
+    assert!(matches!(
+        action("alter table t add constraint pos check (a > 0)"),
+        AlterAction::AddConstraint(TableConstraint::Check(c)) if c.name == "pos"
+    ));
+    assert!(matches!(
+        action("alter table t add primary key (a, b)"),
+        AlterAction::AddConstraint(TableConstraint::PrimaryKey { name: None, columns, .. }) if columns.len() == 2
+    ));
+    assert!(matches!(
+        action("alter table t add constraint t_uq unique (a) deferrable initially deferred"),
+        AlterAction::AddConstraint(TableConstraint::Unique { name: Some(n), deferrable: Deferrable::InitiallyDeferred, .. }) if n == "t_uq"
+    ));
+    let AlterAction::AddConstraint(TableConstraint::ForeignKey(fk)) =
+        action("alter table t add foreign key (pid) references p(id) on delete cascade")
+    else {
+        panic!("Expected foreign key");
+    };
+    assert_eq!(fk.referenced_table, "p");
+    assert_eq!(fk.local_columns, vec!["pid".to_string()]);
+    assert_eq!(fk.name, None);
+    let AlterAction::AddConstraint(TableConstraint::ForeignKey(fk)) =
+        action("alter table t add constraint t_parent foreign key (pid) references p(id) deferrable")
+    else {
+        panic!("Expected foreign key");
+    };
+    assert_eq!(fk.name.as_deref(), Some("t_parent"));
+    assert_eq!(fk.deferrable, Deferrable::InitiallyImmediate);
+}
+
+#[test]
+fn test_invalid_alter() {
+    assert!(parse_query("alter table t").is_err());
+    assert!(parse_query("alter table t drop").is_err());
+    assert!(parse_query("alter table t rename column a b").is_err());
+    assert!(parse_query("alter table t alter column a set null").is_err());
+    assert!(parse_query("alter table t frobnicate").is_err());
+}
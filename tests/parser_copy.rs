@@ -0,0 +1,99 @@
+use povertygres::executer::copy::{decode_records, encode_record};
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::{CopyFormat, CopySource, Query};
+
+#[test]
+fn test_copy_from_defaults_to_text() {
+    let q = parse_query("copy users (id, name) from '/tmp/users.txt'").unwrap();
+    if let Query::Copy {
+        source: CopySource::Table {
+            table_name,
+            column_names,
+        },
+        to_file,
+        path,
+        options,
+        ..
+    } = q
+    {
+        assert_eq!(table_name, "users");
+        assert_eq!(column_names, Some(vec!["id".to_string(), "name".to_string()]));
+        assert!(!to_file);
+        assert_eq!(path.as_deref(), Some("/tmp/users.txt"));
+        assert_eq!(options.format, CopyFormat::Text);
+        assert_eq!(options.delimiter, '\t');
+        assert_eq!(options.null, "\\N");
+        assert!(!options.header);
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
+
+#[test]
+fn test_copy_to_csv_options() {
+    let q = parse_query(
+        "copy users to '/tmp/u.csv' with (format csv, header, delimiter ';', quote '''', null 'NA')",
+    )
+    .unwrap();
+    if let Query::Copy { to_file, options, .. } = q {
+        assert!(to_file);
+        assert_eq!(options.format, CopyFormat::Csv);
+        assert!(options.header);
+        assert_eq!(options.delimiter, ';');
+        assert_eq!(options.quote, '\'');
+        assert_eq!(options.null, "NA");
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
+
+#[test]
+fn test_copy_select_to() {
+    let q = parse_query("copy (select id from users where id > 1) to '/tmp/ids.txt'").unwrap();
+    if let Query::Copy {
+        source: CopySource::Query(select),
+        to_file,
+        ..
+    } = q
+    {
+        assert!(to_file);
+        assert!(matches!(*select, Query::Select { filter: Some(_), .. }));
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
+
+#[test]
+fn test_copy_select_from_is_error() {
+    assert!(parse_query("copy (select id from users) from '/tmp/ids.txt'").is_err());
+    assert!(parse_query("copy users from '/tmp/x' with (format xml)").is_err());
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let Query::Copy { options, .. } =
+        parse_query("copy t to '/tmp/t.csv' with (format csv)").unwrap()
+    else {
+        panic!("Expected COPY");
+    };
+    let record = vec![
+        Some("a, \"b\"".to_string()),
+        None,
+        Some(String::new()),
+        Some("two\nlines".to_string()),
+    ];
+    let line = encode_record(&record, &options);
+    assert_eq!(line, "\"a, \"\"b\"\"\",,\"\",\"two\nlines\"");
+    assert_eq!(decode_records(&format!("{}\n", line), &options).unwrap(), vec![record]);
+}
+
+#[test]
+fn test_text_round_trip() {
+    let Query::Copy { options, .. } = parse_query("copy t to '/tmp/t.txt'").unwrap() else {
+        panic!("Expected COPY");
+    };
+    let record = vec![Some("tab\there\\".to_string()), None];
+    let line = encode_record(&record, &options);
+    assert_eq!(line, "tab\\there\\\\\t\\N");
+    assert_eq!(decode_records(&line, &options).unwrap(), vec![record]);
+}
@@ -1,6 +1,6 @@
 use povertygres::parser::main::parse_query;
-use povertygres::types::parser_types::Query;
-use povertygres::types::storage_types::{ColumnType, Value};
+use povertygres::types::parser_types::{Operand, Query};
+use povertygres::types::storage_types::{ColumnType, Deferrable, FkAction, Value};
 
 #[test]
 fn test_basic_create() {
@@ -10,9 +10,15 @@ fn test_basic_create() {
         columns,
         primary_key,
         foreign_keys,
+        unique_constraints,
+        checks,
+        deferrable_keys,
+        if_not_exists,
+        sequences,
     } = query
     {
         assert_eq!(table_name, "users");
+        assert!(!if_not_exists);
         assert_eq!(columns.len(), 2);
         assert_eq!(columns[0].name, "id");
         assert_eq!(columns[0].column_type, ColumnType::Int);
@@ -20,6 +26,10 @@ fn test_basic_create() {
         assert_eq!(columns[1].column_type, ColumnType::Text);
         assert!(primary_key.is_none());
         assert!(foreign_keys.is_empty());
+        assert!(checks.is_empty());
+        assert!(deferrable_keys.is_empty());
+        assert!(unique_constraints.is_empty());
+        assert!(sequences.is_empty());
     } else {
         panic!("Unexpected query variant");
     }
@@ -34,7 +44,7 @@ fn test_primary_key_inline() {
         ..
     } = query
     {
-        assert_eq!(primary_key, Some("id".to_string()));
+        assert_eq!(primary_key, Some(vec!["id".to_string()]));
         assert!(columns[0].not_null); // PK всегда NOT NULL
     } else {
         panic!("Unexpected query variant");
@@ -45,7 +55,7 @@ fn test_primary_key_inline() {
 fn test_primary_key_table_level() {
     let query = parse_query("create table users (id int, name text, primary key id)").unwrap();
     if let Query::CreateTable { primary_key, .. } = query {
-        assert_eq!(primary_key, Some("id".to_string()));
+        assert_eq!(primary_key, Some(vec!["id".to_string()]));
     } else {
         panic!("Unexpected query variant");
     }
@@ -59,9 +69,9 @@ fn test_not_null_and_default() {
     .unwrap();
     if let Query::CreateTable { columns, .. } = query {
         assert!(columns[0].not_null);
-        assert_eq!(columns[0].default, Some(Value::Int(5)));
-        assert_eq!(columns[1].default, Some(Value::Bool(true)));
-        assert_eq!(columns[2].default, Some(Value::Text("hi".to_string())));
+        assert_eq!(columns[0].default, Some(Operand::Literal(Value::Int(5))));
+        assert_eq!(columns[1].default, Some(Operand::Literal(Value::Bool(true))));
+        assert_eq!(columns[2].default, Some(Operand::Literal(Value::Text("hi".to_string()))));
     }
 }
 
@@ -91,10 +101,103 @@ fn test_foreign_key_table_level() {
     }
 }
 
+#[test]
+fn test_foreign_key_actions_inline() {
+    let query = parse_query(
+        "create table orders (id int, user_id int references users(id) on delete cascade on update set null)",
+    )
+    .unwrap();
+    if let Query::CreateTable { foreign_keys, .. } = query {
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].on_delete, FkAction::Cascade);
+        assert_eq!(foreign_keys[0].on_update, FkAction::SetNull);
+    } else {
+        panic!("expected CreateTable");
+    }
+}
+
+#[test]
+fn test_composite_foreign_key_with_actions() {
+    let query = parse_query(
+        "create table items (a int, b int, foreign key (a, b) references pairs(x, y) on delete set default on update restrict)",
+    )
+    .unwrap();
+    if let Query::CreateTable { foreign_keys, .. } = query {
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].local_columns, vec!["a", "b"]);
+        assert_eq!(foreign_keys[0].referenced_table, "pairs");
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["x", "y"]);
+        assert_eq!(foreign_keys[0].on_delete, FkAction::SetDefault);
+        assert_eq!(foreign_keys[0].on_update, FkAction::Restrict);
+    } else {
+        panic!("expected CreateTable");
+    }
+}
+
+#[test]
+fn test_foreign_key_action_invalid() {
+    let res = parse_query("create table t (a int references p(id) on delete explode)");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_check_constraints_named() {
+    let query = parse_query(
+        "create table acc (id int, bal int check (bal >= 0), lo int, hi int, constraint range_ok check (lo <= hi), check (id > 0), check (id != 7))",
+    )
+    .unwrap();
+    if let Query::CreateTable { columns, checks, .. } = query {
+        assert_eq!(columns.len(), 4);
+        let names: Vec<&str> = checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["acc_bal_check", "range_ok", "acc_check", "acc_check1"]);
+    } else {
+        panic!("expected CreateTable");
+    }
+}
+
+#[test]
+fn test_check_unknown_column() {
+    let res = parse_query("create table t (a int, check (b > 0))");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_deferrable_constraints() {
+    let query = parse_query(
+        "create table t (id int primary key deferrable, code int unique deferrable initially deferred, p int references parents(id) deferrable initially deferred, q int, r int, unique (q, r) not deferrable, foreign key (q) references parents(id) on delete cascade deferrable)",
+    )
+    .unwrap();
+    if let Query::CreateTable { foreign_keys, deferrable_keys, .. } = query {
+        assert_eq!(
+            deferrable_keys,
+            vec![
+                (vec!["id".to_string()], Deferrable::InitiallyImmediate),
+                (vec!["code".to_string()], Deferrable::InitiallyDeferred),
+                (vec!["q".to_string(), "r".to_string()], Deferrable::NotDeferrable),
+            ]
+        );
+        assert_eq!(foreign_keys[0].deferrable, Deferrable::InitiallyDeferred);
+        assert_eq!(foreign_keys[1].on_delete, FkAction::Cascade);
+        assert_eq!(foreign_keys[1].deferrable, Deferrable::InitiallyImmediate);
+    } else {
+        panic!("expected CreateTable");
+    }
+}
+
+#[test]
+fn test_not_deferrable_initially_deferred_rejected() {
+    let res = parse_query(
+        "create table t (p int references parents(id) not deferrable initially deferred)",
+    );
+    assert!(res.is_err());
+}
+
 #[test]
 fn test_invalid_type() {
-    let res = parse_query("create table t (x float)");
+    // an unknown name may be a user-defined type, but those take no modifiers
+    let res = parse_query("create table t (x money(2))");
     assert!(res.is_err());
+    assert!(parse_query("create table t (x numeric(1, 2, 3))").is_err());
 }
 
 #[test]
@@ -102,3 +205,173 @@ fn test_invalid_syntax_missing_paren() {
     let res = parse_query("create table t id int, name text)");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_unique_column_and_table_level() {
+    let query = parse_query(
+        "create table users (id int, email text unique, a int, b int, unique (a, b))",
+    )
+    .unwrap();
+    if let Query::CreateTable {
+        columns,
+        unique_constraints,
+        ..
+    } = query
+    {
+        assert_eq!(columns.len(), 4);
+        assert_eq!(
+            unique_constraints,
+            vec![
+                vec!["email".to_string()],
+                vec!["a".to_string(), "b".to_string()]
+            ]
+        );
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_composite_primary_key() {
+    let query =
+        parse_query("create table m (a int, b text, c int, primary key (a, b))").unwrap();
+    if let Query::CreateTable {
+        primary_key,
+        columns,
+        ..
+    } = query
+    {
+        assert_eq!(primary_key, Some(vec!["a".to_string(), "b".to_string()]));
+        assert!(columns[0].not_null);
+        assert!(columns[1].not_null);
+        assert!(!columns[2].not_null);
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_multiple_primary_keys_rejected() {
+    let res = parse_query("create table m (a int primary key, b int, primary key (a, b))");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_primary_key_unknown_column() {
+    let res = parse_query("create table m (a int, primary key (a, z))");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_create_table_if_not_exists() {
+    let query = parse_query("CREATE TABLE IF NOT EXISTS users (id int)").unwrap();
+    if let Query::CreateTable {
+        table_name,
+        if_not_exists,
+        ..
+    } = query
+    {
+        assert_eq!(table_name, "users");
+        assert!(if_not_exists);
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_integer_widths() {
+    let query = parse_query("create table t (a smallint, b integer, c bigint, d int8 default -5)").unwrap();
+    let Query::CreateTable { columns, .. } = query else {
+        panic!("Unexpected query variant");
+    };
+    let types: Vec<ColumnType> = columns.into_iter().map(|c| c.column_type).collect();
+    assert_eq!(
+        types,
+        vec![ColumnType::SmallInt, ColumnType::Int, ColumnType::BigInt, ColumnType::BigInt]
+    );
+}
+
+#[test]
+fn test_default_must_fit_integer_width() {
+    assert!(parse_query("create table t (a smallint default 32768)").is_err());
+    assert!(parse_query("create table t (a smallint default -32768)").is_ok());
+    assert!(parse_query("create table t (a int default 2147483648)").is_err());
+    assert!(ColumnType::Int.coerce(Value::Int(i32::MIN as i64)).is_ok());
+    assert!(ColumnType::Int.coerce(Value::Int(i32::MIN as i64 - 1)).is_err());
+}
+
+#[test]
+fn test_float_and_numeric_types() {
+    let query = parse_query(
+        "create table t (a real, b double precision, c float8, d numeric(10, 2), e decimal, f numeric(5) default 1.5)",
+    )
+    .unwrap();
+    let Query::CreateTable { columns, .. } = query else {
+        panic!("Unexpected query variant");
+    };
+    let types: Vec<ColumnType> = columns.into_iter().map(|c| c.column_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            ColumnType::Real,
+            ColumnType::Double,
+            ColumnType::Double,
+            ColumnType::Numeric(Some((10, 2))),
+            ColumnType::Numeric(None),
+            ColumnType::Numeric(Some((5, 0))),
+        ]
+    );
+    assert!(parse_query("create table t (a numeric(2, 3))").is_err());
+    assert!(parse_query("create table t (a numeric(3, 1) default 100)").is_err());
+}
+
+#[test]
+fn test_date_time_types() {
+    let query = parse_query(
+        r#"create table t (a date, b time, c timestamp without time zone, d timestamp with time zone, e timestamptz, f interval, g date default "2024-01-31")"#,
+    )
+    .unwrap();
+    let Query::CreateTable { columns, .. } = query else {
+        panic!("Unexpected query variant");
+    };
+    assert_eq!(columns[6].default, Some(Operand::Literal(Value::Date(19753))));
+    let types: Vec<ColumnType> = columns.into_iter().map(|c| c.column_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            ColumnType::Date,
+            ColumnType::Time,
+            ColumnType::Timestamp,
+            ColumnType::TimestampTz,
+            ColumnType::TimestampTz,
+            ColumnType::Interval,
+            ColumnType::Date,
+        ]
+    );
+    assert!(parse_query(r#"create table t (a date default "yesterday-ish")"#).is_err());
+}
+
+#[test]
+fn test_string_and_bytea_types() {
+    let query = parse_query(
+        "create table t (a varchar(10), b character varying (3), c character varying, d char(4), e character, f bytea)",
+    )
+    .unwrap();
+    let Query::CreateTable { columns, .. } = query else {
+        panic!("Unexpected query variant");
+    };
+    let types: Vec<ColumnType> = columns.into_iter().map(|c| c.column_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            ColumnType::Varchar(Some(10)),
+            ColumnType::Varchar(Some(3)),
+            ColumnType::Varchar(None),
+            ColumnType::Char(4),
+            ColumnType::Char(1),
+            ColumnType::Bytea,
+        ]
+    );
+    assert!(parse_query("create table t (a varchar(0))").is_err());
+    assert!(parse_query(r#"create table t (a char(2) default "abc")"#).is_err());
+}
@@ -1,12 +1,12 @@
 use povertygres::parser::main::parse_query;
 use povertygres::types::filter_types::CmpOp;
-use povertygres::types::parser_types::{Condition, Operand, Query};
+use povertygres::types::parser_types::{Condition, FromItem, Operand, Query};
 use povertygres::types::storage_types::Value;
 
 #[test]
 fn test_delete_basic() {
     let q = parse_query("delete from users").unwrap();
-    if let Query::Delete { table_name, filter } = q {
+    if let Query::Delete { table_name, filter, .. } = q {
         assert_eq!(table_name, "users");
         assert!(filter.is_none());
     } else {
@@ -17,7 +17,7 @@ fn test_delete_basic() {
 #[test]
 fn test_delete_with_filter() {
     let q = parse_query("delete from users where age > 18").unwrap();
-    if let Query::Delete { table_name, filter } = q {
+    if let Query::Delete { table_name, filter, .. } = q {
         assert_eq!(table_name, "users");
         let cond = filter.expect("Expected filter");
         match cond {
@@ -35,3 +35,37 @@ fn test_delete_missing_from() {
     let res = parse_query("delete users");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_delete_returning() {
+    let q = parse_query("delete from users where id = 1 returning *").unwrap();
+    if let Query::Delete { filter, returning, .. } = q {
+        assert!(filter.is_some());
+        assert_eq!(returning, Some(vec!["*".to_string()]));
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
+
+#[test]
+fn test_delete_using_join() {
+    let q = parse_query(
+        "delete from orders using users u join bans b on u.id = b.user_id where orders.user_id = u.id",
+    )
+    .unwrap();
+    if let Query::Delete {
+        table_name,
+        using,
+        aliases,
+        filter,
+        ..
+    } = q
+    {
+        assert_eq!(table_name, "orders");
+        assert!(matches!(using, Some(FromItem::Join { .. })));
+        assert_eq!(aliases.get("b"), Some(&"bans".to_string()));
+        assert!(filter.is_some());
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
@@ -0,0 +1,60 @@
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::Query;
+
+#[test]
+fn test_drop_table() {
+    let q = parse_query("DROP TABLE users").unwrap();
+    if let Query::DropTable { table_names, if_exists, cascade } = q {
+        assert_eq!(table_names, vec!["users".to_string()]);
+        assert!(!if_exists);
+        assert!(!cascade);
+    } else {
+        panic!("Expected DropTable");
+    }
+}
+
+#[test]
+fn test_drop_table_if_exists_cascade() {
+    let q = parse_query("drop table if exists a, b CASCADE").unwrap();
+    if let Query::DropTable { table_names, if_exists, cascade } = q {
+        assert_eq!(table_names, vec!["a".to_string(), "b".to_string()]);
+        assert!(if_exists);
+        assert!(cascade);
+    } else {
+        panic!("Expected DropTable");
+    }
+    let q = parse_query("drop table a restrict").unwrap();
+    assert!(matches!(q, Query::DropTable { cascade: false, .. }));
+}
+
+#[test]
+fn test_drop_index() {
+    let q = parse_query("drop index if exists users_name_idx").unwrap();
+    if let Query::DropIndex { index_name, if_exists } = q {
+        assert_eq!(index_name, "users_name_idx");
+        assert!(if_exists);
+    } else {
+        panic!("Expected DropIndex");
+    }
+    assert!(parse_query("drop index a, b").is_err());
+}
+
+#[test]
+fn test_truncate() {
+    let q = parse_query("TRUNCATE TABLE orders, items CASCADE").unwrap();
+    if let Query::Truncate { table_names, cascade } = q {
+        assert_eq!(table_names, vec!["orders".to_string(), "items".to_string()]);
+        assert!(cascade);
+    } else {
+        panic!("Expected Truncate");
+    }
+    let q = parse_query("truncate orders").unwrap();
+    assert!(matches!(q, Query::Truncate { cascade: false, .. }));
+}
+
+#[test]
+fn test_invalid_drop() {
+    assert!(parse_query("drop table").is_err());
+    assert!(parse_query("drop table a b").is_err());
+    assert!(parse_query("drop view v").is_err());
+}
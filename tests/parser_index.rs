@@ -8,11 +8,14 @@ fn test_create_index_with_name() {
         index_name,
         table_name,
         column_names,
+        unique,
+        ..
     } = q
     {
         assert_eq!(index_name, "idx_users_id");
         assert_eq!(table_name, "users");
         assert_eq!(column_names, vec!["id"]);
+        assert!(!unique);
     } else {
         panic!("Expected CreateIndex");
     }
@@ -25,11 +28,14 @@ fn test_create_index_auto_name_single_col() {
         index_name,
         table_name,
         column_names,
+        unique,
+        ..
     } = q
     {
         assert_eq!(index_name, "users_id_idx");
         assert_eq!(table_name, "users");
         assert_eq!(column_names, vec!["id"]);
+        assert!(!unique);
     } else {
         panic!("Expected CreateIndex");
     }
@@ -42,11 +48,66 @@ fn test_create_index_auto_name_multi_col() {
         index_name,
         table_name,
         column_names,
+        unique,
+        ..
     } = q
     {
         assert_eq!(index_name, "users_id_name_idx");
         assert_eq!(table_name, "users");
         assert_eq!(column_names, vec!["id", "name"]);
+        assert!(!unique);
+    } else {
+        panic!("Expected CreateIndex");
+    }
+}
+
+#[test]
+fn test_create_unique_index() {
+    let q = parse_query("create unique index users_email_key on users(email)").unwrap();
+    if let Query::CreateIndex {
+        index_name,
+        table_name,
+        column_names,
+        unique,
+        ..
+    } = q
+    {
+        assert_eq!(index_name, "users_email_key");
+        assert_eq!(table_name, "users");
+        assert_eq!(column_names, vec!["email"]);
+        assert!(unique);
+    } else {
+        panic!("Expected CreateIndex");
+    }
+}
+
+#[test]
+fn test_create_unique_index_auto_name() {
+    let q = parse_query("create unique index on users(a, b)").unwrap();
+    if let Query::CreateIndex {
+        index_name, unique, ..
+    } = q
+    {
+        assert_eq!(index_name, "users_a_b_idx");
+        assert!(unique);
+    } else {
+        panic!("Expected CreateIndex");
+    }
+}
+
+#[test]
+fn test_create_index_if_not_exists() {
+    let q = parse_query("create unique index if not exists users_email on users(email)").unwrap();
+    if let Query::CreateIndex {
+        index_name,
+        if_not_exists,
+        unique,
+        ..
+    } = q
+    {
+        assert_eq!(index_name, "users_email");
+        assert!(if_not_exists);
+        assert!(unique);
     } else {
         panic!("Expected CreateIndex");
     }
@@ -1,5 +1,6 @@
 use povertygres::parser::main::parse_query;
-use povertygres::types::parser_types::Query;
+use povertygres::types::filter_types::ArithOp;
+use povertygres::types::parser_types::{ConflictAction, InsertSource, OnConflict, Operand, Query};
 use povertygres::types::storage_types::Value;
 
 #[test]
@@ -8,18 +9,19 @@ fn test_insert_basic() {
     if let Query::Insert {
         table_name,
         column_names,
-        values,
+        source: InsertSource::Values(rows),
+        ..
     } = query
     {
         assert_eq!(table_name, "users");
         assert!(column_names.is_none());
         assert_eq!(
-            values,
-            vec![
-                Value::Int(1),
-                Value::Text("alice".to_string()),
-                Value::Bool(true)
-            ]
+            rows,
+            vec![vec![
+                Operand::Literal(Value::Int(1)),
+                Operand::Literal(Value::Text("alice".to_string())),
+                Operand::Literal(Value::Bool(true))
+            ]]
         );
     } else {
         panic!("Unexpected query variant");
@@ -32,7 +34,8 @@ fn test_insert_with_columns() {
     if let Query::Insert {
         table_name,
         column_names,
-        values,
+        source: InsertSource::Values(rows),
+        ..
     } = query
     {
         assert_eq!(table_name, "users");
@@ -40,17 +43,31 @@ fn test_insert_with_columns() {
             column_names,
             Some(vec!["id".to_string(), "name".to_string()])
         );
-        assert_eq!(values, vec![Value::Int(2), Value::Text("bob".to_string())]);
+        assert_eq!(
+            rows,
+            vec![vec![
+                Operand::Literal(Value::Int(2)),
+                Operand::Literal(Value::Text("bob".to_string()))
+            ]]
+        );
     }
 }
 
 #[test]
 fn test_insert_with_null_and_bool() {
     let query = parse_query("insert into users values (null, true, false)").unwrap();
-    if let Query::Insert { values, .. } = query {
+    if let Query::Insert {
+        source: InsertSource::Values(rows),
+        ..
+    } = query
+    {
         assert_eq!(
-            values,
-            vec![Value::Null, Value::Bool(true), Value::Bool(false)]
+            rows,
+            vec![vec![
+                Operand::Literal(Value::Null),
+                Operand::Literal(Value::Bool(true)),
+                Operand::Literal(Value::Bool(false))
+            ]]
         );
     }
 }
@@ -66,3 +83,125 @@ fn test_insert_unrecognized_value() {
     let res = parse_query("insert into users values (foo)");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_insert_multiple_rows() {
+    let query = parse_query(r#"insert into users values (1, "a, b"), (2, "(c)")"#).unwrap();
+    if let Query::Insert {
+        source: InsertSource::Values(rows),
+        ..
+    } = query
+    {
+        assert_eq!(
+            rows,
+            vec![
+                vec![Operand::Literal(Value::Int(1)), Operand::Literal(Value::Text("a, b".to_string()))],
+                vec![Operand::Literal(Value::Int(2)), Operand::Literal(Value::Text("(c)".to_string()))],
+            ]
+        );
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_insert_select() {
+    let query = parse_query("insert into archive (id) select id from users where id > 10").unwrap();
+    if let Query::Insert {
+        table_name,
+        column_names,
+        source: InsertSource::Select(select),
+        ..
+    } = query
+    {
+        assert_eq!(table_name, "archive");
+        assert_eq!(column_names, Some(vec!["id".to_string()]));
+        assert!(matches!(*select, Query::Select { filter: Some(_), .. }));
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_insert_returning() {
+    let query = parse_query(r#"insert into users values (1, "returning x") returning id, name"#).unwrap();
+    if let Query::Insert { returning, .. } = query {
+        assert_eq!(returning, Some(vec!["id".to_string(), "name".to_string()]));
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_returning_on_select_is_error() {
+    assert!(parse_query("select * from users returning id").is_err());
+}
+
+#[test]
+fn test_insert_on_conflict_do_nothing() {
+    let query = parse_query("insert into users values (1) on conflict do nothing").unwrap();
+    if let Query::Insert {
+        on_conflict: Some(OnConflict { target, action }),
+        ..
+    } = query
+    {
+        assert!(target.is_none());
+        assert!(matches!(action, ConflictAction::Nothing));
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_insert_on_conflict_do_update() {
+    let query = parse_query(
+        "insert into users values (1, 2) on conflict (id) do update set n = excluded.n, m = 0 where users.n < excluded.n returning *",
+    )
+    .unwrap();
+    if let Query::Insert {
+        source: InsertSource::Values(rows),
+        on_conflict: Some(OnConflict { target, action }),
+        returning,
+        ..
+    } = query
+    {
+        assert_eq!(rows, vec![vec![Operand::Literal(Value::Int(1)), Operand::Literal(Value::Int(2))]]);
+        assert_eq!(target, Some(vec!["id".to_string()]));
+        assert_eq!(returning, Some(vec!["*".to_string()]));
+        let ConflictAction::Update {
+            column_names,
+            values,
+            filter,
+        } = action
+        else {
+            panic!("Expected DO UPDATE");
+        };
+        assert_eq!(column_names, vec!["n", "m"]);
+        assert!(matches!(&values[0], Operand::Column(c) if c == "excluded.n"));
+        assert!(matches!(&values[1], Operand::Literal(Value::Int(0))));
+        assert!(filter.is_some());
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
+
+#[test]
+fn test_on_conflict_do_update_requires_target() {
+    assert!(parse_query("insert into users values (1) on conflict do update set n = 1").is_err());
+}
+
+#[test]
+fn test_insert_expression_values() {
+    let query = parse_query(r#"insert into events values (1, now(), date "2024-01-31" + 1)"#).unwrap();
+    if let Query::Insert {
+        source: InsertSource::Values(rows),
+        ..
+    } = query
+    {
+        assert!(matches!(&rows[0][1], Operand::Func(name, args) if name == "now" && args.is_empty()));
+        assert!(matches!(&rows[0][2], Operand::Arith(ArithOp::Add, lhs, _)
+            if **lhs == Operand::Literal(Value::Date(19753))));
+    } else {
+        panic!("Unexpected query variant");
+    }
+}
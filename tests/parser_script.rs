@@ -0,0 +1,32 @@
+use povertygres::parser::script::parse_script;
+use povertygres::types::parser_types::Query;
+
+#[test]
+fn test_script_splits_outside_quotes_and_skips_comments() {
+    let script = "-- header; not a statement\ninsert into t values (1, \"a;b\");\n\nselect * from t -- trailing\n;";
+    let statements = parse_script(script);
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].0, "insert into t values (1, \"a;b\")");
+    assert!(statements[0].1.is_ok());
+    assert_eq!(statements[1].0, "select * from t");
+}
+
+#[test]
+fn test_script_copy_from_stdin_takes_inline_data() {
+    let script = "copy t (id, name) from stdin;\n1\tx;y\n2\t\\N\n\\.\nselect * from t;";
+    let statements = parse_script(script);
+    assert_eq!(statements.len(), 2);
+    let Ok(Query::Copy { path, data, .. }) = &statements[0].1 else {
+        panic!("Unexpected statement: {:?}", statements[0]);
+    };
+    assert!(path.is_none());
+    assert_eq!(data.as_deref(), Some("1\tx;y\n2\t\\N\n"));
+    assert!(matches!(statements[1].1, Ok(Query::Select { .. })));
+}
+
+#[test]
+fn test_script_copy_data_needs_terminator() {
+    let statements = parse_script("copy t from stdin;\n1\n2\n");
+    assert_eq!(statements.len(), 1);
+    assert!(statements[0].1.is_err());
+}
@@ -108,3 +108,34 @@ fn test_select_missing_from() {
     let res = parse_query("select id, name users");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_select_join_without_aliases() {
+    let q = parse_query("select * from users join orders on users.id = orders.user_id").unwrap();
+    if let Query::Select { from_table, aliases, .. } = q {
+        assert!(aliases.is_empty());
+        match from_table {
+            FromItem::Join { left, right, .. } => {
+                assert!(matches!(*left, FromItem::Table(ref t) if t == "users"));
+                assert!(matches!(*right, FromItem::Table(ref t) if t == "orders"));
+            }
+            _ => panic!("Expected join"),
+        }
+    }
+}
+
+#[test]
+fn test_select_from_unnest() {
+    let q = parse_query("select x from unnest(array[1, 2]) as u(x)").unwrap();
+    let Query::Select { from_table, aliases, .. } = q else {
+        panic!("Expected Select");
+    };
+    let FromItem::Function { name, args, alias, column } = from_table else {
+        panic!("Expected a function in FROM");
+    };
+    assert_eq!(name, "unnest");
+    assert_eq!(alias, "u");
+    assert_eq!(column, "x");
+    assert!(matches!(&args[..], [Operand::Func(f, items)] if f == "array" && items.len() == 2));
+    assert!(aliases.is_empty());
+}
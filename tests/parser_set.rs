@@ -0,0 +1,38 @@
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::Query;
+
+#[test]
+fn test_set_option_eq() {
+    let q = parse_query("set auto_index_foreign_keys = on").unwrap();
+    if let Query::SetOption { name, value } = q {
+        assert_eq!(name, "auto_index_foreign_keys");
+        assert_eq!(value, "on");
+    } else {
+        panic!("Expected SetOption");
+    }
+}
+
+#[test]
+fn test_set_option_to() {
+    let q = parse_query("SET Auto_Index_Foreign_Keys TO off").unwrap();
+    if let Query::SetOption { name, value } = q {
+        assert_eq!(name, "auto_index_foreign_keys");
+        assert_eq!(value, "off");
+    } else {
+        panic!("Expected SetOption");
+    }
+}
+
+#[test]
+fn test_set_option_missing_value() {
+    assert!(parse_query("set auto_index_foreign_keys").is_err());
+}
+
+#[test]
+fn test_set_constraints() {
+    let q = parse_query("SET CONSTRAINTS ALL DEFERRED").unwrap();
+    assert!(matches!(q, Query::SetConstraints { deferred: true }));
+    let q = parse_query("set constraints all immediate").unwrap();
+    assert!(matches!(q, Query::SetConstraints { deferred: false }));
+    assert!(parse_query("set constraints fk_name deferred").is_err());
+}
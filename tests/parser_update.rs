@@ -1,6 +1,6 @@
 use povertygres::parser::main::parse_query;
 use povertygres::types::filter_types::CmpOp;
-use povertygres::types::parser_types::{Condition, Operand, Query};
+use povertygres::types::parser_types::{Condition, FromItem, Operand, Query};
 use povertygres::types::storage_types::Value;
 
 #[test]
@@ -11,11 +11,12 @@ fn test_update_basic() {
         column_names,
         values,
         filter,
+        ..
     } = q
     {
         assert_eq!(table_name, "users");
         assert_eq!(column_names, vec!["name"]);
-        assert_eq!(values, vec![Value::Text("alice".to_string())]);
+        assert_eq!(values, vec![Operand::Literal(Value::Text("alice".to_string()))]);
         assert!(filter.is_none());
     } else {
         panic!("Unexpected query variant: {:?}", q);
@@ -32,7 +33,10 @@ fn test_update_multiple_columns() {
     } = q
     {
         assert_eq!(column_names, vec!["age", "active"]);
-        assert_eq!(values, vec![Value::Int(20), Value::Bool(true)]);
+        assert_eq!(
+            values,
+            vec![Operand::Literal(Value::Int(20)), Operand::Literal(Value::Bool(true))]
+        );
     }
 }
 
@@ -46,7 +50,7 @@ fn test_update_with_null() {
     } = q
     {
         assert_eq!(column_names, vec!["nickname"]);
-        assert_eq!(values, vec![Value::Null]);
+        assert_eq!(values, vec![Operand::Literal(Value::Null)]);
     }
 }
 
@@ -73,6 +77,50 @@ fn test_update_missing_set() {
 
 #[test]
 fn test_update_unrecognized_value() {
-    let res = parse_query("update users set name = foo");
+    let res = parse_query("update users set name = #foo");
     assert!(res.is_err());
+    // single-quoted text is a literal, like double-quoted text
+    assert!(parse_query("update users set name = 'foo'").is_ok());
+}
+
+#[test]
+fn test_update_returning() {
+    let q = parse_query("update users set age = 30 where id = 1 returning id, age").unwrap();
+    if let Query::Update { filter, returning, .. } = q {
+        assert!(filter.is_some());
+        assert_eq!(returning, Some(vec!["id".to_string(), "age".to_string()]));
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
+}
+
+#[test]
+fn test_update_from() {
+    let q = parse_query(
+        "update accounts set balance = p.amount, note = \"paid\" from payments p where accounts.id = p.acc",
+    )
+    .unwrap();
+    if let Query::Update {
+        table_name,
+        values,
+        from,
+        aliases,
+        filter,
+        ..
+    } = q
+    {
+        assert_eq!(table_name, "accounts");
+        assert_eq!(
+            values,
+            vec![
+                Operand::Column("p.amount".to_string()),
+                Operand::Literal(Value::Text("paid".to_string()))
+            ]
+        );
+        assert!(matches!(from, Some(FromItem::Table(ref t)) if t == "payments"));
+        assert_eq!(aliases.get("p"), Some(&"payments".to_string()));
+        assert!(filter.is_some());
+    } else {
+        panic!("Unexpected query variant: {:?}", q);
+    }
 }
@@ -1,5 +1,5 @@
 use povertygres::parser::r#where::parse_where;
-use povertygres::types::filter_types::CmpOp;
+use povertygres::types::filter_types::{ArithOp, CmpOp};
 use povertygres::types::parser_types::{Condition, Operand};
 use povertygres::types::storage_types::Value;
 
@@ -68,3 +68,75 @@ fn test_mismatched_parenthesis() {
     let res = parse_where("(age = 10");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_negative_literal() {
+    // a '-' after an operand is not a sign but a subtraction
+    assert!(matches!(
+        parse_where("a = b -5").unwrap(),
+        Condition::Cmp(CmpOp::Eq, _, Operand::Arith(ArithOp::Sub, _, _))
+    ));
+    let cond = parse_where("a > -9223372036854775808").unwrap();
+    assert!(matches!(
+        cond,
+        Condition::Cmp(CmpOp::Gt, Operand::Column(_), Operand::Literal(Value::Int(i64::MIN)))
+    ));
+}
+
+#[test]
+fn test_functions_and_typed_literals() {
+    let cond = parse_where(r#"date_trunc("day", ts) >= timestamp "2024-01-31 10:00" - interval "1 day""#).unwrap();
+    let Condition::Cmp(CmpOp::Gte, Operand::Func(name, args), Operand::Arith(ArithOp::Sub, lhs, rhs)) = cond else {
+        panic!("unexpected condition: {:?}", cond);
+    };
+    assert_eq!(name, "date_trunc");
+    assert_eq!(args, vec![Operand::Literal(Value::Text("day".into())), Operand::Column("ts".into())]);
+    assert!(matches!(*lhs, Operand::Literal(Value::Timestamp(_))));
+    assert!(matches!(*rhs, Operand::Literal(Value::Interval(_))));
+
+    let cond = parse_where("extract(year from d) = 2024").unwrap();
+    assert!(matches!(cond, Condition::Cmp(CmpOp::Eq, Operand::Func(name, args), _)
+        if name == "extract" && args[0] == Operand::Literal(Value::Text("year".into()))));
+    assert!(parse_where(r#"d = date "2024-02-30""#).is_err());
+}
+
+#[test]
+fn test_jsonb_operators() {
+    let cond = parse_where(r#"doc -> 'meta' ->> 'ua' = 'ff' and doc @> '{"a": 1}' and doc ? 'k'"#).unwrap();
+    let Condition::And(lhs, has_key) = cond else {
+        panic!("unexpected condition: {:?}", cond);
+    };
+    let Condition::And(eq, contains) = *lhs else {
+        panic!("unexpected condition: {:?}", lhs);
+    };
+    let Condition::Cmp(CmpOp::Eq, Operand::Arith(ArithOp::JsonGetText, inner, key), _) = *eq else {
+        panic!("unexpected condition: {:?}", eq);
+    };
+    assert!(matches!(*inner, Operand::Arith(ArithOp::JsonGet, _, _)));
+    assert_eq!(*key, Operand::Literal(Value::Text("ua".into())));
+    assert!(matches!(*contains, Condition::Cmp(CmpOp::Contains, _, Operand::Literal(Value::Text(ref t))) if t == r#"{"a": 1}"#));
+    assert!(matches!(*has_key, Condition::Cmp(CmpOp::HasKey, _, _)));
+
+    // single-quoted literals double their quotes
+    let cond = parse_where("name = 'it''s'").unwrap();
+    assert!(matches!(cond, Condition::Cmp(_, _, Operand::Literal(Value::Text(ref t))) if t == "it's"));
+    assert!(parse_where("doc -> 'a").is_err());
+}
+
+#[test]
+fn test_array_operators() {
+    let cond = parse_where("tags[1] = 'a' and 3 = any(array[1, 2, 3])").unwrap();
+    let Condition::And(first, any) = cond else {
+        panic!("unexpected condition: {:?}", cond);
+    };
+    let Condition::Cmp(CmpOp::Eq, Operand::Arith(ArithOp::Subscript, arr, index), _) = *first else {
+        panic!("unexpected condition: {:?}", first);
+    };
+    assert_eq!(*arr, Operand::Column("tags".into()));
+    assert_eq!(*index, Operand::Literal(Value::Int(1)));
+    let Condition::Cmp(CmpOp::Eq, _, Operand::Func(name, args)) = *any else {
+        panic!("unexpected condition: {:?}", any);
+    };
+    assert_eq!(name, "any");
+    assert!(matches!(&args[..], [Operand::Func(f, items)] if f == "array" && items.len() == 3));
+}
@@ -0,0 +1,45 @@
+use povertygres::types::page_types::Page;
+use povertygres::types::jsonb::Jsonb;
+use povertygres::types::numeric::Numeric;
+use povertygres::types::storage_types::{Attribute, ColumnType, Row, Value};
+
+fn attribute(column_type: ColumnType) -> Attribute {
+    Attribute { column_type, added_in: 0, missing: Value::Null, dropped: false }
+}
+
+fn insert(column_type: ColumnType, values: Vec<Value>) -> Result<usize, String> {
+    let attributes: Vec<Attribute> = values.iter().map(|_| attribute(column_type.clone())).collect();
+    Page::new(0).insert_tuple(Row { values }, &attributes, 1, 0)
+}
+
+#[test]
+fn test_values_longer_than_their_length_field_are_rejected() {
+    let long = 70_000;
+    let cases = [
+        (ColumnType::Text, Value::Text("x".repeat(long))),
+        (ColumnType::Bytea, Value::Bytea(vec![7; long])),
+        (ColumnType::Numeric(None), Value::Numeric(Numeric::parse(&"9".repeat(long)).unwrap())),
+    ];
+    for (column_type, value) in cases {
+        let err = insert(column_type.clone(), vec![value]).unwrap_err();
+        assert!(err.contains("value too long for type"), "{}: {}", column_type, err);
+    }
+
+    // values that fit their length field still have to fit the page
+    let err = insert(ColumnType::Text, vec![Value::Text("x".repeat(40_000)), Value::Text("y".repeat(40_000))])
+        .unwrap_err();
+    assert!(err.contains("Not enough space"), "{}", err);
+    assert_eq!(insert(ColumnType::Text, vec![Value::Text("x".repeat(4_000))]), Ok(0));
+}
+
+#[test]
+fn test_jsonb_longer_than_its_length_field_is_rejected() {
+    // the binary form is what gets stored: a long array of small numbers
+    let items = vec!["1"; 30_000].join(", ");
+    let doc = Jsonb::parse(&format!("[{}]", items)).unwrap();
+    let err = insert(ColumnType::Jsonb, vec![Value::Jsonb(doc)]).unwrap_err();
+    assert!(err.contains("value too long for type"), "{}", err);
+
+    let doc = Jsonb::parse(r#"{"a": [1, 2, "x"]}"#).unwrap();
+    assert_eq!(insert(ColumnType::Jsonb, vec![Value::Jsonb(doc)]), Ok(0));
+}
@@ -0,0 +1,53 @@
+use povertygres::storage::heap_file::HeapFile;
+use povertygres::types::page_types::VisibilityMap;
+use povertygres::types::storage_types::{Column, ColumnType, Row, Value};
+use povertygres::types::transaction_types::TransactionManager;
+use std::collections::HashMap;
+
+fn schema() -> Vec<Column> {
+    vec![Column {
+        name: "id".to_string(),
+        column_type: ColumnType::Int,
+        not_null: false,
+        default: None,
+        attnum: 0,
+        identity: None,
+    }]
+}
+
+#[test]
+fn test_bits_set_and_clear() {
+    let mut vm = VisibilityMap::default();
+    assert!(!vm.is_all_visible(9));
+    vm.set_all_visible(9);
+    assert!(vm.is_all_visible(9));
+    assert!(!vm.is_all_visible(8));
+    assert!(vm.clear(9));
+    assert!(!vm.clear(9));
+    assert!(!vm.is_all_visible(9));
+}
+
+#[test]
+fn test_vacuum_marks_and_insert_clears() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.tbl");
+    let heap = HeapFile::new(path.to_str().unwrap(), &schema());
+    let mut tm = TransactionManager::new();
+
+    let xid = tm.alloc_xid();
+    tm.begin(xid);
+    heap.insert_rows(vec![Row { values: vec![Value::Int(1)] }], &schema(), xid).unwrap();
+
+    // uncommitted inserter: page must not be marked
+    heap.vacuum(&tm, &schema(), "t", &mut HashMap::new());
+    assert!(!heap.read_visibility_map().is_all_visible(0));
+
+    tm.commit(xid);
+    heap.vacuum(&tm, &schema(), "t", &mut HashMap::new());
+    assert!(heap.read_visibility_map().is_all_visible(0));
+
+    let xid = tm.alloc_xid();
+    tm.begin(xid);
+    heap.insert_rows(vec![Row { values: vec![Value::Int(2)] }], &schema(), xid).unwrap();
+    assert!(!heap.read_visibility_map().is_all_visible(0));
+}
@@ -0,0 +1,95 @@
+use povertygres::executer::functions::{aggregate, arith, call, set_returning};
+use povertygres::types::array::{format_array, parse_array};
+use povertygres::types::filter_types::ArithOp;
+use povertygres::types::page_types::Page;
+use povertygres::types::storage_types::{Attribute, ColumnType, Row, Value};
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+#[test]
+fn test_array_literal_text() {
+    assert_eq!(
+        parse_array("{1, 2 ,NULL}"),
+        Some(vec![Some("1".to_string()), Some("2".to_string()), None])
+    );
+    assert_eq!(
+        parse_array(r#"{"a b","say \"hi\"","NULL"}"#),
+        Some(vec![Some("a b".to_string()), Some("say \"hi\"".to_string()), Some("NULL".to_string())])
+    );
+    assert_eq!(parse_array("{}"), Some(vec![]));
+    for bad in ["1,2", "{1,2", "{{1},{2}}", "{1,,2}", "{\"a\"b}"] {
+        assert_eq!(parse_array(bad), None, "{} should not parse", bad);
+    }
+
+    let items = vec![text("a b"), Value::Null, text("null"), text(""), text("x")];
+    let out = format_array(&items);
+    assert_eq!(out, r#"{"a b",NULL,"null","",x}"#);
+    let back: Vec<Option<String>> = parse_array(&out).unwrap();
+    assert_eq!(back, vec![Some("a b".into()), None, Some("null".into()), Some("".into()), Some("x".into())]);
+}
+
+#[test]
+fn test_array_coerce() {
+    let ints = ColumnType::Array(Box::new(ColumnType::Int));
+    assert_eq!(
+        ints.coerce(text("{1,NULL,3}")).unwrap(),
+        Value::Array(vec![Value::Int(1), Value::Null, Value::Int(3)])
+    );
+    assert!(ints.coerce(text("{1,x}")).is_err());
+    assert!(ints.coerce(text("1,2")).is_err());
+    assert!(ints.coerce(Value::Array(vec![text("a")])).is_err());
+
+    let short = ColumnType::Array(Box::new(ColumnType::Varchar(Some(2))));
+    assert!(short.coerce(text("{ab,c}")).is_ok());
+    assert!(short.coerce(text("{abc}")).is_err());
+    assert_eq!(ints.to_string(), "integer[]");
+}
+
+#[test]
+fn test_array_page_roundtrip() {
+    let attributes = [
+        Attribute {
+            column_type: ColumnType::Array(Box::new(ColumnType::Int)),
+            added_in: 0,
+            missing: Value::Null,
+            dropped: false,
+        },
+        Attribute {
+            column_type: ColumnType::Array(Box::new(ColumnType::Text)),
+            added_in: 0,
+            missing: Value::Null,
+            dropped: false,
+        },
+    ];
+    let row = Row {
+        values: vec![
+            Value::Array(vec![Value::Int(7), Value::Null, Value::Int(-1)]),
+            Value::Array(vec![]),
+        ],
+    };
+    let mut page = Page::new(0);
+    let slot = page.insert_tuple(row.clone(), &attributes, 1, 0).unwrap();
+    let (_, read) = page.get_tuple(slot, &attributes).unwrap();
+    assert_eq!(read.values, row.values);
+}
+
+#[test]
+fn test_array_functions() {
+    let arr = call("array", vec![Value::Int(1), Value::Int(2)]).unwrap();
+    assert_eq!(arr, Value::Array(vec![Value::Int(1), Value::Int(2)]));
+    assert!(call("array", vec![Value::Int(1), text("a")]).is_err());
+    assert!(call("array", vec![arr.clone()]).is_err());
+
+    assert_eq!(arith(ArithOp::Subscript, arr.clone(), Value::Int(2)).unwrap(), Value::Int(2));
+    assert_eq!(arith(ArithOp::Subscript, arr.clone(), Value::Int(0)).unwrap(), Value::Null);
+    assert_eq!(arith(ArithOp::Subscript, arr.clone(), Value::Int(3)).unwrap(), Value::Null);
+
+    assert_eq!(
+        aggregate("array_agg", vec![text("b"), Value::Null, text("a")]).unwrap(),
+        Value::Array(vec![text("b"), Value::Null, text("a")])
+    );
+    assert_eq!(set_returning("unnest", vec![arr]).unwrap(), vec![Value::Int(1), Value::Int(2)]);
+    assert_eq!(set_returning("unnest", vec![Value::Null]).unwrap(), vec![]);
+}
@@ -0,0 +1,103 @@
+use povertygres::parser::r#where::parse_where;
+use povertygres::types::cast::{CastContext, cast_context, cast_value};
+use povertygres::types::filter_types::{ArithOp, CmpOp};
+use povertygres::types::numeric::{Float, Numeric};
+use povertygres::types::parser_types::{Condition, Operand};
+use povertygres::types::storage_types::{ColumnType, Value};
+
+#[test]
+fn test_cast_table() {
+    use CastContext::*;
+    let int_array = ColumnType::Array(Box::new(ColumnType::Int));
+    let text_array = ColumnType::Array(Box::new(ColumnType::Text));
+    let cases = [
+        (ColumnType::SmallInt, ColumnType::BigInt, Some(Implicit)),
+        (ColumnType::Int, ColumnType::Numeric(Some((6, 2))), Some(Implicit)),
+        (ColumnType::Double, ColumnType::Int, Some(Assignment)),
+        (ColumnType::Varchar(Some(3)), ColumnType::Text, Some(Implicit)),
+        (ColumnType::Int, ColumnType::Varchar(None), Some(Assignment)),
+        (ColumnType::Text, ColumnType::Int, Some(Explicit)),
+        (ColumnType::Text, ColumnType::Bool, Some(Explicit)),
+        (ColumnType::Text, ColumnType::Date, Some(Assignment)),
+        (ColumnType::Int, ColumnType::Bool, Some(Explicit)),
+        (ColumnType::Date, ColumnType::TimestampTz, Some(Implicit)),
+        (ColumnType::Timestamp, ColumnType::Date, Some(Assignment)),
+        (text_array.clone(), int_array.clone(), Some(Explicit)),
+        (int_array, text_array, Some(Assignment)),
+        (ColumnType::Int, ColumnType::Interval, None),
+        (ColumnType::Date, ColumnType::Time, None),
+        (ColumnType::Uuid, ColumnType::Bytea, None),
+    ];
+    for (from, to, expected) in cases {
+        assert_eq!(cast_context(&from, &to), expected, "{} -> {}", from, to);
+    }
+}
+
+#[test]
+fn test_cast_value() {
+    let explicit = |value: Value, to: &ColumnType| cast_value(value, to, CastContext::Explicit);
+
+    assert_eq!(explicit(Value::Text(" 42 ".into()), &ColumnType::Int), Ok(Value::Int(42)));
+    assert_eq!(
+        explicit(Value::Text("3.14159".into()), &ColumnType::Numeric(Some((6, 2)))),
+        Ok(Value::Numeric(Numeric::parse("3.14").unwrap()))
+    );
+    assert_eq!(explicit(Value::Text("on".into()), &ColumnType::Bool), Ok(Value::Bool(true)));
+    assert_eq!(explicit(Value::Int(0), &ColumnType::Bool), Ok(Value::Bool(false)));
+    assert_eq!(explicit(Value::Null, &ColumnType::Int), Ok(Value::Null));
+    assert_eq!(
+        explicit(
+            Value::Array(vec![Value::Text("1".into()), Value::Null]),
+            &ColumnType::Array(Box::new(ColumnType::BigInt))
+        ),
+        Ok(Value::Array(vec![Value::Int(1), Value::Null]))
+    );
+    assert!(explicit(Value::Text("x".into()), &ColumnType::Int).is_err());
+    assert!(explicit(Value::Int(1), &ColumnType::Interval).is_err());
+
+    // an explicit cast to VARCHAR(n) truncates, an assignment is rejected
+    assert_eq!(explicit(Value::Text("abcdef".into()), &ColumnType::Varchar(Some(3))), Ok(Value::Text("abc".into())));
+    assert!(cast_value(Value::Text("abcdef".into()), &ColumnType::Varchar(Some(3)), CastContext::Assignment).is_err());
+
+    // text reads as a number only when the cast is written out
+    assert!(cast_value(Value::Text("42".into()), &ColumnType::Int, CastContext::Assignment).is_err());
+    assert_eq!(
+        cast_value(Value::Int(42), &ColumnType::Text, CastContext::Assignment),
+        Ok(Value::Text("42".into()))
+    );
+}
+
+#[test]
+fn test_assignment_accepts() {
+    assert!(ColumnType::Int.accepts(&Value::Float(Float(2.0))));
+    assert!(ColumnType::Text.accepts(&Value::Int(7)));
+    assert!(ColumnType::Date.accepts(&Value::Text("2024-01-01".into())));
+    assert!(ColumnType::Int.accepts(&Value::Null));
+    assert!(!ColumnType::Int.accepts(&Value::Text("42".into())));
+    assert!(!ColumnType::Bool.accepts(&Value::Int(1)));
+    assert!(!ColumnType::Array(Box::new(ColumnType::Int)).accepts(&Value::Array(vec![Value::Text("1".into())])));
+}
+
+#[test]
+fn test_parse_casts() {
+    let cond = parse_where("cast(price as numeric(6, 2)) > '1.5'::numeric").unwrap();
+    let Condition::Cmp(CmpOp::Gt, Operand::Cast(left, left_type), Operand::Cast(right, right_type)) = cond else {
+        panic!("unexpected condition: {:?}", cond);
+    };
+    assert_eq!(*left, Operand::Column("price".into()));
+    assert_eq!(left_type, ColumnType::Numeric(Some((6, 2))));
+    assert_eq!(*right, Operand::Literal(Value::Text("1.5".into())));
+    assert_eq!(right_type, ColumnType::Numeric(None));
+
+    // :: binds tighter than arithmetic, subscripts tighter than ::
+    let cond = parse_where("id - 1::int = tags[1]::double precision").unwrap();
+    let Condition::Cmp(CmpOp::Eq, Operand::Arith(ArithOp::Sub, _, one), Operand::Cast(subscript, ty)) = cond else {
+        panic!("unexpected condition: {:?}", cond);
+    };
+    assert_eq!(*one, Operand::Cast(Box::new(Operand::Literal(Value::Int(1))), ColumnType::Int));
+    assert!(matches!(*subscript, Operand::Arith(ArithOp::Subscript, _, _)));
+    assert_eq!(ty, ColumnType::Double);
+
+    assert!(parse_where("x::nosuch(1, 2, 3) = 1").is_err());
+    assert!(parse_where("cast(x int) = 1").is_err());
+}
@@ -0,0 +1,86 @@
+use povertygres::executer::functions::{arith, call};
+use povertygres::types::datetime::{self, Interval};
+use povertygres::types::filter_types::ArithOp;
+use povertygres::types::storage_types::{ColumnType, Value};
+
+fn ts(s: &str) -> i64 {
+    datetime::parse_timestamp(s, false).unwrap()
+}
+
+fn interval(s: &str) -> Interval {
+    Interval::parse(s).unwrap()
+}
+
+#[test]
+fn test_date_and_time_round_trip() {
+    assert_eq!(datetime::parse_date("1970-01-01"), Some(0));
+    assert_eq!(datetime::parse_date("2024-02-29").map(datetime::format_date).as_deref(), Some("2024-02-29"));
+    assert_eq!(datetime::parse_date("2023-02-29"), None);
+    assert_eq!(datetime::format_date(-1), "1969-12-31");
+    assert_eq!(datetime::parse_time("24:00").map(datetime::format_time).as_deref(), Some("24:00:00"));
+    assert_eq!(datetime::format_timestamp(ts("2024-01-31 23:59:59.25")), "2024-01-31 23:59:59.25");
+    assert_eq!(datetime::parse_time("12:60"), None);
+}
+
+#[test]
+fn test_timestamptz_is_stored_as_utc() {
+    let t = datetime::parse_timestamp("2024-01-31 10:00:00+02", true).unwrap();
+    assert_eq!(datetime::format_timestamptz(t), "2024-01-31 08:00:00+00");
+    assert_eq!(datetime::parse_timestamp("2024-01-31T08:00:00Z", true), Some(t));
+    // like PostgreSQL, a plain timestamp ignores a zone in its input
+    assert_eq!(datetime::parse_timestamp("2024-01-31 10:00:00+02", false), Some(ts("2024-01-31 10:00:00")));
+}
+
+#[test]
+fn test_interval_parse_display_and_order() {
+    assert_eq!(interval("1 year 2 mons 3 days 04:05:06").to_string(), "1 year 2 mons 3 days 04:05:06");
+    assert_eq!(interval("P1Y2M3DT4H5M6S"), interval("1 year 2 months 3 days 4 hours 5 minutes 6 seconds"));
+    assert_eq!(interval("2 days ago").to_string(), "-2 days");
+    assert_eq!(interval("1 mon"), interval("30 days"));
+    assert!(interval("1 day") < interval("25 hours"));
+    assert!(Interval::parse("3 fortnights").is_none());
+}
+
+#[test]
+fn test_interval_arithmetic_clamps_month_end() {
+    let jan31 = ts("2024-01-31 12:00:00");
+    assert_eq!(datetime::add_interval(jan31, &interval("1 month")), Some(ts("2024-02-29 12:00:00")));
+    assert_eq!(datetime::add_interval(jan31, &interval("-1 day -12 hours")), Some(ts("2024-01-30 00:00:00")));
+    let diff = datetime::timestamp_diff(ts("2024-03-01 06:00:00"), ts("2024-02-28 00:00:00")).unwrap();
+    assert_eq!(diff.to_string(), "2 days 06:00:00");
+}
+
+#[test]
+fn test_date_trunc_and_extract() {
+    let t = Value::Timestamp(ts("2024-05-15 13:45:30.5"));
+    let trunc = |field: &str| call("date_trunc", vec![Value::Text(field.into()), t.clone()]).unwrap().to_string();
+    assert_eq!(trunc("month"), "2024-05-01 00:00:00");
+    assert_eq!(trunc("week"), "2024-05-13 00:00:00");
+    assert_eq!(trunc("hour"), "2024-05-15 13:00:00");
+
+    let extract = |field: &str, v: Value| call("extract", vec![Value::Text(field.into()), v]).unwrap().to_string();
+    assert_eq!(extract("year", t.clone()), "2024");
+    assert_eq!(extract("second", t.clone()), "30.500000");
+    assert_eq!(extract("dow", t.clone()), "3");
+    assert_eq!(extract("epoch", Value::Interval(interval("1 day"))), "86400.000000");
+    assert!(call("date_trunc", vec![Value::Text("fortnight".into()), t]).is_err());
+    assert_eq!(call("extract", vec![Value::Text("year".into()), Value::Null]).unwrap(), Value::Null);
+}
+
+#[test]
+fn test_operators_on_dates_and_times() {
+    let date = Value::Date(datetime::parse_date("2024-02-28").unwrap());
+    assert_eq!(arith(ArithOp::Add, date.clone(), Value::Int(2)).unwrap().to_string(), "2024-03-01");
+    assert_eq!(
+        arith(ArithOp::Sub, date.clone(), Value::Text("2024-01-01".into())).unwrap(),
+        Value::Int(58)
+    );
+    assert_eq!(
+        arith(ArithOp::Add, date, Value::Text("1 hour".into())).unwrap().to_string(),
+        "2024-02-28 01:00:00"
+    );
+    let time = ColumnType::Time.parse_temporal("23:30").unwrap();
+    assert_eq!(arith(ArithOp::Add, time, Value::Interval(interval("1 hour"))).unwrap().to_string(), "00:30:00");
+    assert!(arith(ArithOp::Add, Value::Date(0), Value::Bool(true)).is_err());
+    assert!(arith(ArithOp::Add, Value::Int(i64::MAX), Value::Int(1)).is_err());
+}
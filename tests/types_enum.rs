@@ -0,0 +1,98 @@
+use povertygres::parser::main::parse_query;
+use povertygres::types::b_tree::BTreeIndex;
+use povertygres::types::enum_type::{EnumLabel, EnumType};
+use povertygres::types::page_types::Page;
+use povertygres::types::parser_types::{Operand, Query};
+use povertygres::types::storage_types::{Attribute, ColumnType, Row, Value};
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+/// mood AS ENUM ('sad', 'ok', 'happy'), with label OIDs that do not follow declaration order
+fn mood() -> Arc<EnumType> {
+    let labels = [(30, "sad"), (10, "ok"), (20, "happy")];
+    Arc::new(EnumType {
+        name: "mood".to_string(),
+        oid: 5,
+        labels: labels.iter().map(|(oid, label)| EnumLabel { oid: *oid, label: label.to_string() }).collect(),
+    })
+}
+
+#[test]
+fn test_parse_create_type() {
+    let Query::CreateType { type_name, labels } =
+        parse_query("create type mood as enum ('sad', 'ok', 'it''s, fine')").unwrap()
+    else {
+        panic!("Expected CreateType");
+    };
+    assert_eq!(type_name, "mood");
+    assert_eq!(labels, vec!["sad", "ok", "it's, fine"]);
+
+    assert!(parse_query("create type mood as enum (sad)").is_err());
+    assert!(parse_query("create type mood as (a int)").is_err());
+}
+
+#[test]
+fn test_enum_column_is_resolved_by_name() {
+    let Query::CreateTable { columns, .. } =
+        parse_query("create table t (m mood default 'ok', ms mood[])").unwrap()
+    else {
+        panic!("Expected CreateTable");
+    };
+    // the parser only names the type; its DEFAULT is checked once the type is looked up
+    assert_eq!(columns[0].column_type, ColumnType::Enum(Arc::new(EnumType::named("mood"))));
+    assert_eq!(columns[0].default, Some(Operand::Literal(Value::Text("ok".into()))));
+
+    let enums = HashMap::from([("mood".to_string(), mood())]);
+    assert_eq!(columns[0].column_type.resolve(&enums).unwrap(), ColumnType::Enum(mood()));
+    assert_eq!(
+        columns[1].column_type.resolve(&enums).unwrap(),
+        ColumnType::Array(Box::new(ColumnType::Enum(mood())))
+    );
+    assert!(ColumnType::Enum(Arc::new(EnumType::named("color"))).resolve(&enums).is_err());
+}
+
+#[test]
+fn test_enum_input_and_order() {
+    let ty = ColumnType::Enum(mood());
+    let value = |label: &str| ty.coerce(Value::Text(label.to_string())).unwrap();
+    assert_eq!(value("happy"), Value::Enum(mood(), 2));
+    assert_eq!(value("happy").to_string(), "happy");
+    assert!(ty.coerce(Value::Text("angry".into())).is_err());
+    assert!(ty.coerce(Value::Text("Happy".into())).is_err());
+
+    // declaration order, not label order or OID order
+    assert!(value("sad") < value("ok"));
+    assert!(value("ok") < value("happy"));
+
+    let mut index = BTreeIndex::new("t_m".into(), "t".into(), vec!["m".into()], false);
+    for (i, label) in ["happy", "sad", "ok"].iter().enumerate() {
+        index.insert(vec![value(label)], (0, i));
+    }
+    let from_ok = index.search_range(Bound::Included(vec![value("ok")]), Bound::Unbounded);
+    let positions: Vec<usize> = from_ok.iter().map(|(_, (_, slot))| *slot).collect();
+    assert_eq!(positions, vec![2, 0]);
+}
+
+#[test]
+fn test_enum_page_roundtrip() {
+    let attributes = [Attribute {
+        column_type: ColumnType::Enum(mood()),
+        added_in: 0,
+        missing: Value::Null,
+        dropped: false,
+    }];
+    let mut page = Page::new(0);
+    let empty = page.header.free_start;
+    let slot = page.insert_tuple(Row { values: vec![Value::Enum(mood(), 1)] }, &attributes, 1, 0).unwrap();
+    let (_, row) = page.get_tuple(slot, &attributes).unwrap();
+    assert_eq!(row.values, vec![Value::Enum(mood(), 1)]);
+
+    let empty_tuple = {
+        let mut page = Page::new(0);
+        page.insert_tuple(Row { values: vec![Value::Null] }, &attributes, 1, 0).unwrap();
+        page.header.free_start
+    };
+    // the label is stored as its 4-byte OID
+    assert_eq!(page.header.free_start - empty, empty_tuple - empty + 4);
+}
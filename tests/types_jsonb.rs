@@ -0,0 +1,85 @@
+use povertygres::executer::functions::{aggregate, arith, call};
+use povertygres::types::filter_types::ArithOp;
+use povertygres::types::jsonb::Jsonb;
+use povertygres::types::storage_types::{ColumnType, Value};
+
+fn doc(s: &str) -> Jsonb {
+    Jsonb::parse(s).unwrap()
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+#[test]
+fn test_parse_normalizes_objects() {
+    // keys sort shorter first, the last duplicate wins, whitespace is dropped
+    let d = doc(r#" { "bb": 1, "a": [true, null, "x\"y"], "bb": 2.50, "c": {} } "#);
+    assert_eq!(d.to_string(), r#"{"a": [true, null, "x\"y"], "c": {}, "bb": 2.50}"#);
+    assert_eq!(doc(r#""é😀""#), Jsonb::String("é😀".to_string()));
+    assert_eq!(doc("1.0"), doc("1"));
+    for bad in ["{bad", "[1,]", "01", "1.", "'a'", r#"{"a" 1}"#, "[1] 2", ""] {
+        assert!(Jsonb::parse(bad).is_none(), "{} should not parse", bad);
+    }
+    assert!(ColumnType::Jsonb.coerce(text("{")).is_err());
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let d = doc(r#"{"a": [1, -2.5e3, "s"], "b": {"c": null, "d": false}}"#);
+    let mut bytes = Vec::new();
+    d.encode(&mut bytes);
+    assert_eq!(Jsonb::decode(&bytes), Some(d));
+    assert_eq!(Jsonb::decode(&bytes[..bytes.len() - 1]), None);
+}
+
+#[test]
+fn test_containment_and_key_existence() {
+    let d = doc(r#"{"tags": ["a", "b"], "meta": {"ua": "ff", "n": 1}, "x": 1}"#);
+    assert!(d.contains(&doc(r#"{"tags": ["b"]}"#)));
+    assert!(d.contains(&doc(r#"{"meta": {"n": 1.0}}"#)));
+    assert!(d.contains(&doc("{}")));
+    assert!(!d.contains(&doc(r#"{"tags": "a"}"#)));
+    assert!(!d.contains(&doc(r#"{"meta": {"ua": "chrome"}}"#)));
+    // only a top-level array contains a bare scalar
+    assert!(doc(r#"["a", 1]"#).contains(&doc(r#""a""#)));
+    assert!(!doc(r#"{"l": [1, 2]}"#).contains(&doc(r#"{"l": 1}"#)));
+
+    assert!(d.has_key("meta"));
+    assert!(!d.has_key("ua"));
+    assert!(doc(r#"["a", 1]"#).has_key("a"));
+    assert!(!doc(r#"["a", 1]"#).has_key("1"));
+}
+
+#[test]
+fn test_arrow_operators() {
+    let d = Value::Jsonb(doc(r#"{"a": {"b": "x"}, "l": [10, 20, 30], "n": null}"#));
+    let get = |op, v: &Value, key: Value| arith(op, v.clone(), key).unwrap();
+    let a = get(ArithOp::JsonGet, &d, text("a"));
+    assert_eq!(a, Value::Jsonb(doc(r#"{"b": "x"}"#)));
+    assert_eq!(get(ArithOp::JsonGetText, &a, text("b")), text("x"));
+    assert_eq!(get(ArithOp::JsonGet, &a, text("b")), Value::Jsonb(doc(r#""x""#)));
+    let l = get(ArithOp::JsonGet, &d, text("l"));
+    assert_eq!(get(ArithOp::JsonGetText, &l, Value::Int(-1)), text("30"));
+    assert_eq!(get(ArithOp::JsonGet, &l, Value::Int(3)), Value::Null);
+    assert_eq!(get(ArithOp::JsonGetText, &d, text("n")), Value::Null);
+    assert_eq!(get(ArithOp::JsonGet, &d, text("missing")), Value::Null);
+    assert!(arith(ArithOp::JsonGet, Value::Int(1), text("a")).is_err());
+}
+
+#[test]
+fn test_build_object_and_agg() {
+    let built = call(
+        "jsonb_build_object",
+        vec![text("id"), Value::Int(1), text("name"), Value::Null, text("id"), Value::Bool(true)],
+    )
+    .unwrap();
+    assert_eq!(built.to_string(), r#"{"id": true, "name": null}"#);
+    assert!(call("jsonb_build_object", vec![text("a")]).is_err());
+    assert!(call("jsonb_build_object", vec![Value::Null, Value::Int(1)]).is_err());
+
+    let agg = aggregate("jsonb_agg", vec![Value::Int(1), text("a"), Value::Null, built]).unwrap();
+    assert_eq!(agg.to_string(), r#"[1, "a", null, {"id": true, "name": null}]"#);
+    assert_eq!(aggregate("jsonb_agg", Vec::new()).unwrap(), Value::Null);
+    assert!(call("jsonb_agg", vec![Value::Int(1)]).is_err());
+}
@@ -0,0 +1,165 @@
+use povertygres::executer::filter::eval_operand;
+use povertygres::executer::functions::{arith, int_arith};
+use povertygres::executer::join::JoinTableColumn;
+use povertygres::parser::r#where::parse_expression;
+use povertygres::types::filter_types::ArithOp;
+use povertygres::types::numeric::{Float, Numeric};
+use povertygres::types::parser_types::Operand;
+use povertygres::types::storage_types::{ColumnType, Row, Value};
+
+fn num(s: &str) -> Numeric {
+    Numeric::parse(s).unwrap()
+}
+
+#[test]
+fn test_numeric_parse_and_display() {
+    assert_eq!(num("1.50").to_string(), "1.50");
+    assert_eq!(num("-0.001").to_string(), "-0.001");
+    assert_eq!(num("1.5e3").to_string(), "1500");
+    assert_eq!(num("-0.0").to_string(), "0.0");
+    assert_eq!(num("12345678901234567890.5").to_string(), "12345678901234567890.5");
+    assert!(Numeric::parse("1.2.3").is_none());
+    assert!(Numeric::parse("").is_none());
+}
+
+#[test]
+fn test_numeric_rounds_half_away_from_zero() {
+    assert_eq!(num("2.005").round(2).to_string(), "2.01");
+    assert_eq!(num("-2.005").round(2).to_string(), "-2.01");
+    assert_eq!(num("9.995").round(2).to_string(), "10.00");
+    assert_eq!(num("0.004").round(2).to_string(), "0.00");
+    assert_eq!(num("7").round(2).to_string(), "7.00");
+    assert_eq!(num("2.5").to_i64(), Some(3));
+}
+
+#[test]
+fn test_numeric_ordering_ignores_scale() {
+    assert_eq!(num("1.5"), num("1.500"));
+    assert!(num("-2") < num("-1.99"));
+    assert!(num("10") > num("9.999"));
+    assert!(num("0") > num("-0.1"));
+}
+
+#[test]
+fn test_float_ordering_puts_nan_last() {
+    let mut values = [Float(f64::NAN), Float(1.0), Float(f64::INFINITY), Float(-2.0)];
+    values.sort();
+    assert_eq!(values.iter().map(|f| f.to_string()).collect::<Vec<_>>(), ["-2", "1", "Infinity", "NaN"]);
+    assert_eq!(Float(f64::NAN), Float(f64::NAN));
+    assert_eq!(Float(0.0), Float(-0.0));
+}
+
+#[test]
+fn test_mixed_number_comparison() {
+    assert!(Value::Int(2).cmp_number(&Value::Numeric(num("2.00"))).unwrap().is_eq());
+    assert!(Value::Int(2).cmp_number(&Value::Float(Float(2.5))).unwrap().is_lt());
+    assert!(Value::Int(2).cmp_number(&Value::Text("2".into())).is_none());
+}
+
+#[test]
+fn test_coerce_to_column_type() {
+    let money = ColumnType::Numeric(Some((5, 2)));
+    assert_eq!(money.coerce(Value::Int(3)).unwrap().to_string(), "3.00");
+    assert_eq!(money.coerce(Value::Numeric(num("1.234"))).unwrap().to_string(), "1.23");
+    assert!(money.coerce(Value::Numeric(num("1000"))).is_err());
+    assert_eq!(ColumnType::Int.coerce(Value::Numeric(num("7.5"))).unwrap(), Value::Int(8));
+    assert_eq!(ColumnType::Real.coerce(Value::Numeric(num("0.1"))).unwrap().to_string(), "0.1");
+    assert!(ColumnType::Real.coerce(Value::Float(Float(1e39))).is_err());
+}
+
+#[test]
+fn test_numeric_multiply_is_exact() {
+    assert_eq!(num("2.50").times(&num("2.50")).to_string(), "6.2500");
+    assert_eq!(num("-1.5").times(&num("0.02")).to_string(), "-0.030");
+    assert_eq!(num("0.00").times(&num("-3")).to_string(), "0.00");
+    assert_eq!(
+        num("99999999999999999999").times(&num("99999999999999999999")).to_string(),
+        "9999999999999999999800000000000000000001"
+    );
+}
+
+#[test]
+fn test_numeric_divide_follows_postgres_scale() {
+    let div = |a: &str, b: &str| num(a).divide(&num(b)).unwrap().to_string();
+    assert_eq!(div("1", "3"), "0.33333333333333333333");
+    assert_eq!(div("10", "3"), "3.3333333333333333");
+    assert_eq!(div("2", "3"), "0.66666666666666666667");
+    assert_eq!(div("-2", "3"), "-0.66666666666666666667");
+    assert_eq!(div("100.00", "7"), "14.2857142857142857");
+    assert_eq!(div("1", "0.0003"), "3333.3333333333333333");
+    assert_eq!(div("6", "3"), "2.0000000000000000");
+    assert_eq!(div("1.000000000000000000000", "4"), "0.250000000000000000000");
+    assert_eq!(num("1").divide(&num("0.00")), Err("division by zero".to_string()));
+
+    // stored into NUMERIC(p, s), a quotient rounds to the column's scale
+    let money = ColumnType::Numeric(Some((10, 2)));
+    let third = Value::Numeric(num("2.50").divide(&num("3")).unwrap());
+    assert_eq!(money.coerce(third).unwrap().to_string(), "0.83");
+}
+
+#[test]
+fn test_multiply_and_divide_operators() {
+    let eval = |op, a: Value, b: Value| arith(op, a, b).map(|v| v.to_string());
+    assert_eq!(eval(ArithOp::Mul, Value::Int(6), Value::Int(7)).unwrap(), "42");
+    assert_eq!(eval(ArithOp::Div, Value::Int(-7), Value::Int(2)).unwrap(), "-3");
+    assert_eq!(eval(ArithOp::Mul, Value::Int(3), Value::Numeric(num("1.25"))).unwrap(), "3.75");
+    assert_eq!(eval(ArithOp::Div, Value::Float(Float(1.5)), Value::Int(4)).unwrap(), "0.375");
+    assert_eq!(eval(ArithOp::Mul, Value::Float(Float(2.0)), Value::Numeric(num("0.5"))).unwrap(), "1");
+
+    for zero in [Value::Int(0), Value::Numeric(num("0.0")), Value::Float(Float(0.0))] {
+        assert!(eval(ArithOp::Div, Value::Int(1), zero).unwrap_err().to_string().contains("division by zero"));
+    }
+    assert!(eval(ArithOp::Mul, Value::Float(Float(1e308)), Value::Int(10)).is_err());
+    assert!(eval(ArithOp::Mul, Value::Date(0), Value::Int(2)).is_err());
+    assert_eq!(eval(ArithOp::Mul, Value::Null, Value::Int(2)).unwrap(), "NULL");
+
+    // * and / bind tighter than + and -, and left to right
+    let expr = |s: &str| parse_expression(s).unwrap();
+    let int = |n| Box::new(Operand::Literal(Value::Int(n)));
+    assert_eq!(
+        expr("1 + 2 * 3"),
+        Operand::Arith(ArithOp::Add, int(1), Box::new(Operand::Arith(ArithOp::Mul, int(2), int(3))))
+    );
+    assert_eq!(
+        expr("8 / 4 / 2"),
+        Operand::Arith(ArithOp::Div, Box::new(Operand::Arith(ArithOp::Div, int(8), int(4))), int(2))
+    );
+    assert_eq!(expr("2 * -3"), Operand::Arith(ArithOp::Mul, int(2), int(-3)));
+}
+
+#[test]
+fn test_integer_overflow_per_width() {
+    let err = |op, a, b, ty: ColumnType| int_arith(op, a, b, &ty).unwrap_err().to_string();
+    assert!(err(ArithOp::Mul, i16::MIN as i64, -1, ColumnType::SmallInt).contains("smallint out of range"));
+    assert!(err(ArithOp::Add, i16::MAX as i64, 1, ColumnType::SmallInt).contains("smallint out of range"));
+    assert!(err(ArithOp::Add, i32::MAX as i64, 1, ColumnType::Int).contains("integer out of range"));
+    assert!(err(ArithOp::Sub, i32::MIN as i64, 1, ColumnType::Int).contains("integer out of range"));
+    assert!(err(ArithOp::Mul, i64::MAX, 2, ColumnType::BigInt).contains("bigint out of range"));
+    assert!(err(ArithOp::Div, i64::MIN, -1, ColumnType::BigInt).contains("bigint out of range"));
+    assert!(err(ArithOp::Div, 1, 0, ColumnType::SmallInt).contains("division by zero"));
+    assert_eq!(int_arith(ArithOp::Add, i16::MAX as i64, 1, &ColumnType::Int).unwrap(), 32768);
+    assert_eq!(int_arith(ArithOp::Add, i32::MAX as i64, 1, &ColumnType::BigInt).unwrap(), 2147483648);
+
+    // the wider operand's type decides: smallint * smallint stays smallint,
+    // smallint * an integer literal is integer
+    let col = |name: &str, column_type| JoinTableColumn {
+        table_alias: "t".into(),
+        column_name: name.into(),
+        column_type,
+    };
+    let cols = [col("s", ColumnType::SmallInt), col("i", ColumnType::Int), col("b", ColumnType::BigInt)];
+    let row = Row { values: vec![Value::Int(i16::MIN as i64), Value::Int(i32::MAX as i64), Value::Int(i64::MAX)] };
+    let eval = |s: &str| {
+        eval_operand(&parse_expression(s).unwrap(), &row, &cols, None, None)
+            .map(|v| v.into_owned())
+            .map_err(|e| e.to_string())
+    };
+    assert!(eval("s * cast(-1 as smallint)").unwrap_err().contains("smallint out of range"));
+    assert!(eval("s + s").unwrap_err().contains("smallint out of range"));
+    assert_eq!(eval("s * -1"), Ok(Value::Int(32768)));
+    assert!(eval("i + 1").unwrap_err().contains("integer out of range"));
+    assert!(eval("i * 2 / 2").unwrap_err().contains("integer out of range"));
+    assert_eq!(eval("i + cast(1 as bigint)"), Ok(Value::Int(2147483648)));
+    assert_eq!(eval("i + 2147483648"), Ok(Value::Int(4294967295)));
+    assert!(eval("b + 1").unwrap_err().contains("bigint out of range"));
+}
@@ -0,0 +1,154 @@
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::{FromItem, Operand, Overriding, Query};
+use povertygres::types::sequence::{SEQ_LOG_VALS, Sequence, SequenceOptions};
+use povertygres::types::storage_types::{ColumnType, Identity, Value};
+use std::path::PathBuf;
+
+fn sequence(options: SequenceOptions) -> Result<Sequence, String> {
+    Sequence::new("s", 1, options, PathBuf::from("unused.seq"))
+}
+
+#[test]
+fn test_sequence_defaults_and_bounds() {
+    let s = sequence(SequenceOptions::default()).unwrap();
+    assert_eq!(s.data_type, ColumnType::BigInt);
+    assert_eq!((s.min_value, s.max_value, s.start), (1, i64::MAX, 1));
+
+    let down = sequence(SequenceOptions {
+        data_type: Some(ColumnType::SmallInt),
+        increment: Some(-2),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!((down.min_value, down.max_value, down.start), (i16::MIN as i64, -1, -1));
+
+    let invalid = [
+        SequenceOptions { increment: Some(0), ..Default::default() },
+        SequenceOptions { data_type: Some(ColumnType::Text), ..Default::default() },
+        SequenceOptions { data_type: Some(ColumnType::Int), max_value: Some(1 << 40), ..Default::default() },
+        SequenceOptions { min_value: Some(5), max_value: Some(5), ..Default::default() },
+        SequenceOptions { start: Some(0), ..Default::default() },
+    ];
+    for options in invalid {
+        assert!(sequence(options.clone()).is_err(), "{:?}", options);
+    }
+}
+
+#[test]
+fn test_nextval_setval_currval() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = SequenceOptions { max_value: Some(3), ..Default::default() };
+    let s = Sequence::new("s", 1, options, dir.path().join("s.seq")).unwrap();
+    s.write_state().unwrap();
+
+    assert!(s.currval().unwrap_err().contains("not yet defined"));
+    assert_eq!(s.nextval(), Ok(1));
+    assert_eq!(s.nextval(), Ok(2));
+    assert_eq!(s.currval(), Ok(2));
+    assert_eq!(s.nextval(), Ok(3));
+    assert!(s.nextval().unwrap_err().contains("reached maximum value"));
+
+    assert_eq!(s.setval(1, false), Ok(1));
+    assert_eq!(s.nextval(), Ok(1));
+    assert_eq!(s.setval(2, true), Ok(2));
+    assert_eq!(s.nextval(), Ok(3));
+    assert!(s.setval(4, true).is_err());
+
+    let options = SequenceOptions { max_value: Some(2), cycle: true, ..Default::default() };
+    let cycling = Sequence::new("c", 2, options, dir.path().join("c.seq")).unwrap();
+    let values: Vec<i64> = (0..5).map(|_| cycling.nextval().unwrap()).collect();
+    assert_eq!(values, vec![1, 2, 1, 2, 1]);
+}
+
+#[test]
+fn test_sequence_file_skips_ahead_after_crash() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("s.seq");
+    let s = Sequence::new("s", 1, SequenceOptions::default(), file.clone()).unwrap();
+    s.write_state().unwrap();
+    for expected in 1..=5 {
+        assert_eq!(s.nextval(), Ok(expected));
+    }
+
+    // a crash loses the values handed out from memory, never reuses one
+    let reopened = Sequence::new("s", 1, SequenceOptions::default(), file.clone()).unwrap();
+    reopened.load_state().unwrap();
+    let next = reopened.nextval().unwrap();
+    assert!(next > 5 && next <= 5 + SEQ_LOG_VALS, "{}", next);
+
+    // a clean shutdown writes the exact value
+    reopened.flush().unwrap();
+    let again = Sequence::new("s", 1, SequenceOptions::default(), file).unwrap();
+    again.load_state().unwrap();
+    assert_eq!(again.nextval(), Ok(next + 1));
+}
+
+#[test]
+fn test_parse_sequences_serial_and_identity() {
+    let query = parse_query(
+        "create sequence if not exists s as integer increment by -2 minvalue -100 maxvalue 0 start with -10 cycle owned by t.id",
+    )
+    .unwrap();
+    let Query::CreateSequence { sequence_name, if_not_exists, options } = query else {
+        panic!("Expected CreateSequence");
+    };
+    assert_eq!(sequence_name, "s");
+    assert!(if_not_exists);
+    assert_eq!(
+        options,
+        SequenceOptions {
+            data_type: Some(ColumnType::Int),
+            increment: Some(-2),
+            min_value: Some(-100),
+            max_value: Some(0),
+            start: Some(-10),
+            cycle: true,
+            owned_by: Some(("t".to_string(), "id".to_string())),
+            sequence_name: None,
+        }
+    );
+    assert!(parse_query("create sequence s sequence name x").is_err());
+
+    let query = parse_query(
+        "create table t (id serial primary key, code bigint generated always as identity (start with 100 increment by 5), n int)",
+    )
+    .unwrap();
+    let Query::CreateTable { columns, sequences, .. } = query else {
+        panic!("Expected CreateTable");
+    };
+    assert_eq!(columns[0].column_type, ColumnType::Int);
+    assert_eq!(columns[0].identity, None);
+    assert_eq!(
+        columns[0].default,
+        Some(Operand::Func("nextval".to_string(), vec![Operand::Literal(Value::Text("t_id_seq".into()))]))
+    );
+    assert_eq!(columns[1].identity, Some(Identity::Always));
+    assert!(columns[1].not_null);
+    assert_eq!(sequences.len(), 2);
+    assert_eq!(sequences[1].0, "t_code_seq");
+    assert_eq!(sequences[1].1.start, Some(100));
+    assert_eq!(sequences[1].1.owned_by, Some(("t".to_string(), "code".to_string())));
+    assert!(parse_query("create table t (id serial default 1)").is_err());
+    assert!(parse_query("create table t (id text generated by default as identity)").is_err());
+
+    let Query::Insert { overriding, .. } =
+        parse_query("insert into t (id) overriding system value values (1)").unwrap()
+    else {
+        panic!("Expected Insert");
+    };
+    assert_eq!(overriding, Some(Overriding::SystemValue));
+
+    let Query::DropSequence { sequence_names, if_exists, cascade } =
+        parse_query("drop sequence if exists a, b cascade").unwrap()
+    else {
+        panic!("Expected DropSequence");
+    };
+    assert_eq!(sequence_names, vec!["a".to_string(), "b".to_string()]);
+    assert!(if_exists && cascade);
+
+    let Query::Select { from_table, .. } = parse_query("select nextval('s')").unwrap() else {
+        panic!("Expected Select");
+    };
+    assert!(matches!(from_table, FromItem::Empty));
+    assert!(parse_query("select *").is_err());
+}
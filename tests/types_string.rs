@@ -0,0 +1,59 @@
+use povertygres::types::bytea::{format_bytea, parse_bytea};
+use povertygres::types::catalog_types::CatColumnType;
+use povertygres::types::storage_types::{ColumnType, Value};
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+#[test]
+fn test_varchar_length_check() {
+    let ty = ColumnType::Varchar(Some(3));
+    assert_eq!(ty.coerce(text("abc")), Ok(text("abc")));
+    assert_eq!(ty.coerce(text("ab")), Ok(text("ab")));
+    // only spaces may be cut off
+    assert_eq!(ty.coerce(text("abc   ")), Ok(text("abc")));
+    assert_eq!(
+        ty.coerce(text("abcd")),
+        Err("value too long for type character varying(3)".to_string())
+    );
+    // length counts characters, not bytes
+    assert_eq!(ty.coerce(text("äöü")), Ok(text("äöü")));
+    assert_eq!(ColumnType::Varchar(None).coerce(text("any length")), Ok(text("any length")));
+}
+
+#[test]
+fn test_char_pads_to_length() {
+    let ty = ColumnType::Char(4);
+    assert_eq!(ty.coerce(text("ab")), Ok(text("ab  ")));
+    assert_eq!(ty.coerce(text("")), Ok(text("    ")));
+    assert_eq!(ty.coerce(text("abcd  ")), Ok(text("abcd")));
+    assert!(ty.coerce(text("abcde")).is_err());
+}
+
+#[test]
+fn test_bytea_hex_and_escape_input() {
+    assert_eq!(parse_bytea("\\x0102ff"), Some(vec![1, 2, 255]));
+    assert_eq!(parse_bytea("\\x01 02 FF"), Some(vec![1, 2, 255]));
+    assert_eq!(parse_bytea("\\x"), Some(vec![]));
+    assert_eq!(parse_bytea("\\x1"), None);
+    assert_eq!(parse_bytea("\\xzz"), None);
+    assert_eq!(parse_bytea("ab\\\\c\\001"), Some(vec![b'a', b'b', b'\\', b'c', 1]));
+    assert_eq!(parse_bytea("\\400"), None);
+    assert_eq!(parse_bytea("a\\b"), None);
+    assert_eq!(format_bytea(&[0, 15, 255]), "\\x000fff");
+    assert_eq!(ColumnType::Bytea.coerce(text("\\xdead")), Ok(Value::Bytea(vec![0xde, 0xad])));
+}
+
+#[test]
+fn test_typmods_round_trip_through_catalog() {
+    for ty in [ColumnType::Varchar(Some(20)), ColumnType::Varchar(None), ColumnType::Char(3)] {
+        let cat = match ty {
+            ColumnType::Varchar(_) => CatColumnType::Varchar,
+            _ => CatColumnType::Char,
+        };
+        assert_eq!(ColumnType::from_catalog(&cat, &ty.typmod()), ty);
+    }
+    assert_eq!(ColumnType::Char(3).to_string(), "character(3)");
+    assert_eq!(ColumnType::Varchar(Some(20)).to_string(), "character varying(20)");
+}
@@ -0,0 +1,74 @@
+use povertygres::executer::functions::{call, is_volatile};
+use povertygres::parser::main::parse_query;
+use povertygres::types::parser_types::{Operand, Query};
+use povertygres::types::storage_types::{ColumnType, Value};
+use povertygres::types::uuid::{format_uuid, parse_uuid};
+
+const CANONICAL: &str = "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11";
+
+#[test]
+fn test_uuid_input_forms() {
+    let bytes = parse_uuid(CANONICAL).unwrap();
+    assert_eq!(bytes[0], 0xa0);
+    assert_eq!(bytes[15], 0x11);
+    assert_eq!(format_uuid(&bytes), CANONICAL);
+    for same in [
+        "A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11",
+        "{a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}",
+        "a0eebc999c0b4ef8bb6d6bb9bd380a11",
+        "a0ee-bc99-9c0b-4ef8-bb6d-6bb9-bd38-0a11",
+    ] {
+        assert_eq!(parse_uuid(same), Some(bytes), "{}", same);
+    }
+    for bad in [
+        "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1",
+        "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a111",
+        "a0eebc99--9c0b-4ef8-bb6d-6bb9bd380a11",
+        "-a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+        "a0eeb-c99-9c0b-4ef8-bb6d-6bb9bd380a11",
+        "{a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+        "g0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+    ] {
+        assert_eq!(parse_uuid(bad), None, "{} should not parse", bad);
+    }
+    assert!(ColumnType::Uuid.coerce(Value::Text("nope".to_string())).is_err());
+}
+
+#[test]
+fn test_gen_random_uuid() {
+    let Value::Uuid(a) = call("gen_random_uuid", vec![]).unwrap() else {
+        panic!("expected a uuid");
+    };
+    let Value::Uuid(b) = call("gen_random_uuid", vec![]).unwrap() else {
+        panic!("expected a uuid");
+    };
+    assert_ne!(a, b);
+    // version 4, RFC 4122 variant
+    assert_eq!(a[6] >> 4, 4);
+    assert_eq!(a[8] >> 6, 0b10);
+    assert!(call("gen_random_uuid", vec![Value::Int(1)]).is_err());
+}
+
+#[test]
+fn test_uuid_ordering_is_bytewise() {
+    let low = Value::Uuid(parse_uuid("00000000-0000-0000-0000-0000000000ff").unwrap());
+    let high = Value::Uuid(parse_uuid("00000000-0000-0000-0000-000000000100").unwrap());
+    assert!(low < high);
+}
+
+#[test]
+fn test_expression_default() {
+    let Query::CreateTable { columns, .. } =
+        parse_query("create table t (id uuid default gen_random_uuid() primary key, n int default 1 + 2)").unwrap()
+    else {
+        panic!("Expected CreateTable");
+    };
+    assert_eq!(columns[0].column_type, ColumnType::Uuid);
+    assert_eq!(columns[0].default, Some(Operand::Func("gen_random_uuid".to_string(), vec![])));
+    assert!(columns[0].not_null);
+    assert!(is_volatile(columns[0].default.as_ref().unwrap()));
+    assert!(!is_volatile(columns[1].default.as_ref().unwrap()));
+
+    assert!(parse_query("create table t (a int, b int default a + 1)").is_err());
+    assert!(parse_query("create table t (id uuid default 5)").is_err());
+}